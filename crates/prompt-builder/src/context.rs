@@ -1,5 +1,6 @@
 use crate::snapshot::CodebaseSnapshot;
-use sqlite_db::{Discipline, ProjectMetadata, Subsystem as Feature, Task};
+use crate::templates::SectionTemplate;
+use sqlite_db::{Discipline, ProjectMetadata, Subsystem as Feature, SubsystemStatus, Task};
 use std::collections::HashMap;
 
 /// A feature comment scored by RAG relevance to the current task.
@@ -24,6 +25,10 @@ pub struct PromptContext {
     // Key: relative path, Value: file content
     pub file_contents: HashMap<String, String>,
 
+    // Target feature files for feature_files, most relevant first (caller ranks
+    // context_files + knowledge_paths + files touched in past iterations; contents in file_contents)
+    pub feature_files: Vec<String>,
+
     // State files
     pub progress_txt: Option<String>,
     pub learnings_txt: Option<String>,
@@ -46,6 +51,9 @@ pub struct PromptContext {
     // Per-section instruction overrides keyed by section name (e.g. "braindump_instructions")
     pub instruction_overrides: HashMap<String, String>,
 
+    // Project section templates keyed by section name (caller loads `.ralph/prompts/*.md`)
+    pub section_templates: HashMap<String, SectionTemplate>,
+
     // RAG: pre-computed relevant comments for the target feature (caller does search)
     // If Some, feature_context uses these instead of all comments
     pub relevant_comments: Option<Vec<ScoredFeatureComment>>,
@@ -74,6 +82,23 @@ impl PromptContext {
     pub fn feature_by_name(&self, name: &str) -> Option<&Feature> {
         self.features.iter().find(|f| f.name == name)
     }
+
+    /// Features to list in prompts: all but archived ones.
+    pub fn listed_features(&self) -> impl Iterator<Item = &Feature> {
+        self.features
+            .iter()
+            .filter(|f| f.status != SubsystemStatus::Archived)
+    }
+
+    /// Tasks to list in prompts: not archived and not in an archived feature.
+    pub fn listed_tasks(&self) -> impl Iterator<Item = &Task> {
+        self.tasks.iter().filter(|t| {
+            t.archived_at.is_none()
+                && self
+                    .feature_by_name(&t.subsystem)
+                    .map_or(true, |f| f.status != SubsystemStatus::Archived)
+        })
+    }
 }
 
 #[cfg(test)]
@@ -88,6 +113,7 @@ pub fn test_context() -> PromptContext {
             created: None,
         },
         file_contents: HashMap::new(),
+        feature_files: vec![],
         progress_txt: None,
         learnings_txt: None,
         claude_ralph_md: None,
@@ -100,6 +126,61 @@ pub fn test_context() -> PromptContext {
         target_feature: None,
         codebase_snapshot: None,
         instruction_overrides: HashMap::new(),
+        section_templates: HashMap::new(),
         relevant_comments: None,
     }
 }
+
+/// A pending backend task in `subsystem`; tests adjust it with struct update syntax.
+#[cfg(test)]
+pub fn test_task(id: u32, subsystem: &str) -> Task {
+    Task {
+        id,
+        subsystem: subsystem.to_owned(),
+        discipline: "backend".to_owned(),
+        title: format!("Task {id}"),
+        description: None,
+        status: sqlite_db::TaskStatus::Pending,
+        priority: None,
+        tags: vec![],
+        depends_on: vec![],
+        created: None,
+        updated: None,
+        completed: None,
+        acceptance_criteria: vec![],
+        context_files: vec![],
+        output_artifacts: vec![],
+        hints: None,
+        estimated_turns: None,
+        provenance: None,
+        agent: None,
+        model: None,
+        effort: None,
+        thinking: None,
+        pseudocode: None,
+        enriched_at: None,
+        archived_at: None,
+        signals: vec![],
+        subsystem_display_name: subsystem.to_uppercase(),
+        subsystem_acronym: "TEST".to_owned(),
+        discipline_display_name: "Backend".to_owned(),
+        discipline_acronym: "BACK".to_owned(),
+        discipline_icon: "Server".to_owned(),
+        discipline_color: "#8b5cf6".to_owned(),
+    }
+}
+
+/// An active feature without comments; tests adjust it with struct update syntax.
+#[cfg(test)]
+pub fn test_feature(name: &str) -> Feature {
+    Feature {
+        id: 1,
+        name: name.to_owned(),
+        display_name: name.to_uppercase(),
+        acronym: "TEST".to_owned(),
+        description: None,
+        created: None,
+        status: SubsystemStatus::Active,
+        comments: vec![],
+    }
+}
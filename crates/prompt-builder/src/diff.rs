@@ -0,0 +1,169 @@
+//! Line diff between two rendered prompts, in unified format. Pure function — no I/O.
+
+/// Unchanged lines kept around each change.
+const CONTEXT_LINES: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op<'a> {
+    Same(&'a str),
+    Removed(&'a str),
+    Added(&'a str),
+}
+
+/// Longest-common-subsequence line matching. Common head and tail are stripped first, so
+/// the quadratic table only covers the region that actually changed.
+fn line_ops<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<Op<'a>> {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let old_mid = &old[prefix..old.len() - suffix];
+    let new_mid = &new[prefix..new.len() - suffix];
+
+    // lcs[i * width + j] = LCS length of old_mid[i..] and new_mid[j..]
+    let width = new_mid.len() + 1;
+    let mut lcs = vec![0u32; (old_mid.len() + 1) * width];
+    for i in (0..old_mid.len()).rev() {
+        for j in (0..new_mid.len()).rev() {
+            lcs[i * width + j] = if old_mid[i] == new_mid[j] {
+                lcs[(i + 1) * width + j + 1] + 1
+            } else {
+                lcs[(i + 1) * width + j].max(lcs[i * width + j + 1])
+            };
+        }
+    }
+
+    let mut ops: Vec<Op<'a>> = old[..prefix].iter().map(|line| Op::Same(line)).collect();
+    let (mut i, mut j) = (0, 0);
+    while i < old_mid.len() && j < new_mid.len() {
+        if old_mid[i] == new_mid[j] {
+            ops.push(Op::Same(old_mid[i]));
+            i += 1;
+            j += 1;
+        } else if lcs[(i + 1) * width + j] >= lcs[i * width + j + 1] {
+            ops.push(Op::Removed(old_mid[i]));
+            i += 1;
+        } else {
+            ops.push(Op::Added(new_mid[j]));
+            j += 1;
+        }
+    }
+    ops.extend(old_mid[i..].iter().map(|line| Op::Removed(line)));
+    ops.extend(new_mid[j..].iter().map(|line| Op::Added(line)));
+    ops.extend(old[old.len() - suffix..].iter().map(|line| Op::Same(line)));
+    ops
+}
+
+fn hunk_range(start: usize, len: usize) -> String {
+    // Unified format numbers lines from 1; an empty range points at the line before it.
+    let first = if len == 0 { start } else { start + 1 };
+    format!("{first},{len}")
+}
+
+/// Unified diff of `old` → `new` without file headers. Empty when the texts match.
+pub fn unified_diff(old: &str, new: &str) -> String {
+    let old_lines: Vec<&str> = old.lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+    let ops = line_ops(&old_lines, &new_lines);
+
+    // Old/new line counts before each op, for hunk headers.
+    let mut positions = Vec::with_capacity(ops.len() + 1);
+    let (mut old_pos, mut new_pos) = (0, 0);
+    for op in &ops {
+        positions.push((old_pos, new_pos));
+        match op {
+            Op::Same(_) => {
+                old_pos += 1;
+                new_pos += 1;
+            }
+            Op::Removed(_) => old_pos += 1,
+            Op::Added(_) => new_pos += 1,
+        }
+    }
+    positions.push((old_pos, new_pos));
+
+    let changes: Vec<usize> = ops
+        .iter()
+        .enumerate()
+        .filter(|(_, op)| !matches!(op, Op::Same(_)))
+        .map(|(index, _)| index)
+        .collect();
+
+    let mut out = String::new();
+    let mut next = 0;
+    while next < changes.len() {
+        // Changes closer than twice the context share a hunk.
+        let mut last = next;
+        while last + 1 < changes.len() && changes[last + 1] - changes[last] <= 2 * CONTEXT_LINES {
+            last += 1;
+        }
+        let start = changes[next].saturating_sub(CONTEXT_LINES);
+        let end = (changes[last] + 1 + CONTEXT_LINES).min(ops.len());
+
+        let (old_start, new_start) = positions[start];
+        let (old_end, new_end) = positions[end];
+        out.push_str(&format!(
+            "@@ -{} +{} @@\n",
+            hunk_range(old_start, old_end - old_start),
+            hunk_range(new_start, new_end - new_start)
+        ));
+        for op in &ops[start..end] {
+            let (marker, line) = match op {
+                Op::Same(line) => (' ', line),
+                Op::Removed(line) => ('-', line),
+                Op::Added(line) => ('+', line),
+            };
+            out.push(marker);
+            out.push_str(line);
+            out.push('\n');
+        }
+        next = last + 1;
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identical_texts_have_no_diff() {
+        assert_eq!(unified_diff("a\nb\n", "a\nb\n"), "");
+        assert_eq!(unified_diff("", ""), "");
+    }
+
+    #[test]
+    fn single_change_has_context() {
+        let old = "1\n2\n3\n4\n5\n6\n7\n8\n";
+        let new = "1\n2\n3\n4\nfive\n6\n7\n8\n";
+        assert_eq!(
+            unified_diff(old, new),
+            "@@ -2,7 +2,7 @@\n 2\n 3\n 4\n-5\n+five\n 6\n 7\n 8\n"
+        );
+    }
+
+    #[test]
+    fn distant_changes_get_separate_hunks() {
+        let mut old = String::new();
+        for i in 1..=20 {
+            old.push_str(&format!("line {i}\n"));
+        }
+        let new = old
+            .replace("line 2\n", "line two\n")
+            .replace("line 18\n", "");
+        let diff = unified_diff(&old, &new);
+
+        let headers: Vec<&str> = diff.lines().filter(|l| l.starts_with("@@")).collect();
+        assert_eq!(headers, vec!["@@ -1,5 +1,5 @@", "@@ -15,6 +15,5 @@"]);
+        assert!(diff.contains("-line 2\n+line two\n"));
+        assert!(diff.contains("-line 18\n"));
+    }
+
+    #[test]
+    fn additions_to_empty_text() {
+        assert_eq!(unified_diff("", "a\nb"), "@@ -0,0 +1,2 @@\n+a\n+b\n");
+    }
+}
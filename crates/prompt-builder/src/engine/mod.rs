@@ -0,0 +1,348 @@
+//! Built-in sections rendered from minijinja templates in `templates/sections/`.
+//!
+//! Templates see a `ContextView` of the prompt context. A template that renders to nothing
+//! skips its section. Sections without a template (budgeted or ranked output such as
+//! `feature_files` and `repo_map`) and templates that fail to render use the section's
+//! Rust builder, which produces the same output.
+//!
+//! Extra filters:
+//! - `truncate(length, end="")`: the first `length` characters; `end` is added when cut.
+//! - `bullets(marker="-")`: one `marker item` line per list entry.
+//! - `fence(lang="")`: wraps text in a fenced code block.
+//!
+//! Project section templates (`.ralph/prompts/<section>.md`, see `templates`) render in
+//! the same environment, with the built-in rendering bound to `section`.
+
+pub(crate) mod view;
+
+pub use view::ContextView;
+
+use crate::context::PromptContext;
+use crate::recipe::Section;
+use minijinja::{context, Environment, Template, UndefinedBehavior, Value};
+use std::sync::OnceLock;
+
+/// Section name → template source.
+const SECTION_TEMPLATES: &[(&str, &str)] = &[
+    (
+        "dependency_context",
+        include_str!("../../templates/sections/dependency_context.md.j2"),
+    ),
+    (
+        "discipline_listing",
+        include_str!("../../templates/sections/discipline_listing.md.j2"),
+    ),
+    (
+        "discipline_persona",
+        include_str!("../../templates/sections/discipline_persona.md.j2"),
+    ),
+    (
+        "feature_listing",
+        include_str!("../../templates/sections/feature_listing.md.j2"),
+    ),
+    (
+        "previous_attempts",
+        include_str!("../../templates/sections/previous_attempts.md.j2"),
+    ),
+    (
+        "project_context",
+        include_str!("../../templates/sections/project_context.md.j2"),
+    ),
+    (
+        "project_metadata",
+        include_str!("../../templates/sections/project_metadata.md.j2"),
+    ),
+    (
+        "state_files",
+        include_str!("../../templates/sections/state_files.md.j2"),
+    ),
+    (
+        "task_details",
+        include_str!("../../templates/sections/task_details.md.j2"),
+    ),
+    (
+        "task_files",
+        include_str!("../../templates/sections/task_files.md.j2"),
+    ),
+    (
+        "task_listing",
+        include_str!("../../templates/sections/task_listing.md.j2"),
+    ),
+    (
+        "user_input",
+        include_str!("../../templates/sections/user_input.md.j2"),
+    ),
+];
+
+fn truncate(value: &str, length: usize, end: Option<&str>) -> String {
+    match value.char_indices().nth(length) {
+        Some((cut, _)) => format!("{}{}", &value[..cut], end.unwrap_or_default()),
+        None => value.to_owned(),
+    }
+}
+
+fn bullets(items: Vec<String>, marker: Option<&str>) -> String {
+    let marker = marker.unwrap_or("-");
+    let mut out = String::new();
+    for item in &items {
+        if !out.is_empty() {
+            out.push('\n');
+        }
+        out.push_str(marker);
+        out.push(' ');
+        out.push_str(item);
+    }
+    out
+}
+
+fn fence(content: &str, lang: Option<&str>) -> String {
+    format!("```{}\n{content}\n```", lang.unwrap_or_default())
+}
+
+fn environment() -> &'static Environment<'static> {
+    static ENV: OnceLock<Environment<'static>> = OnceLock::new();
+    ENV.get_or_init(|| {
+        let mut env = Environment::new();
+        env.set_trim_blocks(true);
+        env.set_lstrip_blocks(true);
+        env.set_undefined_behavior(UndefinedBehavior::Strict);
+        env.add_filter("truncate", truncate);
+        env.add_filter("bullets", bullets);
+        env.add_filter("fence", fence);
+        for (name, source) in SECTION_TEMPLATES {
+            if let Err(error) = env.add_template(name, source) {
+                tracing::error!(section = name, %error, "Invalid built-in section template");
+            }
+        }
+        env
+    })
+}
+
+/// Whether the section is rendered from a built-in template.
+pub fn has_template(name: &str) -> bool {
+    environment().get_template(name).is_ok()
+}
+
+/// Render the section's template. `None` when it has no template; a blank rendering is
+/// `Ok(None)`.
+fn render(name: &str, ctx: &PromptContext) -> Option<Result<Option<String>, minijinja::Error>> {
+    let template = environment().get_template(name).ok()?;
+    Some(
+        template
+            .render(ContextView::new(ctx))
+            .map(|text| (!text.trim().is_empty()).then_some(text)),
+    )
+}
+
+/// Compile a project section template in the built-in templates' environment.
+pub(crate) fn compile(source: &str) -> Result<Template<'_, '_>, minijinja::Error> {
+    environment().template_from_str(source)
+}
+
+/// Render a project section template against `ctx`, with `section` bound to the built-in
+/// rendering. A blank rendering is `Ok(None)`.
+pub(crate) fn render_override(
+    source: &str,
+    ctx: &PromptContext,
+    section: Option<&str>,
+) -> Result<Option<String>, minijinja::Error> {
+    let view = Value::from_serialize(ContextView::new(ctx));
+    let text = compile(source)?.render(context! { section, ..view })?;
+    let text = text.trim();
+    Ok((!text.is_empty()).then(|| text.to_owned()))
+}
+
+/// Build a section from its template, falling back to its Rust builder.
+pub fn build_section(section: &Section, ctx: &PromptContext) -> Option<String> {
+    match render(section.name, ctx) {
+        Some(Ok(text)) => text,
+        Some(Err(error)) => {
+            tracing::warn!(
+                section = section.name,
+                %error,
+                "Section template failed to render; using the built-in builder"
+            );
+            (section.build)(ctx)
+        }
+        None => (section.build)(ctx),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::{test_context, test_feature, test_task};
+    use sqlite_db::{
+        Discipline, Priority, Subsystem as Feature, SubsystemStatus, Task, TaskSignal, TaskStatus,
+    };
+
+    fn task(id: u32, subsystem: &str, status: TaskStatus) -> Task {
+        Task {
+            status,
+            ..test_task(id, subsystem)
+        }
+    }
+
+    fn feature(name: &str, description: Option<&str>) -> Feature {
+        Feature {
+            description: description.map(str::to_owned),
+            ..test_feature(name)
+        }
+    }
+
+    fn signal(author: &str, body: &str) -> TaskSignal {
+        serde_json::from_value(serde_json::json!({ "id": 1, "author": author, "body": body }))
+            .unwrap()
+    }
+
+    fn discipline(name: &str, skills: &[&str], conventions: Option<&str>) -> Discipline {
+        Discipline {
+            id: 1,
+            name: name.to_owned(),
+            display_name: format!("{name} engineer"),
+            icon: "Server".to_owned(),
+            color: "#8b5cf6".to_owned(),
+            acronym: "BACK".to_owned(),
+            description: None,
+            system_prompt: Some("You build reliable services.".to_owned()),
+            agent: None,
+            model: None,
+            effort: None,
+            thinking: None,
+            skills: skills.iter().map(|s| (*s).to_owned()).collect(),
+            conventions: conventions.map(str::to_owned),
+            mcp_servers: vec![],
+            stack_id: None,
+            image_path: None,
+            crops: None,
+            image_prompt: None,
+        }
+    }
+
+    /// A context that exercises every branch of the templated sections.
+    fn rich_context() -> PromptContext {
+        let mut done = task(1, "auth", TaskStatus::Done);
+        done.description = Some("Add the login form. Then wire it up".to_owned());
+        let mut long = task(2, "auth", TaskStatus::Done);
+        long.description = Some("x".repeat(150));
+        let mut pending = task(3, "auth", TaskStatus::Pending);
+        pending.priority = Some(Priority::High);
+        let mut target = task(4, "auth", TaskStatus::InProgress);
+        target.description = Some("Refresh tokens before they expire.".to_owned());
+        target.depends_on = vec![1, 2, 3, 99];
+        target.acceptance_criteria = vec!["Tokens refresh".to_owned(), "Tests pass".to_owned()];
+        target.pseudocode = Some("loop { refresh() }".to_owned());
+        target.hints = Some("See auth/token.rs".to_owned());
+        target.context_files = vec!["src/token.rs".to_owned(), "missing.rs".to_owned()];
+        target.signals = vec![
+            signal("agent", "Tried a timer.\n"),
+            signal("human", "Use the existing scheduler."),
+        ];
+        let other = task(5, "billing", TaskStatus::Blocked);
+
+        let mut ctx = test_context();
+        ctx.tasks = vec![done, long, pending, target, other];
+        ctx.features = vec![feature("auth", Some("Sign-in")), feature("billing", None)];
+        ctx.disciplines = vec![
+            discipline(
+                "backend",
+                &["Rust", "SQL"],
+                Some("Return Result<T, String>."),
+            ),
+            discipline("frontend", &[], None),
+            discipline("design", &["Figma", "Color", "Type", "Motion"], None),
+        ];
+        ctx.target_task_id = Some(4);
+        ctx.file_contents
+            .insert("src/token.rs".to_owned(), "fn refresh() {}".to_owned());
+        ctx.user_input = Some("Please add token refresh".to_owned());
+        ctx.claude_ralph_md = Some("# Ralph\n\nConventions here.".to_owned());
+        ctx.progress_txt = Some("Did things".to_owned());
+        ctx.learnings_txt = Some("Learned things".to_owned());
+        ctx
+    }
+
+    fn variants() -> Vec<(&'static str, PromptContext)> {
+        let mut no_target = rich_context();
+        no_target.target_task_id = None;
+
+        let mut bare_target = rich_context();
+        bare_target.target_task_id = Some(3);
+
+        let mut learnings_only = rich_context();
+        learnings_only.progress_txt = None;
+        learnings_only.disciplines[0].skills.clear();
+
+        let mut conventions_only = rich_context();
+        conventions_only.disciplines[0].skills.clear();
+        conventions_only.tasks[3].acceptance_criteria.clear();
+        conventions_only.tasks[3].description = None;
+
+        let mut no_files_read = rich_context();
+        no_files_read.file_contents.clear();
+        no_files_read.tasks[0].status = TaskStatus::Pending;
+        no_files_read.tasks[1].status = TaskStatus::Pending;
+
+        let mut archived = rich_context();
+        archived.tasks[1].archived_at = Some("2026-01-01T00:00:00Z".to_owned());
+        archived.features[1].status = SubsystemStatus::Archived;
+
+        vec![
+            ("empty", test_context()),
+            ("rich", rich_context()),
+            ("no target", no_target),
+            ("bare target", bare_target),
+            ("learnings only", learnings_only),
+            ("conventions only", conventions_only),
+            ("no files read", no_files_read),
+            ("archived", archived),
+        ]
+    }
+
+    #[test]
+    fn every_template_is_valid() {
+        for (name, _) in SECTION_TEMPLATES {
+            assert!(has_template(name), "{name} failed to parse");
+            assert!(
+                crate::sections::get_section(name).is_some(),
+                "{name} is not a section"
+            );
+        }
+    }
+
+    #[test]
+    fn default_templates_match_rust_builders_byte_for_byte() {
+        for (label, ctx) in variants() {
+            for (name, _) in SECTION_TEMPLATES {
+                let section = crate::sections::get_section(name).unwrap();
+                let rendered = render(name, &ctx).unwrap().unwrap();
+                assert_eq!(
+                    rendered,
+                    (section.build)(&ctx),
+                    "{name} differs for the {label:?} context"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn filters() {
+        assert_eq!(truncate("héllo", 2, None), "hé");
+        assert_eq!(truncate("hello", 3, Some("…")), "hel…");
+        assert_eq!(truncate("hi", 3, Some("…")), "hi");
+        assert_eq!(
+            bullets(vec!["a".to_owned(), "b".to_owned()], None),
+            "- a\n- b"
+        );
+        assert_eq!(bullets(vec!["a".to_owned()], Some("- [ ]")), "- [ ] a");
+        assert_eq!(fence("x", Some("rust")), "```rust\nx\n```");
+    }
+
+    #[test]
+    fn sections_without_template_use_builder() {
+        let section = crate::sections::get_section("feature_state").unwrap();
+        assert!(!has_template("feature_state"));
+        let ctx = rich_context();
+        assert_eq!(build_section(&section, &ctx), (section.build)(&ctx));
+    }
+}
@@ -0,0 +1,235 @@
+//! The data section templates see: a snake_case, serializable view of `PromptContext`.
+//!
+//! Targets are resolved up front (`task`, `feature`, `discipline`, `dependencies`,
+//! `task_files`) so templates don't have to search lists. Optional values are `none`.
+
+use crate::context::PromptContext;
+use serde::Serialize;
+use sqlite_db::{Discipline, Subsystem as Feature, Task};
+
+#[derive(Debug, Serialize)]
+pub struct ProjectView<'a> {
+    title: &'a str,
+    description: Option<&'a str>,
+    path: &'a str,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SignalView<'a> {
+    author: &'a str,
+    body: &'a str,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TaskView<'a> {
+    id: u32,
+    title: &'a str,
+    description: Option<&'a str>,
+    status: &'static str,
+    priority: Option<&'static str>,
+    subsystem: &'a str,
+    discipline: &'a str,
+    tags: &'a [String],
+    depends_on: &'a [u32],
+    acceptance_criteria: &'a [String],
+    context_files: &'a [String],
+    hints: Option<&'a str>,
+    pseudocode: Option<&'a str>,
+    signals: Vec<SignalView<'a>>,
+}
+
+impl<'a> TaskView<'a> {
+    fn new(task: &'a Task) -> Self {
+        Self {
+            id: task.id,
+            title: &task.title,
+            description: task.description.as_deref(),
+            status: task.status.as_str(),
+            priority: task.priority.as_ref().map(sqlite_db::Priority::as_str),
+            subsystem: &task.subsystem,
+            discipline: &task.discipline,
+            tags: &task.tags,
+            depends_on: &task.depends_on,
+            acceptance_criteria: &task.acceptance_criteria,
+            context_files: &task.context_files,
+            hints: task.hints.as_deref(),
+            pseudocode: task.pseudocode.as_deref(),
+            signals: task
+                .signals
+                .iter()
+                .map(|signal| SignalView {
+                    author: &signal.author,
+                    body: &signal.body,
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct FeatureView<'a> {
+    name: &'a str,
+    display_name: &'a str,
+    acronym: &'a str,
+    description: Option<&'a str>,
+    task_count: usize,
+}
+
+impl<'a> FeatureView<'a> {
+    fn new(feature: &'a Feature, ctx: &PromptContext) -> Self {
+        Self {
+            name: &feature.name,
+            display_name: &feature.display_name,
+            acronym: &feature.acronym,
+            description: feature.description.as_deref(),
+            task_count: ctx
+                .listed_tasks()
+                .filter(|task| task.subsystem == feature.name)
+                .count(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct DisciplineView<'a> {
+    name: &'a str,
+    display_name: &'a str,
+    acronym: &'a str,
+    icon: &'a str,
+    description: Option<&'a str>,
+    system_prompt: Option<&'a str>,
+    skills: &'a [String],
+    conventions: Option<&'a str>,
+}
+
+impl<'a> DisciplineView<'a> {
+    fn new(discipline: &'a Discipline) -> Self {
+        Self {
+            name: &discipline.name,
+            display_name: &discipline.display_name,
+            acronym: &discipline.acronym,
+            icon: &discipline.icon,
+            description: discipline.description.as_deref(),
+            system_prompt: discipline.system_prompt.as_deref(),
+            skills: &discipline.skills,
+            conventions: discipline.conventions.as_deref(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct FileView<'a> {
+    path: &'a str,
+    content: &'a str,
+}
+
+/// Top-level names a template can use, in `ContextView` field order.
+pub const FIELDS: &[&str] = &[
+    "project",
+    "tasks",
+    "features",
+    "disciplines",
+    "task",
+    "feature",
+    "discipline",
+    "dependencies",
+    "task_files",
+    "user_input",
+    "claude_ralph_md",
+    "progress_txt",
+    "learnings_txt",
+];
+
+#[derive(Debug, Serialize)]
+pub struct ContextView<'a> {
+    project: ProjectView<'a>,
+    tasks: Vec<TaskView<'a>>,
+    features: Vec<FeatureView<'a>>,
+    disciplines: Vec<DisciplineView<'a>>,
+    /// The prompt's target task, with its feature and discipline.
+    task: Option<TaskView<'a>>,
+    feature: Option<FeatureView<'a>>,
+    discipline: Option<DisciplineView<'a>>,
+    /// The target task's `depends_on`, in order; unknown ids are left out.
+    dependencies: Vec<TaskView<'a>>,
+    /// The target task's context files that were read, in order.
+    task_files: Vec<FileView<'a>>,
+    user_input: Option<&'a str>,
+    claude_ralph_md: Option<&'a str>,
+    progress_txt: Option<&'a str>,
+    learnings_txt: Option<&'a str>,
+}
+
+impl<'a> ContextView<'a> {
+    pub fn new(ctx: &'a PromptContext) -> Self {
+        let target = ctx.target_task();
+        let feature = ctx.target_task_feature().or_else(|| {
+            ctx.target_feature
+                .as_deref()
+                .and_then(|name| ctx.feature_by_name(name))
+        });
+
+        Self {
+            project: ProjectView {
+                title: &ctx.metadata.title,
+                description: ctx.metadata.description.as_deref(),
+                path: &ctx.project_path,
+            },
+            tasks: ctx.listed_tasks().map(TaskView::new).collect(),
+            features: ctx
+                .listed_features()
+                .map(|feature| FeatureView::new(feature, ctx))
+                .collect(),
+            disciplines: ctx.disciplines.iter().map(DisciplineView::new).collect(),
+            task: target.map(TaskView::new),
+            feature: feature.map(|feature| FeatureView::new(feature, ctx)),
+            discipline: ctx.target_task_discipline().map(DisciplineView::new),
+            dependencies: target
+                .map(|task| {
+                    task.depends_on
+                        .iter()
+                        .filter_map(|id| ctx.tasks.iter().find(|t| t.id == *id))
+                        .map(TaskView::new)
+                        .collect()
+                })
+                .unwrap_or_default(),
+            task_files: target
+                .map(|task| {
+                    task.context_files
+                        .iter()
+                        .filter_map(|path| {
+                            ctx.file_contents
+                                .get(path)
+                                .map(|content| FileView { path, content })
+                        })
+                        .collect()
+                })
+                .unwrap_or_default(),
+            user_input: ctx.user_input.as_deref(),
+            claude_ralph_md: ctx.claude_ralph_md.as_deref(),
+            progress_txt: ctx.progress_txt.as_deref(),
+            learnings_txt: ctx.learnings_txt.as_deref(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::test_context;
+
+    #[test]
+    fn fields_match_the_serialized_view() {
+        let ctx = test_context();
+        let value = serde_json::to_value(ContextView::new(&ctx)).unwrap();
+        let keys: Vec<&str> = value
+            .as_object()
+            .unwrap()
+            .keys()
+            .map(String::as_str)
+            .collect();
+        let mut fields = FIELDS.to_vec();
+        fields.sort_unstable();
+        assert_eq!(keys, fields);
+    }
+}
@@ -0,0 +1,113 @@
+use serde::Deserialize;
+use std::path::{Component, Path};
+
+/// Section the batch enrichment run overrides so the model answers in JSON.
+pub const INSTRUCTIONS_SECTION: &str = "enrichment_instructions";
+
+/// Instructions asking for the JSON answer `parse_response` reads, instead of an
+/// `enrich_task` tool call.
+pub fn response_instructions() -> String {
+    include_str!("../templates/instructions/enrichment_json.md")
+        .trim_end()
+        .to_owned()
+}
+
+/// Pseudocode, acceptance criteria and context files a model proposed for a draft task.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct Enrichment {
+    #[serde(default)]
+    pub pseudocode: String,
+    #[serde(default)]
+    pub acceptance_criteria: Vec<String>,
+    #[serde(default)]
+    pub context_files: Vec<String>,
+}
+
+impl Enrichment {
+    /// Reasons this enrichment can't be applied as-is; empty when it is usable.
+    /// Whether the context files exist is left to the caller, which knows the project root.
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        if self.pseudocode.is_empty() {
+            problems.push("pseudocode is empty".to_owned());
+        }
+        if self.acceptance_criteria.is_empty() {
+            problems.push("no acceptance criteria".to_owned());
+        }
+        for file in &self.context_files {
+            let relative = Path::new(file)
+                .components()
+                .all(|component| matches!(component, Component::Normal(_) | Component::CurDir));
+            if !relative {
+                problems.push(format!(
+                    "context file `{file}` is not a path inside the project"
+                ));
+            }
+        }
+        problems
+    }
+}
+
+/// Parse a model's answer into an `Enrichment`. Tolerates prose or a code fence around
+/// the JSON object; blank criteria and files are dropped and everything is trimmed.
+pub fn parse_response(text: &str) -> Result<Enrichment, String> {
+    let (Some(start), Some(end)) = (text.find('{'), text.rfind('}')) else {
+        return Err("response contains no JSON object".to_owned());
+    };
+    if end < start {
+        return Err("response contains no JSON object".to_owned());
+    }
+    let parsed: Enrichment = serde_json::from_str(&text[start..=end])
+        .map_err(|e| format!("response is not a valid enrichment: {e}"))?;
+
+    let clean = |items: Vec<String>| -> Vec<String> {
+        items
+            .iter()
+            .map(|item| item.trim())
+            .filter(|item| !item.is_empty())
+            .map(str::to_owned)
+            .collect()
+    };
+    Ok(Enrichment {
+        pseudocode: parsed.pseudocode.trim().to_owned(),
+        acceptance_criteria: clean(parsed.acceptance_criteria),
+        context_files: clean(parsed.context_files),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_fenced_json_and_trims() {
+        let text = "Here you go:\n```json\n{\"pseudocode\": \" 1. Edit src/a.rs \", \
+                    \"acceptance_criteria\": [\"Works\", \"  \"], \
+                    \"context_files\": [\" src/a.rs \"]}\n```";
+        let enrichment = parse_response(text).unwrap();
+        assert_eq!(enrichment.pseudocode, "1. Edit src/a.rs");
+        assert_eq!(enrichment.acceptance_criteria, vec!["Works"]);
+        assert_eq!(enrichment.context_files, vec!["src/a.rs"]);
+        assert!(enrichment.problems().is_empty());
+    }
+
+    #[test]
+    fn rejects_text_without_json() {
+        assert!(parse_response("I could not enrich this task.").is_err());
+        assert!(parse_response("} nothing {").is_err());
+    }
+
+    #[test]
+    fn reports_missing_fields_and_escaping_paths() {
+        let enrichment = parse_response(
+            "{\"pseudocode\": \"\", \"context_files\": [\"../secrets\", \"/etc/passwd\"]}",
+        )
+        .unwrap();
+        let problems = enrichment.problems();
+        assert_eq!(problems.len(), 4);
+        assert!(problems[0].contains("pseudocode"));
+        assert!(problems[1].contains("acceptance criteria"));
+        assert!(problems[2].contains("../secrets"));
+        assert!(problems[3].contains("/etc/passwd"));
+    }
+}
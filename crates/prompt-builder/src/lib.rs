@@ -1,11 +1,17 @@
 pub mod context;
+pub mod diff;
+pub mod engine;
+pub mod enrichment;
 pub mod mcp;
+pub mod outline;
 pub mod output;
 pub mod recipe;
 pub mod recipes;
 pub mod sections;
 pub mod snapshot;
 pub mod stagnation;
+pub mod symbols;
+pub mod templates;
 
 // Re-exports for convenience
 pub use context::{PromptContext, ScoredFeatureComment};
@@ -15,6 +21,7 @@ pub use recipe::PromptSection;
 pub use sections::metadata::SectionInfo;
 pub use snapshot::CodebaseSnapshot;
 pub use stagnation::{check_completion, hash_content};
+pub use templates::{SectionTemplate, TemplateError};
 
 /// The six prompt surfaces Ralph supports.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -29,6 +36,17 @@ pub enum PromptType {
 }
 
 impl PromptType {
+    /// Every prompt type, in the order the recipes are listed.
+    pub const ALL: [Self; 7] = [
+        Self::Braindump,
+        Self::Yap,
+        Self::Ramble,
+        Self::Discuss,
+        Self::TaskExecution,
+        Self::OpusReview,
+        Self::Enrichment,
+    ];
+
     /// Parse from a lowercase string (e.g. "braindump", "task_execution").
     pub fn parse(s: &str) -> Option<Self> {
         match s {
@@ -317,7 +317,7 @@ fn json_escape(s: &str) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::context::test_context;
+    use crate::context::{test_context, test_task};
 
     #[test]
     fn empty_tools_returns_empty() {
@@ -363,37 +363,10 @@ mod tests {
         let mut ctx = test_context();
         ctx.target_task_id = Some(1);
         ctx.tasks = vec![sqlite_db::Task {
-            id: 1,
-            subsystem: "auth".to_owned(),
             discipline: "frontend".to_owned(),
             title: "Build login".to_owned(),
-            description: None,
-            status: sqlite_db::TaskStatus::Pending,
             priority: Some(sqlite_db::Priority::Medium),
-            tags: vec![],
-            depends_on: vec![],
-            created: None,
-            updated: None,
-            completed: None,
-            acceptance_criteria: vec![],
-            context_files: vec![],
-            output_artifacts: vec![],
-            hints: None,
-            estimated_turns: None,
-            provenance: None,
-            agent: None,
-            model: None,
-            effort: None,
-            thinking: None,
-            pseudocode: None,
-            enriched_at: None,
-            signals: vec![],
-            subsystem_display_name: "Auth".to_owned(),
-            subsystem_acronym: "AU".to_owned(),
-            discipline_display_name: "Frontend".to_owned(),
-            discipline_acronym: "FE".to_owned(),
-            discipline_icon: "code".to_owned(),
-            discipline_color: "blue".to_owned(),
+            ..test_task(1, "auth")
         }];
         let mut env = HashMap::new();
         env.insert("NODE_ENV".to_owned(), "development".to_owned());
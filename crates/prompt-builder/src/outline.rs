@@ -0,0 +1,203 @@
+//! Lightweight, line-based outline of top-level declarations.
+//!
+//! Only unindented lines are considered, so nested items (methods, inner functions) are
+//! skipped. Good enough to tell an agent where things live without parsing each language.
+
+/// Declaration keywords, after visibility/export modifiers are stripped.
+const KEYWORDS: &[&str] = &[
+    "fn ",
+    "struct ",
+    "enum ",
+    "trait ",
+    "impl ",
+    "impl<",
+    "type ",
+    "const ",
+    "static ",
+    "mod ",
+    "macro_rules!",
+    "class ",
+    "interface ",
+    "function ",
+    "def ",
+    "func ",
+];
+const MODIFIERS: &[&str] = &[
+    "pub(crate) ",
+    "pub(super) ",
+    "pub ",
+    "export default ",
+    "export ",
+    "async ",
+    "unsafe ",
+    "abstract ",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Symbol {
+    /// Declaration keyword, e.g. `fn`, `struct`, `class`, `impl`.
+    pub kind: String,
+    pub name: String,
+    /// 1-based line number.
+    pub line: usize,
+}
+
+impl Symbol {
+    pub fn label(&self) -> String {
+        format!("{} {}", self.kind, self.name)
+    }
+}
+
+fn strip_modifiers(line: &str) -> &str {
+    let mut rest = line;
+    while let Some(stripped) = MODIFIERS
+        .iter()
+        .find_map(|modifier| rest.strip_prefix(modifier))
+    {
+        rest = stripped;
+    }
+    rest
+}
+
+fn declaration(line: &str) -> Option<(&'static str, &str)> {
+    if line.starts_with(char::is_whitespace) {
+        return None;
+    }
+    let rest = strip_modifiers(line);
+    KEYWORDS.iter().find_map(|keyword| {
+        let kind = keyword.trim_end_matches(['<', ' ']);
+        rest.starts_with(keyword)
+            .then(|| (kind, &rest[kind.len()..]))
+    })
+}
+
+/// Whether the line starts a top-level declaration.
+pub fn is_signature(line: &str) -> bool {
+    declaration(line).is_some()
+}
+
+fn identifier(text: &str) -> &str {
+    let end = text
+        .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '$'))
+        .unwrap_or(text.len());
+    &text[..end]
+}
+
+/// Skip a leading `<...>` generic parameter list, honoring nesting.
+fn skip_generics(text: &str) -> &str {
+    let mut depth = 0;
+    for (index, c) in text.char_indices() {
+        match c {
+            '<' => depth += 1,
+            '>' => {
+                depth -= 1;
+                if depth == 0 {
+                    return &text[index + 1..];
+                }
+            }
+            _ => {}
+        }
+    }
+    ""
+}
+
+fn symbol_name(kind: &str, after: &str) -> Option<String> {
+    let after = after.trim_start();
+    let name = match kind {
+        // `impl<T> Trait for Type where ... {` → `Trait for Type`
+        "impl" => {
+            let head = if after.starts_with('<') {
+                skip_generics(after)
+            } else {
+                after
+            };
+            let head = head.split(['{', ';']).next().unwrap_or(head);
+            head.split(" where ").next().unwrap_or(head).trim()
+        }
+        // Go methods: `func (r *Repo) Save(` → `Save`
+        "func" if after.starts_with('(') => identifier(
+            after
+                .split_once(')')
+                .map_or("", |(_, rest)| rest.trim_start()),
+        ),
+        _ => identifier(after),
+    };
+    (!name.is_empty()).then(|| name.to_owned())
+}
+
+/// Top-level declarations in source order.
+pub fn top_level_symbols(content: &str) -> Vec<Symbol> {
+    content
+        .lines()
+        .enumerate()
+        .filter_map(|(index, line)| {
+            let (kind, after) = declaration(line)?;
+            let name = symbol_name(kind, after)?;
+            Some(Symbol {
+                kind: kind.to_owned(),
+                name,
+                line: index + 1,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn labels(content: &str) -> Vec<String> {
+        top_level_symbols(content)
+            .iter()
+            .map(Symbol::label)
+            .collect()
+    }
+
+    #[test]
+    fn rust_items() {
+        let source = "use std::fmt;\n\
+                      pub struct Session {\n    id: u32,\n}\n\
+                      pub(crate) enum State { On, Off }\n\
+                      impl<T: Clone> From<T> for Session where T: Copy {\n    fn from(_: T) -> Self { todo!() }\n}\n\
+                      pub async fn refresh() {}\n\
+                      macro_rules! log { () => {} }\n";
+
+        assert_eq!(
+            labels(source),
+            vec![
+                "struct Session",
+                "enum State",
+                "impl From<T> for Session",
+                "fn refresh",
+                "macro_rules! log",
+            ]
+        );
+        assert_eq!(top_level_symbols(source)[0].line, 2);
+    }
+
+    #[test]
+    fn typescript_python_and_go_items() {
+        assert_eq!(
+            labels(
+                "export default function App() {}\nexport const API_URL = ''\ninterface Props {}\n"
+            ),
+            vec!["function App", "const API_URL", "interface Props"]
+        );
+        assert_eq!(
+            labels("class Repo:\n    def save(self):\n        pass\n\ndef main():\n    pass\n"),
+            vec!["class Repo", "def main"]
+        );
+        assert_eq!(
+            labels("func (r *Repo) Save() error {\n}\nfunc main() {\n}\n"),
+            vec!["func Save", "func main"]
+        );
+    }
+
+    #[test]
+    fn indented_and_plain_lines_are_not_signatures() {
+        assert!(is_signature("pub fn main() {"));
+        assert!(!is_signature("    fn nested() {"));
+        assert!(!is_signature("let x = 1;"));
+        assert!(!is_signature("// fn commented()"));
+    }
+}
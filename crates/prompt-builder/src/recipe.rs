@@ -27,6 +27,16 @@ pub struct PromptSection {
     pub content: String,
 }
 
+/// Build one section from its built-in template (or Rust builder), then pass it through
+/// the project's template for it, if any.
+fn render_section(section: &Section, ctx: &PromptContext) -> Option<String> {
+    crate::templates::apply(
+        section.name,
+        crate::engine::build_section(section, ctx),
+        ctx,
+    )
+}
+
 /// Build sections from an arbitrary list of section names.
 pub fn build_sections_from_names(
     section_names: &[&str],
@@ -36,7 +46,7 @@ pub fn build_sections_from_names(
         .iter()
         .filter_map(|name| {
             let section = crate::sections::get_section(name)?;
-            (section.build)(ctx).map(|c| PromptSection {
+            render_section(&section, ctx).map(|c| PromptSection {
                 name: (*name).to_owned(),
                 content: c,
             })
@@ -50,7 +60,7 @@ pub fn build_sections(recipe: &Recipe, ctx: &PromptContext) -> Vec<PromptSection
         .sections
         .iter()
         .filter_map(|s| {
-            (s.build)(ctx).map(|c| PromptSection {
+            render_section(s, ctx).map(|c| PromptSection {
                 name: s.name.to_owned(),
                 content: c,
             })
@@ -70,7 +80,7 @@ pub fn execute_recipe(recipe: &Recipe, ctx: &PromptContext) -> PromptOutput {
     let mut prompt = String::new();
     let mut sections_built = 0;
     for section in &recipe.sections {
-        if let Some(text) = (section.build)(ctx) {
+        if let Some(text) = render_section(section, ctx) {
             tracing::trace!(
                 section_name = section.name,
                 content_len = text.len(),
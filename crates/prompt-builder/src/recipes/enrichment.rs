@@ -9,6 +9,7 @@ pub fn recipe() -> Recipe {
         sections: vec![
             sections::project_context(),
             sections::codebase_state(),
+            sections::repo_map(),
             sections::feature_context(),
             sections::feature_state(),
             sections::state_files(),
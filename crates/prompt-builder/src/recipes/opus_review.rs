@@ -1,4 +1,3 @@
-use crate::mcp::tools::McpTool;
 use crate::mcp::McpMode;
 use crate::recipe::Recipe;
 use crate::sections;
@@ -11,16 +10,14 @@ pub fn recipe() -> Recipe {
             sections::feature_context(),
             sections::feature_files(),
             sections::feature_state(),
-            sections::task_listing(),
             sections::state_files(),
+            sections::previous_attempts(),
+            sections::task_details(),
+            sections::task_files(),
+            sections::user_input(),
             sections::opus_review_instructions(),
         ],
-        mcp_mode: McpMode::BashTools,
-        mcp_tools: vec![
-            McpTool::SetTaskStatus,
-            McpTool::AppendLearning,
-            McpTool::UpdateFeature,
-            McpTool::ListTasks,
-        ],
+        mcp_mode: McpMode::SignalServer,
+        mcp_tools: vec![],
     }
 }
@@ -10,6 +10,7 @@ pub fn recipe() -> Recipe {
             sections::discipline_persona(),
             sections::feature_context(),
             sections::feature_files(),
+            sections::repo_map(),
             sections::feature_state(),
             sections::state_files(),
             sections::previous_attempts(),
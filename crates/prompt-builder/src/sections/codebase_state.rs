@@ -1,5 +1,11 @@
 use crate::context::PromptContext;
+use crate::outline::Symbol;
 use crate::recipe::Section;
+use crate::snapshot::FileSummary;
+
+/// Files listed in the code map; the rest are summarized as a count.
+const MAX_MAP_FILES: usize = 40;
+const MAX_SYMBOLS_PER_FILE: usize = 8;
 
 fn build(ctx: &PromptContext) -> Option<String> {
     let snap = ctx.codebase_snapshot.as_ref()?;
@@ -36,6 +42,43 @@ fn build(ctx: &PromptContext) -> Option<String> {
         }
     }
 
+    // Code map: files with the most top-level symbols first
+    if !snap.files.is_empty() {
+        let mut files: Vec<&FileSummary> = snap.files.iter().collect();
+        files.sort_by(|a, b| {
+            b.symbols
+                .len()
+                .cmp(&a.symbols.len())
+                .then_with(|| a.path.cmp(&b.path))
+        });
+
+        out.push_str("\n\nCode map:");
+        for file in files.iter().take(MAX_MAP_FILES) {
+            out.push_str(&format!("\n- {} ({} lines)", file.path, file.lines));
+            if !file.symbols.is_empty() {
+                let labels: Vec<String> = file
+                    .symbols
+                    .iter()
+                    .take(MAX_SYMBOLS_PER_FILE)
+                    .map(Symbol::label)
+                    .collect();
+                out.push_str(&format!(": {}", labels.join(", ")));
+                if file.symbols.len() > MAX_SYMBOLS_PER_FILE {
+                    out.push_str(&format!(
+                        ", … +{} more",
+                        file.symbols.len() - MAX_SYMBOLS_PER_FILE
+                    ));
+                }
+            }
+        }
+        if files.len() > MAX_MAP_FILES {
+            out.push_str(&format!(
+                "\n- … and {} more files",
+                files.len() - MAX_MAP_FILES
+            ));
+        }
+    }
+
     Some(out)
 }
 
@@ -45,3 +88,57 @@ pub fn codebase_state() -> Section {
         build,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::test_context;
+    use crate::snapshot::CodebaseSnapshot;
+
+    fn file(path: &str, symbols: &[&str]) -> FileSummary {
+        FileSummary {
+            path: path.to_owned(),
+            language: "Rust".to_owned(),
+            size_bytes: 100,
+            lines: 10,
+            symbols: symbols
+                .iter()
+                .enumerate()
+                .map(|(index, name)| Symbol {
+                    kind: "fn".to_owned(),
+                    name: (*name).to_owned(),
+                    line: index + 1,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn code_map_lists_files_with_most_symbols_first() {
+        let mut ctx = test_context();
+        let many: Vec<String> = (0..10).map(|i| format!("f{i}")).collect();
+        let many: Vec<&str> = many.iter().map(String::as_str).collect();
+        ctx.codebase_snapshot = Some(CodebaseSnapshot {
+            total_files: 3,
+            is_empty_project: false,
+            files: vec![
+                file("src/empty.rs", &[]),
+                file("src/lib.rs", &["run"]),
+                file("src/big.rs", &many),
+            ],
+            ..CodebaseSnapshot::default()
+        });
+
+        let output = build(&ctx).unwrap();
+        let map = &output[output.find("Code map:").unwrap()..];
+        let lines: Vec<&str> = map.lines().skip(1).collect();
+        assert_eq!(
+            lines,
+            vec![
+                "- src/big.rs (10 lines): fn f0, fn f1, fn f2, fn f3, fn f4, fn f5, fn f6, fn f7, … +2 more",
+                "- src/lib.rs (10 lines): fn run",
+                "- src/empty.rs (10 lines)",
+            ]
+        );
+    }
+}
@@ -71,58 +71,14 @@ pub fn feature_context() -> Section {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::context::{test_context, ScoredFeatureComment};
-    use sqlite_db::{
-        Subsystem as Feature, SubsystemComment as FeatureComment, SubsystemStatus as FeatureStatus,
-        Task, TaskStatus,
-    };
+    use crate::context::{test_context, test_feature, test_task, ScoredFeatureComment};
+    use sqlite_db::{Subsystem as Feature, SubsystemComment as FeatureComment};
 
-    fn test_feature(name: &str, display: &str, description: Option<&str>) -> Feature {
+    fn feature(name: &str, display: &str, description: Option<&str>) -> Feature {
         Feature {
-            id: 1,
-            name: name.to_owned(),
             display_name: display.to_owned(),
-            acronym: "TEST".to_owned(),
             description: description.map(ToOwned::to_owned),
-            created: None,
-            status: FeatureStatus::Active,
-            comments: vec![],
-        }
-    }
-
-    fn test_task(id: u32, feature: &str) -> Task {
-        Task {
-            id,
-            subsystem: feature.to_owned(),
-            discipline: "backend".to_owned(),
-            title: "Test task".to_owned(),
-            description: None,
-            status: TaskStatus::Pending,
-            priority: None,
-            tags: vec![],
-            depends_on: vec![],
-            created: None,
-            updated: None,
-            completed: None,
-            acceptance_criteria: vec![],
-            context_files: vec![],
-            output_artifacts: vec![],
-            hints: None,
-            estimated_turns: None,
-            provenance: None,
-            agent: None,
-            model: None,
-            effort: None,
-            thinking: None,
-            pseudocode: None,
-            enriched_at: None,
-            signals: vec![],
-            subsystem_display_name: "Test".to_owned(),
-            subsystem_acronym: "TEST".to_owned(),
-            discipline_display_name: "Backend".to_owned(),
-            discipline_acronym: "BACK".to_owned(),
-            discipline_icon: "Server".to_owned(),
-            discipline_color: "#8b5cf6".to_owned(),
+            ..test_feature(name)
         }
     }
 
@@ -150,11 +106,7 @@ mod tests {
     #[test]
     fn feature_description_only() {
         let mut ctx = test_context();
-        ctx.features = vec![test_feature(
-            "auth",
-            "Authentication",
-            Some("Handles login"),
-        )];
+        ctx.features = vec![feature("auth", "Authentication", Some("Handles login"))];
         ctx.tasks = vec![test_task(1, "auth")];
         ctx.target_task_id = Some(1);
 
@@ -167,7 +119,7 @@ mod tests {
     #[test]
     fn fallback_injects_all_comments() {
         let mut ctx = test_context();
-        let mut feat = test_feature("auth", "Auth", Some("Login system"));
+        let mut feat = feature("auth", "Auth", Some("Login system"));
         feat.comments = vec![
             test_comment(1, "architecture", "Use JWT", None),
             test_comment(2, "convention", "snake_case everywhere", None),
@@ -194,7 +146,7 @@ mod tests {
     #[test]
     fn rag_injects_relevant_comments() {
         let mut ctx = test_context();
-        ctx.features = vec![test_feature("auth", "Auth", Some("Login"))];
+        ctx.features = vec![feature("auth", "Auth", Some("Login"))];
         ctx.tasks = vec![test_task(1, "auth")];
         ctx.target_task_id = Some(1);
         ctx.relevant_comments = Some(vec![
@@ -223,7 +175,7 @@ mod tests {
     #[test]
     fn rag_empty_vec_no_knowledge_section() {
         let mut ctx = test_context();
-        ctx.features = vec![test_feature("auth", "Auth", Some("Login"))];
+        ctx.features = vec![feature("auth", "Auth", Some("Login"))];
         ctx.tasks = vec![test_task(1, "auth")];
         ctx.target_task_id = Some(1);
         ctx.relevant_comments = Some(vec![]);
@@ -235,7 +187,7 @@ mod tests {
     #[test]
     fn comment_with_reason_annotated() {
         let mut ctx = test_context();
-        let mut feat = test_feature("auth", "Auth", Some("Login"));
+        let mut feat = feature("auth", "Auth", Some("Login"));
         feat.comments = vec![test_comment(
             1,
             "gotcha",
@@ -253,7 +205,7 @@ mod tests {
     #[test]
     fn summary_preferred_over_body_in_fallback() {
         let mut ctx = test_context();
-        let mut feat = test_feature("auth", "Auth", Some("Login"));
+        let mut feat = feature("auth", "Auth", Some("Login"));
         let mut c = test_comment(1, "gotcha", "Full detailed reasoning about bcrypt", None);
         c.summary = Some("Use bcrypt".to_owned());
         feat.comments = vec![c];
@@ -269,7 +221,7 @@ mod tests {
     #[test]
     fn summary_preferred_over_body_in_rag() {
         let mut ctx = test_context();
-        ctx.features = vec![test_feature("auth", "Auth", Some("Login"))];
+        ctx.features = vec![feature("auth", "Auth", Some("Login"))];
         ctx.tasks = vec![test_task(1, "auth")];
         ctx.target_task_id = Some(1);
         ctx.relevant_comments = Some(vec![ScoredFeatureComment {
@@ -288,7 +240,7 @@ mod tests {
     #[test]
     fn comment_without_reason_no_annotation() {
         let mut ctx = test_context();
-        let mut feat = test_feature("auth", "Auth", Some("Login"));
+        let mut feat = feature("auth", "Auth", Some("Login"));
         feat.comments = vec![test_comment(1, "gotcha", "Use bcrypt", None)];
         ctx.features = vec![feat];
         ctx.tasks = vec![test_task(1, "auth")];
@@ -1,8 +1,79 @@
 use crate::context::PromptContext;
+use crate::outline::is_signature;
 use crate::recipe::Section;
 
-fn build(_ctx: &PromptContext) -> Option<String> {
-    None
+/// Character budget for all inlined feature files together.
+const BUDGET_CHARS: usize = 24_000;
+/// Lines kept when a file without recognizable signatures has to be elided.
+const ELIDED_HEAD_LINES: usize = 40;
+
+/// Shorten a file that doesn't fit: its top-level signatures, or else its first lines.
+fn elide(content: &str) -> String {
+    let total_lines = content.lines().count();
+    let signatures: Vec<&str> = content.lines().filter(|line| is_signature(line)).collect();
+
+    if signatures.is_empty() {
+        let head: Vec<&str> = content.lines().take(ELIDED_HEAD_LINES).collect();
+        format!(
+            "{}\n// … first {} of {total_lines} lines shown",
+            head.join("\n"),
+            head.len()
+        )
+    } else {
+        format!(
+            "{}\n// … signatures only ({total_lines} lines total)",
+            signatures.join("\n")
+        )
+    }
+}
+
+fn file_block(path: &str, content: &str) -> String {
+    format!("### {path}\n\n```\n{content}\n```\n\n")
+}
+
+fn build(ctx: &PromptContext) -> Option<String> {
+    let task = ctx.target_task()?;
+
+    let mut out = String::from("## Feature Files\n\n");
+    let mut remaining = BUDGET_CHARS;
+    let mut inlined_any = false;
+    let mut omitted = Vec::new();
+
+    // `feature_files` arrives ranked; task context files are already in task_files.
+    for path in &ctx.feature_files {
+        if task.context_files.contains(path) {
+            continue;
+        }
+        let Some(content) = ctx.file_contents.get(path) else {
+            continue;
+        };
+
+        let full = file_block(path, content);
+        let block = if full.len() <= remaining {
+            full
+        } else {
+            file_block(path, &elide(content))
+        };
+        if block.len() <= remaining {
+            remaining -= block.len();
+            out.push_str(&block);
+            inlined_any = true;
+        } else {
+            omitted.push(path.as_str());
+        }
+    }
+
+    if !inlined_any && omitted.is_empty() {
+        return None;
+    }
+    if !omitted.is_empty() {
+        out.push_str("Also relevant (not inlined, read if needed):\n");
+        for path in omitted {
+            out.push_str(&format!("- {path}\n"));
+        }
+    }
+
+    Some(out.trim_end().to_owned())
 }
 
 pub fn feature_files() -> Section {
@@ -11,3 +82,101 @@ pub fn feature_files() -> Section {
         build,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::{test_context, test_task};
+    use sqlite_db::Task;
+
+    fn ctx_with_files(files: &[(&str, String)], task_files: &[&str]) -> PromptContext {
+        let mut ctx = test_context();
+        ctx.tasks = vec![Task {
+            context_files: task_files.iter().map(|p| (*p).to_owned()).collect(),
+            ..test_task(1, "auth")
+        }];
+        ctx.target_task_id = Some(1);
+        for (path, content) in files {
+            ctx.feature_files.push((*path).to_owned());
+            ctx.file_contents
+                .insert((*path).to_owned(), content.clone());
+        }
+        ctx
+    }
+
+    #[test]
+    fn no_target_task_or_files_returns_none() {
+        let ctx = test_context();
+        assert!(build(&ctx).is_none());
+
+        let ctx = ctx_with_files(&[], &[]);
+        assert!(build(&ctx).is_none());
+    }
+
+    #[test]
+    fn inlines_files_in_rank_order_and_skips_task_files() {
+        let ctx = ctx_with_files(
+            &[
+                ("src/auth/session.rs", "fn session() {}".to_owned()),
+                ("src/auth/login.rs", "fn login() {}".to_owned()),
+                ("src/auth/token.rs", "fn token() {}".to_owned()),
+            ],
+            &["src/auth/token.rs"],
+        );
+
+        let output = build(&ctx).unwrap();
+        assert!(output.starts_with("## Feature Files"));
+        let session = output.find("### src/auth/session.rs").unwrap();
+        let login = output.find("### src/auth/login.rs").unwrap();
+        assert!(session < login);
+        assert!(!output.contains("src/auth/token.rs"));
+    }
+
+    #[test]
+    fn long_files_are_elided_to_signatures() {
+        let mut source = String::from("pub struct Session {\n    id: u32,\n}\n\n");
+        source.push_str("impl Session {\n");
+        source.push_str(&"    let filler = 1;\n".repeat(2_000));
+        source.push_str("}\n\npub async fn refresh(session: &Session) {}\n");
+        let ctx = ctx_with_files(&[("src/auth/session.rs", source)], &[]);
+
+        let output = build(&ctx).unwrap();
+        assert!(output.contains("pub struct Session {"));
+        assert!(output.contains("impl Session {"));
+        assert!(output.contains("pub async fn refresh(session: &Session) {}"));
+        assert!(output.contains("signatures only"));
+        assert!(!output.contains("filler"));
+    }
+
+    #[test]
+    fn files_without_signatures_keep_their_head() {
+        let mut notes = String::new();
+        for i in 0..5_000 {
+            notes.push_str(&format!("note line {i}\n"));
+        }
+        let ctx = ctx_with_files(&[("docs/auth.md", notes)], &[]);
+
+        let output = build(&ctx).unwrap();
+        assert!(output.contains("note line 0\n"));
+        assert!(output.contains(&format!("note line {}\n", ELIDED_HEAD_LINES - 1)));
+        assert!(!output.contains(&format!("note line {ELIDED_HEAD_LINES}\n")));
+        assert!(output.contains(&format!("first {ELIDED_HEAD_LINES} of 5000 lines shown")));
+    }
+
+    #[test]
+    fn files_past_budget_are_listed_not_inlined() {
+        let big = "x".repeat(BUDGET_CHARS - 100);
+        let ctx = ctx_with_files(
+            &[
+                ("src/auth/big.rs", big),
+                ("src/auth/next.rs", "y".repeat(500)),
+            ],
+            &[],
+        );
+
+        let output = build(&ctx).unwrap();
+        assert!(output.contains("### src/auth/big.rs"));
+        assert!(!output.contains("### src/auth/next.rs"));
+        assert!(output.contains("Also relevant (not inlined, read if needed):\n- src/auth/next.rs"));
+    }
+}
@@ -2,9 +2,7 @@ use crate::context::PromptContext;
 use crate::recipe::Section;
 
 fn build(ctx: &PromptContext) -> Option<String> {
-    if ctx.features.is_empty() {
-        return None;
-    }
+    ctx.listed_features().next()?;
 
     let mut out = String::from(
         "## Existing Features\n\n\
@@ -12,11 +10,10 @@ fn build(ctx: &PromptContext) -> Option<String> {
          |---|---|---|",
     );
 
-    for feature in &ctx.features {
+    for feature in ctx.listed_features() {
         let desc = feature.description.as_deref().unwrap_or("-");
         let task_count = ctx
-            .tasks
-            .iter()
+            .listed_tasks()
             .filter(|t| t.subsystem == feature.name)
             .count();
         out.push_str(&format!(
@@ -34,6 +34,13 @@ pub fn all_sections() -> Vec<SectionInfo> {
             category: "project",
             is_instruction: false,
         },
+        SectionInfo {
+            name: "repo_map",
+            display_name: "Repository Map",
+            description: "Key symbols per file in the feature, ranked by the task's context files",
+            category: "project",
+            is_instruction: false,
+        },
         SectionInfo {
             name: "feature_listing",
             display_name: "Feature Listing",
@@ -51,7 +58,7 @@ pub fn all_sections() -> Vec<SectionInfo> {
         SectionInfo {
             name: "feature_files",
             display_name: "Feature Files",
-            description: "Inlined contents of feature context files",
+            description: "Feature context files plus files past iterations touched most",
             category: "feature",
             is_instruction: false,
         },
@@ -163,7 +170,7 @@ pub fn all_sections() -> Vec<SectionInfo> {
         SectionInfo {
             name: "opus_review_instructions",
             display_name: "Opus Review Instructions",
-            description: "Instructions for reviewing recent work quality",
+            description: "Instructions for reviewing a task reported as done",
             category: "instructions",
             is_instruction: true,
         },
@@ -11,6 +11,7 @@ pub mod metadata;
 pub mod previous_attempts;
 pub mod project_context;
 pub mod project_metadata;
+pub mod repo_map;
 pub mod state_files;
 pub mod task_details;
 pub mod task_files;
@@ -36,6 +37,7 @@ pub use instructions::yap::yap_instructions;
 pub use previous_attempts::previous_attempts;
 pub use project_context::project_context;
 pub use project_metadata::project_metadata;
+pub use repo_map::repo_map;
 pub use state_files::state_files;
 pub use task_details::task_details;
 pub use task_files::task_files;
@@ -50,6 +52,7 @@ pub fn get_section(name: &str) -> Option<Section> {
         "codebase_state" => Some(codebase_state::codebase_state()),
         "project_context" => Some(project_context::project_context()),
         "project_metadata" => Some(project_metadata::project_metadata()),
+        "repo_map" => Some(repo_map::repo_map()),
         "discipline_persona" => Some(discipline_persona::discipline_persona()),
         "feature_context" => Some(feature_context::feature_context()),
         "feature_files" => Some(feature_files::feature_files()),
@@ -0,0 +1,260 @@
+use crate::context::PromptContext;
+use crate::recipe::Section;
+use crate::snapshot::FileSummary;
+use sqlite_db::Task;
+use std::collections::HashMap;
+
+/// Token budget for the whole map.
+const TOKEN_BUDGET: usize = 1_500;
+/// Rough chars-per-token estimate used to turn the budget into characters.
+const CHARS_PER_TOKEN: usize = 4;
+/// Symbols listed per file before the rest are summarized as "+N more".
+const MAX_SYMBOLS_PER_FILE: usize = 20;
+
+fn parent_dir(path: &str) -> &str {
+    path.rsplit_once('/').map_or("", |(dir, _)| dir)
+}
+
+/// Dirs holding the subsystem's and the task's files. Empty means the whole project.
+fn scope_dirs<'a>(ctx: &'a PromptContext, task: &'a Task) -> Vec<&'a str> {
+    let mut dirs: Vec<&str> = ctx
+        .feature_files
+        .iter()
+        .chain(&task.context_files)
+        .map(|path| parent_dir(path))
+        .collect();
+    if dirs.contains(&"") {
+        return Vec::new();
+    }
+    dirs.sort_unstable();
+    dirs.dedup();
+    dirs
+}
+
+fn in_scope(path: &str, dirs: &[&str]) -> bool {
+    dirs.is_empty()
+        || dirs.iter().any(|dir| {
+            path.strip_prefix(dir)
+                .is_some_and(|rest| rest.starts_with('/'))
+        })
+}
+
+/// How often each identifier appears in the task's context files.
+fn reference_counts<'a>(ctx: &'a PromptContext, task: &Task) -> HashMap<&'a str, usize> {
+    let mut counts = HashMap::new();
+    for path in &task.context_files {
+        let Some(content) = ctx.file_contents.get(path) else {
+            continue;
+        };
+        for word in content
+            .split(|c: char| !(c.is_alphanumeric() || c == '_' || c == '$'))
+            .filter(|word| !word.is_empty())
+        {
+            *counts.entry(word).or_insert(0) += 1;
+        }
+    }
+    counts
+}
+
+fn file_block(file: &FileSummary) -> String {
+    let mut block = format!("{}:\n", file.path);
+    for symbol in file.symbols.iter().take(MAX_SYMBOLS_PER_FILE) {
+        block.push_str(&format!("  L{} {}\n", symbol.line, symbol.label()));
+    }
+    if file.symbols.len() > MAX_SYMBOLS_PER_FILE {
+        block.push_str(&format!(
+            "  … +{} more\n",
+            file.symbols.len() - MAX_SYMBOLS_PER_FILE
+        ));
+    }
+    block
+}
+
+fn build(ctx: &PromptContext) -> Option<String> {
+    let task = ctx.target_task()?;
+    let snapshot = ctx.codebase_snapshot.as_ref()?;
+
+    let dirs = scope_dirs(ctx, task);
+    let references = reference_counts(ctx, task);
+    let mut ranked: Vec<(bool, usize, &FileSummary)> = snapshot
+        .files
+        .iter()
+        .filter(|file| !file.symbols.is_empty() && in_scope(&file.path, &dirs))
+        .map(|file| {
+            let is_context_file = task.context_files.contains(&file.path);
+            let score = file
+                .symbols
+                .iter()
+                .map(|symbol| references.get(symbol.name.as_str()).copied().unwrap_or(0))
+                .sum();
+            (is_context_file, score, file)
+        })
+        .collect();
+    if ranked.is_empty() {
+        return None;
+    }
+    // Task context files first, then files whose symbols they reference most.
+    ranked.sort_by(|a, b| {
+        b.0.cmp(&a.0)
+            .then(b.1.cmp(&a.1))
+            .then_with(|| a.2.path.cmp(&b.2.path))
+    });
+
+    let mut out = String::from(
+        "## Repository Map\n\n\
+         Top-level symbols in this feature's files, most referenced by the task's files first:\n\n",
+    );
+    let mut remaining = TOKEN_BUDGET * CHARS_PER_TOKEN;
+    let mut shown = 0;
+    for (_, _, file) in &ranked {
+        let block = file_block(file);
+        if block.len() > remaining {
+            break;
+        }
+        remaining -= block.len();
+        out.push_str(&block);
+        shown += 1;
+    }
+    if shown < ranked.len() {
+        out.push_str(&format!(
+            "… and {} more files in scope\n",
+            ranked.len() - shown
+        ));
+    }
+
+    Some(out.trim_end().to_owned())
+}
+
+pub fn repo_map() -> Section {
+    Section {
+        name: "repo_map",
+        build,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::{test_context, test_task};
+    use crate::outline::Symbol;
+    use crate::snapshot::CodebaseSnapshot;
+
+    fn file(path: &str, symbols: &[(&str, &str)]) -> FileSummary {
+        FileSummary {
+            path: path.to_owned(),
+            language: "Rust".to_owned(),
+            size_bytes: 100,
+            lines: 10,
+            symbols: symbols
+                .iter()
+                .enumerate()
+                .map(|(index, (kind, name))| Symbol {
+                    kind: (*kind).to_owned(),
+                    name: (*name).to_owned(),
+                    line: index + 1,
+                })
+                .collect(),
+        }
+    }
+
+    fn ctx_with(files: Vec<FileSummary>, context_files: &[(&str, &str)]) -> PromptContext {
+        let mut ctx = test_context();
+        ctx.tasks = vec![Task {
+            context_files: context_files
+                .iter()
+                .map(|(path, _)| (*path).to_owned())
+                .collect(),
+            ..test_task(1, "auth")
+        }];
+        ctx.target_task_id = Some(1);
+        for (path, content) in context_files {
+            ctx.file_contents
+                .insert((*path).to_owned(), (*content).to_owned());
+        }
+        ctx.codebase_snapshot = Some(CodebaseSnapshot {
+            total_files: files.len(),
+            is_empty_project: files.is_empty(),
+            files,
+            ..CodebaseSnapshot::default()
+        });
+        ctx
+    }
+
+    #[test]
+    fn no_target_task_or_snapshot_returns_none() {
+        assert!(build(&test_context()).is_none());
+
+        let mut ctx = ctx_with(vec![], &[]);
+        ctx.codebase_snapshot = None;
+        assert!(build(&ctx).is_none());
+    }
+
+    #[test]
+    fn ranks_context_files_then_referenced_files() {
+        let ctx = ctx_with(
+            vec![
+                file("src/auth/audit.rs", &[("fn", "audit")]),
+                file("src/auth/login.rs", &[("fn", "login")]),
+                file(
+                    "src/auth/session.rs",
+                    &[("struct", "Session"), ("fn", "refresh")],
+                ),
+                file("src/auth/token.rs", &[("fn", "issue_token")]),
+            ],
+            &[(
+                "src/auth/login.rs",
+                "fn login() { let s = Session::new(); refresh(&s); issue_token(); }",
+            )],
+        );
+
+        let output = build(&ctx).unwrap();
+        assert!(output.starts_with("## Repository Map"));
+        let order: Vec<usize> = [
+            "src/auth/login.rs:",
+            "src/auth/session.rs:",
+            "src/auth/token.rs:",
+            "src/auth/audit.rs:",
+        ]
+        .iter()
+        .map(|path| output.find(path).unwrap())
+        .collect();
+        assert!(order.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(output.contains("  L1 struct Session\n  L2 fn refresh"));
+    }
+
+    #[test]
+    fn only_files_in_subsystem_dirs_are_listed() {
+        let mut ctx = ctx_with(
+            vec![
+                file("src/auth/login.rs", &[("fn", "login")]),
+                file("src/billing/invoice.rs", &[("fn", "invoice")]),
+                file("src/session/store.rs", &[("fn", "store")]),
+            ],
+            &[("src/auth/login.rs", "fn login() {}")],
+        );
+        ctx.feature_files = vec!["src/session/mod.rs".to_owned()];
+
+        let output = build(&ctx).unwrap();
+        assert!(output.contains("src/auth/login.rs:"));
+        assert!(output.contains("src/session/store.rs:"));
+        assert!(!output.contains("src/billing"));
+    }
+
+    #[test]
+    fn stops_at_token_budget() {
+        let names: Vec<String> = (0..MAX_SYMBOLS_PER_FILE)
+            .map(|i| format!("item_{i}"))
+            .collect();
+        let symbols: Vec<(&str, &str)> = names.iter().map(|name| ("fn", name.as_str())).collect();
+        let files: Vec<FileSummary> = (0..100)
+            .map(|i| file(&format!("src/auth/file_{i:03}.rs"), &symbols))
+            .collect();
+        let ctx = ctx_with(files, &[]);
+
+        let output = build(&ctx).unwrap();
+        assert!(output.len() <= TOKEN_BUDGET * CHARS_PER_TOKEN + 200);
+        assert!(output.contains("src/auth/file_000.rs:"));
+        assert!(!output.contains("src/auth/file_099.rs:"));
+        assert!(output.contains("more files in scope"));
+    }
+}
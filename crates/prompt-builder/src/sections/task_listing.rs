@@ -2,9 +2,7 @@ use crate::context::PromptContext;
 use crate::recipe::Section;
 
 fn build(ctx: &PromptContext) -> Option<String> {
-    if ctx.tasks.is_empty() {
-        return None;
-    }
+    ctx.listed_tasks().next()?;
 
     let mut out = String::from(
         "## Existing Tasks\n\n\
@@ -12,7 +10,7 @@ fn build(ctx: &PromptContext) -> Option<String> {
          |---|---|---|---|---|",
     );
 
-    for task in &ctx.tasks {
+    for task in ctx.listed_tasks() {
         let priority = task
             .priority
             .as_ref()
@@ -36,3 +34,56 @@ pub fn task_listing() -> Section {
         build,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::{test_context, test_feature, test_task};
+    use crate::sections::feature_listing;
+    use sqlite_db::{Subsystem as Feature, SubsystemStatus as FeatureStatus, Task};
+
+    fn titled(id: u32, feature: &str, title: &str) -> Task {
+        Task {
+            title: title.to_owned(),
+            ..test_task(id, feature)
+        }
+    }
+
+    fn archived_feature(name: &str) -> Feature {
+        Feature {
+            status: FeatureStatus::Archived,
+            ..test_feature(name)
+        }
+    }
+
+    #[test]
+    fn archived_tasks_and_features_are_not_listed() {
+        let mut ctx = test_context();
+        ctx.features = vec![test_feature("auth"), archived_feature("legacy")];
+        let mut archived = titled(2, "auth", "Old login");
+        archived.archived_at = Some("2026-01-01".to_owned());
+        ctx.tasks = vec![
+            titled(1, "auth", "Login"),
+            archived,
+            titled(3, "legacy", "Legacy export"),
+        ];
+
+        let tasks = build(&ctx).unwrap();
+        assert!(tasks.contains("Login"));
+        assert!(!tasks.contains("Old login"));
+        assert!(!tasks.contains("Legacy export"));
+
+        let features = (feature_listing().build)(&ctx).unwrap();
+        assert!(features.contains("| AUTH | - | 1 |"));
+        assert!(!features.contains("LEGACY"));
+    }
+
+    #[test]
+    fn only_archived_tasks_returns_none() {
+        let mut ctx = test_context();
+        ctx.features = vec![archived_feature("legacy")];
+        ctx.tasks = vec![titled(1, "legacy", "Legacy export")];
+        assert!(build(&ctx).is_none());
+        assert!((feature_listing().build)(&ctx).is_none());
+    }
+}
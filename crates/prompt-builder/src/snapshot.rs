@@ -1,27 +1,33 @@
-use std::collections::{BTreeMap, BTreeSet};
-use std::path::Path;
+use crate::outline::Symbol;
+use crate::symbols;
+use ignore::WalkBuilder;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 
-/// Dirs to skip when walking the project tree.
-const EXCLUDED_DIRS: &[&str] = &[
+/// Never project code, even when no `.gitignore` says so. Hidden dirs (`.git`, `.ralph`, ...)
+/// are skipped by the walker itself.
+const ALWAYS_EXCLUDED_DIRS: &[&str] = &[
     "node_modules",
-    ".git",
     "target",
     "build",
     "dist",
-    ".next",
-    ".venv",
     "venv",
     "__pycache__",
-    ".cache",
     "tmp",
     "temp",
-    ".tmp",
     "vendor",
-    ".idea",
-    ".vscode",
-    ".ralph",
 ];
 
+/// Project-specific ignore file, same syntax as `.gitignore`.
+pub const RALPH_IGNORE_FILE: &str = ".ralphignore";
+
+/// Files larger than this get size and line counts but no outline.
+const MAX_OUTLINE_BYTES: u64 = 512 * 1024;
+
+/// Dir tree entries kept in the snapshot.
+const MAX_DIR_TREE_ENTRIES: usize = 30;
+
 /// Extension → language name mapping for source code files.
 fn ext_to_language(ext: &str) -> Option<&'static str> {
     match ext {
@@ -53,6 +59,17 @@ fn ext_to_language(ext: &str) -> Option<&'static str> {
     }
 }
 
+/// Per-file facts recorded by the snapshot.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileSummary {
+    /// Relative to the project root, `/`-separated.
+    pub path: String,
+    pub language: String,
+    pub size_bytes: u64,
+    pub lines: usize,
+    pub symbols: Vec<Symbol>,
+}
+
 /// Lightweight filesystem snapshot of a project's codebase.
 #[derive(Debug, Clone)]
 pub struct CodebaseSnapshot {
@@ -61,6 +78,8 @@ pub struct CodebaseSnapshot {
     pub top_dirs: Vec<String>,
     pub dir_tree: Vec<String>,
     pub is_empty_project: bool,
+    /// Source files sorted by path.
+    pub files: Vec<FileSummary>,
 }
 
 impl Default for CodebaseSnapshot {
@@ -71,6 +90,7 @@ impl Default for CodebaseSnapshot {
             top_dirs: Vec::new(),
             dir_tree: Vec::new(),
             is_empty_project: true,
+            files: Vec::new(),
         }
     }
 }
@@ -78,80 +98,168 @@ impl Default for CodebaseSnapshot {
 /// Walk the project directory and produce a snapshot.
 /// I/O errors are swallowed — returns Default on failure.
 pub fn analyze(project_path: &Path) -> CodebaseSnapshot {
-    analyze_inner(project_path).unwrap_or_default()
+    Snapshotter::new(project_path).refresh()
 }
 
-fn analyze_inner(project_path: &Path) -> Option<CodebaseSnapshot> {
-    let mut languages: BTreeMap<String, usize> = BTreeMap::new();
-    let mut total_files: usize = 0;
-    let mut top_dirs: Vec<String> = Vec::new();
-    let mut dir_tree_set: BTreeSet<String> = BTreeSet::new();
-
-    // Collect top-level dirs
-    let entries = std::fs::read_dir(project_path).ok()?;
-    for entry in entries.flatten() {
-        let ft = entry.file_type().ok()?;
-        if ft.is_dir() {
-            if let Some(name) = entry.file_name().to_str() {
-                if !name.starts_with('.') && !EXCLUDED_DIRS.contains(&name) {
-                    top_dirs.push(name.to_owned());
-                }
-            }
+struct CachedFile {
+    modified: Option<SystemTime>,
+    summary: FileSummary,
+}
+
+/// Re-walks the tree on `refresh`, but only re-reads files whose size or mtime changed.
+/// Honors `.gitignore`, `.git/info/exclude` and `.ralphignore`.
+pub struct Snapshotter {
+    root: PathBuf,
+    cache: HashMap<String, CachedFile>,
+    dirs: Vec<PathBuf>,
+}
+
+impl Snapshotter {
+    pub fn new(root: &Path) -> Self {
+        Self {
+            root: root.to_path_buf(),
+            cache: HashMap::new(),
+            dirs: Vec::new(),
         }
     }
-    top_dirs.sort();
-
-    // Walk the tree
-    let mut stack: Vec<(std::path::PathBuf, usize)> = vec![(project_path.to_path_buf(), 0)];
-    while let Some((dir, depth)) = stack.pop() {
-        let Ok(entries) = std::fs::read_dir(&dir) else {
-            continue;
-        };
-        for entry in entries.flatten() {
-            let path = entry.path();
-            let name = match entry.file_name().to_str() {
-                Some(n) => n.to_owned(),
-                None => continue,
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Dirs the last `refresh` walked, root included. Everything else is ignored or excluded.
+    pub fn dirs(&self) -> &[PathBuf] {
+        &self.dirs
+    }
+
+    pub fn refresh(&mut self) -> CodebaseSnapshot {
+        if !self.root.is_dir() {
+            self.cache.clear();
+            self.dirs.clear();
+            return CodebaseSnapshot::default();
+        }
+
+        let mut languages: BTreeMap<String, usize> = BTreeMap::new();
+        let mut top_dirs: Vec<String> = Vec::new();
+        let mut dir_tree_set: BTreeSet<String> = BTreeSet::new();
+        let mut cache = HashMap::with_capacity(self.cache.len());
+        let mut dirs = Vec::with_capacity(self.dirs.len());
+
+        let walker = WalkBuilder::new(&self.root)
+            .hidden(true)
+            .git_ignore(true)
+            .git_exclude(true)
+            .git_global(false)
+            .require_git(false)
+            .add_custom_ignore_filename(RALPH_IGNORE_FILE)
+            .filter_entry(|entry| {
+                !(entry.file_type().is_some_and(|ft| ft.is_dir())
+                    && entry
+                        .file_name()
+                        .to_str()
+                        .is_some_and(|name| ALWAYS_EXCLUDED_DIRS.contains(&name)))
+            })
+            .build();
+
+        for entry in walker.flatten() {
+            let Some(file_type) = entry.file_type() else {
+                continue;
+            };
+            let Ok(relative) = entry.path().strip_prefix(&self.root) else {
+                continue;
             };
+            let relative = relative.to_string_lossy().replace('\\', "/");
+            if file_type.is_dir() {
+                dirs.push(entry.path().to_path_buf());
+            }
+            if relative.is_empty() {
+                continue;
+            }
 
-            if path.is_dir() {
-                if name.starts_with('.') || EXCLUDED_DIRS.contains(&name.as_str()) {
-                    continue;
-                }
+            if file_type.is_dir() {
                 // Record depth-2 dir tree entries (top/sub)
-                if depth == 0 && dir_tree_set.len() < 30 {
-                    // This is a top-level dir; its children will be depth=1
-                    dir_tree_set.insert(name.clone());
-                } else if depth == 1 && dir_tree_set.len() < 30 {
-                    // depth=1 child: record as "parent/child"
-                    let parent = dir
-                        .strip_prefix(project_path)
-                        .unwrap_or(dir.as_path())
-                        .to_string_lossy();
-                    dir_tree_set.insert(format!("{parent}/{name}"));
+                if entry.depth() == 1 {
+                    top_dirs.push(relative.clone());
                 }
-                stack.push((path, depth + 1));
-            } else if path.is_file() {
-                if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
-                    if let Some(lang) = ext_to_language(ext) {
-                        *languages.entry(lang.to_owned()).or_insert(0) += 1;
-                        total_files += 1;
-                    }
+                if entry.depth() <= 2 && dir_tree_set.len() < MAX_DIR_TREE_ENTRIES {
+                    dir_tree_set.insert(relative);
                 }
+                continue;
+            }
+            if !file_type.is_file() {
+                continue;
             }
+
+            let Some(language) = entry
+                .path()
+                .extension()
+                .and_then(|e| e.to_str())
+                .and_then(ext_to_language)
+            else {
+                continue;
+            };
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            let modified = metadata.modified().ok();
+            let size_bytes = metadata.len();
+
+            let cached = self.cache.remove(&relative).filter(|cached| {
+                cached.modified.is_some()
+                    && cached.modified == modified
+                    && cached.summary.size_bytes == size_bytes
+            });
+            let summary = match cached {
+                Some(cached) => cached.summary,
+                None => summarize_file(entry.path(), relative.clone(), language, size_bytes),
+            };
+
+            *languages.entry(language.to_owned()).or_insert(0) += 1;
+            cache.insert(relative, CachedFile { modified, summary });
+        }
+
+        self.cache = cache;
+        self.dirs = dirs;
+        top_dirs.sort();
+
+        let mut files: Vec<FileSummary> = self
+            .cache
+            .values()
+            .map(|cached| cached.summary.clone())
+            .collect();
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+
+        CodebaseSnapshot {
+            total_files: files.len(),
+            languages,
+            top_dirs,
+            dir_tree: dir_tree_set.into_iter().collect(),
+            is_empty_project: files.is_empty(),
+            files,
         }
     }
+}
 
-    let is_empty_project = total_files == 0;
-    let dir_tree: Vec<String> = dir_tree_set.into_iter().collect();
+fn summarize_file(path: &Path, relative: String, language: &str, size_bytes: u64) -> FileSummary {
+    let content = std::fs::read(path).unwrap_or_default();
+    let lines = count_lines(&content);
+    let symbols = if size_bytes <= MAX_OUTLINE_BYTES {
+        std::str::from_utf8(&content)
+            .map_or_else(|_| Vec::new(), |text| symbols::extract(&relative, text))
+    } else {
+        Vec::new()
+    };
+    FileSummary {
+        path: relative,
+        language: language.to_owned(),
+        size_bytes,
+        lines,
+        symbols,
+    }
+}
 
-    Some(CodebaseSnapshot {
-        total_files,
-        languages,
-        top_dirs,
-        dir_tree,
-        is_empty_project,
-    })
+fn count_lines(content: &[u8]) -> usize {
+    String::from_utf8_lossy(content).lines().count()
 }
 
 #[cfg(test)]
@@ -215,4 +323,102 @@ mod tests {
 
         let _ = fs::remove_dir_all(&tmp);
     }
+
+    #[test]
+    fn dirs_lists_only_walked_dirs() {
+        let tmp = std::env::temp_dir().join("ralph-snap-test-dirs");
+        let _ = fs::remove_dir_all(&tmp);
+        for dir in [
+            "src/api",
+            "node_modules/pkg",
+            "target/debug",
+            ".ralph/db",
+            "fixtures",
+        ] {
+            fs::create_dir_all(tmp.join(dir)).unwrap();
+        }
+        fs::write(tmp.join(".ralphignore"), "fixtures/\n").unwrap();
+
+        let mut snapshotter = Snapshotter::new(&tmp);
+        snapshotter.refresh();
+        let mut dirs = snapshotter.dirs().to_vec();
+        dirs.sort();
+        assert_eq!(
+            dirs,
+            vec![tmp.clone(), tmp.join("src"), tmp.join("src/api")]
+        );
+
+        let _ = fs::remove_dir_all(&tmp);
+    }
+
+    #[test]
+    fn honors_gitignore_and_ralphignore() {
+        let tmp = std::env::temp_dir().join("ralph-snap-test-ignore");
+        let _ = fs::remove_dir_all(&tmp);
+        fs::create_dir_all(tmp.join("src")).unwrap();
+        fs::create_dir_all(tmp.join("generated")).unwrap();
+        fs::create_dir_all(tmp.join("fixtures")).unwrap();
+        fs::write(tmp.join(".gitignore"), "generated/\n*.gen.ts\n").unwrap();
+        fs::write(tmp.join(".ralphignore"), "fixtures/\n").unwrap();
+        fs::write(tmp.join("src").join("app.ts"), "").unwrap();
+        fs::write(tmp.join("src").join("api.gen.ts"), "").unwrap();
+        fs::write(tmp.join("generated").join("types.ts"), "").unwrap();
+        fs::write(tmp.join("fixtures").join("sample.py"), "").unwrap();
+
+        let snap = analyze(&tmp);
+        let paths: Vec<&str> = snap.files.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(paths, vec!["src/app.ts"]);
+        assert_eq!(snap.top_dirs, vec!["src"]);
+
+        let _ = fs::remove_dir_all(&tmp);
+    }
+
+    #[test]
+    fn records_size_lines_and_outline() {
+        let tmp = std::env::temp_dir().join("ralph-snap-test-outline");
+        let _ = fs::remove_dir_all(&tmp);
+        fs::create_dir_all(&tmp).unwrap();
+        let source =
+            "pub struct Repo;\n\nimpl Repo {\n    pub fn save(&self) {}\n}\n\nfn main() {}";
+        fs::write(tmp.join("main.rs"), source).unwrap();
+
+        let snap = analyze(&tmp);
+        let file = &snap.files[0];
+        assert_eq!(file.path, "main.rs");
+        assert_eq!(file.language, "Rust");
+        assert_eq!(file.size_bytes, source.len() as u64);
+        assert_eq!(file.lines, 7);
+        let labels: Vec<String> = file.symbols.iter().map(Symbol::label).collect();
+        assert_eq!(labels, vec!["struct Repo", "impl Repo", "fn main"]);
+
+        let _ = fs::remove_dir_all(&tmp);
+    }
+
+    #[test]
+    fn refresh_picks_up_added_changed_and_removed_files() {
+        let tmp = std::env::temp_dir().join("ralph-snap-test-refresh");
+        let _ = fs::remove_dir_all(&tmp);
+        fs::create_dir_all(&tmp).unwrap();
+        fs::write(tmp.join("a.rs"), "fn a() {}\n").unwrap();
+        fs::write(tmp.join("b.rs"), "fn b() {}\n").unwrap();
+
+        let mut snapshotter = Snapshotter::new(&tmp);
+        assert_eq!(snapshotter.refresh().total_files, 2);
+
+        fs::write(tmp.join("a.rs"), "fn a() {}\nfn a2() {}\n").unwrap();
+        fs::remove_file(tmp.join("b.rs")).unwrap();
+        fs::write(tmp.join("c.py"), "def c():\n    pass\n").unwrap();
+
+        let snap = snapshotter.refresh();
+        let summary: Vec<(&str, usize)> = snap
+            .files
+            .iter()
+            .map(|f| (f.path.as_str(), f.symbols.len()))
+            .collect();
+        assert_eq!(summary, vec![("a.rs", 2), ("c.py", 1)]);
+        assert_eq!(snap.languages.get("Rust"), Some(&1));
+        assert_eq!(snap.languages.get("Python"), Some(&1));
+
+        let _ = fs::remove_dir_all(&tmp);
+    }
 }
@@ -0,0 +1,242 @@
+//! Tree-sitter symbol extraction for the languages agents work in most.
+//!
+//! Records top-level functions, types and exports. Files in other languages (or that fail
+//! to parse) fall back to the line-based `outline`.
+
+use crate::outline::{self, Symbol};
+use tree_sitter::{Language, Node, Parser};
+
+fn language_for(path: &str) -> Option<Language> {
+    let ext = path.rsplit_once('.')?.1;
+    let language = match ext {
+        "rs" => tree_sitter_rust::LANGUAGE,
+        "ts" | "mts" | "cts" => tree_sitter_typescript::LANGUAGE_TYPESCRIPT,
+        "tsx" => tree_sitter_typescript::LANGUAGE_TSX,
+        "js" | "jsx" | "mjs" | "cjs" => tree_sitter_javascript::LANGUAGE,
+        "py" => tree_sitter_python::LANGUAGE,
+        "go" => tree_sitter_go::LANGUAGE,
+        _ => return None,
+    };
+    Some(language.into())
+}
+
+/// Top-level symbols of a source file, in source order.
+pub fn extract(path: &str, content: &str) -> Vec<Symbol> {
+    parse_symbols(path, content).unwrap_or_else(|| outline::top_level_symbols(content))
+}
+
+fn parse_symbols(path: &str, content: &str) -> Option<Vec<Symbol>> {
+    let language = language_for(path)?;
+    let mut parser = Parser::new();
+    parser.set_language(&language).ok()?;
+    let tree = parser.parse(content, None)?;
+
+    let mut collector = Collector {
+        source: content.as_bytes(),
+        // Go and JS both call it `function_declaration`.
+        function_keyword: if path.ends_with(".go") {
+            "func"
+        } else {
+            "function"
+        },
+        symbols: Vec::new(),
+    };
+    let root = tree.root_node();
+    let mut cursor = root.walk();
+    for node in root.named_children(&mut cursor) {
+        collector.collect(node, None);
+    }
+    Some(collector.symbols)
+}
+
+/// Declaration keyword for a node kind, across the supported grammars.
+fn declaration_kind(node_kind: &str) -> Option<&'static str> {
+    Some(match node_kind {
+        "function_item" => "fn",
+        "struct_item" => "struct",
+        "enum_item" | "enum_declaration" => "enum",
+        "union_item" => "union",
+        "trait_item" => "trait",
+        "type_item" | "type_alias_declaration" => "type",
+        "const_item" => "const",
+        "static_item" => "static",
+        "mod_item" => "mod",
+        "macro_definition" => "macro_rules!",
+        "class_declaration" | "abstract_class_declaration" | "class_definition" => "class",
+        "interface_declaration" => "interface",
+        "function_definition" => "def",
+        _ => return None,
+    })
+}
+
+struct Collector<'a> {
+    source: &'a [u8],
+    function_keyword: &'static str,
+    symbols: Vec<Symbol>,
+}
+
+impl<'a> Collector<'a> {
+    fn text(&self, node: Node<'_>) -> &'a str {
+        node.utf8_text(self.source).unwrap_or_default()
+    }
+
+    fn field_text(&self, node: Node<'_>, field: &str) -> Option<&'a str> {
+        node.child_by_field_name(field)
+            .map(|child| self.text(child))
+    }
+
+    fn push(&mut self, node: Node<'_>, kind: &str, name: &str, export: Option<&str>) {
+        let name = name.trim();
+        if name.is_empty() {
+            return;
+        }
+        self.symbols.push(Symbol {
+            kind: export.map_or_else(|| kind.to_owned(), |prefix| format!("{prefix} {kind}")),
+            name: name.to_owned(),
+            line: node.start_position().row + 1,
+        });
+    }
+
+    /// `export` is the prefix inherited from an enclosing TS/JS export statement.
+    fn collect(&mut self, node: Node<'_>, export: Option<&str>) {
+        match node.kind() {
+            "export_statement" => {
+                let prefix = if self.text(node).starts_with("export default") {
+                    "export default"
+                } else {
+                    "export"
+                };
+                let mut cursor = node.walk();
+                for child in node.named_children(&mut cursor) {
+                    self.collect(child, Some(prefix));
+                }
+            }
+            // Python decorators wrap the real definition.
+            "decorated_definition" => {
+                if let Some(definition) = node.child_by_field_name("definition") {
+                    self.collect(definition, export);
+                }
+            }
+            // `impl<T> Trait for Type where ...` → `Trait for Type`
+            "impl_item" => {
+                let Some(type_name) = self.field_text(node, "type") else {
+                    return;
+                };
+                let name = self.field_text(node, "trait").map_or_else(
+                    || type_name.to_owned(),
+                    |trait_name| format!("{trait_name} for {type_name}"),
+                );
+                self.push(node, "impl", &name, export);
+            }
+            // Go methods are listed by name, without the receiver.
+            "function_declaration" | "generator_function_declaration" | "method_declaration" => {
+                if let Some(name) = self.field_text(node, "name") {
+                    self.push(node, self.function_keyword, name, export);
+                }
+            }
+            // Go `type ( A struct{}; B int )`
+            "type_declaration" => {
+                let mut cursor = node.walk();
+                for spec in node.named_children(&mut cursor) {
+                    if let Some(name) = self.field_text(spec, "name") {
+                        self.push(spec, "type", name, export);
+                    }
+                }
+            }
+            // TS/JS `const api = ...`; Go `const ( ... )` / `var x = ...`
+            "lexical_declaration"
+            | "variable_declaration"
+            | "const_declaration"
+            | "var_declaration" => {
+                let keyword = node.child(0).map_or("const", |first| self.text(first));
+                let mut cursor = node.walk();
+                for declarator in node.named_children(&mut cursor) {
+                    if let Some(name) = self.field_text(declarator, "name") {
+                        self.push(declarator, keyword, name, export);
+                    }
+                }
+            }
+            kind => {
+                if let (Some(keyword), Some(name)) =
+                    (declaration_kind(kind), self.field_text(node, "name"))
+                {
+                    self.push(node, keyword, name, export);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn labels(path: &str, content: &str) -> Vec<String> {
+        extract(path, content).iter().map(Symbol::label).collect()
+    }
+
+    #[test]
+    fn rust_items() {
+        let source = "use std::fmt;\n\
+                      pub struct Session {\n    id: u32,\n}\n\
+                      impl<T: Clone> From<T> for Session where T: Copy {\n    fn from(_: T) -> Self { todo!() }\n}\n\
+                      pub async fn refresh() {}\n\
+                      macro_rules! log { () => {} }\n";
+
+        assert_eq!(
+            labels("src/session.rs", source),
+            vec![
+                "struct Session",
+                "impl From<T> for Session",
+                "fn refresh",
+                "macro_rules! log",
+            ]
+        );
+        assert_eq!(extract("src/session.rs", source)[0].line, 2);
+    }
+
+    #[test]
+    fn typescript_exports() {
+        let source = "import { x } from './x'\n\
+                      export default function App() {}\n\
+                      export const API_URL = ''\n\
+                      interface Props {}\n\
+                      export type Id = string\n";
+
+        assert_eq!(
+            labels("src/App.tsx", source),
+            vec![
+                "export default function App",
+                "export const API_URL",
+                "interface Props",
+                "export type Id",
+            ]
+        );
+    }
+
+    #[test]
+    fn python_and_go_items() {
+        assert_eq!(
+            labels(
+                "repo.py",
+                "@dataclass\nclass Repo:\n    def save(self):\n        pass\n\ndef main():\n    pass\n"
+            ),
+            vec!["class Repo", "def main"]
+        );
+        assert_eq!(
+            labels(
+                "repo.go",
+                "package repo\n\ntype Repo struct{}\n\nfunc (r *Repo) Save() error {\n\treturn nil\n}\n\nfunc main() {\n}\n"
+            ),
+            vec!["type Repo", "func Save", "func main"]
+        );
+    }
+
+    #[test]
+    fn unsupported_languages_fall_back_to_outline() {
+        assert_eq!(
+            labels("Main.java", "class Main {\n}\ninterface Shape {}\n"),
+            vec!["class Main", "interface Shape"]
+        );
+    }
+}
@@ -0,0 +1,266 @@
+//! Section templates: `.ralph/prompts/<section>.md` replaces or extends a built-in section.
+//!
+//! Templates are minijinja, rendered in the same environment and over the same
+//! `ContextView` as the built-in section templates (see `engine`), with the same filters.
+//! `section` is the built-in rendering, so a template that includes `{{ section }}` extends
+//! the section instead of replacing it. A template that renders to nothing but whitespace
+//! drops the section. Parsing and rendering only — no I/O.
+
+use crate::context::PromptContext;
+use crate::engine;
+use std::fmt;
+
+/// Older name for a section's override file, kept working after the section was renamed.
+const LEGACY_FILE_NAMES: &[(&str, &str)] =
+    &[("task_creation_instructions", "braindump_instructions")];
+
+/// A problem in a template file, pointing at the line it was found on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TemplateError {
+    pub file: String,
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.file, self.line, self.message)
+    }
+}
+
+/// A validated section template.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SectionTemplate {
+    file: String,
+    source: String,
+}
+
+impl SectionTemplate {
+    /// Check that `source` compiles and only uses names the context view provides; `file`
+    /// is only used in error messages.
+    pub fn parse(file: &str, source: &str) -> Result<Self, TemplateError> {
+        let error = |line: usize, message: String| TemplateError {
+            file: file.to_owned(),
+            line,
+            message,
+        };
+        let template = engine::compile(source).map_err(|e| {
+            error(
+                e.line().unwrap_or(1),
+                e.detail()
+                    .map_or_else(|| e.kind().to_string(), str::to_owned),
+            )
+        })?;
+
+        let mut unknown: Vec<String> = template
+            .undeclared_variables(false)
+            .into_iter()
+            .filter(|name| name != "section" && !engine::view::FIELDS.contains(&name.as_str()))
+            .collect();
+        if !unknown.is_empty() {
+            unknown.sort();
+            let line = source
+                .lines()
+                .position(|line| line.contains(unknown[0].as_str()))
+                .map_or(1, |index| index + 1);
+            return Err(error(
+                line,
+                format!(
+                    "unknown variable `{}`; expected section or one of: {}",
+                    unknown.join("`, `"),
+                    engine::view::FIELDS.join(", ")
+                ),
+            ));
+        }
+
+        Ok(Self {
+            file: file.to_owned(),
+            source: source.to_owned(),
+        })
+    }
+
+    /// Render against `ctx`. `section` is the built-in rendering bound to `section`.
+    /// `Ok(None)` when the result is blank, which drops the section from the prompt.
+    pub fn render(
+        &self,
+        ctx: &PromptContext,
+        section: Option<&str>,
+    ) -> Result<Option<String>, TemplateError> {
+        engine::render_override(&self.source, ctx, section).map_err(|e| TemplateError {
+            file: self.file.clone(),
+            line: e.line().unwrap_or(1),
+            message: e.to_string(),
+        })
+    }
+}
+
+/// Section a template file overrides, from its name (`task_details.md` → `task_details`).
+/// Errors for non-Markdown files and names that match no section.
+pub fn section_for_file(file: &str) -> Result<&'static str, TemplateError> {
+    let error = |message: String| TemplateError {
+        file: file.to_owned(),
+        line: 1,
+        message,
+    };
+    let stem = file
+        .strip_suffix(".md")
+        .ok_or_else(|| error("section templates must be `.md` files".to_owned()))?;
+    let stem = LEGACY_FILE_NAMES
+        .iter()
+        .find(|(legacy, _)| *legacy == stem)
+        .map_or(stem, |(_, section)| section);
+    crate::sections::get_section(stem)
+        .map(|section| section.name)
+        .ok_or_else(|| error(format!("no prompt section named `{stem}`")))
+}
+
+/// Parse a template file into the section it overrides and its template.
+pub fn load(file: &str, source: &str) -> Result<(&'static str, SectionTemplate), TemplateError> {
+    let section = section_for_file(file)?;
+    Ok((section, SectionTemplate::parse(file, source)?))
+}
+
+/// Apply the context's template for `name`, if any, to the built-in rendering. A template
+/// that fails to render (e.g. reading `task` in a prompt without one) keeps the built-in
+/// rendering.
+pub fn apply(name: &str, built: Option<String>, ctx: &PromptContext) -> Option<String> {
+    let Some(template) = ctx.section_templates.get(name) else {
+        return built;
+    };
+    match template.render(ctx, built.as_deref()) {
+        Ok(rendered) => rendered,
+        Err(error) => {
+            tracing::warn!(
+                section = name,
+                %error,
+                "Section template failed to render; using the built-in section"
+            );
+            built
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::test_context;
+
+    fn render(source: &str, section: Option<&str>) -> Option<String> {
+        SectionTemplate::parse("t.md", source)
+            .unwrap()
+            .render(&test_context(), section)
+            .unwrap()
+    }
+
+    #[test]
+    fn binds_context_view_and_extends_section() {
+        let mut ctx = test_context();
+        ctx.metadata.title = "Ralph".to_owned();
+        ctx.user_input = Some("ship it".to_owned());
+        let template = SectionTemplate::parse(
+            "t.md",
+            "{{ section }}\n\nProject: {{ project.title }}\nAsk: {{ user_input }}\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            template
+                .render(&ctx, Some("## Built-in"))
+                .unwrap()
+                .as_deref(),
+            Some("## Built-in\n\nProject: Ralph\nAsk: ship it")
+        );
+    }
+
+    #[test]
+    fn uses_the_built_in_filters_and_blocks() {
+        let mut ctx = test_context();
+        ctx.disciplines = vec![serde_json::from_value(serde_json::json!({
+            "id": 1,
+            "name": "backend",
+            "display_name": "Backend",
+            "icon": "Server",
+            "color": "#8b5cf6",
+            "acronym": "BACK",
+            "skills": ["Rust", "SQL"]
+        }))
+        .unwrap()];
+        let template = SectionTemplate::parse(
+            "t.md",
+            "{% for d in disciplines %}{{ d.skills | bullets }}{% endfor %}",
+        )
+        .unwrap();
+
+        assert_eq!(
+            template.render(&ctx, None).unwrap().as_deref(),
+            Some("- Rust\n- SQL")
+        );
+    }
+
+    #[test]
+    fn blank_rendering_drops_section_and_raw_is_literal() {
+        assert_eq!(
+            render("{% if task %}{{ task.title }}{% endif %}\n", None),
+            None
+        );
+        assert_eq!(
+            render("Use {% raw %}{{braces}}{% endraw %} as-is", None).as_deref(),
+            Some("Use {{braces}} as-is")
+        );
+    }
+
+    #[test]
+    fn errors_point_at_file_and_line() {
+        let unknown =
+            SectionTemplate::parse("persona.md", "ok\n\nHi {{ tsk.title }}\n").unwrap_err();
+        assert_eq!((unknown.file.as_str(), unknown.line), ("persona.md", 3));
+        assert!(unknown
+            .to_string()
+            .starts_with("persona.md:3: unknown variable `tsk`"));
+
+        let unclosed = SectionTemplate::parse("persona.md", "ok\n{% if task %}\n").unwrap_err();
+        assert_eq!(unclosed.file, "persona.md");
+        assert!(unclosed.message.contains("expected end of block"));
+    }
+
+    #[test]
+    fn file_names_map_to_sections() {
+        assert_eq!(section_for_file("task_details.md").unwrap(), "task_details");
+        assert_eq!(
+            section_for_file("task_creation_instructions.md").unwrap(),
+            "braindump_instructions"
+        );
+        assert!(section_for_file("notes.md")
+            .unwrap_err()
+            .message
+            .contains("notes"));
+        assert!(section_for_file("task_details.txt").is_err());
+    }
+
+    #[test]
+    fn apply_keeps_built_section_without_template_or_on_render_error() {
+        let mut ctx = test_context();
+        assert_eq!(
+            apply("user_input", Some("x".to_owned()), &ctx).as_deref(),
+            Some("x")
+        );
+
+        ctx.section_templates.insert(
+            "user_input".to_owned(),
+            SectionTemplate::parse("user_input.md", "> {{ section }}").unwrap(),
+        );
+        assert_eq!(
+            apply("user_input", Some("x".to_owned()), &ctx).as_deref(),
+            Some("> x")
+        );
+
+        ctx.section_templates.insert(
+            "user_input".to_owned(),
+            SectionTemplate::parse("user_input.md", "{{ task.title }}").unwrap(),
+        );
+        assert_eq!(
+            apply("user_input", Some("x".to_owned()), &ctx).as_deref(),
+            Some("x")
+        );
+    }
+}
@@ -0,0 +1,565 @@
+//! Reads what a prompt needs from disk so `PromptContext` can stay pure data.
+//!
+//! Covers task and subsystem files, the `.ralph` state files, and the RAG comment search.
+//! A file that can't be used is skipped and reported as a backend diagnostic; it never
+//! fails the prompt.
+
+use crate::diagnostics;
+use crate::section_templates::load_section_templates;
+use prompt_builder::{CodebaseSnapshot, PromptContext, ScoredFeatureComment};
+use ralph_rag::extraction::should_exclude_from_auto_accumulation;
+use ralph_rag::journal::{read_journal, JournalEntry};
+use ralph_rag::model::FileAction;
+use sqlite_db::{SqliteDb, Subsystem, Task};
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Longer files are truncated; the agent can still open them itself.
+const MAX_FILE_BYTES: usize = 64 * 1024;
+/// Total budget across all context files; files past it are skipped.
+const MAX_TOTAL_BYTES: usize = 256 * 1024;
+/// A NUL byte in this prefix marks a file as binary.
+const BINARY_SNIFF_BYTES: usize = 8 * 1024;
+const RAG_QUERY_TIMEOUT: Duration = Duration::from_secs(5);
+/// Journal-touched files offered to `feature_files` on top of the pinned subsystem files.
+const MAX_TOUCHED_FILES: usize = 10;
+/// A touch this many iterations ago counts half as much as one in the latest iteration.
+const TOUCH_HALF_LIFE_ITERATIONS: f64 = 5.0;
+
+/// A task's RAG query, embedded before the database is locked: the request can take up to
+/// `RAG_QUERY_TIMEOUT`.
+#[derive(Debug)]
+pub struct QueryEmbedding {
+    embedding: Vec<f32>,
+    max_results: usize,
+    min_score: f32,
+}
+
+#[derive(Default)]
+pub(super) struct LoadedContext {
+    pub file_contents: HashMap<String, String>,
+    pub feature_files: Vec<String>,
+    pub progress_txt: Option<String>,
+    pub learnings_txt: Option<String>,
+    pub claude_ralph_md: Option<String>,
+    pub relevant_comments: Option<Vec<ScoredFeatureComment>>,
+}
+
+pub(super) struct ContextLoader<'a> {
+    project_path: &'a Path,
+    db: &'a SqliteDb,
+    problems: Vec<String>,
+}
+
+impl<'a> ContextLoader<'a> {
+    pub(super) fn new(project_path: &'a Path, db: &'a SqliteDb) -> Self {
+        Self {
+            project_path,
+            db,
+            problems: Vec::new(),
+        }
+    }
+
+    /// State files are always loaded; file contents and RAG comments only for a target task.
+    pub(super) fn load(
+        mut self,
+        target_task: Option<&Task>,
+        target_feature: Option<&Subsystem>,
+        query_embedding: Option<&QueryEmbedding>,
+    ) -> LoadedContext {
+        let mut loaded = LoadedContext {
+            progress_txt: self.read_state_file("progress.txt"),
+            learnings_txt: self.read_state_file("learnings.txt"),
+            claude_ralph_md: self.read_state_file("CLAUDE.RALPH.md"),
+            ..LoadedContext::default()
+        };
+
+        if let Some(task) = target_task {
+            loaded.feature_files = self.feature_files(&task.subsystem);
+            let mut paths = task.context_files.clone();
+            paths.extend(loaded.feature_files.iter().cloned());
+            loaded.file_contents = self.read_context_files(&paths);
+        }
+        if let (Some(feature), Some(query)) = (target_feature, query_embedding) {
+            loaded.relevant_comments = Some(self.relevant_comments(feature, query));
+        }
+
+        for problem in &self.problems {
+            tracing::warn!("{problem}");
+            diagnostics::emit_warning("context-loader", "context-file-skipped", problem);
+        }
+        loaded
+    }
+
+    /// Pinned subsystem files first, then the files past iterations touched most.
+    /// Touched files that no longer exist are dropped quietly.
+    fn feature_files(&self, subsystem: &str) -> Vec<String> {
+        let mut files = self.db.get_subsystem_context_files(subsystem);
+        for path in self.db.get_subsystem_knowledge_paths(subsystem) {
+            if !files.contains(&path) {
+                files.push(path);
+            }
+        }
+
+        let touched: Vec<String> = rank_touched_files(&read_journal(self.project_path, subsystem))
+            .into_iter()
+            .filter(|path| !files.contains(path) && self.project_path.join(path).is_file())
+            .take(MAX_TOUCHED_FILES)
+            .collect();
+        files.extend(touched);
+        files
+    }
+
+    fn read_state_file(&mut self, name: &str) -> Option<String> {
+        let path = self.project_path.join(".ralph").join(name);
+        if !path.exists() {
+            return None;
+        }
+        match read_text_file(&path, MAX_FILE_BYTES) {
+            Ok(content) => Some(content),
+            Err(error) => {
+                self.problems
+                    .push(format!("Skipped .ralph/{name}: {error}"));
+                None
+            }
+        }
+    }
+
+    /// Read files in order until the total budget runs out. Keys are the paths as given.
+    fn read_context_files(&mut self, paths: &[String]) -> HashMap<String, String> {
+        let mut contents = HashMap::new();
+        let mut remaining = MAX_TOTAL_BYTES;
+
+        for path in paths {
+            if contents.contains_key(path) {
+                continue;
+            }
+            if remaining == 0 {
+                self.problems.push(format!(
+                    "Skipped context file {path}: context size budget used up"
+                ));
+                continue;
+            }
+            let result = resolve_project_file(self.project_path, path)
+                .and_then(|resolved| read_text_file(&resolved, MAX_FILE_BYTES.min(remaining)));
+            match result {
+                Ok(content) => {
+                    remaining = remaining.saturating_sub(content.len());
+                    contents.insert(path.clone(), content);
+                }
+                Err(error) => self
+                    .problems
+                    .push(format!("Skipped context file {path}: {error}")),
+            }
+        }
+        contents
+    }
+
+    fn relevant_comments(
+        &self,
+        feature: &Subsystem,
+        query: &QueryEmbedding,
+    ) -> Vec<ScoredFeatureComment> {
+        self.db
+            .search_subsystem_comments(
+                &feature.name,
+                &query.embedding,
+                query.max_results,
+                query.min_score,
+            )
+            .into_iter()
+            .map(|row| ScoredFeatureComment {
+                category: row.category,
+                body: row.body,
+                summary: row.summary,
+                reason: row.reason,
+                score: row.score,
+            })
+            .collect()
+    }
+}
+
+/// What a prompt needs besides the project's database and files.
+pub struct PromptInputs {
+    pub user_input: Option<String>,
+    pub instruction_overrides: HashMap<String, String>,
+    pub target_task_id: Option<u32>,
+    /// Where the MCP scripts are written.
+    pub script_dir: PathBuf,
+    pub api_server_port: Option<u16>,
+    pub codebase_snapshot: Option<CodebaseSnapshot>,
+}
+
+/// Read everything a prompt needs from the project. `query_embedding` turns on the RAG
+/// comment search for the target task.
+pub fn assemble_prompt_context(
+    db: &SqliteDb,
+    project_path: &Path,
+    inputs: PromptInputs,
+    query_embedding: Option<&QueryEmbedding>,
+) -> Result<PromptContext, String> {
+    let db_path = project_path.join(".ralph").join("db").join("ralph.db");
+    let section_templates = load_section_templates(project_path)?;
+
+    let features = db.get_subsystems();
+    let tasks = db.get_tasks();
+    let target_task = inputs
+        .target_task_id
+        .and_then(|id| tasks.iter().find(|task| task.id == id));
+    let target_feature =
+        target_task.and_then(|task| features.iter().find(|f| f.name == task.subsystem));
+    let loaded =
+        ContextLoader::new(project_path, db).load(target_task, target_feature, query_embedding);
+    let target_feature_name = target_feature.map(|feature| feature.name.clone());
+
+    Ok(PromptContext {
+        features,
+        tasks,
+        disciplines: db.get_disciplines(),
+        metadata: db.get_project_info(),
+        file_contents: loaded.file_contents,
+        feature_files: loaded.feature_files,
+        progress_txt: loaded.progress_txt,
+        learnings_txt: loaded.learnings_txt,
+        claude_ralph_md: loaded.claude_ralph_md,
+        project_path: project_path.to_string_lossy().to_string(),
+        db_path: db_path.to_string_lossy().to_string(),
+        script_dir: inputs.script_dir.to_string_lossy().to_string(),
+        api_server_port: inputs.api_server_port,
+        user_input: inputs.user_input,
+        target_task_id: inputs.target_task_id,
+        target_feature: target_feature_name,
+        codebase_snapshot: inputs.codebase_snapshot,
+        instruction_overrides: inputs.instruction_overrides,
+        section_templates,
+        relevant_comments: loaded.relevant_comments,
+    })
+}
+
+/// The task a prompt targets, when its subsystem has comment embeddings to search.
+pub(super) fn rag_target(db: &SqliteDb, task_id: Option<u32>) -> Option<Task> {
+    let task = db.get_task_by_id(task_id?)?;
+    let has_embeddings = db
+        .get_subsystems()
+        .iter()
+        .filter(|feature| feature.name == task.subsystem)
+        .flat_map(|feature| &feature.comments)
+        .any(|comment| db.has_comment_embedding(comment.id));
+    has_embeddings.then_some(task)
+}
+
+/// Ollama settings for embedding comments and the queries that search them.
+pub fn build_embedding_config(
+    ext_config: &ralph_external::ExternalServicesConfig,
+) -> ralph_external::comment_embeddings::CommentEmbeddingConfig<'_> {
+    ralph_external::comment_embeddings::CommentEmbeddingConfig {
+        ollama: &ext_config.ollama,
+        document_prefix: "search_document: ",
+        query_prefix: "search_query: ",
+        min_search_score: 0.4,
+        max_search_results: 10,
+    }
+}
+
+/// `None` keeps the old behavior of injecting every comment: used when Ollama can't embed
+/// the query.
+pub(super) fn embed_rag_query(task: &Task) -> Option<QueryEmbedding> {
+    let ext_config = match ralph_external::ExternalServicesConfig::load() {
+        Ok(config) => config,
+        Err(error) => {
+            tracing::warn!(error = %error, "RAG search skipped: external services config");
+            return None;
+        }
+    };
+    let embed_config = build_embedding_config(&ext_config);
+    match block_on_embed_query(&embed_config, &rag_query(task)) {
+        Ok(embedding) => Some(QueryEmbedding {
+            embedding,
+            max_results: embed_config.max_search_results as usize,
+            min_score: embed_config.min_search_score,
+        }),
+        Err(error) => {
+            tracing::warn!(task_id = task.id, error = %error, "RAG search skipped");
+            None
+        }
+    }
+}
+
+/// Rank files by how often past iterations touched them, recent iterations weighing more.
+/// Deleted files and infrastructure files (lockfiles, build output) are left out.
+fn rank_touched_files(entries: &[JournalEntry]) -> Vec<String> {
+    let mut scores: HashMap<&str, f64> = HashMap::new();
+    for (age, entry) in entries.iter().rev().enumerate() {
+        let weight = 0.5_f64.powf(age as f64 / TOUCH_HALF_LIFE_ITERATIONS);
+        let mut seen = Vec::new();
+        for touched in &entry.record.files_touched {
+            let path = touched.path.as_str();
+            if touched.action == FileAction::Deleted
+                || should_exclude_from_auto_accumulation(path)
+                || seen.contains(&path)
+            {
+                continue;
+            }
+            seen.push(path);
+            *scores.entry(path).or_default() += weight;
+        }
+    }
+
+    let mut ranked: Vec<(&str, f64)> = scores.into_iter().collect();
+    ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+    ranked
+        .into_iter()
+        .map(|(path, _)| path.to_owned())
+        .collect()
+}
+
+fn rag_query(task: &Task) -> String {
+    let mut query = task.title.clone();
+    if let Some(description) = &task.description {
+        query.push('\n');
+        query.push_str(description);
+    }
+    for criterion in &task.acceptance_criteria {
+        query.push('\n');
+        query.push_str(criterion);
+    }
+    query
+}
+
+fn block_on_embed_query(
+    config: &ralph_external::comment_embeddings::CommentEmbeddingConfig<'_>,
+    query: &str,
+) -> Result<Vec<f32>, String> {
+    if tokio::runtime::Handle::try_current().is_ok() {
+        return Err("cannot block on the embedding request from an async task".to_owned());
+    }
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(|error| format!("failed to start the embedding runtime: {error}"))?;
+    runtime.block_on(async {
+        tokio::time::timeout(
+            RAG_QUERY_TIMEOUT,
+            ralph_external::comment_embeddings::embed_query(config, query),
+        )
+        .await
+        .map_err(|_| "embedding request timed out".to_owned())?
+    })
+}
+
+/// Resolve a project-relative path, rejecting anything that lands outside the project
+/// (absolute paths, `..`, symlinks pointing elsewhere).
+pub(super) fn resolve_project_file(project_path: &Path, relative: &str) -> Result<PathBuf, String> {
+    let relative_path = Path::new(relative.trim());
+    if relative_path.as_os_str().is_empty() {
+        return Err("empty path".to_owned());
+    }
+    if relative_path.is_absolute() {
+        return Err("absolute paths are not allowed".to_owned());
+    }
+
+    let root = project_path
+        .canonicalize()
+        .map_err(|e| format!("cannot resolve project root: {e}"))?;
+    let resolved = root
+        .join(relative_path)
+        .canonicalize()
+        .map_err(|e| format!("cannot open: {e}"))?;
+    if !resolved.starts_with(&root) {
+        return Err("path escapes the project directory".to_owned());
+    }
+    if !resolved.is_file() {
+        return Err("not a regular file".to_owned());
+    }
+    Ok(resolved)
+}
+
+/// Read at most `max_bytes` of a UTF-8 text file, marking the cut when truncated.
+fn read_text_file(path: &Path, max_bytes: usize) -> Result<String, String> {
+    let file = std::fs::File::open(path).map_err(|e| format!("cannot open: {e}"))?;
+    let total_bytes = file
+        .metadata()
+        .map_err(|e| format!("cannot stat: {e}"))?
+        .len();
+
+    let mut bytes = Vec::new();
+    file.take(max_bytes as u64)
+        .read_to_end(&mut bytes)
+        .map_err(|e| format!("cannot read: {e}"))?;
+    let truncated = total_bytes > bytes.len() as u64;
+
+    if bytes[..bytes.len().min(BINARY_SNIFF_BYTES)].contains(&0) {
+        return Err("binary file".to_owned());
+    }
+    let mut text = match String::from_utf8(bytes) {
+        Ok(text) => text,
+        // The cut landed inside a multi-byte character; drop the partial character.
+        Err(error) if truncated && error.utf8_error().error_len().is_none() => {
+            let valid_up_to = error.utf8_error().valid_up_to();
+            let mut bytes = error.into_bytes();
+            bytes.truncate(valid_up_to);
+            String::from_utf8(bytes).map_err(|_| "not valid UTF-8".to_owned())?
+        }
+        Err(_) => return Err("not valid UTF-8".to_owned()),
+    };
+
+    if truncated {
+        text.push_str(&format!("\n… (truncated, {total_bytes} bytes total)"));
+    }
+    Ok(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn resolve_rejects_paths_outside_project() {
+        let dir = tempdir().unwrap();
+        let project = dir.path().join("project");
+        std::fs::create_dir_all(project.join("src")).unwrap();
+        std::fs::write(project.join("src/lib.rs"), "fn main() {}\n").unwrap();
+        std::fs::write(dir.path().join("secret.txt"), "token\n").unwrap();
+
+        assert!(resolve_project_file(&project, "src/lib.rs").is_ok());
+        assert!(resolve_project_file(&project, "./src/../src/lib.rs").is_ok());
+        assert!(resolve_project_file(&project, "../secret.txt")
+            .unwrap_err()
+            .contains("escapes"));
+        let absolute = dir.path().join("secret.txt");
+        assert!(resolve_project_file(&project, &absolute.to_string_lossy())
+            .unwrap_err()
+            .contains("absolute"));
+        assert!(resolve_project_file(&project, "src").is_err());
+        assert!(resolve_project_file(&project, "missing.rs").is_err());
+
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(dir.path().join("secret.txt"), project.join("link.txt"))
+                .unwrap();
+            assert!(resolve_project_file(&project, "link.txt")
+                .unwrap_err()
+                .contains("escapes"));
+        }
+    }
+
+    #[test]
+    fn read_text_file_truncates_and_rejects_binary() {
+        let dir = tempdir().unwrap();
+        let text = dir.path().join("long.txt");
+        std::fs::write(&text, "é".repeat(10)).unwrap();
+
+        let content = read_text_file(&text, 5).unwrap();
+        assert!(content.starts_with("éé\n"));
+        assert!(content.ends_with("(truncated, 20 bytes total)"));
+        assert_eq!(read_text_file(&text, 1024).unwrap(), "é".repeat(10));
+
+        let binary = dir.path().join("image.png");
+        std::fs::write(&binary, [0x89, b'P', b'N', b'G', 0, 0, 1]).unwrap();
+        assert_eq!(read_text_file(&binary, 1024).unwrap_err(), "binary file");
+
+        let latin1 = dir.path().join("latin1.txt");
+        std::fs::write(&latin1, [b'c', b'a', 0xe9]).unwrap();
+        assert_eq!(
+            read_text_file(&latin1, 1024).unwrap_err(),
+            "not valid UTF-8"
+        );
+    }
+
+    #[test]
+    fn context_files_skip_problems_and_respect_budget() {
+        let dir = tempdir().unwrap();
+        let full_files: Vec<String> = (0..MAX_TOTAL_BYTES / MAX_FILE_BYTES)
+            .map(|index| format!("full-{index}.rs"))
+            .collect();
+        for name in &full_files {
+            std::fs::write(dir.path().join(name), "a".repeat(MAX_FILE_BYTES)).unwrap();
+        }
+        std::fs::write(dir.path().join("late.rs"), "b").unwrap();
+        let db = SqliteDb::open_in_memory(None).unwrap();
+        let mut loader = ContextLoader::new(dir.path(), &db);
+
+        let mut paths = vec!["missing.rs".to_owned(), full_files[0].clone()];
+        paths.extend(full_files.iter().cloned());
+        paths.push("late.rs".to_owned());
+        let contents = loader.read_context_files(&paths);
+
+        assert_eq!(contents.len(), full_files.len());
+        assert!(contents
+            .values()
+            .all(|content| content.len() == MAX_FILE_BYTES));
+        assert_eq!(loader.problems.len(), 2);
+        assert!(loader.problems[0].starts_with("Skipped context file missing.rs"));
+        assert!(loader.problems[1].contains("late.rs: context size budget used up"));
+    }
+
+    fn journal_entry(files: &[(&str, FileAction)]) -> JournalEntry {
+        use ralph_rag::model::{FileTouched, IterationOutcome, IterationRecord, ModelTier};
+        JournalEntry::new(IterationRecord {
+            iteration_number: 1,
+            task_id: 1,
+            task_title: "Task".into(),
+            feature: "auth".into(),
+            discipline: "backend".into(),
+            timestamp: "2026-02-07T10:00:00Z".into(),
+            outcome: IterationOutcome::Success,
+            summary: "Done".into(),
+            errors: vec![],
+            decisions: vec![],
+            files_touched: files
+                .iter()
+                .map(|(path, action)| FileTouched {
+                    path: (*path).to_owned(),
+                    action: *action,
+                })
+                .collect(),
+            tokens_used: None,
+            duration_ms: None,
+            model_tier: ModelTier::Haiku,
+        })
+    }
+
+    #[test]
+    fn touched_files_rank_by_frequency_and_recency() {
+        let entries = vec![
+            journal_entry(&[("src/old.rs", FileAction::Modified)]),
+            journal_entry(&[("src/old.rs", FileAction::Modified)]),
+            journal_entry(&[
+                ("src/often.rs", FileAction::Modified),
+                ("src/often.rs", FileAction::Read),
+            ]),
+            journal_entry(&[("src/often.rs", FileAction::Read)]),
+            journal_entry(&[
+                ("src/often.rs", FileAction::Modified),
+                ("src/latest.rs", FileAction::Created),
+                ("src/gone.rs", FileAction::Deleted),
+                ("Cargo.lock", FileAction::Modified),
+            ]),
+        ];
+
+        assert_eq!(
+            rank_touched_files(&entries),
+            vec!["src/often.rs", "src/old.rs", "src/latest.rs"]
+        );
+    }
+
+    #[test]
+    fn state_files_load_from_ralph_dir() {
+        let dir = tempdir().unwrap();
+        let ralph_dir = dir.path().join(".ralph");
+        std::fs::create_dir_all(&ralph_dir).unwrap();
+        std::fs::write(ralph_dir.join("CLAUDE.RALPH.md"), "# Project\n").unwrap();
+        std::fs::write(ralph_dir.join("progress.txt"), "step 1 done\n").unwrap();
+        let db = SqliteDb::open_in_memory(None).unwrap();
+
+        let loaded = ContextLoader::new(dir.path(), &db).load(None, None, None);
+
+        assert_eq!(loaded.claude_ralph_md.as_deref(), Some("# Project\n"));
+        assert_eq!(loaded.progress_txt.as_deref(), Some("step 1 done\n"));
+        assert_eq!(loaded.learnings_txt, None);
+        assert!(loaded.file_contents.is_empty());
+    }
+}
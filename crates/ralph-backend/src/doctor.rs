@@ -0,0 +1,463 @@
+//! The project doctor: named checks across the locked project's database, its `.ralph/` files
+//! and the tools and services runs depend on, so problems show up before a run rather than
+//! halfway through one. Checks with a safe automatic fix apply it when asked to.
+
+use crate::context_loader::resolve_project_file;
+use crate::project::{claude_ralph_md_template, CLAUDE_RALPH_MD};
+use crate::state::{AppState, CommandContext};
+use ralph_macros::ipc_type;
+use serde::{Deserialize, Serialize};
+use sqlite_db::{SqliteDb, SubsystemStatus, Task, TaskStatus};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+#[ipc_type]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DoctorSeverity {
+    Ok,
+    /// Worth knowing; nothing breaks.
+    Info,
+    /// Something works worse or is skipped.
+    Warning,
+    /// Runs will fail.
+    Error,
+}
+
+impl DoctorSeverity {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Ok => "ok",
+            Self::Info => "info",
+            Self::Warning => "warning",
+            Self::Error => "error",
+        }
+    }
+}
+
+#[ipc_type]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DoctorCheck {
+    /// Stable name of the check, e.g. `context_files`.
+    pub name: String,
+    pub severity: DoctorSeverity,
+    pub message: String,
+    /// One line per offending item.
+    pub details: Vec<String>,
+    /// What the automatic fix would do, for a problem that has a safe one.
+    pub fix: Option<String>,
+    /// Whether this run applied the fix.
+    pub fixed: bool,
+}
+
+impl DoctorCheck {
+    fn ok(name: &str, message: impl Into<String>) -> Self {
+        Self::problem(name, DoctorSeverity::Ok, message, Vec::new())
+    }
+
+    fn problem(
+        name: &str,
+        severity: DoctorSeverity,
+        message: impl Into<String>,
+        details: Vec<String>,
+    ) -> Self {
+        Self {
+            name: name.to_owned(),
+            severity,
+            message: message.into(),
+            details,
+            fix: None,
+            fixed: false,
+        }
+    }
+
+    fn failed(name: &str, error: &str) -> Self {
+        Self::problem(
+            name,
+            DoctorSeverity::Error,
+            format!("The check could not run: {error}"),
+            Vec::new(),
+        )
+    }
+
+    fn fixed(name: &str, message: impl Into<String>) -> Self {
+        Self {
+            fixed: true,
+            ..Self::ok(name, message)
+        }
+    }
+
+    fn with_fix(self, fix: &str) -> Self {
+        Self {
+            fix: Some(fix.to_owned()),
+            ..self
+        }
+    }
+}
+
+/// Run every check on the locked project. With `fix`, problems that have a safe fix are
+/// fixed and reported as such.
+pub async fn run_doctor(state: &AppState, fix: bool) -> Result<Vec<DoctorCheck>, String> {
+    let ctx = CommandContext::new(state);
+    let project_path = ctx.locked_project_path()?;
+    let mut checks = ctx.db(|db| {
+        Ok(vec![
+            check_database(db),
+            check_claude_ralph_md(db, &project_path, fix),
+            check_context_files(db, &project_path),
+            check_archived_work(db),
+            check_discipline_images(db, &project_path, fix),
+            check_mcp_servers(db),
+        ])
+    })?;
+    checks.push(check_text_mirror(state));
+    checks.push(check_bun());
+    checks.push(check_ollama().await);
+    Ok(checks)
+}
+
+fn check_database(db: &SqliteDb) -> DoctorCheck {
+    const NAME: &str = "database";
+    match db.check_integrity() {
+        Ok(problems) if problems.is_empty() => DoctorCheck::ok(NAME, "The database is consistent"),
+        Ok(problems) => DoctorCheck::problem(
+            NAME,
+            DoctorSeverity::Error,
+            "The database is damaged; restore a backup",
+            problems,
+        ),
+        Err(error) => DoctorCheck::failed(NAME, &error),
+    }
+}
+
+fn check_claude_ralph_md(db: &SqliteDb, project_path: &Path, fix: bool) -> DoctorCheck {
+    const NAME: &str = "claude_ralph_md";
+    let path = project_path.join(".ralph").join(CLAUDE_RALPH_MD);
+    if path.is_file() {
+        return DoctorCheck::ok(NAME, format!("{CLAUDE_RALPH_MD} is present"));
+    }
+    if !fix {
+        return DoctorCheck::problem(
+            NAME,
+            DoctorSeverity::Warning,
+            format!(".ralph/{CLAUDE_RALPH_MD} is missing; agents start without project context"),
+            Vec::new(),
+        )
+        .with_fix("Create it from the starter template");
+    }
+    match std::fs::write(
+        &path,
+        claude_ralph_md_template(&db.get_project_info().title),
+    ) {
+        Ok(()) => DoctorCheck::fixed(
+            NAME,
+            format!("Created .ralph/{CLAUDE_RALPH_MD} from the starter template"),
+        ),
+        Err(error) => DoctorCheck::failed(NAME, &error.to_string()),
+    }
+}
+
+/// Tasks a run can still pick up.
+fn open_tasks(tasks: Vec<Task>) -> impl Iterator<Item = Task> {
+    tasks
+        .into_iter()
+        .filter(|task| !matches!(task.status, TaskStatus::Done | TaskStatus::Skipped))
+}
+
+fn check_context_files(db: &SqliteDb, project_path: &Path) -> DoctorCheck {
+    const NAME: &str = "context_files";
+    let tasks = match db.list_tasks(false) {
+        Ok(tasks) => tasks,
+        Err(error) => return DoctorCheck::failed(NAME, &error),
+    };
+    let details: Vec<String> = open_tasks(tasks)
+        .flat_map(|task| {
+            task.context_files
+                .iter()
+                .filter_map(|file| {
+                    let error = resolve_project_file(project_path, file).err()?;
+                    Some(format!("Task #{}: {file} ({error})", task.id))
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    if details.is_empty() {
+        return DoctorCheck::ok(NAME, "Every context file of open tasks exists");
+    }
+    DoctorCheck::problem(
+        NAME,
+        DoctorSeverity::Warning,
+        format!(
+            "{} context file(s) can't be read; agents will start without them",
+            details.len()
+        ),
+        details,
+    )
+}
+
+/// Unfinished work that archiving keeps from ever running: tasks left in an archived
+/// subsystem, and tasks waiting on an archived task that isn't done.
+fn check_archived_work(db: &SqliteDb) -> DoctorCheck {
+    const NAME: &str = "archived_work";
+    let (tasks, hidden) = match db.list_tasks(true).and_then(|tasks| {
+        let hidden = db.archived_task_ids()?;
+        Ok((tasks, hidden))
+    }) {
+        Ok(found) => found,
+        Err(error) => return DoctorCheck::failed(NAME, &error),
+    };
+    let archived_subsystems: HashSet<String> = db
+        .list_subsystems(true)
+        .into_iter()
+        .filter(|subsystem| subsystem.status == SubsystemStatus::Archived)
+        .map(|subsystem| subsystem.name)
+        .collect();
+    let unfinished: HashSet<u32> = tasks
+        .iter()
+        .filter(|task| task.status != TaskStatus::Done)
+        .map(|task| task.id)
+        .collect();
+
+    let mut details = Vec::new();
+    for task in open_tasks(tasks) {
+        if task.archived_at.is_some() {
+            continue;
+        }
+        if archived_subsystems.contains(&task.subsystem) {
+            details.push(format!(
+                "Task #{} '{}' is in archived subsystem '{}'",
+                task.id, task.title, task.subsystem
+            ));
+        } else if let Some(dependency) = task
+            .depends_on
+            .iter()
+            .find(|&&id| hidden.contains(&id) && unfinished.contains(&id))
+        {
+            details.push(format!(
+                "Task #{} '{}' depends on #{dependency}, which is archived and not done",
+                task.id, task.title
+            ));
+        }
+    }
+    if details.is_empty() {
+        return DoctorCheck::ok(NAME, "No open task is held up by archived work");
+    }
+    DoctorCheck::problem(
+        NAME,
+        DoctorSeverity::Warning,
+        format!(
+            "{} task(s) will never run; archive them or unarchive what they need",
+            details.len()
+        ),
+        details,
+    )
+}
+
+fn check_discipline_images(db: &SqliteDb, project_path: &Path, fix: bool) -> DoctorCheck {
+    const NAME: &str = "discipline_images";
+    let ralph_dir = project_path.join(".ralph");
+    let missing: Vec<(String, String)> = db
+        .get_disciplines()
+        .into_iter()
+        .filter_map(|discipline| {
+            let image_path = discipline.image_path?;
+            (!ralph_dir.join(&image_path).is_file()).then_some((discipline.name, image_path))
+        })
+        .collect();
+    if missing.is_empty() {
+        return DoctorCheck::ok(NAME, "Every discipline image exists");
+    }
+    let details = missing
+        .iter()
+        .map(|(name, image_path)| format!("{name}: .ralph/{image_path}"))
+        .collect();
+    if !fix {
+        return DoctorCheck::problem(
+            NAME,
+            DoctorSeverity::Info,
+            format!("{} discipline image(s) are missing", missing.len()),
+            details,
+        )
+        .with_fix("Clear them so the disciplines show their icons");
+    }
+    for (name, _) in &missing {
+        if let Err(error) = db.clear_discipline_image(name) {
+            return DoctorCheck::failed(NAME, &error);
+        }
+    }
+    DoctorCheck {
+        details,
+        ..DoctorCheck::fixed(
+            NAME,
+            format!("Cleared {} missing discipline image(s)", missing.len()),
+        )
+    }
+}
+
+fn check_mcp_servers(db: &SqliteDb) -> DoctorCheck {
+    const NAME: &str = "mcp_servers";
+    let details: Vec<String> = db
+        .get_disciplines()
+        .iter()
+        .flat_map(|discipline| {
+            discipline
+                .mcp_servers
+                .iter()
+                .filter(|server| find_on_path(&server.command).is_none())
+                .map(|server| {
+                    format!(
+                        "{}: '{}' runs {}, which is not on PATH",
+                        discipline.name, server.name, server.command
+                    )
+                })
+        })
+        .collect();
+    if details.is_empty() {
+        return DoctorCheck::ok(NAME, "Every discipline MCP server command is on PATH");
+    }
+    DoctorCheck::problem(
+        NAME,
+        DoctorSeverity::Warning,
+        format!("{} MCP server(s) can't start", details.len()),
+        details,
+    )
+}
+
+fn check_text_mirror(state: &AppState) -> DoctorCheck {
+    const NAME: &str = "text_mirror";
+    let conflicts = match state.text_mirror.conflicts() {
+        Ok(conflicts) => conflicts,
+        Err(error) => return DoctorCheck::failed(NAME, &error),
+    };
+    if conflicts.is_empty() {
+        return DoctorCheck::ok(NAME, "No text mirror conflicts");
+    }
+    let details = conflicts
+        .iter()
+        .map(|conflict| {
+            conflict.line.map_or_else(
+                || format!("{}: {}", conflict.path, conflict.message),
+                |line| format!("{}:{line}: {}", conflict.path, conflict.message),
+            )
+        })
+        .collect();
+    DoctorCheck::problem(
+        NAME,
+        DoctorSeverity::Warning,
+        "Some .ralph/mirror/ files don't import; syncing is paused until they are fixed",
+        details,
+    )
+}
+
+fn check_bun() -> DoctorCheck {
+    const NAME: &str = "bun";
+    find_on_path("bun").map_or_else(
+        || {
+            DoctorCheck::problem(
+                NAME,
+                DoctorSeverity::Error,
+                "bun is not on PATH; agents can't start the ralph-signals MCP server or report back",
+                Vec::new(),
+            )
+        },
+        |path| DoctorCheck::ok(NAME, format!("bun is at {}", path.display())),
+    )
+}
+
+async fn check_ollama() -> DoctorCheck {
+    const NAME: &str = "ollama";
+    let config = match ralph_external::ExternalServicesConfig::load() {
+        Ok(config) => config.ollama,
+        Err(error) => return DoctorCheck::failed(NAME, &error),
+    };
+    let status = ralph_external::check_ollama_available(&config).await;
+    if !status.available {
+        return DoctorCheck::problem(
+            NAME,
+            DoctorSeverity::Warning,
+            format!(
+                "Ollama is not reachable at {}; comment search and task enrichment are off",
+                config.api_url
+            ),
+            status.error.into_iter().collect(),
+        );
+    }
+    let missing: Vec<String> = [&config.embedding_model, &config.llm_model]
+        .into_iter()
+        .filter(|model| !has_model(&status.models, model))
+        .map(|model| format!("Run `ollama pull {model}`"))
+        .collect();
+    if missing.is_empty() {
+        return DoctorCheck::ok(NAME, format!("Ollama is up at {}", config.api_url));
+    }
+    DoctorCheck::problem(
+        NAME,
+        DoctorSeverity::Warning,
+        "Ollama is up but lacks configured models",
+        missing,
+    )
+}
+
+/// Ollama lists untagged models as `name:latest`.
+fn has_model(installed: &[String], model: &str) -> bool {
+    installed
+        .iter()
+        .any(|name| name == model || name.strip_suffix(":latest") == Some(model))
+}
+
+/// Where `command` resolves: itself if it is a path, otherwise the first match on PATH.
+fn find_on_path(command: &str) -> Option<PathBuf> {
+    let command = Path::new(command);
+    if command.components().count() > 1 {
+        return command.is_file().then(|| command.to_path_buf());
+    }
+    std::env::split_paths(&std::env::var_os("PATH")?)
+        .map(|dir| dir.join(command))
+        .find(|candidate| candidate.is_file())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn find_on_path_resolves_bare_names_and_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        let tool = dir.path().join("tool");
+        std::fs::write(&tool, "").unwrap();
+
+        assert_eq!(find_on_path(&tool.to_string_lossy()), Some(tool));
+        assert!(find_on_path(&dir.path().join("nope").to_string_lossy()).is_none());
+        assert!(find_on_path("ralph-doctor-no-such-command").is_none());
+    }
+
+    #[test]
+    fn has_model_accepts_the_latest_tag() {
+        let installed = vec!["nomic-embed-text:latest".to_owned(), "qwen:7b".to_owned()];
+        assert!(has_model(&installed, "nomic-embed-text"));
+        assert!(has_model(&installed, "qwen:7b"));
+        assert!(!has_model(&installed, "qwen"));
+    }
+
+    #[test]
+    fn missing_claude_ralph_md_is_created_only_with_fix() {
+        let project = tempfile::tempdir().unwrap();
+        std::fs::create_dir(project.path().join(".ralph")).unwrap();
+        let db = SqliteDb::open_in_memory(None).unwrap();
+
+        let check = check_claude_ralph_md(&db, project.path(), false);
+        assert_eq!(check.severity, DoctorSeverity::Warning);
+        assert!(check.fix.is_some());
+        assert!(!project.path().join(".ralph").join(CLAUDE_RALPH_MD).exists());
+
+        let check = check_claude_ralph_md(&db, project.path(), true);
+        assert!(check.fixed);
+        assert_eq!(check.severity, DoctorSeverity::Ok);
+        assert!(project
+            .path()
+            .join(".ralph")
+            .join(CLAUDE_RALPH_MD)
+            .is_file());
+    }
+}
@@ -0,0 +1,383 @@
+//! The execution loop: fills worker slots with runnable tasks, settles each task when its
+//! session exits and starts reviews of the ones that need one.
+
+use crate::session_host::SessionHost;
+use crate::session_launch::{generate_agent_session_id, launch_agent_session, SessionStarter};
+use crate::state::{AppState, CommandContext, REVIEW_SESSION_KIND};
+use crate::terminal::{SessionExit, SessionExitHook, TerminalBridgeStartHumanSessionArgs};
+use crate::worker_pool::{self, ExecutionState, ExecutionStatus, ExecutionWorker};
+use ralph_errors::{codes, ralph_err, ToStringErr};
+use ralph_macros::ipc_type;
+use serde::{Deserialize, Serialize};
+use sqlite_db::{Task, TaskStatus};
+
+#[ipc_type]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecutionStartArgs {
+    /// Maximum tasks running at once; keeps the current limit when omitted.
+    pub concurrency: Option<u32>,
+    /// Run each task in its own git worktree.
+    pub use_worktrees: Option<bool>,
+}
+
+#[ipc_type]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecutionConcurrencySetArgs {
+    pub concurrency: u32,
+}
+
+fn warn_execution(code: &str, message: &str) {
+    tracing::warn!("{message}");
+    crate::diagnostics::emit_warning("execution", code, message);
+}
+
+/// Task launch options fall back to the task's discipline, like the task sidebar's "Execute".
+fn worker_session_args(
+    db: &sqlite_db::SqliteDb,
+    task: &Task,
+    terminal_session_id: String,
+    use_worktree: bool,
+) -> TerminalBridgeStartHumanSessionArgs {
+    let discipline = db
+        .get_disciplines()
+        .into_iter()
+        .find(|discipline| discipline.name == task.discipline);
+    let from_discipline =
+        |pick: fn(&sqlite_db::Discipline) -> Option<String>| discipline.as_ref().and_then(pick);
+    let model = task
+        .model
+        .clone()
+        .or_else(|| from_discipline(|d| d.model.clone()));
+    let effort = model.as_ref().and(
+        task.effort
+            .clone()
+            .or_else(|| from_discipline(|d| d.effort.clone())),
+    );
+
+    TerminalBridgeStartHumanSessionArgs {
+        terminal_session_id,
+        kind: "task_execution".to_owned(),
+        task_id: Some(task.id),
+        agent: task
+            .agent
+            .clone()
+            .or_else(|| from_discipline(|d| d.agent.clone())),
+        model,
+        effort,
+        permission_level: None,
+        post_start_preamble: None,
+        init_prompt: None,
+        mcp_mode: None,
+        thinking: task
+            .thinking
+            .or_else(|| discipline.as_ref().and_then(|d| d.thinking)),
+        use_worktree: Some(use_worktree),
+    }
+}
+
+/// Review sessions run with the discipline's review agent and model, falling back to the ones
+/// the task ran with. A different reviewer agent starts from its own default model.
+fn review_session_args(
+    db: &sqlite_db::SqliteDb,
+    task: &Task,
+    terminal_session_id: String,
+    use_worktree: bool,
+    brief: String,
+) -> TerminalBridgeStartHumanSessionArgs {
+    let settings = db.get_discipline_review_settings(&task.discipline);
+    let mut args = worker_session_args(db, task, terminal_session_id, use_worktree);
+    REVIEW_SESSION_KIND.clone_into(&mut args.kind);
+    args.init_prompt = Some(brief);
+    if settings.agent.is_some() && settings.agent != args.agent {
+        args.agent = settings.agent;
+        args.model = None;
+        args.effort = None;
+    }
+    if settings.model.is_some() && settings.model != args.model {
+        args.model = settings.model;
+        args.effort = None;
+    }
+    args
+}
+
+/// Move the task off `in_progress` according to the closing signal its session sent.
+/// Returns the task instead when it reported `done` and its discipline requires a review;
+/// it stays `in_progress` until the review settles it.
+fn settle_worker_task(
+    state: &AppState,
+    task_id: u32,
+    baseline_signal_id: u32,
+) -> Result<Option<Task>, String> {
+    CommandContext::new(state).db(|db| {
+        let Some(task) = db.get_task_by_id(task_id) else {
+            return Ok(None);
+        };
+        if task.status != TaskStatus::InProgress {
+            return Ok(None);
+        }
+        let closing_verb = worker_pool::closing_verb_since(&task, baseline_signal_id);
+        let status = worker_pool::task_status_after_session(closing_verb);
+        if status == TaskStatus::Done
+            && db.get_discipline_review_settings(&task.discipline).required
+        {
+            return Ok(Some(task));
+        }
+        db.set_task_status(task_id, status)?;
+        Ok(None)
+    })
+}
+
+/// Confirm or reopen a reviewed task according to the review's verdict.
+fn settle_review_task(
+    state: &AppState,
+    task_id: u32,
+    baseline_signal_id: u32,
+) -> Result<(), String> {
+    // `None` when someone else already moved the task off `in_progress`.
+    let settled = CommandContext::new(state).db(|db| {
+        let Some(task) = db.get_task_by_id(task_id) else {
+            return Ok(None);
+        };
+        if task.status != TaskStatus::InProgress {
+            return Ok(None);
+        }
+        let verdict = worker_pool::review_verdict_since(&task, baseline_signal_id);
+        db.set_task_status(task_id, worker_pool::task_status_after_review(verdict))?;
+        Ok(Some(verdict))
+    })?;
+    match settled {
+        Some(Some(verdict)) => tracing::info!(task_id, ?verdict, "Task review finished"),
+        Some(None) => warn_execution(
+            "review-no-verdict",
+            &format!("Review of task {task_id} ended without a verdict; blocking it for a person to check"),
+        ),
+        None => {}
+    }
+    Ok(())
+}
+
+/// Start the review of a task whose worker reported `done`. The reviewer takes a worker slot.
+fn start_review<H: SessionHost>(
+    host: &H,
+    task: &Task,
+    implementer: &ExecutionWorker,
+) -> Result<(), String> {
+    let mut pool = host.app_state().execution.lock().err_str(codes::INTERNAL)?;
+    if !matches!(
+        pool.status(),
+        ExecutionStatus::Running | ExecutionStatus::Paused
+    ) {
+        return ralph_err!(codes::LOOP_ENGINE, "Execution is stopping");
+    }
+    let use_worktrees = pool.use_worktrees();
+    let (worker, baseline_signal_id) = start_worker(
+        host,
+        task,
+        use_worktrees,
+        Some(&implementer.agent_session_id),
+    )?;
+    pool.add_worker(worker, baseline_signal_id);
+    Ok(())
+}
+
+/// Settle the task of a finished worker, starting its review when one is required.
+fn finish_worker<H: SessionHost>(host: &H, worker: &ExecutionWorker, baseline: u32) {
+    let state = host.app_state();
+    let task_id = worker.task_id;
+    let settled = if worker.review {
+        settle_review_task(state, task_id, baseline).map(|()| None)
+    } else {
+        settle_worker_task(state, task_id, baseline)
+    };
+    match settled {
+        Ok(Some(task)) => {
+            if let Err(error) = start_review(host, &task, worker) {
+                warn_execution(
+                    "review-start-failed",
+                    &format!(
+                        "Task {task_id} was not reviewed: {error}; blocking it for a person to check"
+                    ),
+                );
+                // Its discipline requires a review, so it isn't done; as for a review
+                // without a verdict.
+                let status = worker_pool::task_status_after_review(None);
+                let _ = CommandContext::new(state).db(|db| db.set_task_status(task_id, status));
+            }
+        }
+        Ok(None) => {}
+        Err(error) => warn_execution(
+            "worker-settle-failed",
+            &format!("Failed to settle task {task_id}: {error}"),
+        ),
+    }
+}
+
+fn worker_exit_hook<H: SessionHost>(host: H, terminal_session_id: String) -> SessionExitHook {
+    Box::new(move |exit: &SessionExit| {
+        let worker = host
+            .app_state()
+            .execution
+            .lock()
+            .ok()
+            .and_then(|mut pool| pool.remove_worker(&terminal_session_id));
+        if let Some((worker, baseline_signal_id)) = worker {
+            tracing::info!(
+                task_id = worker.task_id,
+                agent_session_id = %worker.agent_session_id,
+                review = worker.review,
+                exit_code = exit.exit_code,
+                "Execution worker finished"
+            );
+            finish_worker(&host, &worker, baseline_signal_id);
+        }
+        dispatch(&host);
+    })
+}
+
+/// Start a worker on `task`, or a reviewer when `review_of` names the agent session whose
+/// work it reviews.
+fn start_worker<H: SessionHost>(
+    host: &H,
+    task: &Task,
+    use_worktree: bool,
+    review_of: Option<&str>,
+) -> Result<(ExecutionWorker, u32), String> {
+    let role = if review_of.is_some() {
+        "review"
+    } else {
+        "task"
+    };
+    let terminal_session_id = format!("worker-{role}-{}-{}", task.id, generate_agent_session_id());
+    let baseline_signal_id = task
+        .signals
+        .iter()
+        .map(|signal| signal.id)
+        .max()
+        .unwrap_or(0);
+    let command_ctx = CommandContext::new(host.app_state());
+    let args = command_ctx.db(|db| {
+        db.set_task_status(task.id, TaskStatus::InProgress)?;
+        Ok(review_of.map_or_else(
+            || worker_session_args(db, task, terminal_session_id.clone(), use_worktree),
+            |agent_session_id| {
+                let implementer = db.get_agent_session_by_id(agent_session_id);
+                review_session_args(
+                    db,
+                    task,
+                    terminal_session_id.clone(),
+                    use_worktree,
+                    worker_pool::review_brief(task.id, implementer.as_ref()),
+                )
+            },
+        ))
+    })?;
+
+    match launch_agent_session(
+        host,
+        args,
+        SessionStarter::System,
+        Some(worker_exit_hook(host.clone(), terminal_session_id.clone())),
+    ) {
+        Ok(result) => Ok((
+            ExecutionWorker {
+                task_id: task.id,
+                agent_session_id: result.agent_session_id,
+                terminal_session_id,
+                review: review_of.is_some(),
+            },
+            baseline_signal_id,
+        )),
+        Err(error) => {
+            // A review that can't start is settled by the caller.
+            if review_of.is_none() {
+                let _ = command_ctx.db(|db| db.set_task_status(task.id, TaskStatus::Pending));
+            }
+            Err(error)
+        }
+    }
+}
+
+/// Tasks a run may pick from. Archived tasks are never started, but the ones that are done
+/// still satisfy dependencies.
+fn runnable_tasks(db: &sqlite_db::SqliteDb) -> Result<Vec<Task>, String> {
+    let archived = db.archived_task_ids()?;
+    let mut tasks = db.get_tasks();
+    tasks.retain(|task| task.status == TaskStatus::Done || !archived.contains(&task.id));
+    Ok(tasks)
+}
+
+/// Fill free worker slots with runnable tasks. Runs on start/resume and after every worker exit.
+pub fn dispatch<H: SessionHost>(host: &H) {
+    let state = host.app_state();
+    let Ok(mut pool) = state.execution.lock() else {
+        return;
+    };
+    let tasks = match CommandContext::new(state).db(runnable_tasks) {
+        Ok(tasks) => tasks,
+        Err(error) => {
+            tracing::warn!(error = %error, "Execution dispatch could not read tasks");
+            Vec::new()
+        }
+    };
+
+    let use_worktrees = pool.use_worktrees();
+    for task in pool.plan(&tasks) {
+        match start_worker(host, task, use_worktrees, None) {
+            Ok((worker, baseline_signal_id)) => pool.add_worker(worker, baseline_signal_id),
+            Err(error) => warn_execution(
+                "worker-start-failed",
+                &format!("Failed to start task {}: {error}", task.id),
+            ),
+        }
+    }
+    pool.settle();
+    host.emit_execution_state(&pool.state());
+}
+
+/// Start (or continue) a run on the locked project and fill the free worker slots.
+pub fn start_execution<H: SessionHost>(
+    host: &H,
+    args: ExecutionStartArgs,
+) -> Result<ExecutionState, String> {
+    let state = host.app_state();
+    CommandContext::new(state).locked_project_path()?;
+    let mut pool = state.execution.lock().err_str(codes::INTERNAL)?;
+    if let Some(concurrency) = args.concurrency {
+        pool.set_concurrency(concurrency)?;
+    }
+    if pool.status() == ExecutionStatus::Idle {
+        // Agents change the database through their MCP tools; keep what it was before.
+        let backup = CommandContext::new(state)
+            .db(|db| db.create_backup(sqlite_db::BackupReason::Execution));
+        if let Err(error) = backup {
+            warn_execution(
+                "backup-failed",
+                &format!("Failed to back up the database before the run: {error}"),
+            );
+        }
+    }
+    let use_worktrees = args.use_worktrees.unwrap_or_else(|| pool.use_worktrees());
+    pool.start(use_worktrees)?;
+    drop(pool);
+    dispatch(host);
+    Ok(state.execution.lock().err_str(codes::INTERNAL)?.state())
+}
+
+/// Terminate every worker. Their tasks go back to `pending` as the sessions exit.
+pub fn stop_execution<H: SessionHost>(host: &H) -> Result<(), String> {
+    let state = host.app_state();
+    let terminal_session_ids = {
+        let mut pool = state.execution.lock().err_str(codes::INTERNAL)?;
+        let ids = pool.stop();
+        host.emit_execution_state(&pool.state());
+        ids
+    };
+    for terminal_session_id in terminal_session_ids {
+        if let Err(error) = state.pty_manager.terminate(&terminal_session_id) {
+            tracing::warn!(terminal_session_id = %terminal_session_id, error = %error, "Failed to stop execution worker");
+        }
+    }
+    Ok(())
+}
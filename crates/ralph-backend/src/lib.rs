@@ -1 +1,25 @@
+//! The backend behind the desktop app's commands, without Tauri: project state, prompt
+//! rendering, agent sessions and the execution loop. The app and the `ralph` CLI both run on it.
+
+mod context_loader;
+pub mod diagnostics;
+pub mod doctor;
+pub mod execution;
 pub mod project;
+pub mod recent_projects;
+mod section_templates;
+pub mod session_host;
+pub mod session_launch;
+pub mod session_recovery;
+pub mod snapshot_service;
+pub mod stacks;
+pub mod state;
+pub mod terminal;
+pub mod text_mirror;
+pub mod worker_pool;
+pub mod workspace;
+pub mod worktree;
+pub mod xdg;
+
+pub use context_loader::{assemble_prompt_context, build_embedding_config, PromptInputs};
+pub use state::{AppState, CommandContext};
@@ -1,5 +1,9 @@
-use ralph_errors::{codes, ralph_err};
-use std::path::Path;
+use crate::state::{AppState, CommandContext, OpenProject};
+use crate::text_mirror::TextMirrorService;
+use crate::worker_pool::ExecutionStatus;
+use ralph_errors::{codes, ralph_err, RalphResultExt, ToStringErr};
+use sqlite_db::{DbBackup, PrdImportMode, PrdImportReport, SqliteDb};
+use std::path::{Path, PathBuf};
 
 pub fn validate_project_path(path: &Path) -> Result<(), String> {
     tracing::debug!(path = %path.display(), "Validating project path");
@@ -20,7 +24,7 @@ pub fn validate_project_path(path: &Path) -> Result<(), String> {
     if !ralph_dir.exists() {
         return ralph_err!(
             codes::PROJECT_PATH,
-            "No .ralph/ folder. Initialize with:\n  ralph --init \"{}\"",
+            "No .ralph/ folder. Initialize with:\n  ralph init \"{}\"",
             path.display()
         );
     }
@@ -33,11 +37,13 @@ pub fn validate_project_path(path: &Path) -> Result<(), String> {
     }
 
     let db_file = ralph_dir.join("db").join("ralph.db");
-    if !db_file.exists() {
+    // With a text mirror the database is only a cache, rebuilt from the mirror on lock.
+    let mirror_file = ralph_dir.join("mirror").join("project.yaml");
+    if !db_file.exists() && !mirror_file.is_file() {
         tracing::error!(path = %path.display(), "No .ralph/db/ralph.db found");
         return ralph_err!(
             codes::PROJECT_PATH,
-            "No .ralph/db/ralph.db found. Initialize with:\n  ralph --init \"{}\"",
+            "No .ralph/db/ralph.db found. Initialize with:\n  ralph init \"{}\"",
             path.display()
         );
     }
@@ -46,11 +52,487 @@ pub fn validate_project_path(path: &Path) -> Result<(), String> {
     Ok(())
 }
 
+fn seed_disciplines_for_stack(
+    db: &SqliteDb,
+    stack: u8,
+    ralph_dir: Option<&Path>,
+) -> Result<(), String> {
+    let defs = predefined_disciplines::get_disciplines_for_stack(stack);
+    if defs.is_empty() && stack != 0 {
+        return ralph_err!(
+            codes::DISCIPLINE_OPS,
+            "No disciplines defined for stack {stack}"
+        );
+    }
+
+    if let Some(ralph_dir) = ralph_dir {
+        let images_dir = ralph_dir.join("images").join("disciplines");
+        let _ = std::fs::create_dir_all(&images_dir);
+    }
+
+    for d in &defs {
+        let skills_json = serde_json::to_string(&d.skills).map_err(|error| {
+            format!(
+                "Failed to serialize skills for discipline '{}': {error}",
+                d.name
+            )
+        })?;
+
+        let image_path = ralph_dir.and_then(|ralph_dir| {
+            predefined_disciplines::get_discipline_image(stack, &d.name).and_then(|bytes| {
+                let rel = format!("images/disciplines/{}.png", d.name);
+                let abs = ralph_dir.join(&rel);
+                std::fs::write(&abs, bytes).is_ok().then_some(rel)
+            })
+        });
+
+        let crops_json = d.crops.as_ref().and_then(|crops| {
+            serde_json::to_string(crops).ok().or_else(|| {
+                tracing::warn!(
+                    discipline = %d.name,
+                    "Failed to serialize crops; storing no crops"
+                );
+                None
+            })
+        });
+        let image_prompt_json = d.image_prompt.as_ref().and_then(|prompt| {
+            serde_json::to_string(prompt).ok().or_else(|| {
+                tracing::warn!(
+                    discipline = %d.name,
+                    "Failed to serialize image_prompt; storing no prompt"
+                );
+                None
+            })
+        });
+
+        db.create_discipline(sqlite_db::DisciplineInput {
+            name: d.name.clone(),
+            display_name: d.display_name.clone(),
+            acronym: d.acronym.clone(),
+            icon: d.icon.clone(),
+            color: d.color.clone(),
+            description: d.description.clone(),
+            system_prompt: Some(d.system_prompt.clone()),
+            agent: None,
+            model: None,
+            effort: None,
+            thinking: None,
+            skills: skills_json,
+            conventions: Some(d.conventions.clone()),
+            mcp_servers: "[]".to_owned(),
+            image_path,
+            crops: crops_json,
+            image_prompt: image_prompt_json,
+        })?;
+    }
+    Ok(())
+}
+
+/// `.ralph/` file with the project context every agent session reads.
+pub const CLAUDE_RALPH_MD: &str = "CLAUDE.RALPH.md";
+
+/// Starting content of `CLAUDE.RALPH.md`, for the user to fill in.
+pub fn claude_ralph_md_template(project_title: &str) -> String {
+    format!(
+        "# {project_title} - Ralph Context
+
+## Project Overview
+
+Add context about this project that Claude should know when working on it.
+
+## Architecture
+
+Describe the architecture, tech stack, and key components.
+
+## Coding Standards
+
+- List any coding conventions
+- Style guides
+- Best practices
+
+## Important Notes
+
+- Any gotchas or things to watch out for
+- Known issues or limitations
+- Dependencies or external services
+"
+    )
+}
+
+/// Create `.ralph/` in `path` with a database seeded with the disciplines of `stack`
+/// (0 seeds none) and a `CLAUDE.RALPH.md` to fill in.
+pub fn initialize_project(path: &Path, project_title: &str, stack: u8) -> Result<(), String> {
+    tracing::info!("Initializing Ralph project with stack {}", stack);
+
+    if !path.exists() {
+        return ralph_err!(
+            codes::PROJECT_PATH,
+            "Directory not found: {}",
+            path.display()
+        );
+    }
+    if !path.is_dir() {
+        return ralph_err!(codes::PROJECT_PATH, "Not a directory: {}", path.display());
+    }
+
+    let ralph_dir = path.join(".ralph");
+    if ralph_dir.exists() {
+        return ralph_err!(
+            codes::PROJECT_INIT,
+            ".ralph/ already exists at {}",
+            path.display()
+        );
+    }
+
+    std::fs::create_dir(&ralph_dir)
+        .ralph_err(codes::PROJECT_INIT, "Failed to create .ralph/ directory")?;
+
+    let db_dir = ralph_dir.join("db");
+    std::fs::create_dir(&db_dir)
+        .ralph_err(codes::PROJECT_INIT, "Failed to create .ralph/db/ directory")?;
+
+    let db_path = db_dir.join("ralph.db");
+    let db = SqliteDb::open(&db_path, None)?;
+    seed_disciplines_for_stack(&db, stack, Some(&ralph_dir))?;
+    db.initialize_metadata(
+        project_title.to_owned(),
+        Some("Add project description here".to_owned()),
+    )?;
+    let claude_path = ralph_dir.join(CLAUDE_RALPH_MD);
+    let claude_template = claude_ralph_md_template(project_title);
+
+    std::fs::write(&claude_path, claude_template)
+        .ralph_err(codes::FILESYSTEM, "Failed to create CLAUDE.RALPH.md")?;
+
+    Ok(())
+}
+
+/// Name a project is listed under: its directory name.
+pub fn project_name(path: &Path) -> String {
+    path.file_name()
+        .map_or_else(|| "Unknown".to_owned(), |n| n.to_string_lossy().to_string())
+}
+
+/// Open the project at canonical `path`: its database, with orphaned sessions reconciled,
+/// and its text mirror, synced.
+fn open_project_state(path: &Path) -> Result<OpenProject, String> {
+    let db_dir = path.join(".ralph").join("db");
+    // A project checked out with only its text mirror has no database yet.
+    std::fs::create_dir_all(&db_dir)
+        .ralph_err(codes::FILESYSTEM, "Failed to create .ralph/db/ directory")?;
+    let db = SqliteDb::open(&db_dir.join("ralph.db"), None)?;
+    let text_mirror = TextMirrorService::default();
+    text_mirror.attach(path)?;
+    text_mirror.sync(&db);
+
+    if let Err(error) = crate::session_recovery::recover_orphaned_sessions(&db) {
+        tracing::warn!(error = %error, "Failed to reconcile orphaned agent sessions");
+        crate::diagnostics::emit_warning(
+            "session-recovery",
+            "orphaned-sessions-reconcile-failed",
+            &error,
+        );
+    }
+    Ok(OpenProject { db, text_mirror })
+}
+
+/// The project at canonical `path`: taken from the open projects, or opened.
+fn take_open_project(state: &AppState, path: &Path) -> Result<OpenProject, String> {
+    let open = state
+        .open_projects
+        .lock()
+        .err_str(codes::INTERNAL)?
+        .remove(path);
+    open.map_or_else(|| open_project_state(path), Ok)
+}
+
+/// Make `project` the locked one, handing back the database and mirror it replaces.
+fn install_project(
+    state: &AppState,
+    path: &Path,
+    project: OpenProject,
+) -> Result<Option<OpenProject>, String> {
+    let db = state
+        .db
+        .lock()
+        .err_str(codes::INTERNAL)?
+        .replace(project.db);
+    let text_mirror = state.text_mirror.replace(project.text_mirror)?;
+
+    let _ = crate::recent_projects::add(
+        &state.xdg,
+        path.to_string_lossy().to_string(),
+        project_name(path),
+    );
+    Ok(db.map(|db| OpenProject { db, text_mirror }))
+}
+
+/// Lock the project at `path`, which `validate_project_path` accepted.
+pub fn project_lock_validated(state: &AppState, path: String) -> Result<(), String> {
+    let canonical_path =
+        std::fs::canonicalize(&path).ralph_err(codes::PROJECT_PATH, "Failed to resolve path")?;
+
+    let mut locked = state.locked_project.lock().err_str(codes::INTERNAL)?;
+    if locked.is_some() {
+        return ralph_err!(
+            codes::PROJECT_LOCK,
+            "Project already locked for this session"
+        );
+    }
+
+    let project = take_open_project(state, &canonical_path)?;
+    install_project(state, &canonical_path, project)?;
+    *locked = Some(canonical_path);
+    Ok(())
+}
+
+/// Refuse to go on `doing` something that replaces the locked project's database while an
+/// execution run or live agent sessions are going: they keep writing to it through MCP, by
+/// session and task ids the replacement doesn't share.
+fn ensure_no_running_work(state: &AppState, doing: &str) -> Result<(), String> {
+    if state.execution.lock().err_str(codes::INTERNAL)?.status() != ExecutionStatus::Idle {
+        return ralph_err!(codes::PROJECT_LOCK, "Stop the execution run before {doing}");
+    }
+    // Also syncs the text mirror one last time.
+    let running = CommandContext::new(state).db(|db| {
+        Ok(db
+            .project_activity(crate::session_recovery::session_is_live)?
+            .running_sessions)
+    })?;
+    if running > 0 {
+        return ralph_err!(
+            codes::PROJECT_LOCK,
+            "{running} agent session(s) are still running; stop them before {doing}"
+        );
+    }
+    Ok(())
+}
+
+/// Restore backup `name` over the locked project's database, then lock the project again
+/// so everything that read the old database starts over from the restored one.
+pub fn restore_backup(state: &AppState, name: &str) -> Result<DbBackup, String> {
+    let project_path = CommandContext::new(state).locked_project_path()?;
+    ensure_no_running_work(state, "restoring a backup")?;
+
+    let replaced = {
+        let mut guard = state.db.lock().err_str(codes::INTERNAL)?;
+        let db = guard.as_mut().ok_or_else(|| {
+            ralph_errors::err_string(codes::PROJECT_LOCK, "No project locked (database not open)")
+        })?;
+        // Mirror edits not imported yet belong to the state being replaced.
+        state.text_mirror.sync(db);
+        let replaced = db.restore_backup(name)?;
+        // The re-lock imports the mirror; it has to hold the restored state by then.
+        if state.text_mirror.is_enabled()? {
+            state.text_mirror.enable(&project_path, db)?;
+        }
+        replaced
+    };
+
+    *state.db.lock().err_str(codes::INTERNAL)? = None;
+    *state.locked_project.lock().err_str(codes::INTERNAL)? = None;
+    project_lock_validated(state, project_path.to_string_lossy().into_owned())?;
+    Ok(replaced)
+}
+
+/// Import PRD YAML into the locked project. A `Replace` that writes drops every task and
+/// reuses their ids, so like `restore_backup` it waits for running work: a worker settling
+/// task N afterwards would change the unrelated imported task N.
+pub fn import_prd(
+    state: &AppState,
+    yaml: &str,
+    mode: PrdImportMode,
+    dry_run: bool,
+) -> Result<PrdImportReport, String> {
+    if mode == PrdImportMode::Replace && !dry_run {
+        ensure_no_running_work(state, "replacing the project's tasks")?;
+    }
+    CommandContext::new(state).db(|db| db.import_prd_yaml(yaml, mode, dry_run))
+}
+
+/// Lock the project at `path`, keeping the previously locked one open alongside the
+/// others. Refused while the locked project has an execution run or agent sessions going:
+/// the run and the sessions' signals through the API server act on the locked project.
+pub fn switch_project(state: &AppState, path: String) -> Result<(), String> {
+    validate_project_path(Path::new(&path))?;
+    let canonical =
+        std::fs::canonicalize(&path).ralph_err(codes::PROJECT_PATH, "Failed to resolve path")?;
+    let Some(previous) = CommandContext::new(state).maybe_locked_project_path()? else {
+        return project_lock_validated(state, path);
+    };
+    if previous == canonical {
+        return Ok(());
+    }
+    // Also exports the locked project's last changes to its text mirror.
+    ensure_no_running_work(state, "switching projects")?;
+
+    let project = take_open_project(state, &canonical)?;
+    let mut locked = state.locked_project.lock().err_str(codes::INTERNAL)?;
+    if let Some(replaced) = install_project(state, &canonical, project)? {
+        state
+            .open_projects
+            .lock()
+            .err_str(codes::INTERNAL)?
+            .insert(previous, replaced);
+    }
+    *locked = Some(canonical);
+    Ok(())
+}
+
+/// Open the project at `path` alongside the locked one without switching to it. Locks it
+/// when no project is locked yet.
+pub fn open_project(state: &AppState, path: &Path) -> Result<(), String> {
+    validate_project_path(path)?;
+    let canonical =
+        std::fs::canonicalize(path).ralph_err(codes::PROJECT_PATH, "Failed to resolve path")?;
+    let Some(locked) = CommandContext::new(state).maybe_locked_project_path()? else {
+        return project_lock_validated(state, canonical.to_string_lossy().into_owned());
+    };
+    if locked == canonical || open_project_paths(state)?.contains(&canonical) {
+        return Ok(());
+    }
+    let project = open_project_state(&canonical)?;
+    state
+        .open_projects
+        .lock()
+        .err_str(codes::INTERNAL)?
+        .insert(canonical, project);
+    Ok(())
+}
+
+/// Close a project held open besides the locked one. The locked project stays open until
+/// another one is switched to.
+pub fn close_project(state: &AppState, path: &Path) -> Result<(), String> {
+    let path = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    if CommandContext::new(state)
+        .maybe_locked_project_path()?
+        .as_ref()
+        == Some(&path)
+    {
+        return ralph_err!(
+            codes::PROJECT_LOCK,
+            "{} is the locked project; switch to another one before closing it",
+            path.display()
+        );
+    }
+    let closed = state
+        .open_projects
+        .lock()
+        .err_str(codes::INTERNAL)?
+        .remove(&path);
+    if closed.is_none() {
+        return ralph_err!(codes::PROJECT_LOCK, "{} is not open", path.display());
+    }
+    Ok(())
+}
+
+/// Canonical paths of the projects open besides the locked one.
+pub fn open_project_paths(state: &AppState) -> Result<Vec<PathBuf>, String> {
+    Ok(state
+        .open_projects
+        .lock()
+        .err_str(codes::INTERNAL)?
+        .keys()
+        .cloned()
+        .collect())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use sqlite_db::AgentSessionCreateInput;
     use tempfile::tempdir;
 
+    /// A locked project with a manual session whose agent, this test process, is running.
+    fn state_with_live_session() -> AppState {
+        let db = SqliteDb::open_in_memory(None).unwrap();
+        db.create_human_agent_session(AgentSessionCreateInput {
+            id: "manual".to_owned(),
+            kind: "task_execution".to_owned(),
+            task_id: None,
+            agent: Some("claude".to_owned()),
+            model: None,
+            launch_command: None,
+            post_start_preamble: None,
+            init_prompt: None,
+        })
+        .unwrap();
+        db.set_agent_session_pid("manual", std::process::id())
+            .unwrap();
+        let state = AppState::default();
+        *state.db.lock().unwrap() = Some(db);
+        *state.locked_project.lock().unwrap() = Some(std::env::temp_dir());
+        state
+    }
+
+    #[test]
+    fn replacing_import_waits_for_live_sessions() {
+        let state = state_with_live_session();
+        let yaml = CommandContext::new(&state)
+            .db(SqliteDb::export_prd_yaml)
+            .unwrap();
+        let error = import_prd(&state, &yaml, PrdImportMode::Replace, false).unwrap_err();
+        assert!(
+            error.contains("still running; stop them before replacing"),
+            "{error}"
+        );
+        import_prd(&state, &yaml, PrdImportMode::Replace, true).unwrap();
+        import_prd(&state, &yaml, PrdImportMode::Merge, false).unwrap();
+    }
+
+    #[test]
+    fn restore_backup_waits_for_live_sessions() {
+        let state = state_with_live_session();
+        let error = restore_backup(&state, "ralph-20260101T120000000Z-manual.db").unwrap_err();
+        assert!(
+            error.contains("1 agent session(s) are still running"),
+            "{error}"
+        );
+    }
+
+    #[test]
+    fn switching_keeps_the_previous_project_open() {
+        let dirs = [tempdir().unwrap(), tempdir().unwrap(), tempdir().unwrap()];
+        for (dir, title) in dirs.iter().zip(["Api", "Web", "Docs"]) {
+            initialize_project(dir.path(), title, 0).unwrap();
+        }
+        let paths: Vec<PathBuf> = dirs
+            .iter()
+            .map(|dir| dir.path().canonicalize().unwrap())
+            .collect();
+        let (api, web, docs) = (paths[0].clone(), paths[1].clone(), paths[2].clone());
+        let mut state = AppState::default();
+        state.xdg = crate::xdg::XdgDirs::from_base(&dirs[0].path().join("xdg"));
+        let title = |state: &AppState| {
+            CommandContext::new(state)
+                .db(|db| Ok(db.get_project_info().title))
+                .unwrap()
+        };
+
+        switch_project(&state, api.to_string_lossy().into_owned()).unwrap();
+        open_project(&state, &docs).unwrap();
+        switch_project(&state, web.to_string_lossy().into_owned()).unwrap();
+        assert_eq!(title(&state), "Web");
+        assert_eq!(open_project_paths(&state).unwrap().len(), 2);
+
+        // The parked database is the one switched back to, not a fresh copy.
+        state.open_projects.lock().unwrap()[&api]
+            .db
+            .initialize_metadata("Api v2".to_owned(), None)
+            .unwrap();
+        switch_project(&state, api.to_string_lossy().into_owned()).unwrap();
+        assert_eq!(title(&state), "Api v2");
+        assert_eq!(open_project_paths(&state).unwrap(), {
+            let mut open = vec![web.clone(), docs.clone()];
+            open.sort();
+            open
+        });
+
+        assert!(close_project(&state, &api).is_err());
+        close_project(&state, &docs).unwrap();
+        assert_eq!(open_project_paths(&state).unwrap(), [web]);
+    }
+
     #[test]
     fn validate_project_path_errors_when_missing_directory() {
         let dir = tempdir().unwrap();
@@ -95,4 +577,13 @@ mod tests {
         std::fs::write(db_dir.join("ralph.db"), "").unwrap();
         validate_project_path(dir.path()).unwrap();
     }
+
+    #[test]
+    fn validate_project_path_ok_when_only_text_mirror_exists() {
+        let dir = tempdir().unwrap();
+        let mirror_dir = dir.path().join(".ralph").join("mirror");
+        std::fs::create_dir_all(&mirror_dir).unwrap();
+        std::fs::write(mirror_dir.join("project.yaml"), "title: Mirror\n").unwrap();
+        validate_project_path(dir.path()).unwrap();
+    }
 }
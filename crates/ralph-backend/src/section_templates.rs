@@ -0,0 +1,110 @@
+//! Loads the project's section templates from `.ralph/prompts/<section>.md`.
+//!
+//! Unlike context files, a broken template fails the prompt: it is a customization the
+//! team checked in, and silently dropping it would change what agents are told.
+
+use prompt_builder::templates;
+use prompt_builder::SectionTemplate;
+use ralph_errors::{codes, ralph_err};
+use std::collections::HashMap;
+use std::path::Path;
+
+pub(super) fn load_section_templates(
+    project_path: &Path,
+) -> Result<HashMap<String, SectionTemplate>, String> {
+    let dir = project_path.join(".ralph").join("prompts");
+    let Ok(entries) = std::fs::read_dir(&dir) else {
+        return Ok(HashMap::new());
+    };
+
+    let mut files: Vec<_> = entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "md"))
+        .collect();
+    files.sort();
+
+    let mut loaded = HashMap::new();
+    let mut problems = Vec::new();
+    for path in files {
+        let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+        let display = format!(".ralph/prompts/{file_name}");
+        let source = match std::fs::read_to_string(&path) {
+            Ok(source) => source,
+            Err(error) => {
+                problems.push(format!("{display}: {error}"));
+                continue;
+            }
+        };
+        match templates::load(&file_name, &source) {
+            Ok((section, template)) => {
+                if loaded.insert(section.to_owned(), template).is_some() {
+                    problems.push(format!(
+                        "{display}:1: another template already overrides `{section}`"
+                    ));
+                }
+            }
+            Err(mut error) => {
+                // Point at the file relative to the project, not just its name.
+                error.file = display;
+                problems.push(error.to_string());
+            }
+        }
+    }
+
+    if !problems.is_empty() {
+        return ralph_err!(
+            codes::FILESYSTEM,
+            "Invalid prompt templates:\n{}",
+            problems.join("\n")
+        );
+    }
+    Ok(loaded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn project_with(files: &[(&str, &str)]) -> tempfile::TempDir {
+        let dir = tempdir().unwrap();
+        let prompts = dir.path().join(".ralph").join("prompts");
+        std::fs::create_dir_all(&prompts).unwrap();
+        for (name, content) in files {
+            std::fs::write(prompts.join(name), content).unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn loads_templates_by_section_name() {
+        let dir = project_with(&[
+            (
+                "discipline_persona.md",
+                "{{ section }}\n\nAlways write tests.",
+            ),
+            ("notes.txt", "not a template"),
+        ]);
+        let loaded = load_section_templates(dir.path()).unwrap();
+        assert_eq!(loaded.len(), 1);
+        assert!(loaded.contains_key("discipline_persona"));
+    }
+
+    #[test]
+    fn missing_dir_loads_nothing() {
+        let dir = tempdir().unwrap();
+        assert!(load_section_templates(dir.path()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn reports_every_invalid_template_with_file_and_line() {
+        let dir = project_with(&[
+            ("task_details.md", "{{ section }}\n{{ tsk.name }}\n"),
+            ("readme.md", "Team notes"),
+        ]);
+        let error = load_section_templates(dir.path()).unwrap_err();
+        assert!(error.contains(".ralph/prompts/readme.md:1: no prompt section named `readme`"));
+        assert!(error.contains(".ralph/prompts/task_details.md:2: unknown variable `tsk`"));
+    }
+}
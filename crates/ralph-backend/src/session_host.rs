@@ -0,0 +1,18 @@
+//! The process agent sessions and the execution loop run in: the desktop app through its
+//! `AppHandle`, or the `ralph` CLI without a window.
+
+use crate::state::AppState;
+use crate::worker_pool::ExecutionState;
+use ralph_contracts::transport::EventSink;
+use std::sync::Arc;
+
+pub const EXECUTION_STATE_EVENT: &str = "execution-state-changed";
+
+pub trait SessionHost: Clone + Send + Sync + 'static {
+    fn app_state(&self) -> &AppState;
+
+    /// Where terminal output and system messages of the host's sessions go.
+    fn event_sink(&self) -> Arc<dyn EventSink>;
+
+    fn emit_execution_state(&self, state: &ExecutionState);
+}
@@ -0,0 +1,780 @@
+//! Starting agent sessions in PTYs: each backed by an `agent_sessions` row that records how
+//! the session ended. Used by the terminal commands and by execution workers.
+
+use crate::session_host::SessionHost;
+use crate::state::{
+    AppState, CommandContext, ProjectSessionService, RenderedTaskPrompt, TaskPromptSpec,
+};
+use crate::terminal::providers::{
+    fake_agent_enabled, resolve_agent_provider, resolve_post_start_preamble,
+    resolve_session_effort_for_agent, resolve_session_model_for_agent, shell_agent_enabled,
+    AGENT_CODEX, AGENT_FAKE, AGENT_SHELL,
+};
+use crate::terminal::{
+    PtyOutputEvent, SessionConfig, SessionExit, SessionExitHook, SessionInitSettings,
+    SessionTimeouts, TerminalBridgeStartHumanSessionArgs, TerminalBridgeStartHumanSessionResult,
+    TerminalBridgeStartSessionArgs, TerminalBridgeStartTaskSessionArgs,
+};
+use crate::worktree::TaskWorktree;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use ralph_contracts::transport::EventSink;
+use ralph_errors::{codes, ralph_err};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+static AGENT_SESSION_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+fn locked_project_path(state: &AppState) -> Result<PathBuf, String> {
+    ProjectSessionService::new(state).locked_project_path()
+}
+
+fn build_system_message_event(session_id: String, text: String) -> PtyOutputEvent {
+    PtyOutputEvent {
+        session_id,
+        seq: 0,
+        data: STANDARD.encode(text.as_bytes()),
+    }
+}
+
+pub fn emit_system_message(
+    sink: &dyn EventSink,
+    session_id: String,
+    text: String,
+) -> Result<(), String> {
+    let event = build_system_message_event(session_id, text);
+    sink.emit_terminal_output(event)
+}
+
+fn resolve_start_session_context(
+    state: &AppState,
+    mcp_mode: Option<&str>,
+) -> Result<(PathBuf, Option<PathBuf>), String> {
+    let project_path = locked_project_path(state)?;
+    let mcp_config = if let Some(mode) = mcp_mode {
+        Some(state.generate_mcp_config(mode, &project_path)?)
+    } else {
+        None
+    };
+    Ok((project_path, mcp_config))
+}
+
+fn resolve_start_task_session_context(
+    state: &AppState,
+    task_id: u32,
+    spec: TaskPromptSpec,
+) -> Result<(PathBuf, RenderedTaskPrompt), String> {
+    let project_path = locked_project_path(state)?;
+    let prompt = state.render_task_prompt(task_id, &project_path, spec)?;
+    Ok((project_path, prompt))
+}
+
+/// Keep the prompt the agent was handed with the session row. A failure here doesn't stop the
+/// session.
+fn record_session_prompt(
+    state: &AppState,
+    agent_session_id: &str,
+    prompt: &RenderedTaskPrompt,
+    delivered: &str,
+) {
+    let recorded = CommandContext::new(state)
+        .db(|db| db.record_agent_session_prompt(prompt.record_for(agent_session_id, delivered)));
+    if let Err(error) = recorded {
+        let message = format!("Failed to record prompt for session {agent_session_id}: {error}");
+        tracing::warn!("{message}");
+        crate::diagnostics::emit_warning("terminal", "session-prompt-record-failed", &message);
+    }
+}
+
+fn build_session_config(
+    agent: Option<String>,
+    selected_model: Option<String>,
+    effort: Option<String>,
+    thinking: Option<bool>,
+    permission_level: Option<String>,
+    post_start_preamble: Option<String>,
+) -> Result<SessionConfig, String> {
+    let provider_id = resolve_agent_provider(agent.as_deref()).id();
+    if provider_id == AGENT_SHELL || provider_id == AGENT_FAKE {
+        let (label, enabled) = if provider_id == AGENT_SHELL {
+            ("Shell", shell_agent_enabled())
+        } else {
+            ("Fake agent", fake_agent_enabled())
+        };
+        if !enabled {
+            return Err(format!(
+                "{label} terminal sessions are disabled in production builds"
+            ));
+        }
+        if selected_model
+            .as_deref()
+            .map(str::trim)
+            .is_some_and(|value| !value.is_empty())
+        {
+            return Err(format!(
+                "{label} terminal sessions do not support model selection"
+            ));
+        }
+        if effort
+            .as_deref()
+            .map(str::trim)
+            .is_some_and(|value| !value.is_empty())
+        {
+            return Err(format!(
+                "{label} terminal sessions do not support effort selection"
+            ));
+        }
+        let resolved_preamble = resolve_session_post_start_preamble(
+            agent.as_deref(),
+            None,
+            None,
+            thinking,
+            post_start_preamble,
+        );
+        return Ok(SessionConfig {
+            agent,
+            model: None,
+            effort: None,
+            thinking,
+            permission_level,
+            init_settings: SessionInitSettings::default(),
+            post_start_preamble: resolved_preamble,
+            initial_prompt: None,
+            timeouts: SessionTimeouts::default(),
+        });
+    }
+
+    let runtime_model = resolve_session_model_for_agent(agent.as_deref(), selected_model.clone())?;
+    let runtime_effort =
+        resolve_session_effort_for_agent(agent.as_deref(), selected_model.as_deref(), effort)?;
+    let resolved_preamble = resolve_session_post_start_preamble(
+        agent.as_deref(),
+        runtime_model.clone(),
+        runtime_effort.clone(),
+        thinking,
+        post_start_preamble,
+    );
+    Ok(SessionConfig {
+        agent,
+        model: runtime_model,
+        effort: runtime_effort,
+        thinking,
+        permission_level,
+        init_settings: SessionInitSettings::default(),
+        post_start_preamble: resolved_preamble,
+        initial_prompt: None,
+        timeouts: SessionTimeouts::default(),
+    })
+}
+
+/// Session limits for a task session: task override, then discipline, then built-in default.
+fn resolve_task_session_timeouts(
+    state: &AppState,
+    task_id: u32,
+) -> Result<SessionTimeouts, String> {
+    let limits =
+        ProjectSessionService::new(state).with_db(|db| db.effective_session_limits(task_id))?;
+    Ok(SessionTimeouts::from_secs(
+        limits.wall_clock_secs,
+        limits.idle_secs,
+    ))
+}
+
+fn session_exit_status(exit: &SessionExit) -> &'static str {
+    if exit.timed_out.is_some() {
+        "timed_out"
+    } else if exit.cancelled {
+        "cancelled"
+    } else if exit.exit_code == 0 {
+        "finished"
+    } else {
+        "crashed"
+    }
+}
+
+/// Records how the PTY session ended on its `agent_sessions` row. A session that ran in a task
+/// worktree also records the worktree's HEAD; untouched worktrees are removed.
+fn record_agent_session_exit<H: SessionHost>(
+    host: H,
+    agent_session_id: String,
+    worktree: Option<TaskWorktree>,
+    after_exit: Option<SessionExitHook>,
+) -> SessionExitHook {
+    Box::new(move |exit: &SessionExit| {
+        let status = session_exit_status(exit);
+        let head_commit = worktree.as_ref().and_then(|worktree| {
+            match crate::worktree::finish_task_worktree(worktree) {
+                Ok((head, kept)) => {
+                    tracing::debug!(
+                        agent_session_id = %agent_session_id,
+                        branch = %worktree.branch,
+                        kept,
+                        "Finished task worktree"
+                    );
+                    Some(head)
+                }
+                Err(error) => {
+                    tracing::warn!(
+                        agent_session_id = %agent_session_id,
+                        branch = %worktree.branch,
+                        error = %error,
+                        "Failed to finish task worktree"
+                    );
+                    None
+                }
+            }
+        });
+        let result = CommandContext::new(host.app_state()).db(|db| {
+            if let Some(head) = &head_commit {
+                db.set_agent_session_head_commit(&agent_session_id, head)?;
+            }
+            db.finish_agent_session(sqlite_db::AgentSessionFinishInput {
+                id: agent_session_id.clone(),
+                status: status.to_owned(),
+                exit_code: i32::try_from(exit.exit_code).ok(),
+                output_bytes: Some(u32::try_from(exit.output_bytes).unwrap_or(u32::MAX)),
+                error_text: exit.timed_out.as_ref().map(|(_, reason)| reason.clone()),
+            })
+        });
+        if let Err(error) = result {
+            tracing::warn!(
+                agent_session_id = %agent_session_id,
+                status,
+                error = %error,
+                "Failed to record agent session exit"
+            );
+        }
+        if let Some(after_exit) = after_exit {
+            after_exit(exit);
+        }
+    })
+}
+
+/// Start a PTY session in the locked project without an `agent_sessions` row.
+pub fn start_session(
+    sink: Arc<dyn EventSink>,
+    state: &AppState,
+    args: TerminalBridgeStartSessionArgs,
+    on_exit: Option<SessionExitHook>,
+) -> Result<(), String> {
+    tracing::debug!(
+        session_id = %args.session_id,
+        agent = ?args.agent,
+        mcp_mode = ?args.mcp_mode,
+        model = ?args.model,
+        thinking = ?args.thinking,
+        "terminal_start_session"
+    );
+    let (project_path, mcp_config) =
+        resolve_start_session_context(state, args.mcp_mode.as_deref())?;
+    let config = build_session_config(
+        args.agent,
+        args.model,
+        args.effort,
+        args.thinking,
+        args.permission_level,
+        args.post_start_preamble,
+    )?;
+
+    state
+        .pty_manager
+        .create_session(
+            sink,
+            args.session_id,
+            &project_path,
+            mcp_config,
+            config,
+            on_exit,
+        )
+        .map(|_| ())
+}
+
+/// Create the task's worktree and record it on the agent session row.
+fn prepare_task_worktree(
+    state: &AppState,
+    agent_session_id: &str,
+    task_id: Option<u32>,
+) -> Result<TaskWorktree, String> {
+    let Some(task_id) = task_id else {
+        return ralph_err!(
+            codes::GIT_OPS,
+            "Worktree isolation is only available for task sessions"
+        );
+    };
+    let project_path = locked_project_path(state)?;
+    let worktree = crate::worktree::create_task_worktree(&project_path, task_id)?;
+    ProjectSessionService::new(state).with_db(|db| {
+        db.set_agent_session_worktree(
+            agent_session_id,
+            &worktree.branch,
+            &worktree.path.to_string_lossy(),
+            &worktree.base_commit,
+        )
+    })?;
+    Ok(worktree)
+}
+
+/// `working_dir` overrides the project root, e.g. to run the agent inside a task worktree.
+/// The rendered prompt goes to the agent on its command line, so one longer than
+/// `MAX_INITIAL_PROMPT_BYTES` is refused; with an `agent_session_id`, what the agent was
+/// actually handed is recorded on that session.
+fn start_task_session_impl(
+    sink: Arc<dyn EventSink>,
+    state: &AppState,
+    args: TerminalBridgeStartTaskSessionArgs,
+    spec: TaskPromptSpec,
+    agent_session_id: Option<&str>,
+    working_dir: Option<&Path>,
+    on_exit: Option<SessionExitHook>,
+) -> Result<(), String> {
+    tracing::debug!(
+        session_id = %args.session_id,
+        task_id = args.task_id,
+        agent = ?args.agent,
+        model = ?args.model,
+        thinking = ?args.thinking,
+        "terminal_start_task_session"
+    );
+    let (project_path, prompt) = resolve_start_task_session_context(state, args.task_id, spec)?;
+    let mut config = build_session_config(
+        args.agent,
+        args.model,
+        args.effort,
+        args.thinking,
+        args.permission_level,
+        args.post_start_preamble,
+    )?;
+    config.timeouts = resolve_task_session_timeouts(state, args.task_id)?;
+    config.initial_prompt = Some(prompt.prompt());
+
+    let delivered = state.pty_manager.create_session(
+        sink,
+        args.session_id,
+        working_dir.unwrap_or(&project_path),
+        Some(prompt.mcp_config_path.clone()),
+        config,
+        on_exit,
+    )?;
+    match (agent_session_id, delivered) {
+        (Some(agent_session_id), Some(delivered)) => {
+            record_session_prompt(state, agent_session_id, &prompt, &delivered);
+        }
+        (Some(agent_session_id), None) => {
+            tracing::debug!(
+                agent_session_id,
+                "Agent took no initial prompt; nothing recorded"
+            );
+        }
+        (None, _) => {}
+    }
+    Ok(())
+}
+
+pub(crate) fn generate_agent_session_id() -> String {
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0u128, |d| d.as_millis());
+    let counter = AGENT_SESSION_COUNTER.fetch_add(1, Ordering::Relaxed);
+    format!("agent-session-{millis}-{counter}")
+}
+
+fn resolve_session_post_start_preamble(
+    agent: Option<&str>,
+    model: Option<String>,
+    effort: Option<String>,
+    thinking: Option<bool>,
+    user_preamble: Option<String>,
+) -> Option<String> {
+    let config = SessionConfig {
+        agent: agent.map(str::to_owned),
+        model,
+        effort,
+        thinking,
+        permission_level: None,
+        init_settings: SessionInitSettings::default(),
+        post_start_preamble: None,
+        initial_prompt: None,
+        timeouts: SessionTimeouts::default(),
+    };
+    resolve_post_start_preamble(agent, &config, user_preamble)
+}
+
+fn build_launch_command(config: &SessionConfig) -> String {
+    let agent = resolve_agent_provider(config.agent.as_deref()).id();
+    if agent == AGENT_SHELL {
+        return "shell -i".to_owned();
+    }
+    if agent == AGENT_FAKE {
+        return "ralph-fake-agent".to_owned();
+    }
+
+    let mut parts = vec![agent.to_owned()];
+
+    if let Some(model) = config.model.as_deref() {
+        parts.push("--model".to_owned());
+        parts.push(model.to_owned());
+    }
+    if let Some(effort) = config.effort.as_deref() {
+        if agent == AGENT_CODEX {
+            parts.push("--config".to_owned());
+            parts.push(format!("model_reasoning_effort={effort}"));
+        } else {
+            parts.push("--effort".to_owned());
+            parts.push(effort.to_owned());
+        }
+    }
+
+    if agent == AGENT_CODEX {
+        match config.permission_level.as_deref().map(str::trim) {
+            Some("safe") => {
+                parts.push("--sandbox".to_owned());
+                parts.push("workspace-write".to_owned());
+                parts.push("--ask-for-approval".to_owned());
+                parts.push("untrusted".to_owned());
+            }
+            Some("auto") => {
+                parts.push("--full-auto".to_owned());
+            }
+            Some("full_auto") => {
+                parts.push("--dangerously-bypass-approvals-and-sandbox".to_owned());
+            }
+            _ => {
+                parts.push("--sandbox".to_owned());
+                parts.push("workspace-write".to_owned());
+                parts.push("--ask-for-approval".to_owned());
+                parts.push("on-request".to_owned());
+            }
+        }
+    } else {
+        parts.push("--permission-mode".to_owned());
+        parts.push(
+            match config.permission_level.as_deref().map(str::trim) {
+                Some("safe") => "default",
+                Some("auto") => "dontAsk",
+                Some("full_auto") => "bypassPermissions",
+                _ => "delegate",
+            }
+            .to_owned(),
+        );
+    }
+
+    parts.join(" ")
+}
+
+fn mark_agent_session_crashed(command_ctx: &CommandContext<'_>, agent_session_id: &str, err: &str) {
+    let _ = command_ctx.db(|db| {
+        db.finish_agent_session(sqlite_db::AgentSessionFinishInput {
+            id: agent_session_id.to_owned(),
+            status: "crashed".to_owned(),
+            exit_code: Some(1),
+            output_bytes: None,
+            error_text: Some(err.to_owned()),
+        })
+    });
+}
+
+/// Who asked for an agent session; recorded as the row's `started_by`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionStarter {
+    Human,
+    System,
+}
+
+/// Start a PTY session backed by an `agent_sessions` row. The row records how the session
+/// ended; `after_exit` runs once that is recorded.
+pub fn launch_agent_session<H: SessionHost>(
+    host: &H,
+    args: TerminalBridgeStartHumanSessionArgs,
+    starter: SessionStarter,
+    after_exit: Option<SessionExitHook>,
+) -> Result<TerminalBridgeStartHumanSessionResult, String> {
+    let state = host.app_state();
+    let session_config = build_session_config(
+        args.agent.clone(),
+        args.model.clone(),
+        args.effort.clone(),
+        args.thinking,
+        args.permission_level.clone(),
+        args.post_start_preamble.clone(),
+    )?;
+    let launch_command = build_launch_command(&session_config);
+
+    let resolved_post_start_preamble = session_config.post_start_preamble;
+
+    let agent_session_id = generate_agent_session_id();
+    tracing::debug!(
+        terminal_session_id = %args.terminal_session_id,
+        agent_session_id = %agent_session_id,
+        starter = ?starter,
+        "launch_agent_session.created_agent_session_id"
+    );
+
+    let command_ctx = CommandContext::new(state);
+
+    let agent_session_number = command_ctx.db_tx(|db| {
+        let input = sqlite_db::AgentSessionCreateInput {
+            id: agent_session_id.clone(),
+            kind: args.kind.clone(),
+            task_id: args.task_id,
+            agent: args.agent.clone(),
+            model: args.model.clone(),
+            launch_command: Some(launch_command),
+            post_start_preamble: resolved_post_start_preamble,
+            init_prompt: args.init_prompt.clone(),
+        };
+        match starter {
+            SessionStarter::Human => db.create_human_agent_session(input)?,
+            SessionStarter::System => db.create_system_agent_session(input)?,
+        }
+
+        db.get_agent_session_by_id(&agent_session_id)
+            .map(|s| s.session_number)
+            .ok_or_else(|| {
+                format!("Failed to load newly created agent session '{agent_session_id}'")
+            })
+    })?;
+
+    let worktree = if args.use_worktree.unwrap_or(false) {
+        match prepare_task_worktree(state, &agent_session_id, args.task_id) {
+            Ok(worktree) => Some(worktree),
+            Err(err) => {
+                mark_agent_session_crashed(&command_ctx, &agent_session_id, &err);
+                return Err(err);
+            }
+        }
+    } else {
+        None
+    };
+
+    let on_exit = Some(record_agent_session_exit(
+        host.clone(),
+        agent_session_id.clone(),
+        worktree.clone(),
+        after_exit,
+    ));
+    let start_result = if let Some(task_id) = args.task_id {
+        start_task_session_impl(
+            host.event_sink(),
+            state,
+            TerminalBridgeStartTaskSessionArgs {
+                session_id: args.terminal_session_id.clone(),
+                task_id,
+                agent: args.agent.clone(),
+                model: args.model.clone(),
+                effort: args.effort.clone(),
+                permission_level: args.permission_level.clone(),
+                thinking: args.thinking,
+                post_start_preamble: args.post_start_preamble.clone(),
+            },
+            TaskPromptSpec::for_session(&args.kind, args.init_prompt.clone()),
+            Some(&agent_session_id),
+            worktree.as_ref().map(|worktree| worktree.path.as_path()),
+            on_exit,
+        )
+    } else {
+        start_session(
+            host.event_sink(),
+            state,
+            TerminalBridgeStartSessionArgs {
+                session_id: args.terminal_session_id.clone(),
+                agent: args.agent.clone(),
+                mcp_mode: args.mcp_mode.clone(),
+                model: args.model.clone(),
+                effort: args.effort.clone(),
+                permission_level: args.permission_level.clone(),
+                thinking: args.thinking,
+                post_start_preamble: args.post_start_preamble.clone(),
+            },
+            on_exit,
+        )
+    };
+
+    if let Err(err) = start_result {
+        if let Some(worktree) = &worktree {
+            let _ = crate::worktree::finish_task_worktree(worktree);
+        }
+        mark_agent_session_crashed(&command_ctx, &agent_session_id, &err);
+        return Err(err);
+    }
+
+    if let Some(pid) = state.pty_manager.process_id(&args.terminal_session_id) {
+        if let Err(error) = command_ctx.db(|db| db.set_agent_session_pid(&agent_session_id, pid)) {
+            tracing::warn!(
+                agent_session_id = %agent_session_id,
+                error = %error,
+                "Failed to record agent session pid"
+            );
+        }
+    }
+
+    let connected_line =
+        format!("\x1b[2m[connected to agent_session #{agent_session_number:03}]\x1b[0m\r\n");
+    emit_system_message(
+        host.event_sink().as_ref(),
+        args.terminal_session_id,
+        connected_line,
+    )?;
+
+    Ok(TerminalBridgeStartHumanSessionResult {
+        agent_session_id,
+        agent_session_number,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::terminal::SessionTimeoutKind;
+    use base64::engine::general_purpose::STANDARD;
+    use std::path::PathBuf;
+    use tempfile::tempdir;
+
+    #[test]
+    fn locked_project_path_errors_when_unset() {
+        let state = AppState::default();
+        let err = locked_project_path(&state).unwrap_err();
+        assert!(err.contains("No project locked"));
+    }
+
+    #[test]
+    fn locked_project_path_returns_value_when_set() {
+        let state = AppState::default();
+        let expected = PathBuf::from("/tmp/ralph4days-test-project");
+        *state.locked_project.lock().unwrap() = Some(expected.clone());
+        let actual = locked_project_path(&state).unwrap();
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn build_system_message_event_encodes_text_as_base64() {
+        let event =
+            build_system_message_event("session-1".to_owned(), "[session started]\r\n".to_owned());
+        assert_eq!(event.session_id, "session-1");
+        assert_eq!(event.seq, 0);
+        let decoded = STANDARD.decode(event.data).unwrap();
+        assert_eq!(String::from_utf8(decoded).unwrap(), "[session started]\r\n");
+    }
+
+    #[test]
+    fn build_system_message_event_preserves_ansi_and_newlines() {
+        let text = "\u{1b}[2m[session #001 started]\u{1b}[0m\r\n".to_owned();
+        let event = build_system_message_event("session-ansi".to_owned(), text.clone());
+        let decoded = STANDARD.decode(event.data).unwrap();
+        assert_eq!(String::from_utf8(decoded).unwrap(), text);
+    }
+
+    #[test]
+    fn build_launch_command_for_codex_uses_reasoning_effort_config_override() {
+        let config = SessionConfig {
+            agent: Some(AGENT_CODEX.to_owned()),
+            model: Some("gpt-5.3-codex".to_owned()),
+            effort: Some("high".to_owned()),
+            thinking: None,
+            permission_level: None,
+            init_settings: SessionInitSettings::default(),
+            post_start_preamble: None,
+            initial_prompt: None,
+            timeouts: SessionTimeouts::default(),
+        };
+
+        let launch = build_launch_command(&config);
+        assert!(launch.contains("--config model_reasoning_effort=high"));
+        assert!(!launch.contains("--effort "));
+    }
+
+    #[test]
+    fn build_session_config_for_fake_agent_rejects_model_selection() {
+        let err = build_session_config(
+            Some(AGENT_FAKE.to_owned()),
+            Some("sonnet".to_owned()),
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap_err();
+        assert!(err.contains("Fake agent terminal sessions do not support model selection"));
+
+        let config =
+            build_session_config(Some(AGENT_FAKE.to_owned()), None, None, None, None, None)
+                .unwrap();
+        assert_eq!(config.model, None);
+        assert_eq!(build_launch_command(&config), "ralph-fake-agent");
+    }
+
+    fn app_state_with_locked_project(path: PathBuf) -> AppState {
+        let state = AppState::default();
+        *state.locked_project.lock().unwrap() = Some(path);
+        state
+    }
+
+    #[test]
+    fn resolve_start_session_context_errors_when_project_unlocked() {
+        let state = AppState::default();
+        let err = resolve_start_session_context(&state, Some("interactive")).unwrap_err();
+        assert!(err.contains("No project locked"));
+    }
+
+    #[test]
+    fn resolve_start_task_session_context_errors_when_project_unlocked() {
+        let state = AppState::default();
+        let err =
+            resolve_start_task_session_context(&state, 7, TaskPromptSpec::default()).unwrap_err();
+        assert!(err.contains("No project locked"));
+    }
+
+    #[test]
+    fn resolve_start_session_context_allows_no_mcp_config_without_db() {
+        let dir = tempdir().unwrap();
+        let state = app_state_with_locked_project(dir.path().to_path_buf());
+
+        let (project_path, mcp_config) = resolve_start_session_context(&state, None).unwrap();
+        assert_eq!(project_path, dir.path().to_path_buf());
+        assert!(mcp_config.is_none());
+    }
+
+    #[test]
+    fn resolve_start_session_context_requires_open_db_when_generating_mcp() {
+        let dir = tempdir().unwrap();
+        let state = app_state_with_locked_project(dir.path().to_path_buf());
+
+        let err = resolve_start_session_context(&state, Some("interactive")).unwrap_err();
+        assert!(err.contains("database not open"));
+    }
+
+    #[test]
+    fn resolve_start_task_session_context_requires_open_db() {
+        let dir = tempdir().unwrap();
+        let state = app_state_with_locked_project(dir.path().to_path_buf());
+
+        let err =
+            resolve_start_task_session_context(&state, 42, TaskPromptSpec::default()).unwrap_err();
+        assert!(err.contains("database not open"));
+    }
+
+    #[test]
+    fn session_exit_status_prefers_timeout_then_cancellation() {
+        let exit = |exit_code, cancelled, timed_out| SessionExit {
+            exit_code,
+            output_bytes: 0,
+            cancelled,
+            timed_out,
+        };
+        let idle = Some((SessionTimeoutKind::Idle, "idle".to_owned()));
+
+        assert_eq!(session_exit_status(&exit(0, false, None)), "finished");
+        assert_eq!(session_exit_status(&exit(1, false, None)), "crashed");
+        assert_eq!(session_exit_status(&exit(1, true, None)), "cancelled");
+        assert_eq!(session_exit_status(&exit(1, true, idle)), "timed_out");
+    }
+
+    #[test]
+    fn resolve_task_session_timeouts_requires_open_db() {
+        let dir = tempdir().unwrap();
+        let state = app_state_with_locked_project(dir.path().to_path_buf());
+
+        let err = resolve_task_session_timeouts(&state, 42).unwrap_err();
+        assert!(err.contains("database not open"));
+    }
+}
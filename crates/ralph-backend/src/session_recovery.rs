@@ -0,0 +1,200 @@
+//! Crash recovery for agent sessions a previous app process left `running`.
+
+use crate::diagnostics;
+use sqlite_db::{AgentSession, SessionRecovery, SqliteDb};
+
+const ORPHANED_SESSION_ERROR: &str =
+    "Session was still running when Ralph exited; marked crashed when the project was reopened";
+
+/// How long after its row is created an agent may still be starting. A process on the
+/// recorded pid that started later than that is another program that reused the pid.
+const SPAWN_WINDOW_SECS: i64 = 60;
+
+/// Seconds since the OS process with this id started, or `None` when there is no such process.
+#[cfg(unix)]
+fn process_age_secs(pid: u32) -> Option<i64> {
+    let output = std::process::Command::new("ps")
+        .args(["-o", "etime=", "-p", &pid.to_string()])
+        .stderr(std::process::Stdio::null())
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    parse_elapsed(String::from_utf8_lossy(&output.stdout).trim())
+}
+
+/// Seconds since the OS process with this id started, or `None` when there is no such process.
+#[cfg(not(unix))]
+fn process_age_secs(pid: u32) -> Option<i64> {
+    let script = format!(
+        "[int]((Get-Date) - (Get-Process -Id {pid} -ErrorAction Stop).StartTime).TotalSeconds"
+    );
+    let output = std::process::Command::new("powershell")
+        .args(["-NoProfile", "-Command", &script])
+        .stderr(std::process::Stdio::null())
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8_lossy(&output.stdout).trim().parse().ok()
+}
+
+/// `ps` elapsed time, `[[dd-]hh:]mm:ss`, in seconds.
+#[cfg(unix)]
+fn parse_elapsed(text: &str) -> Option<i64> {
+    let (days, clock) = text
+        .split_once('-')
+        .map_or(Some((0, text)), |(days, clock)| {
+            Some((days.parse::<i64>().ok()?, clock))
+        })?;
+    let secs = clock.split(':').try_fold(0i64, |total, part| {
+        Some(total * 60 + part.parse::<i64>().ok()?)
+    })?;
+    Some(days * 86_400 + secs)
+}
+
+/// Whether the agent of a `running` session is still running: its recorded pid is alive and
+/// that process started with the session rather than later, on a reused pid.
+pub(crate) fn session_is_live(session: &AgentSession) -> bool {
+    let Some(pid) = session.pid else {
+        return false;
+    };
+    let Some(started) = session
+        .started
+        .as_deref()
+        .and_then(|started| chrono::DateTime::parse_from_rfc3339(started).ok())
+    else {
+        return false;
+    };
+    let Some(age) = process_age_secs(pid) else {
+        return false;
+    };
+    let process_started = chrono::Utc::now() - chrono::Duration::seconds(age);
+    process_started
+        <= started.with_timezone(&chrono::Utc) + chrono::Duration::seconds(SPAWN_WINDOW_SECS)
+}
+
+fn describe_recovery(recovery: &SessionRecovery) -> String {
+    let mut message = format!(
+        "Recovered {} orphaned agent session(s) from a previous run",
+        recovery.crashed_session_ids.len()
+    );
+    if !recovery.requeued_task_ids.is_empty() {
+        let task_ids = recovery
+            .requeued_task_ids
+            .iter()
+            .map(|id| format!("#{id}"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        message.push_str(&format!("; returned task(s) {task_ids} to pending"));
+    }
+    message
+}
+
+/// Mark sessions whose agent process is gone as `crashed` and requeue their tasks.
+/// Called when a project is locked, before any PTY session can start.
+pub(crate) fn recover_orphaned_sessions(db: &SqliteDb) -> Result<SessionRecovery, String> {
+    let recovery = db.reconcile_orphaned_agent_sessions(session_is_live, ORPHANED_SESSION_ERROR)?;
+
+    if !recovery.is_empty() {
+        let message = describe_recovery(&recovery);
+        tracing::warn!(
+            sessions = ?recovery.crashed_session_ids,
+            tasks = ?recovery.requeued_task_ids,
+            "{message}"
+        );
+        diagnostics::emit_warning("session-recovery", "orphaned-sessions-recovered", &message);
+    }
+
+    Ok(recovery)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlite_db::AgentSessionCreateInput;
+
+    fn seed_running_session(db: &SqliteDb, id: &str, task_id: Option<u32>) {
+        db.create_human_agent_session(AgentSessionCreateInput {
+            id: id.to_owned(),
+            kind: "task_execution".to_owned(),
+            task_id,
+            agent: Some("claude".to_owned()),
+            model: None,
+            launch_command: None,
+            post_start_preamble: None,
+            init_prompt: None,
+        })
+        .unwrap();
+    }
+
+    #[test]
+    fn current_process_has_an_age() {
+        assert!(process_age_secs(std::process::id()).is_some());
+        assert_eq!(process_age_secs(i32::MAX as u32), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn parses_ps_elapsed_times() {
+        assert_eq!(parse_elapsed("00:07"), Some(7));
+        assert_eq!(parse_elapsed("01:02:03"), Some(3723));
+        assert_eq!(parse_elapsed("2-00:00:01"), Some(172_801));
+        assert_eq!(parse_elapsed(""), None);
+    }
+
+    #[test]
+    fn recovers_sessions_whose_process_is_gone() {
+        let db = SqliteDb::open_in_memory(None).unwrap();
+        seed_running_session(&db, "dead", None);
+        db.set_agent_session_pid("dead", i32::MAX as u32).unwrap();
+        seed_running_session(&db, "never-spawned", None);
+        seed_running_session(&db, "alive", None);
+        db.set_agent_session_pid("alive", std::process::id())
+            .unwrap();
+
+        let recovery = recover_orphaned_sessions(&db).unwrap();
+        assert_eq!(
+            recovery.crashed_session_ids,
+            vec!["dead".to_owned(), "never-spawned".to_owned()]
+        );
+        assert!(recovery.requeued_task_ids.is_empty());
+        assert_eq!(
+            db.get_agent_session_by_id("dead")
+                .unwrap()
+                .error_text
+                .as_deref(),
+            Some(ORPHANED_SESSION_ERROR)
+        );
+        assert_eq!(
+            db.get_agent_session_by_id("alive").unwrap().status,
+            "running"
+        );
+    }
+
+    #[test]
+    fn a_pid_reused_after_the_session_started_is_not_live() {
+        let started = chrono::Utc::now() - chrono::Duration::days(30);
+        let db = SqliteDb::open_in_memory(Some(Box::new(sqlite_db::FixedClock(started)))).unwrap();
+        seed_running_session(&db, "reused", None);
+        db.set_agent_session_pid("reused", std::process::id())
+            .unwrap();
+
+        let recovery = recover_orphaned_sessions(&db).unwrap();
+        assert_eq!(recovery.crashed_session_ids, vec!["reused".to_owned()]);
+    }
+
+    #[test]
+    fn describes_recovered_sessions_and_tasks() {
+        let recovery = SessionRecovery {
+            crashed_session_ids: vec!["a".to_owned(), "b".to_owned()],
+            requeued_task_ids: vec![3, 7],
+        };
+        assert_eq!(
+            describe_recovery(&recovery),
+            "Recovered 2 orphaned agent session(s) from a previous run; returned task(s) #3, #7 to pending"
+        );
+    }
+}
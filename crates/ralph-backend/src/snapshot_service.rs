@@ -0,0 +1,209 @@
+//! Keeps the locked project's `CodebaseSnapshot` current without re-walking it for every prompt.
+//!
+//! A filesystem watcher marks the snapshot dirty; the next prompt refreshes it, and the
+//! refresh only re-reads files whose size or mtime changed. Only the dirs the snapshot walks
+//! are watched (each non-recursively), so ignored trees like `node_modules`, `target` or
+//! `.ralph/worktrees` never cost a watch.
+
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use prompt_builder::snapshot::Snapshotter;
+use prompt_builder::CodebaseSnapshot;
+use ralph_errors::{codes, ToStringErr};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Changes under these project dirs never affect the snapshot (Ralph's own db writes
+/// would otherwise keep it permanently dirty).
+const UNWATCHED_DIRS: &[&str] = &[".git", ".ralph"];
+
+struct Tracked {
+    snapshotter: Snapshotter,
+    snapshot: CodebaseSnapshot,
+    dirty: Arc<AtomicBool>,
+    /// `None` when the watcher couldn't start; every call then refreshes.
+    watcher: Option<RecommendedWatcher>,
+    watched: BTreeSet<PathBuf>,
+}
+
+impl Tracked {
+    fn refresh(&mut self) {
+        self.snapshot = self.snapshotter.refresh();
+        let Some(watcher) = self.watcher.as_mut() else {
+            return;
+        };
+
+        let walked: BTreeSet<PathBuf> = self.snapshotter.dirs().iter().cloned().collect();
+        for gone in self.watched.difference(&walked) {
+            // The dir may already be deleted, which drops its watch anyway.
+            let _ = watcher.unwatch(gone);
+        }
+        let mut added = false;
+        for dir in walked.difference(&self.watched) {
+            match watcher.watch(dir, RecursiveMode::NonRecursive) {
+                Ok(()) => added = true,
+                Err(error) => tracing::debug!("Failed to watch {}: {error}", dir.display()),
+            }
+        }
+        // Changes that landed between the walk and the new watches would otherwise be missed.
+        if added {
+            self.dirty.store(true, Ordering::SeqCst);
+        }
+        self.watched = walked;
+    }
+}
+
+#[derive(Default)]
+pub struct SnapshotService {
+    tracked: Mutex<Option<Tracked>>,
+}
+
+impl SnapshotService {
+    pub fn snapshot(&self, project_path: &Path) -> Result<CodebaseSnapshot, String> {
+        let mut guard = self.tracked.lock().err_str(codes::INTERNAL)?;
+        match guard.as_mut() {
+            Some(tracked) if tracked.snapshotter.root() == project_path => {
+                let stale =
+                    tracked.watcher.is_none() || tracked.dirty.swap(false, Ordering::SeqCst);
+                if stale {
+                    tracked.refresh();
+                }
+                Ok(tracked.snapshot.clone())
+            }
+            _ => {
+                let tracked = Self::track(project_path);
+                let snapshot = tracked.snapshot.clone();
+                *guard = Some(tracked);
+                Ok(snapshot)
+            }
+        }
+    }
+
+    fn track(project_path: &Path) -> Tracked {
+        let dirty = Arc::new(AtomicBool::new(false));
+        let watcher = match watcher(project_path, Arc::clone(&dirty)) {
+            Ok(watcher) => Some(watcher),
+            Err(error) => {
+                let message = format!(
+                    "Failed to watch {} for changes: {error}. \
+                     The codebase snapshot will be rebuilt for every prompt.",
+                    project_path.display()
+                );
+                tracing::warn!("{message}");
+                crate::diagnostics::emit_warning("snapshot", "snapshot-watch-failed", &message);
+                None
+            }
+        };
+
+        let mut tracked = Tracked {
+            snapshotter: Snapshotter::new(project_path),
+            snapshot: CodebaseSnapshot::default(),
+            dirty,
+            watcher,
+            watched: BTreeSet::new(),
+        };
+        tracked.refresh();
+        tracked
+    }
+}
+
+fn affects_snapshot(project_path: &Path, path: &Path) -> bool {
+    !UNWATCHED_DIRS
+        .iter()
+        .any(|dir| path.starts_with(project_path.join(dir)))
+}
+
+fn watcher(project_path: &Path, dirty: Arc<AtomicBool>) -> notify::Result<RecommendedWatcher> {
+    let root = project_path.to_path_buf();
+    notify::recommended_watcher(move |result: notify::Result<notify::Event>| {
+        match result {
+            Ok(event) => {
+                if !event.kind.is_access()
+                    && event.paths.iter().any(|path| affects_snapshot(&root, path))
+                {
+                    dirty.store(true, Ordering::SeqCst);
+                }
+            }
+            // Missed events (e.g. queue overflow): assume anything changed.
+            Err(_) => dirty.store(true, Ordering::SeqCst),
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+    use tempfile::tempdir;
+
+    #[test]
+    fn ralph_and_git_dirs_do_not_affect_snapshot() {
+        let root = Path::new("/project");
+        assert!(affects_snapshot(root, Path::new("/project/src/main.rs")));
+        assert!(affects_snapshot(root, Path::new("/project/.ralphignore")));
+        assert!(!affects_snapshot(
+            root,
+            Path::new("/project/.ralph/db/ralph.db")
+        ));
+        assert!(!affects_snapshot(root, Path::new("/project/.git/index")));
+    }
+
+    #[test]
+    fn snapshot_refreshes_after_files_change() {
+        let dir = tempdir().unwrap();
+        let project = dir.path().canonicalize().unwrap();
+        std::fs::write(project.join("main.rs"), "fn main() {}\n").unwrap();
+        let service = SnapshotService::default();
+
+        assert_eq!(service.snapshot(&project).unwrap().total_files, 1);
+
+        std::fs::write(project.join("lib.rs"), "pub fn run() {}\n").unwrap();
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let mut total_files = 1;
+        while total_files != 2 && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(50));
+            total_files = service.snapshot(&project).unwrap().total_files;
+        }
+        assert_eq!(total_files, 2);
+    }
+
+    #[test]
+    fn watches_only_walked_dirs_and_picks_up_new_ones() {
+        let dir = tempdir().unwrap();
+        let project = dir.path().canonicalize().unwrap();
+        std::fs::create_dir_all(project.join("node_modules/pkg")).unwrap();
+        std::fs::create_dir_all(project.join(".ralph/worktrees/task-1")).unwrap();
+        let service = SnapshotService::default();
+        assert_eq!(service.snapshot(&project).unwrap().total_files, 0);
+        let watched: Vec<PathBuf> = service
+            .tracked
+            .lock()
+            .unwrap()
+            .as_ref()
+            .unwrap()
+            .watched
+            .iter()
+            .cloned()
+            .collect();
+        assert_eq!(watched, vec![project.clone()]);
+
+        std::fs::create_dir_all(project.join("src")).unwrap();
+        std::fs::write(project.join("src/main.rs"), "fn main() {}\n").unwrap();
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let mut total_files = 0;
+        while total_files != 1 && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(50));
+            total_files = service.snapshot(&project).unwrap().total_files;
+        }
+        assert_eq!(total_files, 1);
+
+        std::fs::write(project.join("src/lib.rs"), "pub fn run() {}\n").unwrap();
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while total_files != 2 && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(50));
+            total_files = service.snapshot(&project).unwrap().total_files;
+        }
+        assert_eq!(total_files, 2);
+    }
+}
@@ -0,0 +1,48 @@
+//! The discipline stacks a new project can be seeded with.
+
+use ralph_macros::ipc_type;
+
+#[ipc_type]
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VisualIdentityData {
+    pub style: String,
+    pub theme: String,
+    pub tone: String,
+    pub references: String,
+}
+
+#[ipc_type]
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StackMetadataData {
+    pub stack_id: u8,
+    pub name: String,
+    pub description: String,
+    pub philosophy: String,
+    pub visual_identity: VisualIdentityData,
+    pub when_to_use: Vec<String>,
+    pub discipline_count: u8,
+    pub characteristics: Vec<String>,
+}
+
+pub fn stacks_metadata() -> Vec<StackMetadataData> {
+    predefined_disciplines::get_all_stack_metadata()
+        .iter()
+        .map(|m| StackMetadataData {
+            stack_id: m.stack_id,
+            name: m.name.clone(),
+            description: m.description.clone(),
+            philosophy: m.philosophy.clone(),
+            visual_identity: VisualIdentityData {
+                style: m.visual_identity.style.clone(),
+                theme: m.visual_identity.theme.clone(),
+                tone: m.visual_identity.tone.clone(),
+                references: m.visual_identity.references.clone(),
+            },
+            when_to_use: m.when_to_use.clone(),
+            discipline_count: m.discipline_count,
+            characteristics: m.characteristics.clone(),
+        })
+        .collect()
+}
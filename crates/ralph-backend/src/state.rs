@@ -0,0 +1,384 @@
+use crate::context_loader::{assemble_prompt_context, embed_rag_query, rag_target, PromptInputs};
+use crate::diagnostics;
+use crate::terminal::PTYManager;
+use crate::xdg::XdgDirs;
+use prompt_builder::PromptContext;
+use ralph_errors::{codes, RalphResultExt, ToStringErr};
+use sqlite_db::SqliteDb;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// A project held open in this backend besides the locked one. Switching back to it
+/// reuses its database and text mirror instead of opening them again.
+pub(crate) struct OpenProject {
+    pub(crate) db: SqliteDb,
+    pub(crate) text_mirror: crate::text_mirror::TextMirrorService,
+}
+
+pub struct AppState {
+    pub locked_project: Mutex<Option<PathBuf>>,
+    pub db: Mutex<Option<SqliteDb>>,
+    /// The other open projects, keyed by canonical path.
+    pub(crate) open_projects: Mutex<BTreeMap<PathBuf, OpenProject>>,
+    pub codebase_snapshot: crate::snapshot_service::SnapshotService,
+    pub text_mirror: crate::text_mirror::TextMirrorService,
+    pub pty_manager: PTYManager,
+    mcp_dir: PathBuf,
+    pub xdg: XdgDirs,
+    pub api_server_port: Mutex<Option<u16>>,
+    pub execution: Mutex<crate::worker_pool::WorkerPool>,
+    pub enrichment_running: std::sync::atomic::AtomicBool,
+}
+
+impl Default for AppState {
+    fn default() -> Self {
+        let xdg = match XdgDirs::resolve() {
+            Ok(xdg) => xdg,
+            Err(error) => {
+                let message = format!(
+                    "Failed to resolve XDG directories: {error}. Using fallback temp directories."
+                );
+                diagnostics::emit_warning("app-state", "xdg-resolve-fallback", &message);
+                tracing::warn!("{message}");
+                XdgDirs::fallback()
+            }
+        };
+
+        Self {
+            locked_project: Mutex::new(None),
+            db: Mutex::new(None),
+            open_projects: Mutex::new(BTreeMap::new()),
+            codebase_snapshot: crate::snapshot_service::SnapshotService::default(),
+            text_mirror: crate::text_mirror::TextMirrorService::default(),
+            pty_manager: PTYManager::new(),
+            mcp_dir: std::env::temp_dir().join(format!("ralph-mcp-{}", std::process::id())),
+            xdg,
+            api_server_port: Mutex::new(None),
+            execution: Mutex::new(crate::worker_pool::WorkerPool::default()),
+            enrichment_running: std::sync::atomic::AtomicBool::new(false),
+        }
+    }
+}
+
+impl Drop for AppState {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.mcp_dir);
+    }
+}
+
+pub(super) struct ProjectSessionService<'a> {
+    app_state: &'a AppState,
+}
+
+impl<'a> ProjectSessionService<'a> {
+    pub(super) fn new(app_state: &'a AppState) -> Self {
+        Self { app_state }
+    }
+
+    pub(super) fn with_db<T, F>(&self, f: F) -> Result<T, String>
+    where
+        F: FnOnce(&SqliteDb) -> Result<T, String>,
+    {
+        let guard = self.app_state.db.lock().err_str(codes::INTERNAL)?;
+        let db = guard.as_ref().ok_or_else(|| {
+            ralph_errors::err_string(codes::PROJECT_LOCK, "No project locked (database not open)")
+        })?;
+        // Pick up edits to the text mirror first, and write this call's changes to it after.
+        self.app_state.text_mirror.sync(db);
+        let result = f(db);
+        self.app_state.text_mirror.sync(db);
+        result
+    }
+
+    pub(super) fn with_db_tx<T, F>(&self, f: F) -> Result<T, String>
+    where
+        F: FnOnce(&SqliteDb) -> Result<T, String>,
+    {
+        self.with_db(|db| TransactionService::new(db).run(f))
+    }
+
+    pub(super) fn locked_project_path(&self) -> Result<PathBuf, String> {
+        let locked = self
+            .app_state
+            .locked_project
+            .lock()
+            .err_str(codes::INTERNAL)?;
+        locked
+            .as_ref()
+            .cloned()
+            .ok_or_else(|| ralph_errors::err_string(codes::PROJECT_LOCK, "No project locked"))
+    }
+
+    pub(super) fn maybe_locked_project_path(&self) -> Result<Option<PathBuf>, String> {
+        let locked = self
+            .app_state
+            .locked_project
+            .lock()
+            .err_str(codes::INTERNAL)?;
+        Ok(locked.as_ref().cloned())
+    }
+}
+
+pub(super) struct TransactionService<'a> {
+    db: &'a SqliteDb,
+}
+
+impl<'a> TransactionService<'a> {
+    pub(super) fn new(db: &'a SqliteDb) -> Self {
+        Self { db }
+    }
+
+    pub(super) fn run<T, F>(&self, f: F) -> Result<T, String>
+    where
+        F: FnOnce(&SqliteDb) -> Result<T, String>,
+    {
+        self.db.with_transaction(f)
+    }
+}
+
+pub struct CommandContext<'a> {
+    session: ProjectSessionService<'a>,
+}
+
+impl<'a> CommandContext<'a> {
+    pub fn new(app_state: &'a AppState) -> Self {
+        Self {
+            session: ProjectSessionService::new(app_state),
+        }
+    }
+
+    pub fn db<T, F>(&self, f: F) -> Result<T, String>
+    where
+        F: FnOnce(&SqliteDb) -> Result<T, String>,
+    {
+        self.session.with_db(f)
+    }
+
+    pub fn db_tx<T, F>(&self, f: F) -> Result<T, String>
+    where
+        F: FnOnce(&SqliteDb) -> Result<T, String>,
+    {
+        self.session.with_db_tx(f)
+    }
+
+    pub fn locked_project_path(&self) -> Result<PathBuf, String> {
+        self.session.locked_project_path()
+    }
+
+    pub fn maybe_locked_project_path(&self) -> Result<Option<PathBuf>, String> {
+        self.session.maybe_locked_project_path()
+    }
+}
+
+impl AppState {
+    pub fn build_prompt_context(
+        &self,
+        project_path: &Path,
+        user_input: Option<String>,
+        instruction_overrides: HashMap<String, String>,
+        target_task_id: Option<u32>,
+    ) -> Result<PromptContext, String> {
+        let rag_task = CommandContext::new(self).db(|db| Ok(rag_target(db, target_task_id)))?;
+        // Embedding the RAG query waits on Ollama, so it runs with the database unlocked.
+        let query_embedding = rag_task.as_ref().and_then(embed_rag_query);
+
+        let inputs = PromptInputs {
+            user_input,
+            instruction_overrides,
+            target_task_id,
+            script_dir: self.mcp_dir.clone(),
+            api_server_port: *self.api_server_port.lock().err_str(codes::INTERNAL)?,
+            codebase_snapshot: Some(self.codebase_snapshot.snapshot(project_path)?),
+        };
+        CommandContext::new(self)
+            .db(|db| assemble_prompt_context(db, project_path, inputs, query_embedding.as_ref()))
+    }
+
+    pub(super) fn generate_mcp_config(
+        &self,
+        mode: &str,
+        project_path: &std::path::Path,
+    ) -> Result<PathBuf, String> {
+        let prompt_type = match mode {
+            "task_creation" => prompt_builder::PromptType::Braindump,
+            _ => prompt_builder::PromptType::Discuss,
+        };
+
+        // `.ralph/prompts/{section}.md` templates are applied by build_prompt_context.
+        let recipe = prompt_builder::recipes::get(prompt_type);
+        let ctx =
+            self.build_prompt_context(project_path, None, std::collections::HashMap::new(), None)?;
+
+        let (scripts, config_json) =
+            prompt_builder::mcp::generate(&ctx, recipe.mcp_mode, &recipe.mcp_tools);
+
+        self.write_mcp_artifacts(&scripts, &config_json, format!("mcp-{mode}.json"))
+    }
+
+    /// Render the prompt a task session starts with and write its MCP config.
+    /// A saved prompt builder config named after the recipe replaces the built-in sections.
+    pub fn render_task_prompt(
+        &self,
+        task_id: u32,
+        project_path: &std::path::Path,
+        spec: TaskPromptSpec,
+    ) -> Result<RenderedTaskPrompt, String> {
+        let recipe = prompt_builder::recipes::get(spec.prompt_type);
+        let config =
+            CommandContext::new(self).db(|db| db.get_prompt_builder_config(recipe.name))?;
+        let overrides = config
+            .as_ref()
+            .map(|config| {
+                config
+                    .sections
+                    .iter()
+                    .filter(|(_, settings)| settings.enabled)
+                    .filter_map(|(name, settings)| {
+                        settings
+                            .instruction_override
+                            .clone()
+                            .map(|text| (name.clone(), text))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let ctx =
+            self.build_prompt_context(project_path, spec.user_input, overrides, Some(task_id))?;
+
+        let sections = match &config {
+            Some(config) => {
+                let enabled: Vec<&str> = config
+                    .section_order
+                    .iter()
+                    .filter(|name| config.sections.get(*name).is_some_and(|s| s.enabled))
+                    .map(String::as_str)
+                    .collect();
+                prompt_builder::build_custom_sections(&enabled, &ctx)
+            }
+            None => prompt_builder::build_sections(spec.prompt_type, &ctx),
+        };
+
+        let (scripts, mcp_config_json) =
+            prompt_builder::mcp::generate(&ctx, recipe.mcp_mode, &recipe.mcp_tools);
+        let mcp_config_path = self.write_mcp_artifacts(
+            &scripts,
+            &mcp_config_json,
+            format!("mcp-task-{task_id}.json"),
+        )?;
+
+        Ok(RenderedTaskPrompt {
+            recipe: recipe.name,
+            mcp_config_path,
+            mcp_config_json,
+            config_name: config.map(|config| config.name),
+            sections,
+        })
+    }
+
+    fn write_mcp_artifacts(
+        &self,
+        scripts: &[prompt_builder::McpScript],
+        config_json: &str,
+        config_filename: String,
+    ) -> Result<PathBuf, String> {
+        std::fs::create_dir_all(&self.mcp_dir)
+            .ralph_err(codes::FILESYSTEM, "Failed to create MCP dir")?;
+
+        for script in scripts {
+            let script_path = self.mcp_dir.join(&script.filename);
+            std::fs::write(&script_path, &script.content)
+                .ralph_err(codes::FILESYSTEM, "Failed to write MCP script")?;
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                std::fs::set_permissions(&script_path, std::fs::Permissions::from_mode(0o755))
+                    .ralph_err(codes::FILESYSTEM, "Failed to chmod MCP script")?;
+            }
+        }
+
+        let config_path = self.mcp_dir.join(config_filename);
+        std::fs::write(&config_path, config_json)
+            .ralph_err(codes::FILESYSTEM, "Failed to write MCP config")?;
+
+        Ok(config_path)
+    }
+}
+
+/// `agent_sessions.kind` of a session reviewing a task that reported `done`.
+pub(crate) const REVIEW_SESSION_KIND: &str = "review";
+
+/// What a task session's prompt is rendered from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaskPromptSpec {
+    pub prompt_type: prompt_builder::PromptType,
+    pub user_input: Option<String>,
+}
+
+impl Default for TaskPromptSpec {
+    fn default() -> Self {
+        Self {
+            prompt_type: prompt_builder::PromptType::TaskExecution,
+            user_input: None,
+        }
+    }
+}
+
+impl TaskPromptSpec {
+    /// Review sessions render the review recipe with their `init_prompt` as the review brief.
+    /// Other task sessions render task execution; their `init_prompt` is typed in by the UI.
+    pub fn for_session(kind: &str, init_prompt: Option<String>) -> Self {
+        if kind == REVIEW_SESSION_KIND {
+            Self {
+                prompt_type: prompt_builder::PromptType::OpusReview,
+                user_input: init_prompt,
+            }
+        } else {
+            Self::default()
+        }
+    }
+}
+
+/// A task session's prompt, as rendered when the session started.
+#[derive(Debug)]
+pub struct RenderedTaskPrompt {
+    /// Recipe the prompt was rendered from.
+    pub recipe: &'static str,
+    pub mcp_config_path: PathBuf,
+    pub mcp_config_json: String,
+    /// Saved prompt builder config used instead of the built-in recipe.
+    pub config_name: Option<String>,
+    pub sections: Vec<prompt_builder::PromptSection>,
+}
+
+impl RenderedTaskPrompt {
+    pub fn prompt(&self) -> String {
+        self.sections
+            .iter()
+            .map(|section| section.content.as_str())
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+
+    /// `delivered` is the prompt text as handed to the agent.
+    pub(super) fn record_for(
+        &self,
+        session_id: &str,
+        delivered: &str,
+    ) -> sqlite_db::AgentSessionPromptInput {
+        sqlite_db::AgentSessionPromptInput {
+            session_id: session_id.to_owned(),
+            recipe: self.recipe.to_owned(),
+            config_name: self.config_name.clone(),
+            sections: self
+                .sections
+                .iter()
+                .map(|section| section.name.clone())
+                .collect(),
+            prompt_hash: prompt_builder::hash_content(delivered),
+            prompt: delivered.to_owned(),
+            mcp_config: Some(self.mcp_config_json.clone()),
+        }
+    }
+}
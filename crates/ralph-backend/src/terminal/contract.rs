@@ -137,6 +137,8 @@ pub struct TerminalBridgeStartHumanSessionArgs {
     pub init_prompt: Option<String>,
     pub mcp_mode: Option<String>,
     pub thinking: Option<bool>,
+    /// Run a task session in the task's git worktree instead of the project checkout.
+    pub use_worktree: Option<bool>,
 }
 
 #[ipc_type]
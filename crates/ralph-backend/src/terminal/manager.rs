@@ -3,10 +3,14 @@ use portable_pty::{native_pty_system, PtySize};
 use std::collections::{HashMap, VecDeque};
 use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
-use super::providers::resolve_agent_provider;
-use super::session::{PTYSession, SessionConfig};
+use super::providers::{check_initial_prompt_size, resolve_agent_provider};
+use super::session::{
+    PTYSession, SessionConfig, SessionExit, SessionExitHook, SessionTimeoutKind, SessionTimeouts,
+};
 use super::{TerminalBridgeReplayOutputChunk, TerminalBridgeReplayOutputResult};
 use ralph_contracts::terminal::{PtyClosedEvent, PtyOutputEvent};
 use ralph_contracts::transport::EventSink;
@@ -14,6 +18,11 @@ use ralph_contracts::transport::EventSink;
 use ralph_errors::{codes, RalphResultExt, ToStringErr};
 
 const DEFAULT_REPLAY_BUFFER_BYTES: usize = 8 * 1024 * 1024;
+/// How long a timed-out session gets to exit after the interrupt before it is killed.
+const DEFAULT_TIMEOUT_GRACE: Duration = Duration::from_secs(10);
+const WATCHDOG_POLL_INTERVAL: Duration = Duration::from_millis(250);
+/// Ctrl-C; the PTY line discipline turns it into SIGINT for the foreground process.
+const INTERRUPT_BYTE: &[u8] = b"\x03";
 
 fn preview_text(bytes: &[u8], max_chars: usize) -> String {
     let escaped = String::from_utf8_lossy(bytes).escape_debug().to_string();
@@ -121,14 +130,106 @@ impl SessionStreamState {
     }
 }
 
+/// Shared between the reader thread, the watchdog and `terminate`.
+struct SessionActivity {
+    started: Instant,
+    last_output: Mutex<Instant>,
+    closed: AtomicBool,
+    cancelled: AtomicBool,
+    timed_out: Mutex<Option<(SessionTimeoutKind, String)>>,
+}
+
+impl SessionActivity {
+    fn new() -> Self {
+        let now = Instant::now();
+        Self {
+            started: now,
+            last_output: Mutex::new(now),
+            closed: AtomicBool::new(false),
+            cancelled: AtomicBool::new(false),
+            timed_out: Mutex::new(None),
+        }
+    }
+
+    fn record_output(&self) {
+        if let Ok(mut last_output) = self.last_output.lock() {
+            *last_output = Instant::now();
+        }
+    }
+
+    fn expired(
+        &self,
+        timeouts: SessionTimeouts,
+        now: Instant,
+    ) -> Option<(SessionTimeoutKind, Duration)> {
+        let wall_clock = timeouts
+            .wall_clock
+            .filter(|limit| now.duration_since(self.started) >= *limit)
+            .map(|limit| (SessionTimeoutKind::WallClock, limit));
+        wall_clock.or_else(|| {
+            let last_output = *self.last_output.lock().ok()?;
+            timeouts
+                .idle
+                .filter(|limit| now.duration_since(last_output) >= *limit)
+                .map(|limit| (SessionTimeoutKind::Idle, limit))
+        })
+    }
+}
+
+/// Interrupts the session once a limit is hit, then kills it if it has not exited within `grace`.
+fn spawn_watchdog(
+    session_id: String,
+    timeouts: SessionTimeouts,
+    grace: Duration,
+    activity: Arc<SessionActivity>,
+    writer: Arc<Mutex<Box<dyn Write + Send>>>,
+    child: Arc<Mutex<Box<dyn portable_pty::Child + Send + Sync>>>,
+) {
+    std::thread::spawn(move || {
+        let (kind, limit) = loop {
+            std::thread::sleep(WATCHDOG_POLL_INTERVAL);
+            if activity.closed.load(Ordering::SeqCst) {
+                return;
+            }
+            if let Some(expired) = activity.expired(timeouts, Instant::now()) {
+                break expired;
+            }
+        };
+
+        let reason = kind.describe(limit);
+        tracing::warn!(session_id = %session_id, reason = %reason, "PTY session timed out, interrupting");
+        if let Ok(mut timed_out) = activity.timed_out.lock() {
+            *timed_out = Some((kind, reason));
+        }
+        if let Ok(mut guard) = writer.lock() {
+            let _ = guard.write_all(INTERRUPT_BYTE);
+        }
+
+        let deadline = Instant::now() + grace;
+        while Instant::now() < deadline {
+            if activity.closed.load(Ordering::SeqCst) {
+                return;
+            }
+            std::thread::sleep(WATCHDOG_POLL_INTERVAL.min(grace));
+        }
+
+        if let Ok(mut child) = child.lock() {
+            let _ = child.kill();
+            tracing::warn!(session_id = %session_id, "PTY session killed after timeout grace period");
+        }
+    });
+}
+
 struct ManagedSession {
     pty: PTYSession,
     stream: SessionStreamState,
+    activity: Arc<SessionActivity>,
 }
 
 pub struct PTYManager {
     sessions: Arc<Mutex<HashMap<String, ManagedSession>>>,
     replay_buffer_bytes: usize,
+    timeout_grace: Duration,
 }
 
 impl Default for PTYManager {
@@ -146,10 +247,14 @@ impl PTYManager {
         Self {
             sessions: Arc::new(Mutex::new(HashMap::new())),
             replay_buffer_bytes,
+            timeout_grace: DEFAULT_TIMEOUT_GRACE,
         }
     }
 
-    #[tracing::instrument(skip(self, sink), fields(session_id = %session_id))]
+    /// Spawn the configured agent in a new PTY. `on_exit` runs on the reader thread once the
+    /// process has exited, after the closed event is emitted. Returns the initial prompt as
+    /// handed to the agent, or `None` when there was none or the agent can't take one.
+    #[tracing::instrument(skip(self, sink, on_exit), fields(session_id = %session_id))]
     pub fn create_session(
         &self,
         sink: Arc<dyn EventSink>,
@@ -157,12 +262,14 @@ impl PTYManager {
         working_dir: &Path,
         mcp_config: Option<PathBuf>,
         config: SessionConfig,
-    ) -> Result<(), String> {
+        on_exit: Option<SessionExitHook>,
+    ) -> Result<Option<String>, String> {
         tracing::info!(
             working_dir = %working_dir.display(),
             agent = ?config.agent,
             model = ?config.model,
             has_mcp = mcp_config.is_some(),
+            timeouts = ?config.timeouts,
             "Creating PTY session"
         );
 
@@ -192,7 +299,15 @@ impl PTYManager {
         tracing::debug!("PTY opened successfully");
 
         let provider = resolve_agent_provider(config.agent.as_deref());
-        let cmd = provider.build_command(working_dir, mcp_config.as_deref(), &config);
+        let mut cmd = provider.build_command(working_dir, mcp_config.as_deref(), &config);
+        let delivered_prompt = config
+            .initial_prompt
+            .as_deref()
+            .filter(|prompt| provider.apply_initial_prompt(&mut cmd, prompt))
+            .map(str::to_owned);
+        if let Some(prompt) = &delivered_prompt {
+            check_initial_prompt_size(prompt)?;
+        }
 
         tracing::debug!(
             working_dir = %working_dir.display(),
@@ -224,6 +339,8 @@ impl PTYManager {
             .try_clone_reader()
             .ralph_err(codes::TERMINAL, "Failed to clone PTY reader")?;
 
+        let activity = Arc::new(SessionActivity::new());
+
         self.sessions.lock().err_str(codes::INTERNAL)?.insert(
             session_id.clone(),
             ManagedSession {
@@ -234,6 +351,7 @@ impl PTYManager {
                     reader_handle: None,
                 },
                 stream: SessionStreamState::new(),
+                activity: Arc::clone(&activity),
             },
         );
 
@@ -242,6 +360,7 @@ impl PTYManager {
         let child_clone = Arc::clone(&child);
         let sessions_ref = Arc::clone(&self.sessions);
         let replay_buffer_bytes = self.replay_buffer_bytes;
+        let reader_activity = Arc::clone(&activity);
         let reader_handle = std::thread::spawn(move || {
             tracing::debug!(session_id = %sid, "PTY reader thread started");
             let mut buf = [0u8; 4096];
@@ -258,6 +377,7 @@ impl PTYManager {
                     }
                     Ok(n) => {
                         total_bytes += n as u64;
+                        reader_activity.record_output();
                         tracing::trace!(session_id = %sid, bytes = n, total_bytes, "PTY output");
                         tracing::debug!(
                             session_id = %sid,
@@ -300,6 +420,8 @@ impl PTYManager {
                 }
             }
 
+            reader_activity.closed.store(true, Ordering::SeqCst);
+
             let exit_code = child_clone
                 .lock()
                 .ok()
@@ -327,6 +449,20 @@ impl PTYManager {
             if let Ok(mut sessions) = sessions_ref.lock() {
                 sessions.remove(&sid);
             }
+
+            if let Some(on_exit) = on_exit {
+                let timed_out = reader_activity
+                    .timed_out
+                    .lock()
+                    .ok()
+                    .and_then(|timed_out| timed_out.clone());
+                on_exit(&SessionExit {
+                    exit_code,
+                    output_bytes: total_bytes,
+                    cancelled: reader_activity.cancelled.load(Ordering::SeqCst),
+                    timed_out,
+                });
+            }
         });
 
         if let Ok(mut sessions) = self.sessions.lock().err_str(codes::INTERNAL) {
@@ -335,6 +471,17 @@ impl PTYManager {
             }
         }
 
+        if !config.timeouts.is_unbounded() {
+            spawn_watchdog(
+                session_id.clone(),
+                config.timeouts,
+                self.timeout_grace,
+                activity,
+                Arc::clone(&writer),
+                child,
+            );
+        }
+
         if let Some(preamble) = &config.post_start_preamble {
             tracing::debug!(
                 session_id = %session_id,
@@ -354,7 +501,7 @@ impl PTYManager {
 
         tracing::info!(session_id, "PTY session created successfully");
 
-        Ok(())
+        Ok(delivered_prompt)
     }
 
     #[tracing::instrument(skip(self, data), fields(session_id, bytes = data.len()))]
@@ -408,6 +555,16 @@ impl PTYManager {
         Ok(())
     }
 
+    /// OS process id of the session's agent, while the session is open.
+    pub fn process_id(&self, session_id: &str) -> Option<u32> {
+        let child = {
+            let sessions = self.sessions.lock().ok()?;
+            Arc::clone(&sessions.get(session_id)?.pty.child)
+        };
+        let guard = child.lock().ok()?;
+        guard.process_id()
+    }
+
     #[tracing::instrument(skip(self))]
     pub fn terminate(&self, session_id: &str) -> Result<(), String> {
         let session = {
@@ -416,6 +573,7 @@ impl PTYManager {
         };
 
         if let Some(session) = session {
+            session.activity.cancelled.store(true, Ordering::SeqCst);
             if let Ok(mut child) = session.pty.child.lock() {
                 let _ = child.kill();
                 tracing::info!(session_id, "PTY session terminated (killed)");
@@ -461,6 +619,165 @@ impl PTYManager {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::terminal::providers::MAX_INITIAL_PROMPT_BYTES;
+    use crate::terminal::{SessionInitSettings, SessionTimeouts};
+    use ralph_contracts::events::BackendDiagnosticEvent;
+    use std::sync::mpsc;
+
+    struct NullSink;
+
+    impl EventSink for NullSink {
+        fn emit_backend_diagnostic(&self, _payload: BackendDiagnosticEvent) -> Result<(), String> {
+            Ok(())
+        }
+
+        fn emit_terminal_output(&self, _payload: PtyOutputEvent) -> Result<(), String> {
+            Ok(())
+        }
+
+        fn emit_terminal_closed(&self, _payload: PtyClosedEvent) -> Result<(), String> {
+            Ok(())
+        }
+    }
+
+    fn shell_config(timeouts: SessionTimeouts) -> SessionConfig {
+        SessionConfig {
+            agent: Some("shell".to_owned()),
+            model: None,
+            effort: None,
+            thinking: None,
+            permission_level: None,
+            init_settings: SessionInitSettings::default(),
+            post_start_preamble: None,
+            initial_prompt: None,
+            timeouts,
+        }
+    }
+
+    fn start_shell(
+        manager: &PTYManager,
+        session_id: &str,
+        timeouts: SessionTimeouts,
+    ) -> mpsc::Receiver<SessionExit> {
+        let (tx, rx) = mpsc::channel();
+        manager
+            .create_session(
+                Arc::new(NullSink),
+                session_id.to_owned(),
+                &std::env::temp_dir(),
+                None,
+                shell_config(timeouts),
+                Some(Box::new(move |exit: &SessionExit| {
+                    let _ = tx.send(exit.clone());
+                })),
+            )
+            .unwrap();
+        rx
+    }
+
+    #[test]
+    fn test_oversized_initial_prompt_is_refused_before_spawning() {
+        let manager = PTYManager::new();
+        let mut config = shell_config(SessionTimeouts::default());
+        config.agent = Some("fake".to_owned());
+        config.initial_prompt = Some("a".repeat(MAX_INITIAL_PROMPT_BYTES + 1));
+
+        let error = manager
+            .create_session(
+                Arc::new(NullSink),
+                "oversized-prompt".to_owned(),
+                &std::env::temp_dir(),
+                None,
+                config,
+                None,
+            )
+            .unwrap_err();
+        assert!(error.contains("KiB"), "{error}");
+        assert!(manager.sessions.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_idle_session_is_interrupted_then_killed() {
+        let mut manager = PTYManager::new();
+        manager.timeout_grace = Duration::from_millis(200);
+        let exits = start_shell(
+            &manager,
+            "idle-session",
+            SessionTimeouts {
+                wall_clock: None,
+                idle: Some(Duration::from_millis(500)),
+            },
+        );
+
+        let exit = exits.recv_timeout(Duration::from_secs(15)).unwrap();
+        let (kind, reason) = exit.timed_out.unwrap();
+        assert_eq!(kind, SessionTimeoutKind::Idle);
+        assert!(reason.contains("no output"));
+        assert!(!exit.cancelled);
+        assert!(manager.sessions.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_wall_clock_limit_applies_despite_output() {
+        let mut manager = PTYManager::new();
+        manager.timeout_grace = Duration::from_millis(200);
+        let exits = start_shell(
+            &manager,
+            "busy-session",
+            SessionTimeouts {
+                wall_clock: Some(Duration::from_secs(1)),
+                idle: Some(Duration::from_secs(60)),
+            },
+        );
+        manager
+            .send_input(
+                "busy-session",
+                b"while true; do echo tick; sleep 0.1; done\r",
+            )
+            .unwrap();
+
+        let exit = exits.recv_timeout(Duration::from_secs(15)).unwrap();
+        assert_eq!(
+            exit.timed_out.map(|(kind, _)| kind),
+            Some(SessionTimeoutKind::WallClock)
+        );
+        assert!(exit.output_bytes > 0);
+    }
+
+    #[test]
+    fn test_terminate_reports_cancelled_exit() {
+        let manager = PTYManager::new();
+        let exits = start_shell(&manager, "cancelled-session", SessionTimeouts::default());
+        manager.terminate("cancelled-session").unwrap();
+
+        let exit = exits.recv_timeout(Duration::from_secs(15)).unwrap();
+        assert!(exit.cancelled);
+        assert!(exit.timed_out.is_none());
+    }
+
+    #[test]
+    fn test_session_activity_expiry_prefers_wall_clock() {
+        let activity = SessionActivity::new();
+        let timeouts = SessionTimeouts {
+            wall_clock: Some(Duration::from_secs(30)),
+            idle: Some(Duration::from_secs(10)),
+        };
+        let now = activity.started;
+
+        assert_eq!(activity.expired(timeouts, now), None);
+        assert_eq!(
+            activity.expired(timeouts, now + Duration::from_secs(12)),
+            Some((SessionTimeoutKind::Idle, Duration::from_secs(10)))
+        );
+        assert_eq!(
+            activity.expired(timeouts, now + Duration::from_secs(31)),
+            Some((SessionTimeoutKind::WallClock, Duration::from_secs(30)))
+        );
+        assert_eq!(
+            activity.expired(SessionTimeouts::default(), now + Duration::from_secs(3600)),
+            None
+        );
+    }
 
     #[test]
     fn test_pty_manager_new() {
@@ -490,6 +807,19 @@ mod tests {
         assert!(err.contains("No terminal bridge session: missing-session"));
     }
 
+    #[test]
+    fn test_process_id_tracks_open_session() {
+        let manager = PTYManager::new();
+        assert_eq!(manager.process_id("missing-session"), None);
+
+        let exits = start_shell(&manager, "pid-session", SessionTimeouts::default());
+        assert!(manager.process_id("pid-session").is_some_and(|pid| pid > 0));
+
+        manager.terminate("pid-session").unwrap();
+        exits.recv_timeout(Duration::from_secs(15)).unwrap();
+        assert_eq!(manager.process_id("pid-session"), None);
+    }
+
     #[test]
     fn test_terminate_missing_session_is_ok() {
         let manager = PTYManager::new();
@@ -11,10 +11,13 @@ pub use contract::{
 };
 pub use manager::{PTYManager, SessionStreamMode};
 pub use ralph_contracts::terminal::PtyOutputEvent;
-pub use session::{SessionConfig, SessionInitSettings};
+pub use session::{
+    SessionConfig, SessionExit, SessionExitHook, SessionInitSettings, SessionTimeoutKind,
+    SessionTimeouts,
+};
 
 mod contract;
 mod manager;
 mod mappers;
-pub(crate) mod providers;
+pub mod providers;
 mod session;
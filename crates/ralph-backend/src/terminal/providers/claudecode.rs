@@ -21,6 +21,12 @@ impl AgentProvider for ClaudeCodeAdapter {
         AGENT_CLAUDE
     }
 
+    fn apply_initial_prompt(&self, cmd: &mut CommandBuilder, prompt: &str) -> bool {
+        cmd.arg("--");
+        cmd.arg(prompt);
+        true
+    }
+
     fn list_models(&self) -> Vec<String> {
         model_catalog::claudecode_models()
     }
@@ -38,6 +38,12 @@ impl AgentProvider for CodexAdapter {
         None
     }
 
+    fn apply_initial_prompt(&self, cmd: &mut CommandBuilder, prompt: &str) -> bool {
+        cmd.arg("--");
+        cmd.arg(prompt);
+        true
+    }
+
     fn list_models(&self) -> Vec<String> {
         model_catalog::codex_models()
     }
@@ -67,7 +73,7 @@ impl AgentProvider for CodexAdapter {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::terminal::SessionInitSettings;
+    use crate::terminal::{SessionInitSettings, SessionTimeouts};
 
     fn to_argv_strings(cmd: &CommandBuilder) -> Vec<String> {
         cmd.get_argv()
@@ -87,6 +93,8 @@ mod tests {
             permission_level: None,
             init_settings: SessionInitSettings::default(),
             post_start_preamble: None,
+            initial_prompt: None,
+            timeouts: SessionTimeouts::default(),
         };
 
         let cmd = adapter.build_command(Path::new("/tmp"), None, &config);
@@ -113,6 +121,8 @@ mod tests {
             permission_level: None,
             init_settings: SessionInitSettings::default(),
             post_start_preamble: None,
+            initial_prompt: None,
+            timeouts: SessionTimeouts::default(),
         };
 
         let cmd = adapter.build_command(Path::new("/tmp"), None, &config);
@@ -123,4 +133,14 @@ mod tests {
             .iter()
             .any(|arg| arg.starts_with("model_reasoning_effort=")));
     }
+
+    #[test]
+    fn initial_prompt_is_the_last_argument() {
+        let mut cmd = CommandBuilder::new("codex");
+        cmd.arg("--full-auto");
+
+        assert!(CodexAdapter.apply_initial_prompt(&mut cmd, "--fix the task"));
+        let argv = to_argv_strings(&cmd);
+        assert_eq!(argv[argv.len() - 2..], ["--", "--fix the task"]);
+    }
 }
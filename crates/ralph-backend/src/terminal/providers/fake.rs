@@ -0,0 +1,77 @@
+use portable_pty::CommandBuilder;
+use std::path::{Path, PathBuf};
+
+use super::{AgentProvider, SessionConfig, AGENT_FAKE};
+
+const FAKE_AGENT_BIN_ENV: &str = "RALPH_FAKE_AGENT_BIN";
+const FAKE_AGENT_SCENARIO_ENV: &str = "RALPH_FAKE_AGENT_SCENARIO";
+const FAKE_AGENT_BIN_NAME: &str = "ralph-fake-agent";
+const DEFAULT_SCENARIO_RELATIVE_PATH: &str = ".ralph/fake-agent.yaml";
+
+#[derive(Debug, Default)]
+pub struct FakeAdapter;
+
+fn env_path(key: &str) -> Option<PathBuf> {
+    std::env::var(key)
+        .ok()
+        .map(|value| value.trim().to_owned())
+        .filter(|value| !value.is_empty())
+        .map(PathBuf::from)
+}
+
+/// `RALPH_FAKE_AGENT_BIN`, else `ralph-fake-agent` next to the running executable
+/// (or one level up, which is where cargo puts bins relative to test harnesses).
+fn resolve_fake_agent_program() -> PathBuf {
+    if let Some(path) = env_path(FAKE_AGENT_BIN_ENV) {
+        return path;
+    }
+    let file_name = format!("{FAKE_AGENT_BIN_NAME}{}", std::env::consts::EXE_SUFFIX);
+    std::env::current_exe()
+        .ok()
+        .and_then(|exe| {
+            exe.ancestors()
+                .skip(1)
+                .take(2)
+                .map(|dir| dir.join(&file_name))
+                .find(|candidate| candidate.exists())
+        })
+        .unwrap_or_else(|| PathBuf::from(file_name))
+}
+
+fn resolve_scenario_path(working_dir: &Path) -> PathBuf {
+    env_path(FAKE_AGENT_SCENARIO_ENV)
+        .unwrap_or_else(|| working_dir.join(DEFAULT_SCENARIO_RELATIVE_PATH))
+}
+
+impl AgentProvider for FakeAdapter {
+    fn id(&self) -> &'static str {
+        AGENT_FAKE
+    }
+
+    fn apply_initial_prompt(&self, cmd: &mut CommandBuilder, prompt: &str) -> bool {
+        cmd.arg("--prompt");
+        cmd.arg(prompt);
+        true
+    }
+
+    fn list_models(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    fn build_command(
+        &self,
+        working_dir: &Path,
+        mcp_config: Option<&Path>,
+        _config: &SessionConfig,
+    ) -> CommandBuilder {
+        let mut cmd = CommandBuilder::new(resolve_fake_agent_program());
+        cmd.cwd(working_dir);
+        cmd.arg("--scenario");
+        cmd.arg(resolve_scenario_path(working_dir));
+        if let Some(path) = mcp_config {
+            cmd.arg("--mcp-config");
+            cmd.arg(path);
+        }
+        cmd
+    }
+}
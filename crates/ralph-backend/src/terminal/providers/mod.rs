@@ -2,12 +2,15 @@ use super::session::build_settings_json;
 pub use super::session::SessionConfig;
 pub use claudecode::ClaudeCodeAdapter;
 pub use codex::CodexAdapter;
+pub use fake::FakeAdapter;
 pub use model_catalog::ModelEntry;
-pub use provider_trait::{AgentProvider, AGENT_CLAUDE, AGENT_CODEX, AGENT_SHELL};
+pub use provider_trait::{AgentProvider, AGENT_CLAUDE, AGENT_CODEX, AGENT_FAKE, AGENT_SHELL};
+use ralph_errors::{codes, ralph_err};
 pub use shell::ShellAdapter;
 
 mod claudecode;
 mod codex;
+mod fake;
 mod model_catalog;
 #[path = "trait.rs"]
 mod provider_trait;
@@ -16,15 +19,44 @@ mod shell;
 static CLAUDE_ADAPTER: ClaudeCodeAdapter = ClaudeCodeAdapter;
 static CODEX_ADAPTER: CodexAdapter = CodexAdapter;
 static SHELL_ADAPTER: ShellAdapter = ShellAdapter;
+static FAKE_ADAPTER: FakeAdapter = FakeAdapter;
+
+/// The longest first prompt an agent is handed on its command line. Linux refuses a single
+/// argument over 128 KiB (`MAX_ARG_STRLEN`) and Windows a command line over 32K characters,
+/// so the agent would fail to spawn with a longer one.
+#[cfg(unix)]
+pub const MAX_INITIAL_PROMPT_BYTES: usize = 120 * 1024;
+#[cfg(not(unix))]
+pub const MAX_INITIAL_PROMPT_BYTES: usize = 24 * 1024;
+
+/// Refuse a first prompt the agent's command line can't carry, before anything is spawned.
+pub fn check_initial_prompt_size(prompt: &str) -> Result<(), String> {
+    if prompt.len() > MAX_INITIAL_PROMPT_BYTES {
+        return ralph_err!(
+            codes::TERMINAL,
+            "The prompt is {} KiB, more than the {} KiB an agent's command line can take; \
+             give the task fewer or smaller context files",
+            prompt.len().div_ceil(1024),
+            MAX_INITIAL_PROMPT_BYTES / 1024
+        );
+    }
+    Ok(())
+}
 
 pub fn shell_agent_enabled() -> bool {
     cfg!(debug_assertions)
 }
 
+/// The scripted fake agent is a test harness; like the shell agent it never ships in release builds.
+pub fn fake_agent_enabled() -> bool {
+    cfg!(debug_assertions)
+}
+
 pub fn resolve_agent_provider(agent: Option<&str>) -> &'static dyn AgentProvider {
     match normalize_agent(agent).as_deref() {
         Some(AGENT_CLAUDE) => &CLAUDE_ADAPTER,
         Some(AGENT_SHELL) => &SHELL_ADAPTER,
+        Some(AGENT_FAKE) => &FAKE_ADAPTER,
         _ => &CODEX_ADAPTER,
     }
 }
@@ -36,7 +68,7 @@ pub fn list_models_for_agent(agent: Option<&str>) -> Vec<String> {
 pub fn list_model_entries_for_agent(agent: Option<&str>) -> Vec<ModelEntry> {
     match normalize_agent(agent).as_deref() {
         Some(AGENT_CLAUDE) => model_catalog::claudecode_model_entries(),
-        Some(AGENT_SHELL) => Vec::new(),
+        Some(AGENT_SHELL | AGENT_FAKE) => Vec::new(),
         _ => model_catalog::codex_model_entries(),
     }
 }
@@ -143,6 +175,14 @@ fn normalize_agent(agent: Option<&str>) -> Option<String> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn initial_prompt_size_is_capped() {
+        assert!(check_initial_prompt_size(&"a".repeat(MAX_INITIAL_PROMPT_BYTES)).is_ok());
+        let error =
+            check_initial_prompt_size(&"a".repeat(MAX_INITIAL_PROMPT_BYTES + 1)).unwrap_err();
+        assert!(error.contains("fewer or smaller context files"), "{error}");
+    }
+
     #[test]
     fn resolves_codex_provider() {
         let provider = resolve_agent_provider(Some("codex"));
@@ -173,6 +213,13 @@ mod tests {
         assert!(models.is_empty());
     }
 
+    #[test]
+    fn resolves_fake_provider() {
+        let provider = resolve_agent_provider(Some("fake"));
+        assert_eq!(provider.id(), AGENT_FAKE);
+        assert!(list_model_entries_for_agent(Some("fake")).is_empty());
+    }
+
     #[test]
     fn merges_provider_then_user_preamble() {
         let merged =
@@ -6,12 +6,18 @@ use super::SessionConfig;
 pub const AGENT_CLAUDE: &str = "claude";
 pub const AGENT_CODEX: &str = "codex";
 pub const AGENT_SHELL: &str = "shell";
+pub const AGENT_FAKE: &str = "fake";
 
 pub trait AgentProvider: Send + Sync {
     fn id(&self) -> &'static str;
     fn build_post_start_preamble(&self, _config: &SessionConfig) -> Option<String> {
         None
     }
+    /// Hand the session's first prompt to the agent's command line. Returns false when the
+    /// agent can't take one (e.g. a plain shell), so the caller knows it wasn't delivered.
+    fn apply_initial_prompt(&self, _cmd: &mut CommandBuilder, _prompt: &str) -> bool {
+        false
+    }
     fn list_models(&self) -> Vec<String>;
     fn build_command(
         &self,
@@ -1,6 +1,7 @@
 use portable_pty::MasterPty;
 use std::io::Write;
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 #[derive(Debug, Clone)]
 pub struct SessionInitSettings {
@@ -27,6 +28,56 @@ impl Default for SessionInitSettings {
     }
 }
 
+/// Limits enforced by the PTY watchdog. `None` disables the corresponding check.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SessionTimeouts {
+    /// Total time the session may run.
+    pub wall_clock: Option<Duration>,
+    /// Time the session may go without producing any output.
+    pub idle: Option<Duration>,
+}
+
+impl SessionTimeouts {
+    pub fn from_secs(wall_clock_secs: Option<u32>, idle_secs: Option<u32>) -> Self {
+        Self {
+            wall_clock: wall_clock_secs.map(|secs| Duration::from_secs(u64::from(secs))),
+            idle: idle_secs.map(|secs| Duration::from_secs(u64::from(secs))),
+        }
+    }
+
+    pub fn is_unbounded(&self) -> bool {
+        self.wall_clock.is_none() && self.idle.is_none()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionTimeoutKind {
+    WallClock,
+    Idle,
+}
+
+impl SessionTimeoutKind {
+    pub fn describe(self, limit: Duration) -> String {
+        match self {
+            Self::WallClock => format!("Session exceeded wall-clock limit of {}s", limit.as_secs()),
+            Self::Idle => format!("Session produced no output for {}s", limit.as_secs()),
+        }
+    }
+}
+
+/// How a PTY session ended, handed to the session's exit hook.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionExit {
+    pub exit_code: u32,
+    pub output_bytes: u64,
+    /// The session was ended through `PTYManager::terminate`.
+    pub cancelled: bool,
+    /// Set when the watchdog ended the session, with a human-readable reason.
+    pub timed_out: Option<(SessionTimeoutKind, String)>,
+}
+
+pub type SessionExitHook = Box<dyn FnOnce(&SessionExit) + Send>;
+
 #[derive(Debug)]
 pub struct SessionConfig {
     pub agent: Option<String>,
@@ -36,6 +87,9 @@ pub struct SessionConfig {
     pub permission_level: Option<String>,
     pub init_settings: SessionInitSettings,
     pub post_start_preamble: Option<String>,
+    /// Prompt the agent starts working on, handed over on its command line.
+    pub initial_prompt: Option<String>,
+    pub timeouts: SessionTimeouts,
 }
 
 pub(crate) fn build_settings_json(
@@ -102,6 +156,8 @@ mod tests {
             permission_level: None,
             init_settings: SessionInitSettings::default(),
             post_start_preamble: None,
+            initial_prompt: None,
+            timeouts: SessionTimeouts::default(),
         };
 
         let json = build_settings_json(&config.init_settings, config.thinking);
@@ -128,6 +184,8 @@ mod tests {
             permission_level: None,
             init_settings: SessionInitSettings::default(),
             post_start_preamble: None,
+            initial_prompt: None,
+            timeouts: SessionTimeouts::default(),
         };
 
         let json = build_settings_json(&config.init_settings, config.thinking);
@@ -146,6 +204,8 @@ mod tests {
             permission_level: None,
             init_settings: SessionInitSettings::default(),
             post_start_preamble: None,
+            initial_prompt: None,
+            timeouts: SessionTimeouts::default(),
         };
 
         let json = build_settings_json(&config.init_settings, config.thinking);
@@ -164,6 +224,8 @@ mod tests {
             permission_level: None,
             init_settings: SessionInitSettings::default(),
             post_start_preamble: None,
+            initial_prompt: None,
+            timeouts: SessionTimeouts::default(),
         };
 
         let json = build_settings_json(&config.init_settings, config.thinking);
@@ -184,6 +246,8 @@ mod tests {
             permission_level: None,
             init_settings: SessionInitSettings::default(),
             post_start_preamble: None,
+            initial_prompt: None,
+            timeouts: SessionTimeouts::default(),
         };
 
         let json = build_settings_json(&config.init_settings, config.thinking);
@@ -0,0 +1,388 @@
+//! Keeps `.ralph/mirror/`, the git-friendly text copy of the project database, in step
+//! with the database.
+//!
+//! Mirroring is on for a project when the directory exists. Before each database access
+//! the files are re-imported if the watcher saw them change (a checkout, pull or merge),
+//! merged with whatever the database changed since the last export; after it, any
+//! database change is written back out. Files that don't import cleanly, or that changed
+//! on both sides, are left alone and reported as conflicts until they are fixed.
+
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use ralph_errors::{codes, RalphResultExt, ToStringErr};
+use sqlite_db::{MirrorConflict, SqliteDb, MIRROR_DIRS};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+pub const MIRROR_DIR: &str = "mirror";
+
+struct Tracked {
+    dir: PathBuf,
+    /// Set when the watcher saw a change it couldn't pin to paths.
+    dirty: Arc<AtomicBool>,
+    /// Paths the watcher saw change since the last sync.
+    changed: Arc<Mutex<BTreeSet<PathBuf>>>,
+    /// What the last export left in each file, to tell its own writes from anyone else's.
+    written: BTreeMap<PathBuf, String>,
+    /// `None` when the watcher couldn't start; every sync then re-reads the files.
+    watcher: Option<RecommendedWatcher>,
+    /// Database change marker at the last export.
+    exported: Option<(i64, i64)>,
+    conflicts: Vec<MirrorConflict>,
+}
+
+#[derive(Default)]
+pub struct TextMirrorService {
+    tracked: Mutex<Option<Tracked>>,
+}
+
+pub fn mirror_dir(project_path: &Path) -> PathBuf {
+    project_path.join(".ralph").join(MIRROR_DIR)
+}
+
+impl TextMirrorService {
+    /// Start mirroring `project_path` if it has a mirror directory.
+    pub fn attach(&self, project_path: &Path) -> Result<(), String> {
+        let dir = mirror_dir(project_path);
+        let tracked = dir.is_dir().then(|| Self::track(dir));
+        *self.tracked.lock().err_str(codes::INTERNAL)? = tracked;
+        Ok(())
+    }
+
+    /// Create the mirror directory for `project_path` and write the database into it.
+    pub fn enable(&self, project_path: &Path, db: &SqliteDb) -> Result<(), String> {
+        let dir = mirror_dir(project_path);
+        std::fs::create_dir_all(&dir).ralph_err(
+            codes::FILESYSTEM,
+            "Failed to create .ralph/mirror/ directory",
+        )?;
+        let mut guard = self.tracked.lock().err_str(codes::INTERNAL)?;
+        if guard.as_ref().map_or(true, |tracked| tracked.dir != dir) {
+            let tracked = Self::track(dir);
+            // The database is the starting point, not whatever the empty dir holds.
+            tracked.dirty.store(false, Ordering::SeqCst);
+            *guard = Some(tracked);
+        }
+        guard.as_mut().map_or(Ok(()), |tracked| export(tracked, db))
+    }
+
+    /// Put `other`'s mirror in place of this one and hand back the one it replaced.
+    pub fn replace(&self, other: Self) -> Result<Self, String> {
+        let tracked = other.tracked.into_inner().err_str(codes::INTERNAL)?;
+        let previous =
+            std::mem::replace(&mut *self.tracked.lock().err_str(codes::INTERNAL)?, tracked);
+        Ok(Self {
+            tracked: Mutex::new(previous),
+        })
+    }
+
+    pub fn is_enabled(&self) -> Result<bool, String> {
+        Ok(self.tracked.lock().err_str(codes::INTERNAL)?.is_some())
+    }
+
+    /// Conflicts found the last time the files were imported.
+    pub fn conflicts(&self) -> Result<Vec<MirrorConflict>, String> {
+        let guard = self.tracked.lock().err_str(codes::INTERNAL)?;
+        Ok(guard
+            .as_ref()
+            .map(|tracked| tracked.conflicts.clone())
+            .unwrap_or_default())
+    }
+
+    /// Import changed files, then export database changes. Failures are reported as
+    /// diagnostics; they never fail the command that triggered the sync.
+    pub fn sync(&self, db: &SqliteDb) {
+        let Ok(mut guard) = self.tracked.lock() else {
+            return;
+        };
+        let Some(tracked) = guard.as_mut() else {
+            return;
+        };
+        if let Err(error) = sync_tracked(tracked, db) {
+            let message = format!(
+                "Failed to sync {} with the database: {error}",
+                tracked.dir.display()
+            );
+            tracing::warn!("{message}");
+            crate::diagnostics::emit_warning("text-mirror", "mirror-sync-failed", &message);
+        }
+    }
+
+    fn track(dir: PathBuf) -> Tracked {
+        // Start dirty so the files are imported before anything reads the database.
+        let dirty = Arc::new(AtomicBool::new(true));
+        let changed = Arc::default();
+        let watcher = match watch(&dir, Arc::clone(&dirty), Arc::clone(&changed)) {
+            Ok(watcher) => Some(watcher),
+            Err(error) => {
+                let message = format!(
+                    "Failed to watch {} for changes: {error}. \
+                     The mirror will be re-read before every database access.",
+                    dir.display()
+                );
+                tracing::warn!("{message}");
+                crate::diagnostics::emit_warning("text-mirror", "mirror-watch-failed", &message);
+                None
+            }
+        };
+        Tracked {
+            dir,
+            dirty,
+            changed,
+            written: BTreeMap::new(),
+            watcher,
+            exported: None,
+            conflicts: Vec::new(),
+        }
+    }
+}
+
+fn sync_tracked(tracked: &mut Tracked, db: &SqliteDb) -> Result<(), String> {
+    if is_stale(tracked)? {
+        let report = db.import_mirror_files(&read_files(&tracked.dir)?)?;
+        if !report.conflicts.is_empty() && report.conflicts != tracked.conflicts {
+            let message = format!(
+                "{} has {} conflict(s); fix them to resume syncing. First: {}",
+                tracked.dir.display(),
+                report.conflicts.len(),
+                describe(&report.conflicts[0])
+            );
+            tracing::warn!("{message}");
+            crate::diagnostics::emit_warning("text-mirror", "mirror-conflicts", &message);
+        }
+        tracked.conflicts = report.conflicts;
+    }
+    // Exporting now would overwrite the files that need fixing.
+    if !tracked.conflicts.is_empty() {
+        return Ok(());
+    }
+    if tracked.exported != Some(db.mirror_change_marker()?) {
+        export(tracked, db)?;
+    }
+    Ok(())
+}
+
+/// Whether the files may differ from the last export. Paths the watcher reported but
+/// that still hold what the export wrote (or that it removed) are the export's own.
+fn is_stale(tracked: &Tracked) -> Result<bool, String> {
+    let changed = std::mem::take(&mut *tracked.changed.lock().err_str(codes::INTERNAL)?);
+    let dirty = tracked.dirty.swap(false, Ordering::SeqCst);
+    if tracked.watcher.is_none() || dirty {
+        return Ok(true);
+    }
+    Ok(changed.iter().any(|path| {
+        if path.is_dir() {
+            return false;
+        }
+        tracked.written.get(path).map_or_else(
+            || path.exists(),
+            |text| std::fs::read_to_string(path).ok().as_deref() != Some(text.as_str()),
+        )
+    }))
+}
+
+fn describe(conflict: &MirrorConflict) -> String {
+    let location = conflict.line.map_or_else(
+        || conflict.path.clone(),
+        |line| format!("{}:{line}", conflict.path),
+    );
+    format!("{location}: {}", conflict.message)
+}
+
+/// Write every file whose text changed and remove entity files the database no longer has.
+fn export(tracked: &mut Tracked, db: &SqliteDb) -> Result<(), String> {
+    let files = db.mirror_files()?;
+    for (relative, text) in &files {
+        let path = tracked.dir.join(relative);
+        if std::fs::read_to_string(&path).ok().as_deref() == Some(text.as_str()) {
+            continue;
+        }
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .ralph_err(codes::FILESYSTEM, "Failed to create mirror directory")?;
+        }
+        std::fs::write(&path, text).ralph_err(codes::FILESYSTEM, "Failed to write mirror file")?;
+    }
+    for relative in read_files(&tracked.dir)?.keys() {
+        if !files.contains_key(relative) {
+            std::fs::remove_file(tracked.dir.join(relative))
+                .ralph_err(codes::FILESYSTEM, "Failed to remove mirror file")?;
+        }
+    }
+    tracked.written = files
+        .iter()
+        .map(|(relative, text)| (tracked.dir.join(relative), text.clone()))
+        .collect();
+    db.record_mirror_export(&files)?;
+    tracked.exported = Some(db.mirror_change_marker()?);
+    Ok(())
+}
+
+/// The mirror's YAML files, keyed by their path relative to `dir` with `/` separators.
+fn read_files(dir: &Path) -> Result<BTreeMap<String, String>, String> {
+    let mut files = BTreeMap::new();
+    let project = dir.join("project.yaml");
+    if project.is_file() {
+        let text = std::fs::read_to_string(&project)
+            .ralph_err(codes::FILESYSTEM, "Failed to read mirror file")?;
+        files.insert("project.yaml".to_owned(), text);
+    }
+    for sub in MIRROR_DIRS {
+        let Ok(entries) = std::fs::read_dir(dir.join(sub)) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if !path.is_file() || path.extension().map_or(true, |ext| ext != "yaml") {
+                continue;
+            }
+            let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+                continue;
+            };
+            let text = std::fs::read_to_string(&path)
+                .ralph_err(codes::FILESYSTEM, "Failed to read mirror file")?;
+            files.insert(format!("{sub}/{name}"), text);
+        }
+    }
+    Ok(files)
+}
+
+fn watch(
+    dir: &Path,
+    dirty: Arc<AtomicBool>,
+    changed: Arc<Mutex<BTreeSet<PathBuf>>>,
+) -> notify::Result<RecommendedWatcher> {
+    let mut watcher = notify::recommended_watcher(move |result: notify::Result<notify::Event>| {
+        match result {
+            Ok(event) if event.kind.is_access() => {}
+            Ok(event) if !event.paths.is_empty() => {
+                if let Ok(mut changed) = changed.lock() {
+                    changed.extend(event.paths);
+                } else {
+                    dirty.store(true, Ordering::SeqCst);
+                }
+            }
+            // Missed events (e.g. queue overflow) are treated like a change.
+            _ => dirty.store(true, Ordering::SeqCst),
+        }
+    })?;
+    watcher.watch(dir, RecursiveMode::Recursive)?;
+    Ok(watcher)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn seeded_db() -> SqliteDb {
+        let db = SqliteDb::open_in_memory(None).unwrap();
+        db.initialize_metadata("Mirror".to_owned(), None).unwrap();
+        db
+    }
+
+    #[test]
+    fn enable_writes_the_database_and_edits_flow_back() {
+        let dir = tempdir().unwrap();
+        let db = seeded_db();
+        let service = TextMirrorService::default();
+
+        service.enable(dir.path(), &db).unwrap();
+        let project = mirror_dir(dir.path()).join("project.yaml");
+        assert!(std::fs::read_to_string(&project)
+            .unwrap()
+            .starts_with("title: Mirror\n"));
+
+        std::fs::write(&project, "title: Renamed\n").unwrap();
+        // Without waiting on the watcher: force the re-read it would trigger.
+        service
+            .tracked
+            .lock()
+            .unwrap()
+            .as_ref()
+            .unwrap()
+            .dirty
+            .store(true, Ordering::SeqCst);
+        service.sync(&db);
+        assert_eq!(db.get_project_info().title, "Renamed");
+    }
+
+    #[test]
+    fn database_writes_since_the_last_export_survive_file_edits() {
+        let dir = tempdir().unwrap();
+        let db = seeded_db();
+        let service = TextMirrorService::default();
+        service.enable(dir.path(), &db).unwrap();
+
+        // A write that hasn't been exported yet, e.g. from the signal server.
+        db.create_subsystem(sqlite_db::SubsystemInput {
+            name: "auth".to_owned(),
+            display_name: "Auth".to_owned(),
+            acronym: "AUTH".to_owned(),
+            description: None,
+        })
+        .unwrap();
+        let project = mirror_dir(dir.path()).join("project.yaml");
+        std::fs::write(&project, "title: Renamed\n").unwrap();
+        service.attach(dir.path()).unwrap();
+        service.sync(&db);
+
+        assert!(service.conflicts().unwrap().is_empty());
+        assert_eq!(db.get_project_info().title, "Renamed");
+        assert_eq!(db.get_subsystems().len(), 1);
+        assert!(mirror_dir(dir.path())
+            .join("subsystems/AUTH.yaml")
+            .is_file());
+    }
+
+    #[test]
+    fn exports_do_not_mark_the_mirror_changed() {
+        let dir = tempdir().unwrap();
+        let db = seeded_db();
+        let service = TextMirrorService::default();
+        service.enable(dir.path(), &db).unwrap();
+
+        db.initialize_metadata("Changed".to_owned(), None).unwrap();
+        db.create_subsystem(sqlite_db::SubsystemInput {
+            name: "auth".to_owned(),
+            display_name: "Auth".to_owned(),
+            acronym: "AUTH".to_owned(),
+            description: None,
+        })
+        .unwrap();
+        service.sync(&db);
+        std::thread::sleep(std::time::Duration::from_millis(300));
+        let guard = service.tracked.lock().unwrap();
+        let tracked = guard.as_ref().unwrap();
+        assert!(tracked.watcher.is_some());
+        assert!(!tracked.changed.lock().unwrap().is_empty());
+        assert!(!is_stale(tracked).unwrap());
+
+        let project = mirror_dir(dir.path()).join("project.yaml");
+        std::fs::write(&project, "title: Renamed\n").unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(300));
+        assert!(is_stale(tracked).unwrap());
+    }
+
+    #[test]
+    fn conflicted_files_are_reported_and_left_alone() {
+        let dir = tempdir().unwrap();
+        let db = seeded_db();
+        let service = TextMirrorService::default();
+        service.enable(dir.path(), &db).unwrap();
+
+        let project = mirror_dir(dir.path()).join("project.yaml");
+        let conflicted = "<<<<<<< HEAD\ntitle: A\n=======\ntitle: B\n>>>>>>> topic\n";
+        std::fs::write(&project, conflicted).unwrap();
+        service.attach(dir.path()).unwrap();
+        db.initialize_metadata("Changed".to_owned(), None).unwrap();
+        service.sync(&db);
+
+        let conflicts = service.conflicts().unwrap();
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].path, "project.yaml");
+        assert_eq!(conflicts[0].line, Some(1));
+        assert_eq!(std::fs::read_to_string(&project).unwrap(), conflicted);
+    }
+}
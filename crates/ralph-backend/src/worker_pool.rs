@@ -0,0 +1,645 @@
+//! Parallel task execution: which tasks may start now, and the worker slots running them.
+//!
+//! Scheduling is conservative. A task only starts when its dependencies are `done`, nothing else
+//! is running in its subsystem, and none of its `context_files` overlap a running task's.
+
+use ralph_errors::{codes, ralph_err};
+use ralph_macros::ipc_type;
+use serde::{Deserialize, Serialize};
+use sqlite_db::{Priority, Task, TaskStatus};
+use std::collections::HashSet;
+
+pub const DEFAULT_CONCURRENCY: u32 = 2;
+pub const MAX_CONCURRENCY: u32 = 16;
+
+#[ipc_type]
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecutionStatus {
+    #[default]
+    Idle,
+    Running,
+    /// Running workers finish; no new tasks start.
+    Paused,
+    /// Workers are being terminated.
+    Stopping,
+}
+
+#[ipc_type]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecutionWorker {
+    pub task_id: u32,
+    pub agent_session_id: String,
+    pub terminal_session_id: String,
+    /// Reviewing the task's work rather than doing it.
+    pub review: bool,
+}
+
+#[ipc_type]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecutionState {
+    pub status: ExecutionStatus,
+    pub concurrency: u32,
+    pub use_worktrees: bool,
+    pub workers: Vec<ExecutionWorker>,
+}
+
+fn normalize_context_path(path: &str) -> String {
+    let path = path.trim().replace('\\', "/");
+    let path = path.trim_start_matches("./");
+    path.trim_end_matches('/').to_owned()
+}
+
+/// Same file, or one path is a directory containing the other.
+fn context_paths_overlap(a: &str, b: &str) -> bool {
+    let (a, b) = (normalize_context_path(a), normalize_context_path(b));
+    if a.is_empty() || b.is_empty() {
+        return false;
+    }
+    a == b || b.starts_with(&format!("{a}/")) || a.starts_with(&format!("{b}/"))
+}
+
+fn priority_rank(priority: Option<Priority>) -> u8 {
+    match priority {
+        Some(Priority::Critical) => 0,
+        Some(Priority::High) => 1,
+        Some(Priority::Medium) | None => 2,
+        Some(Priority::Low) => 3,
+    }
+}
+
+/// Pick up to `slots` tasks that can start now, highest priority first.
+///
+/// Tasks that are `in_progress` or listed in `running` hold their subsystem and context files;
+/// every pick holds them too, so two picks never conflict with each other. Tasks in `skip` are
+/// never picked.
+fn pick_runnable_tasks<'a>(
+    tasks: &'a [Task],
+    running: &HashSet<u32>,
+    skip: &HashSet<u32>,
+    slots: usize,
+) -> Vec<&'a Task> {
+    let done: HashSet<u32> = tasks
+        .iter()
+        .filter(|task| task.status == TaskStatus::Done)
+        .map(|task| task.id)
+        .collect();
+    let busy = |task: &Task| task.status == TaskStatus::InProgress || running.contains(&task.id);
+    let mut held_subsystems: HashSet<&str> = HashSet::new();
+    let mut held_files: Vec<&str> = Vec::new();
+    for task in tasks.iter().filter(|task| busy(task)) {
+        held_subsystems.insert(&task.subsystem);
+        held_files.extend(task.context_files.iter().map(String::as_str));
+    }
+
+    let mut candidates: Vec<&Task> = tasks
+        .iter()
+        .filter(|task| {
+            task.status == TaskStatus::Pending
+                && !busy(task)
+                && !skip.contains(&task.id)
+                && task.depends_on.iter().all(|dep| done.contains(dep))
+        })
+        .collect();
+    candidates.sort_by_key(|task| (priority_rank(task.priority), task.id));
+
+    let mut picked = Vec::new();
+    for task in candidates {
+        if picked.len() >= slots {
+            break;
+        }
+        if held_subsystems.contains(task.subsystem.as_str()) {
+            continue;
+        }
+        let overlaps = task.context_files.iter().any(|file| {
+            held_files
+                .iter()
+                .any(|held| context_paths_overlap(file, held))
+        });
+        if overlaps {
+            continue;
+        }
+        held_subsystems.insert(&task.subsystem);
+        held_files.extend(task.context_files.iter().map(String::as_str));
+        picked.push(task);
+    }
+    picked
+}
+
+/// Latest closing signal (`done`, `partial`, `stuck`) the task received after `after_signal_id`.
+pub fn closing_verb_since(task: &Task, after_signal_id: u32) -> Option<&str> {
+    task.signals
+        .iter()
+        .filter(|signal| signal.id > after_signal_id)
+        .filter_map(|signal| {
+            signal
+                .signal_verb
+                .as_deref()
+                .filter(|verb| matches!(*verb, "done" | "partial" | "stuck"))
+                .map(|verb| (signal.id, verb))
+        })
+        .max_by_key(|(id, _)| *id)
+        .map(|(_, verb)| verb)
+}
+
+/// Where a worker's task goes when its session ends with the task still `in_progress`.
+/// Anything short of `done` or `stuck` goes back to `pending` for a later run.
+pub fn task_status_after_session(closing_verb: Option<&str>) -> TaskStatus {
+    match closing_verb {
+        Some("done") => TaskStatus::Done,
+        Some("stuck") => TaskStatus::Blocked,
+        _ => TaskStatus::Pending,
+    }
+}
+
+/// What a review session decided, from the signals it sent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReviewVerdict {
+    /// `done`: the work meets the task.
+    Confirmed,
+    /// At least one `warning` or `blocking` flag: the task needs another attempt.
+    Reopened,
+}
+
+/// Verdict of a review that started after `after_signal_id`. Flags win over a `done` sent in
+/// the same review; `info` flags are notes and don't reopen the task.
+pub fn review_verdict_since(task: &Task, after_signal_id: u32) -> Option<ReviewVerdict> {
+    let signals = || {
+        task.signals
+            .iter()
+            .filter(move |signal| signal.id > after_signal_id)
+    };
+    let reopened = signals().any(|signal| {
+        signal.signal_verb.as_deref() == Some("flag")
+            && matches!(signal.severity.as_deref(), Some("warning" | "blocking"))
+    });
+    if reopened {
+        Some(ReviewVerdict::Reopened)
+    } else if closing_verb_since(task, after_signal_id) == Some("done") {
+        Some(ReviewVerdict::Confirmed)
+    } else {
+        None
+    }
+}
+
+/// Where a reviewed task goes when its review session ends. A review that ended without a
+/// verdict (crashed, stopped, timed out) confirmed nothing, so the task is blocked until a
+/// person checks it.
+pub fn task_status_after_review(verdict: Option<ReviewVerdict>) -> TaskStatus {
+    match verdict {
+        Some(ReviewVerdict::Reopened) => TaskStatus::Pending,
+        Some(ReviewVerdict::Confirmed) => TaskStatus::Done,
+        None => TaskStatus::Blocked,
+    }
+}
+
+/// The review brief: which changes the reviewer should read. `session` is the implementer's
+/// agent session; when it ran in a worktree, its base and head commits bound the diff.
+pub fn review_brief(task_id: u32, session: Option<&sqlite_db::AgentSession>) -> String {
+    let mut brief = format!(
+        "Review task #{task_id}. Its implementer reported it as done: confirm it with `done` or \
+         reopen it with `flag`.\n\n"
+    );
+    let worktree = session.and_then(|session| {
+        Some((
+            session.worktree_branch.as_deref()?,
+            session.base_commit.as_deref()?,
+            session.head_commit.as_deref()?,
+        ))
+    });
+    if let Some((branch, base, head)) = worktree {
+        brief.push_str(&format!(
+            "The work is on branch `{branch}`:\n\
+             - `git log {base}..{head}` lists its commits\n\
+             - `git diff {base}..{head}` is the change to review"
+        ));
+        return brief;
+    }
+
+    brief.push_str("The work was done in the project checkout:\n");
+    if let Some(started) = session.and_then(|session| session.started.as_deref()) {
+        brief.push_str(&format!(
+            "- `git log --since=\"{started}\"` lists the commits made since the task started\n"
+        ));
+    }
+    brief.push_str("- `git status` and `git diff` show changes left uncommitted");
+    brief
+}
+
+#[derive(Debug, Clone)]
+struct Worker {
+    info: ExecutionWorker,
+    /// Highest signal id on the task when the worker started.
+    baseline_signal_id: u32,
+}
+
+#[derive(Debug)]
+pub struct WorkerPool {
+    status: ExecutionStatus,
+    concurrency: u32,
+    use_worktrees: bool,
+    workers: Vec<Worker>,
+    /// Tasks already started during this run; each runs at most once per run.
+    attempted: HashSet<u32>,
+}
+
+impl Default for WorkerPool {
+    fn default() -> Self {
+        Self {
+            status: ExecutionStatus::Idle,
+            concurrency: DEFAULT_CONCURRENCY,
+            use_worktrees: false,
+            workers: Vec::new(),
+            attempted: HashSet::new(),
+        }
+    }
+}
+
+impl WorkerPool {
+    pub fn status(&self) -> ExecutionStatus {
+        self.status
+    }
+
+    pub fn use_worktrees(&self) -> bool {
+        self.use_worktrees
+    }
+
+    pub fn set_concurrency(&mut self, concurrency: u32) -> Result<(), String> {
+        if !(1..=MAX_CONCURRENCY).contains(&concurrency) {
+            return ralph_err!(
+                codes::LOOP_ENGINE,
+                "Concurrency must be between 1 and {MAX_CONCURRENCY}"
+            );
+        }
+        self.concurrency = concurrency;
+        Ok(())
+    }
+
+    /// Begin a run. A fresh run (from idle) forgets which tasks were already attempted.
+    pub fn start(&mut self, use_worktrees: bool) -> Result<(), String> {
+        match self.status {
+            ExecutionStatus::Idle => self.attempted.clear(),
+            ExecutionStatus::Stopping => {
+                return ralph_err!(codes::LOOP_ENGINE, "Execution is still stopping");
+            }
+            ExecutionStatus::Running | ExecutionStatus::Paused => {}
+        }
+        self.use_worktrees = use_worktrees;
+        self.status = ExecutionStatus::Running;
+        Ok(())
+    }
+
+    pub fn pause(&mut self) -> Result<(), String> {
+        if self.status != ExecutionStatus::Running {
+            return ralph_err!(codes::LOOP_ENGINE, "Execution is not running");
+        }
+        self.status = ExecutionStatus::Paused;
+        Ok(())
+    }
+
+    pub fn resume(&mut self) -> Result<(), String> {
+        if self.status != ExecutionStatus::Paused {
+            return ralph_err!(codes::LOOP_ENGINE, "Execution is not paused");
+        }
+        self.status = ExecutionStatus::Running;
+        Ok(())
+    }
+
+    /// Stop starting tasks. Returns the terminal sessions of workers that must be terminated.
+    pub fn stop(&mut self) -> Vec<String> {
+        if self.status == ExecutionStatus::Idle {
+            return Vec::new();
+        }
+        self.status = ExecutionStatus::Stopping;
+        self.settle();
+        self.workers
+            .iter()
+            .map(|worker| worker.info.terminal_session_id.clone())
+            .collect()
+    }
+
+    /// Choose tasks for the free worker slots and mark them attempted.
+    pub fn plan<'a>(&mut self, tasks: &'a [Task]) -> Vec<&'a Task> {
+        if self.status != ExecutionStatus::Running {
+            return Vec::new();
+        }
+        let slots = (self.concurrency as usize).saturating_sub(self.workers.len());
+        let running = self
+            .workers
+            .iter()
+            .map(|worker| worker.info.task_id)
+            .collect();
+        let picked = pick_runnable_tasks(tasks, &running, &self.attempted, slots);
+        self.attempted.extend(picked.iter().map(|task| task.id));
+        picked
+    }
+
+    pub fn add_worker(&mut self, info: ExecutionWorker, baseline_signal_id: u32) {
+        self.workers.push(Worker {
+            info,
+            baseline_signal_id,
+        });
+    }
+
+    /// Forget the worker on `terminal_session_id`, returning it with its baseline signal id.
+    pub fn remove_worker(&mut self, terminal_session_id: &str) -> Option<(ExecutionWorker, u32)> {
+        let index = self
+            .workers
+            .iter()
+            .position(|worker| worker.info.terminal_session_id == terminal_session_id)?;
+        let worker = self.workers.remove(index);
+        Some((worker.info, worker.baseline_signal_id))
+    }
+
+    /// Go idle once a stopping run has drained, or a running one has nothing left to do.
+    /// Call after [`Self::plan`] found nothing to start.
+    pub fn settle(&mut self) {
+        if self.workers.is_empty()
+            && matches!(
+                self.status,
+                ExecutionStatus::Running | ExecutionStatus::Stopping
+            )
+        {
+            self.status = ExecutionStatus::Idle;
+        }
+    }
+
+    pub fn state(&self) -> ExecutionState {
+        ExecutionState {
+            status: self.status,
+            concurrency: self.concurrency,
+            use_worktrees: self.use_worktrees,
+            workers: self
+                .workers
+                .iter()
+                .map(|worker| worker.info.clone())
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task(id: u32, subsystem: &str, status: &str) -> Task {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "subsystem": subsystem,
+            "discipline": "backend",
+            "title": format!("Task {id}"),
+            "status": status,
+            "subsystemDisplayName": subsystem,
+            "subsystemAcronym": "SUB",
+            "disciplineDisplayName": "Backend",
+            "disciplineAcronym": "BE",
+            "disciplineIcon": "server",
+            "disciplineColor": "#000000",
+        }))
+        .unwrap()
+    }
+
+    fn with_files(mut task: Task, files: &[&str]) -> Task {
+        task.context_files = files.iter().map(|file| (*file).to_owned()).collect();
+        task
+    }
+
+    fn ids(tasks: &[&Task]) -> Vec<u32> {
+        tasks.iter().map(|task| task.id).collect()
+    }
+
+    fn pick(tasks: &[Task], slots: usize) -> Vec<u32> {
+        ids(&pick_runnable_tasks(
+            tasks,
+            &HashSet::new(),
+            &HashSet::new(),
+            slots,
+        ))
+    }
+
+    #[test]
+    fn context_paths_overlap_on_same_file_or_containing_dir() {
+        assert!(context_paths_overlap("src/lib.rs", "./src/lib.rs"));
+        assert!(context_paths_overlap("src/", "src/lib.rs"));
+        assert!(context_paths_overlap("src\\ui\\app.ts", "src/ui"));
+        assert!(!context_paths_overlap("src/lib.rs", "src/lib.rs.bak"));
+        assert!(!context_paths_overlap("src/ui", "src/uikit/button.ts"));
+        assert!(!context_paths_overlap("", "src"));
+    }
+
+    #[test]
+    fn picks_only_pending_tasks_with_done_dependencies() {
+        let mut blocked_on_dep = task(3, "api", "pending");
+        blocked_on_dep.depends_on = vec![2];
+        let mut ready_after_dep = task(4, "db", "pending");
+        ready_after_dep.depends_on = vec![1];
+        let tasks = vec![
+            task(1, "core", "done"),
+            task(2, "web", "pending"),
+            blocked_on_dep,
+            ready_after_dep,
+            task(5, "cli", "draft"),
+        ];
+
+        assert_eq!(pick(&tasks, 10), vec![2, 4]);
+    }
+
+    #[test]
+    fn one_task_per_subsystem_including_in_progress_ones() {
+        let tasks = vec![
+            task(1, "core", "in_progress"),
+            task(2, "core", "pending"),
+            task(3, "web", "pending"),
+            task(4, "web", "pending"),
+        ];
+
+        assert_eq!(pick(&tasks, 10), vec![3]);
+    }
+
+    #[test]
+    fn overlapping_context_files_never_run_together() {
+        let tasks = vec![
+            with_files(task(1, "core", "in_progress"), &["src/shared/"]),
+            with_files(task(2, "web", "pending"), &["src/shared/types.ts"]),
+            with_files(task(3, "api", "pending"), &["src/api/routes.rs"]),
+            with_files(task(4, "cli", "pending"), &["./src/api/routes.rs"]),
+            task(5, "docs", "pending"),
+        ];
+
+        assert_eq!(pick(&tasks, 10), vec![3, 5]);
+    }
+
+    #[test]
+    fn respects_slots_priority_running_and_skip() {
+        let mut urgent = task(4, "db", "pending");
+        urgent.priority = Some(Priority::Critical);
+        let mut later = task(1, "core", "pending");
+        later.priority = Some(Priority::Low);
+        let tasks = vec![
+            later,
+            task(2, "web", "pending"),
+            task(3, "api", "pending"),
+            urgent,
+        ];
+
+        assert_eq!(pick(&tasks, 2), vec![4, 2]);
+
+        let running = HashSet::from([2]);
+        let skip = HashSet::from([4]);
+        assert_eq!(
+            ids(&pick_runnable_tasks(&tasks, &running, &skip, 10)),
+            vec![3, 1]
+        );
+    }
+
+    #[test]
+    fn closing_verb_since_ignores_older_and_non_closing_signals() {
+        let mut task = task(1, "core", "in_progress");
+        task.signals = serde_json::from_value(serde_json::json!([
+            { "id": 1, "author": "agent", "body": "", "signalVerb": "done" },
+            { "id": 2, "author": "agent", "body": "", "signalVerb": "stuck" },
+            { "id": 3, "author": "agent", "body": "", "signalVerb": "learned" },
+            { "id": 4, "author": "human", "body": "looks good" },
+        ]))
+        .unwrap();
+
+        assert_eq!(closing_verb_since(&task, 0), Some("stuck"));
+        assert_eq!(closing_verb_since(&task, 2), None);
+        assert_eq!(task_status_after_session(Some("done")), TaskStatus::Done);
+        assert_eq!(
+            task_status_after_session(Some("stuck")),
+            TaskStatus::Blocked
+        );
+        assert_eq!(
+            task_status_after_session(Some("partial")),
+            TaskStatus::Pending
+        );
+        assert_eq!(task_status_after_session(None), TaskStatus::Pending);
+    }
+
+    #[test]
+    fn review_verdict_reopens_on_warning_flags_and_confirms_on_done() {
+        let mut task = task(1, "core", "in_progress");
+        task.signals = serde_json::from_value(serde_json::json!([
+            { "id": 1, "author": "agent", "body": "", "signalVerb": "done" },
+            { "id": 2, "author": "agent", "body": "", "signalVerb": "flag", "severity": "info" },
+            { "id": 3, "author": "agent", "body": "", "signalVerb": "done" },
+            { "id": 4, "author": "agent", "body": "", "signalVerb": "flag", "severity": "blocking" },
+        ]))
+        .unwrap();
+
+        assert_eq!(
+            review_verdict_since(&task, 1),
+            Some(ReviewVerdict::Reopened)
+        );
+        assert_eq!(
+            review_verdict_since(&task, 1),
+            review_verdict_since(&task, 3)
+        );
+        task.signals.pop();
+        assert_eq!(
+            review_verdict_since(&task, 1),
+            Some(ReviewVerdict::Confirmed)
+        );
+        assert_eq!(review_verdict_since(&task, 3), None);
+
+        assert_eq!(
+            task_status_after_review(Some(ReviewVerdict::Reopened)),
+            TaskStatus::Pending
+        );
+        assert_eq!(
+            task_status_after_review(Some(ReviewVerdict::Confirmed)),
+            TaskStatus::Done
+        );
+        assert_eq!(task_status_after_review(None), TaskStatus::Blocked);
+    }
+
+    #[test]
+    fn review_brief_points_at_the_worktree_diff_or_the_checkout() {
+        let mut session: sqlite_db::AgentSession = serde_json::from_value(serde_json::json!({
+            "id": "agent-1",
+            "sessionNumber": 1,
+            "kind": "task_execution",
+            "startedBy": "system",
+            "status": "finished",
+            "started": "2026-01-01 10:00:00",
+        }))
+        .unwrap();
+
+        let brief = review_brief(7, Some(&session));
+        assert!(brief.starts_with("Review task #7."));
+        assert!(brief.contains("git log --since=\"2026-01-01 10:00:00\""));
+
+        session.worktree_branch = Some("ralph/task-7".to_owned());
+        session.base_commit = Some("abc".to_owned());
+        session.head_commit = Some("def".to_owned());
+        let brief = review_brief(7, Some(&session));
+        assert!(brief.contains("branch `ralph/task-7`"));
+        assert!(brief.contains("`git diff abc..def`"));
+
+        assert!(!review_brief(7, None).contains("--since"));
+    }
+
+    #[test]
+    fn pool_runs_each_task_once_per_run_and_goes_idle_when_drained() {
+        let tasks = vec![task(1, "core", "pending"), task(2, "web", "pending")];
+        let mut pool = WorkerPool::default();
+        assert!(pool.plan(&tasks).is_empty());
+
+        pool.set_concurrency(1).unwrap();
+        pool.start(false).unwrap();
+        assert_eq!(ids(&pool.plan(&tasks)), vec![1]);
+        pool.add_worker(
+            ExecutionWorker {
+                task_id: 1,
+                agent_session_id: "agent-1".to_owned(),
+                terminal_session_id: "worker-1".to_owned(),
+                review: false,
+            },
+            7,
+        );
+        assert!(pool.plan(&tasks).is_empty());
+
+        let (worker, baseline) = pool.remove_worker("worker-1").unwrap();
+        assert_eq!((worker.task_id, baseline), (1, 7));
+        assert_eq!(ids(&pool.plan(&tasks)), vec![2]);
+        assert!(pool.plan(&tasks).is_empty());
+
+        pool.settle();
+        assert_eq!(pool.status(), ExecutionStatus::Idle);
+        pool.start(false).unwrap();
+        assert_eq!(ids(&pool.plan(&tasks)), vec![1]);
+    }
+
+    #[test]
+    fn pool_state_transitions() {
+        let mut pool = WorkerPool::default();
+        assert!(pool.set_concurrency(0).is_err());
+        assert!(pool.set_concurrency(MAX_CONCURRENCY + 1).is_err());
+        assert!(pool.pause().is_err());
+
+        pool.start(true).unwrap();
+        pool.add_worker(
+            ExecutionWorker {
+                task_id: 1,
+                agent_session_id: "agent-1".to_owned(),
+                terminal_session_id: "worker-1".to_owned(),
+                review: false,
+            },
+            0,
+        );
+        pool.pause().unwrap();
+        assert!(pool.plan(&[task(2, "web", "pending")]).is_empty());
+        pool.resume().unwrap();
+
+        assert_eq!(pool.stop(), vec!["worker-1".to_owned()]);
+        assert_eq!(pool.status(), ExecutionStatus::Stopping);
+        assert!(pool.start(false).is_err());
+        pool.remove_worker("worker-1");
+        pool.settle();
+        assert_eq!(pool.state().status, ExecutionStatus::Idle);
+        assert!(pool.state().use_worktrees);
+    }
+}
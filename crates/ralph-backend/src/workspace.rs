@@ -0,0 +1,193 @@
+//! The workspace: the projects someone works across, registered in `workspace.json` in the
+//! XDG data dir. Unlike the recent projects list it only changes when a project is added or
+//! removed, and it is what the cross-project dashboard summarizes.
+//!
+//! Any number of workspace projects can be open in one backend (`open_project`), each with
+//! its own database and text mirror; one of them is locked, and is what project commands
+//! act on. `switch_project` moves the lock and keeps the previous project open.
+
+use crate::project::{project_name, validate_project_path};
+use crate::session_recovery::session_is_live;
+use crate::state::{AppState, CommandContext};
+use crate::xdg::XdgDirs;
+use ralph_errors::{codes, ralph_err, RalphResultExt, ToStringErr};
+use ralph_macros::ipc_type;
+use serde::{Deserialize, Serialize};
+use sqlite_db::{ProjectActivity, SqliteDb};
+use std::path::{Path, PathBuf};
+
+const FILENAME: &str = "workspace.json";
+
+#[ipc_type]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceProject {
+    /// Canonical project directory.
+    pub path: String,
+    pub name: String,
+    pub added: String,
+}
+
+/// A workspace project on the dashboard.
+#[ipc_type]
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceProjectSummary {
+    pub path: String,
+    pub name: String,
+    /// Whether this is the project the app has locked.
+    pub active: bool,
+    /// Whether the project is open in this backend, locked or not.
+    pub open: bool,
+    /// `None` when the project's database couldn't be read; `error` says why.
+    pub activity: Option<ProjectActivity>,
+    pub error: Option<String>,
+}
+
+pub fn load(xdg: &XdgDirs) -> Result<Vec<WorkspaceProject>, String> {
+    let file = xdg.data().join(FILENAME);
+    if !file.exists() {
+        return Ok(Vec::new());
+    }
+    let contents =
+        std::fs::read_to_string(&file).ralph_err(codes::FILESYSTEM, "Failed to read workspace")?;
+    serde_json::from_str(&contents).ralph_err(codes::FILESYSTEM, "Failed to parse workspace")
+}
+
+fn save(xdg: &XdgDirs, projects: &[WorkspaceProject]) -> Result<(), String> {
+    let file = xdg.ensure_data()?.join(FILENAME);
+    let json = serde_json::to_string_pretty(projects)
+        .ralph_err(codes::FILESYSTEM, "Failed to serialize workspace")?;
+    std::fs::write(&file, json).ralph_err(codes::FILESYSTEM, "Failed to write workspace")
+}
+
+/// Register the project at `path`. A project that is already registered keeps its place.
+pub fn add(xdg: &XdgDirs, path: String, name: String) -> Result<Vec<WorkspaceProject>, String> {
+    let mut projects = load(xdg)?;
+    if projects.iter().any(|p| p.path == path) {
+        return Ok(projects);
+    }
+    projects.push(WorkspaceProject {
+        path,
+        name,
+        added: chrono::Utc::now().to_rfc3339(),
+    });
+    save(xdg, &projects)?;
+    Ok(projects)
+}
+
+/// Unregister the project at `path`. Nothing in the project itself is touched.
+pub fn remove(xdg: &XdgDirs, path: &str) -> Result<Vec<WorkspaceProject>, String> {
+    let mut projects = load(xdg)?;
+    projects.retain(|p| p.path != path);
+    save(xdg, &projects)?;
+    Ok(projects)
+}
+
+pub fn add_project(state: &AppState, path: &Path) -> Result<Vec<WorkspaceProject>, String> {
+    validate_project_path(path)?;
+    let canonical =
+        std::fs::canonicalize(path).ralph_err(codes::PROJECT_PATH, "Failed to resolve path")?;
+    add(
+        &state.xdg,
+        canonical.to_string_lossy().into_owned(),
+        project_name(&canonical),
+    )
+}
+
+/// Unregister the project at `path`, closing it if it is open besides the locked one.
+pub fn remove_project(state: &AppState, path: &Path) -> Result<Vec<WorkspaceProject>, String> {
+    // Registered paths are canonical; a project that has since moved is removed as given.
+    let path = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let projects = remove(&state.xdg, &path.to_string_lossy())?;
+    state
+        .open_projects
+        .lock()
+        .err_str(codes::INTERNAL)?
+        .remove(&path);
+    Ok(projects)
+}
+
+/// Open projects are read through their databases, after picking up their text mirror
+/// edits; the others are opened read-only one at a time and left as they are. A project
+/// that can't be read is listed with its error rather than failing the whole dashboard.
+pub fn workspace_dashboard(state: &AppState) -> Result<Vec<WorkspaceProjectSummary>, String> {
+    let ctx = CommandContext::new(state);
+    let locked = ctx.maybe_locked_project_path()?;
+    let projects = load(&state.xdg)?;
+    let mut summaries = Vec::with_capacity(projects.len());
+    for project in projects {
+        let path = PathBuf::from(&project.path);
+        let active = locked.as_ref() == Some(&path);
+        let (open, activity) = if active {
+            (true, ctx.db(|db| db.project_activity(session_is_live)))
+        } else {
+            let open_projects = state.open_projects.lock().err_str(codes::INTERNAL)?;
+            open_projects.get(&path).map_or_else(
+                || (false, other_project_activity(&path)),
+                |open| {
+                    open.text_mirror.sync(&open.db);
+                    (true, open.db.project_activity(session_is_live))
+                },
+            )
+        };
+        summaries.push(WorkspaceProjectSummary {
+            path: project.path,
+            name: project.name,
+            active,
+            open,
+            error: activity.as_ref().err().cloned(),
+            activity: activity.ok(),
+        });
+    }
+    Ok(summaries)
+}
+
+fn other_project_activity(path: &Path) -> Result<ProjectActivity, String> {
+    let db_path = path.join(".ralph").join("db").join("ralph.db");
+    // A project with only its text mirror gets a database when it is first locked.
+    if !db_path.is_file() {
+        return ralph_err!(
+            codes::PROJECT_PATH,
+            "No database at {}; open the project once to create it",
+            db_path.display()
+        );
+    }
+    SqliteDb::open_read_only(&db_path)?.project_activity(session_is_live)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    fn test_xdg() -> XdgDirs {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or_default();
+        let base = std::env::temp_dir().join(format!("ralph4days-workspace-{nanos}"));
+        XdgDirs::from_base(&base)
+    }
+
+    #[test]
+    fn add_keeps_order_and_ignores_duplicates() {
+        let xdg = test_xdg();
+        assert!(load(&xdg).unwrap().is_empty());
+
+        add(&xdg, "/work/api".to_owned(), "api".to_owned()).unwrap();
+        add(&xdg, "/work/web".to_owned(), "web".to_owned()).unwrap();
+        let projects = add(&xdg, "/work/api".to_owned(), "api".to_owned()).unwrap();
+        let paths: Vec<&str> = projects.iter().map(|p| p.path.as_str()).collect();
+        assert_eq!(paths, ["/work/api", "/work/web"]);
+        assert_eq!(load(&xdg).unwrap(), projects);
+
+        let projects = remove(&xdg, "/work/api").unwrap();
+        assert_eq!(projects.len(), 1);
+        assert_eq!(load(&xdg).unwrap()[0].name, "web");
+
+        if let Some(base) = xdg.data().parent().and_then(|p| p.parent()) {
+            let _ = std::fs::remove_dir_all(base);
+        }
+    }
+}
@@ -0,0 +1,280 @@
+//! Per-task git worktrees, so agent runs never touch the main checkout.
+//!
+//! Each task gets `.ralph/worktrees/task-<id>` checked out on branch `ralph/task-<id>`.
+//! All git access goes through the `git` CLI.
+
+use ralph_errors::{codes, ralph_err, RalphResultExt};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const WORKTREES_DIR: &str = ".ralph/worktrees";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaskWorktree {
+    pub project_path: PathBuf,
+    pub task_id: u32,
+    pub path: PathBuf,
+    pub branch: String,
+    /// Commit the branch was cut from; `base_commit..HEAD` is the agent's work.
+    pub base_commit: String,
+}
+
+pub fn task_branch_name(task_id: u32) -> String {
+    format!("ralph/task-{task_id}")
+}
+
+pub fn task_worktree_path(project_path: &Path, task_id: u32) -> PathBuf {
+    project_path
+        .join(WORKTREES_DIR)
+        .join(format!("task-{task_id}"))
+}
+
+fn git(dir: &Path, args: &[&str]) -> Result<String, String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .ralph_err(codes::GIT_OPS, "Failed to run git")?;
+
+    if !output.status.success() {
+        return ralph_err!(
+            codes::GIT_OPS,
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_owned())
+}
+
+fn branch_exists(project_path: &Path, branch: &str) -> bool {
+    git(
+        project_path,
+        &[
+            "rev-parse",
+            "--verify",
+            "--quiet",
+            &format!("refs/heads/{branch}"),
+        ],
+    )
+    .is_ok()
+}
+
+/// Keeps worktrees out of the main checkout's `git status`.
+fn ensure_worktrees_dir(project_path: &Path) -> Result<(), String> {
+    let dir = project_path.join(WORKTREES_DIR);
+    std::fs::create_dir_all(&dir).ralph_err(codes::FILESYSTEM, "Failed to create worktrees dir")?;
+    let gitignore = dir.join(".gitignore");
+    if !gitignore.exists() {
+        std::fs::write(&gitignore, "*\n")
+            .ralph_err(codes::FILESYSTEM, "Failed to write worktrees .gitignore")?;
+    }
+    Ok(())
+}
+
+/// Create (or reuse, on a retry) the worktree for a task.
+pub fn create_task_worktree(project_path: &Path, task_id: u32) -> Result<TaskWorktree, String> {
+    let head = git(project_path, &["rev-parse", "HEAD"])?;
+    let branch = task_branch_name(task_id);
+    let path = task_worktree_path(project_path, task_id);
+    let path_str = path.to_string_lossy().into_owned();
+
+    if !path.exists() {
+        ensure_worktrees_dir(project_path)?;
+        git(project_path, &["worktree", "prune"])?;
+        if branch_exists(project_path, &branch) {
+            git(project_path, &["worktree", "add", &path_str, &branch])?;
+        } else {
+            git(
+                project_path,
+                &["worktree", "add", "-b", &branch, &path_str, &head],
+            )?;
+        }
+    }
+
+    let base_commit = git(project_path, &["merge-base", &head, &branch])?;
+    Ok(TaskWorktree {
+        project_path: project_path.to_path_buf(),
+        task_id,
+        path,
+        branch,
+        base_commit,
+    })
+}
+
+pub fn head_commit(worktree_path: &Path) -> Result<String, String> {
+    git(worktree_path, &["rev-parse", "HEAD"])
+}
+
+fn has_uncommitted_changes(dir: &Path, include_untracked: bool) -> Result<bool, String> {
+    let untracked = if include_untracked {
+        "--untracked-files=normal"
+    } else {
+        "--untracked-files=no"
+    };
+    git(dir, &["status", "--porcelain", untracked]).map(|status| !status.is_empty())
+}
+
+/// Remove the worktree and its branch. Missing pieces are not an error.
+pub fn discard_task_worktree(project_path: &Path, task_id: u32) -> Result<(), String> {
+    let path = task_worktree_path(project_path, task_id);
+    if path.exists() {
+        git(
+            project_path,
+            &["worktree", "remove", "--force", &path.to_string_lossy()],
+        )?;
+    }
+    git(project_path, &["worktree", "prune"])?;
+
+    let branch = task_branch_name(task_id);
+    if branch_exists(project_path, &branch) {
+        git(project_path, &["branch", "-D", &branch])?;
+    }
+    Ok(())
+}
+
+/// Merge the task branch into the main checkout's current branch, then discard the worktree.
+/// Uncommitted changes left in the worktree are not merged.
+pub fn merge_task_worktree(project_path: &Path, task_id: u32) -> Result<(), String> {
+    let branch = task_branch_name(task_id);
+    if !branch_exists(project_path, &branch) {
+        return ralph_err!(
+            codes::GIT_OPS,
+            "Task {task_id} has no worktree branch to merge"
+        );
+    }
+    if has_uncommitted_changes(project_path, false)? {
+        return ralph_err!(
+            codes::GIT_OPS,
+            "Commit or stash changes in the project checkout before merging {branch}"
+        );
+    }
+
+    let message = format!("Merge {branch}");
+    if let Err(error) = git(project_path, &["merge", "--no-ff", "-m", &message, &branch]) {
+        let _ = git(project_path, &["merge", "--abort"]);
+        return Err(error);
+    }
+
+    discard_task_worktree(project_path, task_id)
+}
+
+/// After a session ends: drop the worktree if the agent left nothing behind, otherwise keep it
+/// for review. Returns the worktree's HEAD and whether it was kept.
+pub fn finish_task_worktree(worktree: &TaskWorktree) -> Result<(String, bool), String> {
+    let head = head_commit(&worktree.path)?;
+    let keep = head != worktree.base_commit || has_uncommitted_changes(&worktree.path, true)?;
+    if !keep {
+        discard_task_worktree(&worktree.project_path, worktree.task_id)?;
+    }
+    Ok((head, keep))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn init_repo(dir: &Path) {
+        for args in [
+            &["init", "-q", "-b", "main"][..],
+            &["config", "user.email", "ralph@example.com"],
+            &["config", "user.name", "Ralph"],
+            &["config", "commit.gpgsign", "false"],
+        ] {
+            git(dir, args).unwrap();
+        }
+        std::fs::write(dir.join("README.md"), "hello\n").unwrap();
+        git(dir, &["add", "."]).unwrap();
+        git(dir, &["commit", "-q", "-m", "initial"]).unwrap();
+    }
+
+    fn commit_in(dir: &Path, file: &str, content: &str) {
+        std::fs::write(dir.join(file), content).unwrap();
+        git(dir, &["add", "."]).unwrap();
+        git(dir, &["commit", "-q", "-m", &format!("edit {file}")]).unwrap();
+    }
+
+    #[test]
+    fn creates_worktree_on_task_branch_and_reuses_it() {
+        let dir = tempdir().unwrap();
+        init_repo(dir.path());
+
+        let worktree = create_task_worktree(dir.path(), 7).unwrap();
+        assert_eq!(worktree.branch, "ralph/task-7");
+        assert_eq!(worktree.path, dir.path().join(".ralph/worktrees/task-7"));
+        assert_eq!(
+            worktree.base_commit,
+            git(dir.path(), &["rev-parse", "HEAD"]).unwrap()
+        );
+        assert_eq!(
+            git(&worktree.path, &["branch", "--show-current"]).unwrap(),
+            "ralph/task-7"
+        );
+        assert!(git(dir.path(), &["status", "--porcelain"])
+            .unwrap()
+            .is_empty());
+
+        assert_eq!(create_task_worktree(dir.path(), 7).unwrap(), worktree);
+    }
+
+    #[test]
+    fn finish_discards_untouched_worktree_and_keeps_commits() {
+        let dir = tempdir().unwrap();
+        init_repo(dir.path());
+
+        let untouched = create_task_worktree(dir.path(), 1).unwrap();
+        let (_, kept) = finish_task_worktree(&untouched).unwrap();
+        assert!(!kept);
+        assert!(!untouched.path.exists());
+        assert!(!branch_exists(dir.path(), "ralph/task-1"));
+
+        let worked = create_task_worktree(dir.path(), 2).unwrap();
+        commit_in(&worked.path, "feature.txt", "done\n");
+        let (head, kept) = finish_task_worktree(&worked).unwrap();
+        assert!(kept);
+        assert_ne!(head, worked.base_commit);
+    }
+
+    #[test]
+    fn merge_brings_task_commits_into_checkout_and_cleans_up() {
+        let dir = tempdir().unwrap();
+        init_repo(dir.path());
+
+        let worktree = create_task_worktree(dir.path(), 3).unwrap();
+        commit_in(&worktree.path, "feature.txt", "done\n");
+
+        merge_task_worktree(dir.path(), 3).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("feature.txt")).unwrap(),
+            "done\n"
+        );
+        assert!(!worktree.path.exists());
+        assert!(!branch_exists(dir.path(), "ralph/task-3"));
+    }
+
+    #[test]
+    fn merge_refuses_dirty_checkout() {
+        let dir = tempdir().unwrap();
+        init_repo(dir.path());
+        let worktree = create_task_worktree(dir.path(), 4).unwrap();
+        commit_in(&worktree.path, "feature.txt", "done\n");
+        std::fs::write(dir.path().join("README.md"), "local edit\n").unwrap();
+
+        let err = merge_task_worktree(dir.path(), 4).unwrap_err();
+        assert!(err.contains("Commit or stash"));
+        assert!(worktree.path.exists());
+    }
+
+    #[test]
+    fn discard_is_idempotent() {
+        let dir = tempdir().unwrap();
+        init_repo(dir.path());
+        create_task_worktree(dir.path(), 5).unwrap();
+
+        discard_task_worktree(dir.path(), 5).unwrap();
+        discard_task_worktree(dir.path(), 5).unwrap();
+        assert!(!task_worktree_path(dir.path(), 5).exists());
+    }
+}
@@ -0,0 +1,908 @@
+//! The `ralph` command line: manage and run a project without the desktop app, e.g. from CI.
+//!
+//! Subcommands run on `ralph-backend`, like the app's commands: the project is locked with
+//! `project_lock_validated` and the database is reached through `CommandContext`, so the
+//! text mirror and session recovery behave the same as in the app. Nothing here links Tauri.
+
+mod output;
+mod run;
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use output::{cell, print_json, Table};
+use ralph_backend::doctor::{run_doctor, DoctorSeverity};
+use ralph_backend::execution::ExecutionStartArgs;
+use ralph_backend::project::{
+    import_prd, initialize_project, project_lock_validated, restore_backup, validate_project_path,
+};
+use ralph_backend::state::{AppState, CommandContext, TaskPromptSpec};
+use ralph_backend::workspace::{add_project, remove_project, workspace_dashboard};
+use ralph_errors::codes;
+use sqlite_db::{
+    BackupReason, PrdImportMode, Priority, ReportFormat, SqliteDb, Task, TaskInput, TaskStatus,
+};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Parser)]
+#[command(
+    name = "ralph",
+    version,
+    about = "Manage and run Ralph projects from a terminal"
+)]
+struct Cli {
+    /// Project directory (defaults to the current directory).
+    #[arg(long, short = 'C', global = true, value_name = "DIR")]
+    project: Option<PathBuf>,
+
+    /// Print JSON instead of tables.
+    #[arg(long, global = true)]
+    json: bool,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Create .ralph/ in a directory, seeded with a stack's disciplines.
+    Init(InitArgs),
+    /// List the discipline stacks `init` can seed.
+    Stacks,
+    /// List, show, create and update tasks.
+    #[command(subcommand)]
+    Task(TaskCommand),
+    /// List open asks and answer them.
+    #[command(subcommand)]
+    Ask(AskCommand),
+    /// Print the prompt a task session would start with.
+    Prompt { task_id: u32 },
+    /// Write the project as PRD YAML, a report or a dependency graph.
+    Export(ExportArgs),
+    /// Import tasks, subsystems and disciplines from PRD YAML.
+    Import(ImportArgs),
+    /// Run the execution loop until no task can start.
+    Run(RunArgs),
+    /// List, take and restore database backups.
+    #[command(subcommand)]
+    Backup(BackupCommand),
+    /// Show the workspace dashboard and register projects on it.
+    #[command(subcommand)]
+    Workspace(WorkspaceCommand),
+    /// Check the project for problems; exits non-zero if any check is an error.
+    Doctor {
+        /// Apply the safe automatic fixes.
+        #[arg(long)]
+        fix: bool,
+    },
+}
+
+#[derive(Debug, Args)]
+struct InitArgs {
+    /// Directory to initialize (defaults to --project or the current directory).
+    path: Option<PathBuf>,
+    /// Project title (defaults to the directory name).
+    #[arg(long)]
+    title: Option<String>,
+    /// Stack id from `ralph stacks`; 0 seeds no disciplines.
+    #[arg(long, default_value_t = 1)]
+    stack: u8,
+}
+
+#[derive(Debug, Subcommand)]
+enum TaskCommand {
+    /// List tasks.
+    List {
+        #[arg(long, value_parser = parse_status)]
+        status: Option<TaskStatus>,
+        #[arg(long)]
+        subsystem: Option<String>,
+        /// Include archived tasks and the tasks of archived subsystems.
+        #[arg(long)]
+        archived: bool,
+    },
+    /// Show a task with its acceptance criteria and signals.
+    Show { id: u32 },
+    /// Create a task.
+    Create(TaskCreateArgs),
+    /// Change a task. Only the given fields change; list options replace the whole list.
+    Update(TaskUpdateArgs),
+    /// Set a task's status.
+    Status {
+        id: u32,
+        #[arg(value_parser = parse_status)]
+        status: TaskStatus,
+    },
+    /// Archive a task: it stays in the database but leaves listings, prompts and runs.
+    Archive { id: u32 },
+    /// Bring an archived task back.
+    Unarchive { id: u32 },
+}
+
+#[derive(Debug, Args)]
+struct TaskCreateArgs {
+    #[arg(long)]
+    subsystem: String,
+    #[arg(long)]
+    discipline: String,
+    #[arg(long)]
+    title: String,
+    #[command(flatten)]
+    fields: TaskFields,
+}
+
+#[derive(Debug, Args)]
+struct TaskUpdateArgs {
+    id: u32,
+    #[arg(long)]
+    subsystem: Option<String>,
+    #[arg(long)]
+    discipline: Option<String>,
+    #[arg(long)]
+    title: Option<String>,
+    #[command(flatten)]
+    fields: TaskFields,
+}
+
+/// Options shared by `task create` and `task update`.
+#[derive(Debug, Args)]
+struct TaskFields {
+    #[arg(long)]
+    description: Option<String>,
+    #[arg(long, value_parser = parse_priority)]
+    priority: Option<Priority>,
+    /// Repeat for several tags.
+    #[arg(long = "tag", value_name = "TAG")]
+    tags: Option<Vec<String>>,
+    /// Comma-separated task ids.
+    #[arg(long, value_delimiter = ',', value_name = "IDS")]
+    depends_on: Option<Vec<u32>>,
+    /// Repeat for several criteria.
+    #[arg(long = "criterion", value_name = "TEXT")]
+    acceptance_criteria: Option<Vec<String>>,
+    /// Repeat for several files.
+    #[arg(long = "context-file", value_name = "PATH")]
+    context_files: Option<Vec<String>>,
+    #[arg(long)]
+    hints: Option<String>,
+    #[arg(long)]
+    estimated_turns: Option<u32>,
+}
+
+#[derive(Debug, Subcommand)]
+enum AskCommand {
+    /// List asks waiting for an answer.
+    List {
+        /// Include answered asks.
+        #[arg(long)]
+        all: bool,
+    },
+    /// Answer an ask by its signal id.
+    Answer { signal_id: u32, answer: String },
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum ExportFormat {
+    /// PRD YAML, the format `import` reads.
+    Prd,
+    /// Report grouped by subsystem and status.
+    Markdown,
+    /// One row per task.
+    Csv,
+    /// Dependency graph for Graphviz.
+    Dot,
+    /// Dependency graph as a Mermaid flowchart.
+    Mermaid,
+}
+
+#[derive(Debug, Args)]
+struct ExportArgs {
+    #[arg(value_enum)]
+    format: ExportFormat,
+    /// Write to a file instead of stdout.
+    #[arg(long, short)]
+    output: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum ImportMode {
+    /// Update matching entries and add new ones.
+    Merge,
+    /// Remove every task and subsystem first.
+    Replace,
+}
+
+#[derive(Debug, Args)]
+struct ImportArgs {
+    /// PRD YAML file; `-` reads stdin.
+    file: PathBuf,
+    #[arg(long, value_enum, default_value = "merge")]
+    mode: ImportMode,
+    /// Validate and report without writing anything.
+    #[arg(long)]
+    dry_run: bool,
+}
+
+#[derive(Debug, Subcommand)]
+enum BackupCommand {
+    /// List backups, newest first.
+    List,
+    /// Back up the database now.
+    Create,
+    /// Roll the database back to a backup; the current state is backed up first.
+    Restore { name: String },
+}
+
+#[derive(Debug, Subcommand)]
+enum WorkspaceCommand {
+    /// Pending asks, running sessions and blocked tasks per registered project.
+    List,
+    /// Register the project (--project or the current directory).
+    Add,
+    /// Unregister the project (--project or the current directory).
+    Remove,
+}
+
+#[derive(Debug, Args)]
+struct RunArgs {
+    /// Maximum tasks running at once.
+    #[arg(long)]
+    concurrency: Option<u32>,
+    /// Run each task in its own git worktree.
+    #[arg(long)]
+    worktrees: bool,
+    /// Copy the agents' terminal output to stdout.
+    #[arg(long)]
+    stream: bool,
+}
+
+fn parse_status(value: &str) -> Result<TaskStatus, String> {
+    TaskStatus::parse(value).ok_or_else(|| {
+        "expected one of: draft, pending, in_progress, done, blocked, skipped".to_owned()
+    })
+}
+
+fn parse_priority(value: &str) -> Result<Priority, String> {
+    Priority::parse(value).ok_or_else(|| "expected one of: low, medium, high, critical".to_owned())
+}
+
+fn main() {
+    let cli = Cli::parse();
+    init_tracing();
+    ralph_backend::diagnostics::register_sink(std::sync::Arc::new(run::CliSink {
+        stream_output: false,
+    }));
+
+    let code = match execute(cli) {
+        Ok(true) => 0,
+        Ok(false) => 1,
+        Err(error) => {
+            eprintln!("ralph: {error}");
+            1
+        }
+    };
+    std::process::exit(code);
+}
+
+/// Logs are off unless `RUST_LOG` asks for them, and go to stderr.
+fn init_tracing() {
+    use tracing_subscriber::EnvFilter;
+
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("off"));
+    tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(std::io::stderr)
+        .init();
+}
+
+/// Returns `false` when the command ran but should exit non-zero, e.g. a run with failed tasks.
+fn execute(cli: Cli) -> Result<bool, String> {
+    let project = cli.project.unwrap_or_else(|| PathBuf::from("."));
+    let json = cli.json;
+    match cli.command {
+        Command::Init(args) => init(args.path.as_deref().unwrap_or(&project), &args, json)?,
+        Command::Stacks => stacks(json)?,
+        Command::Task(command) => task(&open_project(&project)?, command, json)?,
+        Command::Ask(command) => ask(&open_project(&project)?, command, json)?,
+        Command::Prompt { task_id } => prompt(&open_project(&project)?, task_id, json)?,
+        Command::Export(args) => export(&open_project(&project)?, &args)?,
+        Command::Import(args) => import(&open_project(&project)?, &args, json)?,
+        Command::Backup(command) => backup(&open_project(&project)?, command, json)?,
+        Command::Workspace(command) => workspace(&project, command, json)?,
+        Command::Doctor { fix } => return doctor(&open_project(&project)?, fix, json),
+        Command::Run(args) => {
+            return run::run(
+                open_project(&project)?,
+                ExecutionStartArgs {
+                    concurrency: args.concurrency,
+                    use_worktrees: Some(args.worktrees),
+                },
+                args.stream,
+                json,
+            );
+        }
+    }
+    Ok(true)
+}
+
+fn open_project(project: &Path) -> Result<AppState, String> {
+    validate_project_path(project)?;
+    let state = AppState::default();
+    project_lock_validated(&state, project.to_string_lossy().into_owned())?;
+    Ok(state)
+}
+
+fn with_db<T>(
+    state: &AppState,
+    f: impl FnOnce(&SqliteDb) -> Result<T, String>,
+) -> Result<T, String> {
+    CommandContext::new(state).db(f)
+}
+
+fn task_or_error(db: &SqliteDb, id: u32) -> Result<Task, String> {
+    db.get_task_by_id(id)
+        .ok_or_else(|| ralph_errors::err_string(codes::TASK_OPS, format!("Task {id} not found")))
+}
+
+fn init(path: &Path, args: &InitArgs, json: bool) -> Result<(), String> {
+    let title = args.title.clone().unwrap_or_else(|| {
+        std::fs::canonicalize(path)
+            .ok()
+            .and_then(|path| {
+                path.file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+            })
+            .unwrap_or_else(|| "Untitled".to_owned())
+    });
+    initialize_project(path, &title, args.stack)?;
+    if json {
+        print_json(&serde_json::json!({
+            "path": path,
+            "title": title,
+            "stack": args.stack,
+        }))
+    } else {
+        println!("Initialized \"{title}\" in {}", path.display());
+        Ok(())
+    }
+}
+
+fn stacks(json: bool) -> Result<(), String> {
+    let stacks = ralph_backend::stacks::stacks_metadata();
+    if json {
+        return print_json(&stacks);
+    }
+    let mut table = Table::new(&["ID", "NAME", "DISCIPLINES", "DESCRIPTION"]);
+    for stack in &stacks {
+        table.row(vec![
+            stack.stack_id.to_string(),
+            stack.name.clone(),
+            stack.discipline_count.to_string(),
+            cell(&stack.description, 80),
+        ]);
+    }
+    table.print();
+    Ok(())
+}
+
+fn task(state: &AppState, command: TaskCommand, json: bool) -> Result<(), String> {
+    match command {
+        TaskCommand::List {
+            status,
+            subsystem,
+            archived,
+        } => {
+            let tasks: Vec<Task> = with_db(state, |db| db.list_tasks(archived))?
+                .into_iter()
+                .filter(|task| status.map_or(true, |status| task.status == status))
+                .filter(|task| {
+                    subsystem
+                        .as_deref()
+                        .map_or(true, |subsystem| task.subsystem == subsystem)
+                })
+                .collect();
+            if json {
+                return print_json(&tasks);
+            }
+            let mut table = Table::new(&[
+                "ID",
+                "STATUS",
+                "PRIORITY",
+                "SUBSYSTEM",
+                "DISCIPLINE",
+                "TITLE",
+            ]);
+            for task in &tasks {
+                table.row(vec![
+                    task.id.to_string(),
+                    task.status.as_str().to_owned(),
+                    task.priority
+                        .map(|priority| priority.as_str().to_owned())
+                        .unwrap_or_default(),
+                    task.subsystem.clone(),
+                    task.discipline.clone(),
+                    cell(&task.title, 80),
+                ]);
+            }
+            table.print();
+            Ok(())
+        }
+        TaskCommand::Show { id } => {
+            let task = with_db(state, |db| task_or_error(db, id))?;
+            if json {
+                print_json(&task)
+            } else {
+                print_task(&task);
+                Ok(())
+            }
+        }
+        TaskCommand::Create(args) => {
+            let input = TaskInput {
+                subsystem: args.subsystem,
+                discipline: args.discipline,
+                title: args.title,
+                ..Default::default()
+            };
+            let task = with_db(state, |db| {
+                let id = db.create_task(args.fields.apply(input))?;
+                task_or_error(db, id)
+            })?;
+            print_changed_task(&task, "Created", json)
+        }
+        TaskCommand::Update(args) => {
+            let task = with_db(state, |db| {
+                let existing = task_or_error(db, args.id)?;
+                let mut input = task_input(existing);
+                if let Some(subsystem) = args.subsystem {
+                    input.subsystem = subsystem;
+                }
+                if let Some(discipline) = args.discipline {
+                    input.discipline = discipline;
+                }
+                if let Some(title) = args.title {
+                    input.title = title;
+                }
+                db.update_task(args.id, args.fields.apply(input))?;
+                task_or_error(db, args.id)
+            })?;
+            print_changed_task(&task, "Updated", json)
+        }
+        TaskCommand::Status { id, status } => {
+            let task = with_db(state, |db| {
+                db.set_task_status(id, status)?;
+                task_or_error(db, id)
+            })?;
+            print_changed_task(&task, "Updated", json)
+        }
+        TaskCommand::Archive { id } | TaskCommand::Unarchive { id } => {
+            let archive = matches!(command, TaskCommand::Archive { .. });
+            let task = with_db(state, |db| {
+                db.set_task_archived(id, archive)?;
+                task_or_error(db, id)
+            })?;
+            print_changed_task(&task, if archive { "Archived" } else { "Unarchived" }, json)
+        }
+    }
+}
+
+impl TaskFields {
+    fn apply(self, mut input: TaskInput) -> TaskInput {
+        if self.description.is_some() {
+            input.description = self.description;
+        }
+        if self.priority.is_some() {
+            input.priority = self.priority;
+        }
+        if let Some(tags) = self.tags {
+            input.tags = tags;
+        }
+        if let Some(depends_on) = self.depends_on {
+            input.depends_on = depends_on;
+        }
+        if self.acceptance_criteria.is_some() {
+            input.acceptance_criteria = self.acceptance_criteria;
+        }
+        if let Some(context_files) = self.context_files {
+            input.context_files = context_files;
+        }
+        if self.hints.is_some() {
+            input.hints = self.hints;
+        }
+        if self.estimated_turns.is_some() {
+            input.estimated_turns = self.estimated_turns;
+        }
+        input
+    }
+}
+
+/// The update that leaves `task` as it is.
+fn task_input(task: Task) -> TaskInput {
+    TaskInput {
+        subsystem: task.subsystem,
+        discipline: task.discipline,
+        title: task.title,
+        description: task.description,
+        status: None,
+        priority: task.priority,
+        tags: task.tags,
+        depends_on: task.depends_on,
+        acceptance_criteria: Some(task.acceptance_criteria),
+        context_files: task.context_files,
+        output_artifacts: task.output_artifacts,
+        hints: task.hints,
+        estimated_turns: task.estimated_turns,
+        provenance: task.provenance,
+        agent: task.agent,
+        model: task.model,
+        effort: task.effort,
+        thinking: task.thinking,
+    }
+}
+
+fn print_changed_task(task: &Task, verb: &str, json: bool) -> Result<(), String> {
+    if json {
+        return print_json(task);
+    }
+    println!(
+        "{verb} task #{} ({}): {}",
+        task.id,
+        task.status.as_str(),
+        task.title
+    );
+    Ok(())
+}
+
+fn print_task(task: &Task) {
+    println!("#{} {}", task.id, task.title);
+    let mut facts = Table::default();
+    facts.row(vec!["status".to_owned(), task.status.as_str().to_owned()]);
+    if let Some(priority) = task.priority {
+        facts.row(vec!["priority".to_owned(), priority.as_str().to_owned()]);
+    }
+    facts.row(vec!["subsystem".to_owned(), task.subsystem.clone()]);
+    facts.row(vec!["discipline".to_owned(), task.discipline.clone()]);
+    if !task.depends_on.is_empty() {
+        let deps: Vec<String> = task.depends_on.iter().map(|id| format!("#{id}")).collect();
+        facts.row(vec!["depends on".to_owned(), deps.join(", ")]);
+    }
+    if !task.tags.is_empty() {
+        facts.row(vec!["tags".to_owned(), task.tags.join(", ")]);
+    }
+    facts.print();
+
+    if let Some(description) = &task.description {
+        println!("\n{}", description.trim());
+    }
+    if !task.acceptance_criteria.is_empty() {
+        println!("\nAcceptance criteria:");
+        for criterion in &task.acceptance_criteria {
+            println!("  - {criterion}");
+        }
+    }
+    if !task.signals.is_empty() {
+        println!("\nSignals:");
+        for signal in &task.signals {
+            let verb = signal.signal_verb.as_deref().unwrap_or("signal");
+            println!("  #{} {verb}: {}", signal.id, cell(&signal.body, 100));
+        }
+    }
+}
+
+fn ask(state: &AppState, command: AskCommand, json: bool) -> Result<(), String> {
+    match command {
+        AskCommand::List { all } => {
+            let tasks = with_db(state, |db| Ok(db.get_tasks()))?;
+            let asks: Vec<(&Task, &sqlite_db::TaskSignal)> = tasks
+                .iter()
+                .flat_map(|task| task.signals.iter().map(move |signal| (task, signal)))
+                .filter(|(_, signal)| signal.signal_verb.as_deref() == Some("ask"))
+                .filter(|(_, signal)| all || signal.answer.is_none())
+                .collect();
+            if json {
+                let asks: Vec<serde_json::Value> = asks
+                    .iter()
+                    .map(|(task, signal)| {
+                        serde_json::json!({
+                            "signalId": signal.id,
+                            "taskId": task.id,
+                            "taskTitle": task.title,
+                            "question": signal.question.as_deref().unwrap_or(&signal.body),
+                            "blocking": signal.blocking.unwrap_or(false),
+                            "options": signal.options,
+                            "preferred": signal.preferred,
+                            "answer": signal.answer,
+                        })
+                    })
+                    .collect();
+                return print_json(&asks);
+            }
+            let mut table = Table::new(&["SIGNAL", "TASK", "BLOCKING", "QUESTION"]);
+            for (task, signal) in &asks {
+                let question = signal.question.as_deref().unwrap_or(&signal.body);
+                table.row(vec![
+                    signal.id.to_string(),
+                    format!("#{}", task.id),
+                    if signal.blocking.unwrap_or(false) {
+                        "yes"
+                    } else {
+                        "no"
+                    }
+                    .to_owned(),
+                    cell(question, 100),
+                ]);
+            }
+            table.print();
+            Ok(())
+        }
+        AskCommand::Answer { signal_id, answer } => {
+            with_db(state, |db| db.answer_ask(signal_id, answer))?;
+            if json {
+                print_json(&serde_json::json!({ "signalId": signal_id }))
+            } else {
+                println!("Answered ask {signal_id}");
+                Ok(())
+            }
+        }
+    }
+}
+
+fn prompt(state: &AppState, task_id: u32, json: bool) -> Result<(), String> {
+    let project_path = CommandContext::new(state).locked_project_path()?;
+    with_db(state, |db| task_or_error(db, task_id))?;
+    let rendered = state.render_task_prompt(task_id, &project_path, TaskPromptSpec::default())?;
+    if json {
+        return print_json(&serde_json::json!({
+            "recipe": rendered.recipe,
+            "configName": rendered.config_name,
+            "sections": rendered.sections,
+            "prompt": rendered.prompt(),
+        }));
+    }
+    println!("{}", rendered.prompt());
+    Ok(())
+}
+
+fn export(state: &AppState, args: &ExportArgs) -> Result<(), String> {
+    let text = with_db(state, |db| match args.format {
+        ExportFormat::Prd => db.export_prd_yaml(),
+        ExportFormat::Markdown => Ok(db.export_report(ReportFormat::Markdown)),
+        ExportFormat::Csv => Ok(db.export_report(ReportFormat::Csv)),
+        ExportFormat::Dot => Ok(db.export_report(ReportFormat::Dot)),
+        ExportFormat::Mermaid => Ok(db.export_report(ReportFormat::Mermaid)),
+    })?;
+    let Some(path) = &args.output else {
+        print!("{text}");
+        return Ok(());
+    };
+    std::fs::write(path, text).map_err(|e| format!("Failed to write {}: {e}", path.display()))
+}
+
+fn import(state: &AppState, args: &ImportArgs, json: bool) -> Result<(), String> {
+    let yaml = if args.file.as_os_str() == "-" {
+        std::io::read_to_string(std::io::stdin())
+            .map_err(|e| format!("Failed to read stdin: {e}"))?
+    } else {
+        std::fs::read_to_string(&args.file)
+            .map_err(|e| format!("Failed to read {}: {e}", args.file.display()))?
+    };
+    let mode = match args.mode {
+        ImportMode::Merge => PrdImportMode::Merge,
+        ImportMode::Replace => PrdImportMode::Replace,
+    };
+    let report = import_prd(state, &yaml, mode, args.dry_run)?;
+    if json {
+        return print_json(&report);
+    }
+    if args.dry_run {
+        println!("Dry run; nothing was written.");
+    }
+    let mut table = Table::new(&["", "CREATED", "UPDATED", "REMOVED"]);
+    table.row(vec![
+        "disciplines".to_owned(),
+        report.disciplines_created.to_string(),
+        report.disciplines_updated.to_string(),
+        "0".to_owned(),
+    ]);
+    table.row(vec![
+        "subsystems".to_owned(),
+        report.subsystems_created.to_string(),
+        report.subsystems_updated.to_string(),
+        report.subsystems_removed.to_string(),
+    ]);
+    table.row(vec![
+        "tasks".to_owned(),
+        report.tasks_created.to_string(),
+        report.tasks_updated.to_string(),
+        report.tasks_removed.to_string(),
+    ]);
+    table.print();
+    Ok(())
+}
+
+fn backup(state: &AppState, command: BackupCommand, json: bool) -> Result<(), String> {
+    match command {
+        BackupCommand::List => {
+            let backups = with_db(state, SqliteDb::list_backups)?;
+            if json {
+                return print_json(&backups);
+            }
+            let mut table = Table::new(&["CREATED", "REASON", "TASKS", "SIZE", "NAME"]);
+            for backup in &backups {
+                table.row(vec![
+                    backup.created.clone(),
+                    backup.reason.as_str().to_owned(),
+                    backup
+                        .task_count
+                        .map_or_else(|| "?".to_owned(), |count| count.to_string()),
+                    format!("{} KiB", backup.size_bytes.div_ceil(1024)),
+                    backup.name.clone(),
+                ]);
+            }
+            table.print();
+            Ok(())
+        }
+        BackupCommand::Create => {
+            let backup = with_db(state, |db| db.create_backup(BackupReason::Manual))?;
+            if json {
+                return print_json(&backup);
+            }
+            println!("Backed up to {}", backup.name);
+            Ok(())
+        }
+        BackupCommand::Restore { name } => {
+            let replaced = restore_backup(state, &name)?;
+            if json {
+                return print_json(&replaced);
+            }
+            println!(
+                "Restored {name}; the replaced state is in {}",
+                replaced.name
+            );
+            Ok(())
+        }
+    }
+}
+
+fn workspace(project: &Path, command: WorkspaceCommand, json: bool) -> Result<(), String> {
+    // No project is locked; every project is read the way the dashboard reads inactive ones.
+    let state = AppState::default();
+    let projects = match command {
+        WorkspaceCommand::List => {
+            let summaries = workspace_dashboard(&state)?;
+            if json {
+                return print_json(&summaries);
+            }
+            let mut table = Table::new(&["NAME", "ASKS", "RUNNING", "BLOCKED", "PATH"]);
+            for summary in &summaries {
+                let counts = summary.activity.map_or_else(
+                    || vec!["?".to_owned(); 3],
+                    |activity| {
+                        vec![
+                            activity.pending_asks.to_string(),
+                            activity.running_sessions.to_string(),
+                            activity.blocked_tasks.to_string(),
+                        ]
+                    },
+                );
+                let mut row = vec![summary.name.clone()];
+                row.extend(counts);
+                row.push(summary.path.clone());
+                table.row(row);
+            }
+            table.print();
+            for summary in &summaries {
+                if let Some(error) = &summary.error {
+                    eprintln!("{}: {error}", summary.name);
+                }
+            }
+            return Ok(());
+        }
+        WorkspaceCommand::Add => add_project(&state, project)?,
+        WorkspaceCommand::Remove => remove_project(&state, project)?,
+    };
+    if json {
+        return print_json(&projects);
+    }
+    for project in &projects {
+        println!("{}\t{}", project.name, project.path);
+    }
+    Ok(())
+}
+
+fn doctor(state: &AppState, fix: bool, json: bool) -> Result<bool, String> {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(|e| format!("Failed to start the async runtime: {e}"))?;
+    let checks = runtime.block_on(run_doctor(state, fix))?;
+    let healthy = checks
+        .iter()
+        .all(|check| check.severity != DoctorSeverity::Error);
+    if json {
+        print_json(&checks)?;
+        return Ok(healthy);
+    }
+    let mut table = Table::new(&["CHECK", "SEVERITY", "MESSAGE"]);
+    for check in &checks {
+        let severity = if check.fixed {
+            "fixed"
+        } else {
+            check.severity.as_str()
+        };
+        table.row(vec![
+            check.name.clone(),
+            severity.to_owned(),
+            check.message.clone(),
+        ]);
+        for detail in &check.details {
+            table.row(vec![String::new(), String::new(), format!("  {detail}")]);
+        }
+        if let Some(fix) = check.fix.as_deref().filter(|_| !check.fixed) {
+            table.row(vec![String::new(), String::new(), format!("  fix: {fix}")]);
+        }
+    }
+    table.print();
+    Ok(healthy)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Cli {
+        Cli::try_parse_from(std::iter::once("ralph").chain(args.iter().copied())).unwrap()
+    }
+
+    #[test]
+    fn command_definition_is_valid() {
+        use clap::CommandFactory;
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn task_update_only_changes_given_fields() {
+        let Command::Task(TaskCommand::Update(args)) = parse(&[
+            "task",
+            "update",
+            "3",
+            "--priority",
+            "high",
+            "--depends-on",
+            "1,2",
+        ])
+        .command
+        else {
+            panic!("expected task update");
+        };
+        let input = args.fields.apply(TaskInput {
+            title: "Login".to_owned(),
+            tags: vec!["api".to_owned()],
+            ..Default::default()
+        });
+        assert_eq!(input.title, "Login");
+        assert_eq!(input.priority, Some(Priority::High));
+        assert_eq!(input.depends_on, vec![1, 2]);
+        assert_eq!(input.tags, vec!["api"]);
+    }
+
+    #[test]
+    fn global_options_are_accepted_after_the_subcommand() {
+        let cli = parse(&[
+            "task",
+            "list",
+            "--status",
+            "in_progress",
+            "--json",
+            "-C",
+            "/p",
+        ]);
+        assert!(cli.json);
+        assert_eq!(cli.project, Some(PathBuf::from("/p")));
+        assert!(matches!(
+            cli.command,
+            Command::Task(TaskCommand::List {
+                status: Some(TaskStatus::InProgress),
+                ..
+            })
+        ));
+        assert!(Cli::try_parse_from(["ralph", "task", "status", "1", "finished"]).is_err());
+    }
+}
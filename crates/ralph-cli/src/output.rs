@@ -0,0 +1,99 @@
+//! How CLI results are printed: pretty JSON with `--json`, otherwise aligned text tables.
+
+use serde::Serialize;
+
+pub fn print_json<T: Serialize + ?Sized>(value: &T) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(value)
+        .map_err(|e| format!("Failed to serialize output: {e}"))?;
+    println!("{json}");
+    Ok(())
+}
+
+/// Columns padded to their widest cell. The last column is never padded, so long titles
+/// don't leave trailing whitespace.
+#[derive(Debug, Default)]
+pub struct Table {
+    rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    pub fn new(header: &[&str]) -> Self {
+        Self {
+            rows: vec![header.iter().map(|cell| (*cell).to_owned()).collect()],
+        }
+    }
+
+    pub fn row(&mut self, cells: Vec<String>) {
+        self.rows.push(cells);
+    }
+
+    pub fn render(&self) -> String {
+        let columns = self.rows.iter().map(Vec::len).max().unwrap_or(0);
+        let widths: Vec<usize> = (0..columns)
+            .map(|column| {
+                self.rows
+                    .iter()
+                    .filter_map(|row| row.get(column))
+                    .map(|cell| cell.chars().count())
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+
+        let mut output = String::new();
+        for row in &self.rows {
+            let mut line = String::new();
+            for (column, cell) in row.iter().enumerate() {
+                if column + 1 == row.len() {
+                    line.push_str(cell);
+                } else {
+                    line.push_str(&format!("{cell:<width$}  ", width = widths[column]));
+                }
+            }
+            output.push_str(line.trim_end());
+            output.push('\n');
+        }
+        output
+    }
+
+    pub fn print(&self) {
+        print!("{}", self.render());
+    }
+}
+
+/// A single-line cell: line breaks collapsed, cut to `max` characters.
+pub fn cell(text: &str, max: usize) -> String {
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if text.chars().count() <= max {
+        return text;
+    }
+    let mut cut: String = text.chars().take(max.saturating_sub(1)).collect();
+    cut.push('…');
+    cut
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn table_aligns_columns_without_trailing_padding() {
+        let mut table = Table::new(&["ID", "STATUS", "TITLE"]);
+        table.row(vec![
+            "1".to_owned(),
+            "in_progress".to_owned(),
+            "Login".to_owned(),
+        ]);
+        table.row(vec!["12".to_owned(), "done".to_owned(), String::new()]);
+        assert_eq!(
+            table.render(),
+            "ID  STATUS       TITLE\n1   in_progress  Login\n12  done\n"
+        );
+    }
+
+    #[test]
+    fn cell_collapses_lines_and_truncates() {
+        assert_eq!(cell("two\nlines", 20), "two lines");
+        assert_eq!(cell("abcdefgh", 5), "abcd…");
+    }
+}
@@ -0,0 +1,169 @@
+//! `ralph run`: the execution loop without the desktop app. Workers run in PTYs exactly as
+//! they do in the app; their terminal output is dropped unless `--stream` asks for it.
+
+use crate::output::{cell, print_json, Table};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use ralph_backend::execution::{start_execution, ExecutionStartArgs};
+use ralph_backend::session_host::SessionHost;
+use ralph_backend::state::{AppState, CommandContext};
+use ralph_backend::worker_pool::{ExecutionState, ExecutionStatus};
+use ralph_contracts::events::{BackendDiagnosticEvent, BackendDiagnosticLevel};
+use ralph_contracts::terminal::{PtyClosedEvent, PtyOutputEvent};
+use ralph_contracts::transport::EventSink;
+use sqlite_db::TaskStatus;
+use std::collections::BTreeSet;
+use std::io::Write;
+use std::sync::{Arc, Condvar, Mutex};
+
+/// Diagnostics go to stderr; terminal output to stdout when `stream_output` is set.
+pub struct CliSink {
+    pub stream_output: bool,
+}
+
+impl EventSink for CliSink {
+    fn emit_backend_diagnostic(&self, payload: BackendDiagnosticEvent) -> Result<(), String> {
+        let level = match payload.level {
+            BackendDiagnosticLevel::Warning => "warning",
+            BackendDiagnosticLevel::Error => "error",
+        };
+        eprintln!("{level}: {}", payload.message);
+        Ok(())
+    }
+
+    fn emit_terminal_output(&self, payload: PtyOutputEvent) -> Result<(), String> {
+        if !self.stream_output {
+            return Ok(());
+        }
+        let bytes = STANDARD
+            .decode(payload.data)
+            .map_err(|e| format!("Invalid terminal output: {e}"))?;
+        let mut stdout = std::io::stdout().lock();
+        stdout
+            .write_all(&bytes)
+            .and_then(|()| stdout.flush())
+            .map_err(|e| format!("Failed to write terminal output: {e}"))
+    }
+
+    fn emit_terminal_closed(&self, _payload: PtyClosedEvent) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default)]
+struct RunProgress {
+    status: ExecutionStatus,
+    /// `(task_id, review)` of the workers running now.
+    running: BTreeSet<(u32, bool)>,
+    /// Every task a worker started on during the run.
+    attempted: BTreeSet<u32>,
+}
+
+#[derive(Clone)]
+struct CliHost {
+    state: Arc<AppState>,
+    sink: Arc<CliSink>,
+    progress: Arc<(Mutex<RunProgress>, Condvar)>,
+}
+
+impl SessionHost for CliHost {
+    fn app_state(&self) -> &AppState {
+        &self.state
+    }
+
+    fn event_sink(&self) -> Arc<dyn EventSink> {
+        Arc::clone(&self.sink) as Arc<dyn EventSink>
+    }
+
+    /// Report workers starting and finishing on stderr, and wake `wait_until_idle`.
+    fn emit_execution_state(&self, state: &ExecutionState) {
+        let (lock, changed) = &*self.progress;
+        let Ok(mut progress) = lock.lock() else {
+            return;
+        };
+        let running: BTreeSet<(u32, bool)> = state
+            .workers
+            .iter()
+            .map(|worker| (worker.task_id, worker.review))
+            .collect();
+        for (task_id, review) in progress.running.difference(&running) {
+            let role = if *review { "review of task" } else { "task" };
+            eprintln!("{role} #{task_id} finished");
+        }
+        for (task_id, review) in running.difference(&progress.running) {
+            let role = if *review { "review of task" } else { "task" };
+            eprintln!("{role} #{task_id} started");
+        }
+        progress
+            .attempted
+            .extend(running.iter().map(|(task_id, _)| *task_id));
+        progress.running = running;
+        progress.status = state.status;
+        changed.notify_all();
+    }
+}
+
+impl CliHost {
+    fn wait_until_idle(&self) -> Result<BTreeSet<u32>, String> {
+        let (lock, changed) = &*self.progress;
+        let progress = lock
+            .lock()
+            .map_err(|_| "Execution progress lock poisoned".to_owned())?;
+        let progress = changed
+            .wait_while(progress, |progress| {
+                progress.status != ExecutionStatus::Idle
+            })
+            .map_err(|_| "Execution progress lock poisoned".to_owned())?;
+        Ok(progress.attempted.clone())
+    }
+}
+
+/// Run until no task can start and every worker has exited. Returns whether every task
+/// that ran ended `done`.
+pub fn run(
+    state: AppState,
+    args: ExecutionStartArgs,
+    stream_output: bool,
+    json: bool,
+) -> Result<bool, String> {
+    let host = CliHost {
+        state: Arc::new(state),
+        sink: Arc::new(CliSink { stream_output }),
+        progress: Arc::default(),
+    };
+    start_execution(&host, args)?;
+    let attempted = host.wait_until_idle()?;
+
+    let tasks: Vec<sqlite_db::Task> = CommandContext::new(host.app_state())
+        .db(|db| Ok(db.get_tasks()))?
+        .into_iter()
+        .filter(|task| attempted.contains(&task.id))
+        .collect();
+    let all_done = tasks.iter().all(|task| task.status == TaskStatus::Done);
+
+    if json {
+        let summary: Vec<serde_json::Value> = tasks
+            .iter()
+            .map(|task| {
+                serde_json::json!({
+                    "id": task.id,
+                    "title": task.title,
+                    "status": task.status,
+                })
+            })
+            .collect();
+        print_json(&summary)?;
+    } else if tasks.is_empty() {
+        println!("No task was ready to run.");
+    } else {
+        let mut table = Table::new(&["ID", "STATUS", "TITLE"]);
+        for task in &tasks {
+            table.row(vec![
+                task.id.to_string(),
+                task.status.as_str().to_owned(),
+                cell(&task.title, 80),
+            ]);
+        }
+        table.print();
+    }
+    Ok(all_done)
+}
@@ -18,10 +18,11 @@ impl RalphError {
     pub fn code_category(&self) -> &str {
         match self.code {
             1000..=1299 => "PROJECT",
-            2000..=2299 => "DATABASE",
+            2000..=2399 => "DATABASE",
             3000..=3399 => "TASK",
             4000..=4199 => "FEATURE",
             5000..=5099 => "LOOP_ENGINE",
+            6000..=6099 => "GIT",
             7000..=7099 => "TERMINAL",
             8000..=8099 => "FILESYSTEM",
             8100..=8199 => "INTERNAL",
@@ -130,12 +131,14 @@ pub mod codes {
     pub const DB_OPEN: u16 = 2000;
     pub const DB_READ: u16 = 2100;
     pub const DB_WRITE: u16 = 2200;
+    pub const EDIT_CONFLICT: u16 = 2300;
     pub const TASK_VALIDATION: u16 = 3000;
     pub const TASK_OPS: u16 = 3100;
     pub const SIGNAL_OPS: u16 = 3300;
     pub const FEATURE_OPS: u16 = 4000;
     pub const DISCIPLINE_OPS: u16 = 4100;
     pub const LOOP_ENGINE: u16 = 5000;
+    pub const GIT_OPS: u16 = 6000;
     pub const TERMINAL: u16 = 7000;
     pub const FILESYSTEM: u16 = 8000;
     pub const INTERNAL: u16 = 8100;
@@ -172,6 +175,22 @@ mod tests {
             .code_category(),
             "DATABASE"
         );
+        assert_eq!(
+            RalphError {
+                code: codes::EDIT_CONFLICT,
+                message: "test".to_owned()
+            }
+            .code_category(),
+            "DATABASE"
+        );
+        assert_eq!(
+            RalphError {
+                code: codes::GIT_OPS,
+                message: "test".to_owned()
+            }
+            .code_category(),
+            "GIT"
+        );
         assert_eq!(
             RalphError {
                 code: codes::TERMINAL,
@@ -174,6 +174,7 @@ async fn smoke_embed_store_search_render() {
             thinking: None,
             pseudocode: None,
             enriched_at: None,
+            archived_at: None,
             signals: vec![],
             subsystem_display_name: "Auth".to_owned(),
             subsystem_acronym: "AUTH".to_owned(),
@@ -189,6 +190,7 @@ async fn smoke_embed_store_search_render() {
             created: None,
         },
         file_contents: std::collections::HashMap::new(),
+        feature_files: vec![],
         progress_txt: None,
         learnings_txt: None,
         claude_ralph_md: None,
@@ -201,6 +203,7 @@ async fn smoke_embed_store_search_render() {
         target_feature: None,
         codebase_snapshot: None,
         instruction_overrides: std::collections::HashMap::new(),
+        section_templates: std::collections::HashMap::new(),
         relevant_comments: Some(scored),
     };
 
@@ -263,6 +263,7 @@ async fn semantic_search_feeds_into_prompt_builder() {
             created: None,
         },
         file_contents: std::collections::HashMap::new(),
+        feature_files: vec![],
         progress_txt: None,
         learnings_txt: None,
         claude_ralph_md: None,
@@ -275,6 +276,7 @@ async fn semantic_search_feeds_into_prompt_builder() {
         target_feature: None,
         codebase_snapshot: None,
         instruction_overrides: std::collections::HashMap::new(),
+        section_templates: std::collections::HashMap::new(),
         relevant_comments: None,
     };
     ctx.tasks = vec![sqlite_db::Task {
@@ -302,6 +304,7 @@ async fn semantic_search_feeds_into_prompt_builder() {
         thinking: None,
         pseudocode: None,
         enriched_at: None,
+        archived_at: None,
         signals: vec![],
         subsystem_display_name: "Authentication".to_owned(),
         subsystem_acronym: "AUTH".to_owned(),
@@ -0,0 +1,50 @@
+//! What needs a person's attention in a project, counted for the workspace dashboard.
+//! Archived tasks and the tasks of archived subsystems are left out, as in listings.
+
+use crate::types::{AgentSession, ProjectActivity, SubsystemStatus, TaskStatus};
+use crate::SqliteDb;
+use ralph_errors::{codes, RalphResultExt};
+
+/// Joins a task row `t` to its subsystem and keeps it only if neither is archived.
+const LISTED_TASK: &str = "JOIN subsystems f ON t.subsystem_id = f.id \
+     WHERE t.archived_at IS NULL AND f.status != ?1";
+
+impl SqliteDb {
+    /// `is_live` tells whether a `running` session still has its agent, as in
+    /// `reconcile_orphaned_agent_sessions`; rows a crashed process left behind aren't counted.
+    pub fn project_activity<F>(&self, is_live: F) -> Result<ProjectActivity, String>
+    where
+        F: Fn(&AgentSession) -> bool,
+    {
+        let archived = SubsystemStatus::Archived.as_str();
+        let pending_asks = self
+            .conn
+            .query_row(
+                &format!(
+                    "SELECT COUNT(*) FROM task_signals s JOIN runtime_tasks t ON s.task_id = t.id \
+                     {LISTED_TASK} AND s.verb = 'ask' AND s.answer IS NULL"
+                ),
+                [archived],
+                |row| row.get(0),
+            )
+            .ralph_err(codes::DB_READ, "Failed to count pending asks")?;
+        let blocked_tasks = self
+            .conn
+            .query_row(
+                &format!("SELECT COUNT(*) FROM runtime_tasks t {LISTED_TASK} AND t.status = ?2"),
+                [archived, TaskStatus::Blocked.as_str()],
+                |row| row.get(0),
+            )
+            .ralph_err(codes::DB_READ, "Failed to count blocked tasks")?;
+        let running_sessions = self
+            .list_running_agent_sessions()?
+            .iter()
+            .filter(|session| is_live(session))
+            .count() as u32;
+        Ok(ProjectActivity {
+            pending_asks,
+            running_sessions,
+            blocked_tasks,
+        })
+    }
+}
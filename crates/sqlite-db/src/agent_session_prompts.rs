@@ -0,0 +1,103 @@
+use crate::types::{AgentSessionPrompt, AgentSessionPromptInput};
+use crate::SqliteDb;
+use ralph_errors::{codes, ralph_err, RalphResultExt};
+
+impl SqliteDb {
+    /// Store the prompt a session was started with and stamp its hash on the session row.
+    /// Recording again for the same session replaces the earlier prompt.
+    pub fn record_agent_session_prompt(
+        &self,
+        input: AgentSessionPromptInput,
+    ) -> Result<(), String> {
+        if input.recipe.trim().is_empty() {
+            return ralph_err!(codes::TASK_VALIDATION, "Prompt recipe cannot be empty");
+        }
+        let sections_json =
+            serde_json::to_string(&input.sections).ralph_err(codes::DB_WRITE, "JSON error")?;
+        let now = self.now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+
+        self.with_transaction(|db| {
+            let affected = db
+                .conn
+                .execute(
+                    "UPDATE agent_sessions SET prompt_hash = ?1 WHERE id = ?2",
+                    rusqlite::params![input.prompt_hash, input.session_id],
+                )
+                .ralph_err(codes::DB_WRITE, "Failed to record session prompt hash")?;
+            if affected == 0 {
+                return ralph_err!(
+                    codes::TASK_OPS,
+                    "Session '{}' does not exist",
+                    input.session_id
+                );
+            }
+
+            db.conn
+                .execute(
+                    "INSERT INTO agent_session_prompts \
+                     (session_id, recipe, config_name, sections, prompt, mcp_config, created) \
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7) \
+                     ON CONFLICT(session_id) DO UPDATE SET \
+                     recipe = excluded.recipe, config_name = excluded.config_name, \
+                     sections = excluded.sections, prompt = excluded.prompt, \
+                     mcp_config = excluded.mcp_config, created = excluded.created",
+                    rusqlite::params![
+                        input.session_id,
+                        input.recipe,
+                        input.config_name,
+                        sections_json,
+                        input.prompt,
+                        input.mcp_config,
+                        now,
+                    ],
+                )
+                .ralph_err(codes::DB_WRITE, "Failed to record session prompt")?;
+            Ok(())
+        })
+    }
+
+    /// Prompt recorded for session `#session_number`, if any.
+    pub fn get_agent_session_prompt(
+        &self,
+        session_number: u32,
+    ) -> Result<Option<AgentSessionPrompt>, String> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT p.session_id, s.session_number, p.recipe, p.config_name, p.sections, \
+                 p.prompt, s.prompt_hash, p.mcp_config, p.created \
+                 FROM agent_session_prompts p JOIN agent_sessions s ON s.id = p.session_id \
+                 WHERE s.session_number = ?1",
+            )
+            .ralph_err(codes::DB_READ, "Failed to prepare session prompt query")?;
+
+        let result = stmt.query_row([session_number], |row| {
+            let session_number_i64: i64 = row.get(1)?;
+            let sections_json: String = row.get(4)?;
+            Ok((
+                AgentSessionPrompt {
+                    session_id: row.get(0)?,
+                    session_number: u32::try_from(session_number_i64).unwrap_or_default(),
+                    recipe: row.get(2)?,
+                    config_name: row.get(3)?,
+                    sections: Vec::new(),
+                    prompt: row.get(5)?,
+                    prompt_hash: row.get(6)?,
+                    mcp_config: row.get(7)?,
+                    created: row.get(8)?,
+                },
+                sections_json,
+            ))
+        });
+
+        match result {
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => ralph_err!(codes::DB_READ, "Failed to query session prompt: {e}"),
+            Ok((mut prompt, sections_json)) => {
+                prompt.sections = serde_json::from_str(&sections_json)
+                    .ralph_err(codes::DB_READ, "Failed to parse prompt sections")?;
+                Ok(Some(prompt))
+            }
+        }
+    }
+}
@@ -1,9 +1,42 @@
-use crate::types::{AgentSession, AgentSessionCreateInput, AgentSessionUpdateInput};
+use crate::types::{
+    AgentSession, AgentSessionCreateInput, AgentSessionFinishInput, AgentSessionUpdateInput,
+};
 use crate::SqliteDb;
 use ralph_errors::{codes, ralph_err, RalphResultExt};
 
+/// What [`SqliteDb::reconcile_orphaned_agent_sessions`] recovered.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SessionRecovery {
+    /// Sessions moved from `running` to `crashed`.
+    pub crashed_session_ids: Vec<String>,
+    /// Tasks moved from `in_progress` back to `pending`.
+    pub requeued_task_ids: Vec<u32>,
+}
+
+impl SessionRecovery {
+    pub fn is_empty(&self) -> bool {
+        self.crashed_session_ids.is_empty() && self.requeued_task_ids.is_empty()
+    }
+}
+
 impl SqliteDb {
     pub fn create_human_agent_session(&self, input: AgentSessionCreateInput) -> Result<(), String> {
+        self.insert_agent_session(input, "human")
+    }
+
+    /// Create a session started by Ralph itself, e.g. an execution worker.
+    pub fn create_system_agent_session(
+        &self,
+        input: AgentSessionCreateInput,
+    ) -> Result<(), String> {
+        self.insert_agent_session(input, "system")
+    }
+
+    fn insert_agent_session(
+        &self,
+        input: AgentSessionCreateInput,
+        started_by: &str,
+    ) -> Result<(), String> {
         if input.id.trim().is_empty() {
             return ralph_err!(codes::TASK_VALIDATION, "Session id cannot be empty");
         }
@@ -21,7 +54,7 @@ impl SqliteDb {
             .execute(
                 "INSERT INTO agent_sessions (id, session_number, kind, started_by, task_id, agent, model, launch_command, \
                  post_start_preamble, init_prompt, started, status) \
-                 VALUES (?1, (SELECT COALESCE(MAX(session_number), 0) + 1 FROM agent_sessions), ?2, 'human', ?3, ?4, ?5, ?6, ?7, ?8, ?9, 'running')",
+                 VALUES (?1, (SELECT COALESCE(MAX(session_number), 0) + 1 FROM agent_sessions), ?2, ?10, ?3, ?4, ?5, ?6, ?7, ?8, ?9, 'running')",
                 rusqlite::params![
                     input.id,
                     input.kind,
@@ -32,9 +65,10 @@ impl SqliteDb {
                     input.post_start_preamble,
                     input.init_prompt,
                     now,
+                    started_by,
                 ],
             )
-            .ralph_err(codes::DB_WRITE, "Failed to create human agent session")?;
+            .ralph_err(codes::DB_WRITE, "Failed to create agent session")?;
 
         Ok(())
     }
@@ -103,6 +137,172 @@ impl SqliteDb {
         Ok(())
     }
 
+    /// Record the end of a running session (any `started_by`). Sessions that already ended are
+    /// left untouched so a late PTY exit cannot overwrite a reconciled or cancelled status.
+    pub fn finish_agent_session(&self, input: AgentSessionFinishInput) -> Result<bool, String> {
+        if input.id.trim().is_empty() {
+            return ralph_err!(codes::TASK_VALIDATION, "Session id cannot be empty");
+        }
+        if !matches!(
+            input.status.as_str(),
+            "finished" | "crashed" | "timed_out" | "cancelled"
+        ) {
+            return ralph_err!(
+                codes::TASK_VALIDATION,
+                "Invalid final session status '{}'",
+                input.status
+            );
+        }
+
+        let now = self.now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+        let output_bytes_i64 = input.output_bytes.map(i64::from);
+
+        let affected = self
+            .conn
+            .execute(
+                "UPDATE agent_sessions SET \
+                 status = ?1, ended = ?2, exit_code = ?3, \
+                 output_bytes = COALESCE(?4, output_bytes), \
+                 error_text = COALESCE(?5, error_text) \
+                 WHERE id = ?6 AND status = 'running'",
+                rusqlite::params![
+                    input.status,
+                    now,
+                    input.exit_code,
+                    output_bytes_i64,
+                    input.error_text,
+                    input.id,
+                ],
+            )
+            .ralph_err(codes::DB_WRITE, "Failed to finish agent session")?;
+
+        Ok(affected > 0)
+    }
+
+    /// Remember the OS process behind a running session (used for crash recovery).
+    pub fn set_agent_session_pid(&self, id: &str, pid: u32) -> Result<(), String> {
+        let affected = self
+            .conn
+            .execute(
+                "UPDATE agent_sessions SET pid = ?1 WHERE id = ?2 AND status = 'running'",
+                rusqlite::params![pid, id],
+            )
+            .ralph_err(codes::DB_WRITE, "Failed to record agent session pid")?;
+
+        if affected == 0 {
+            return ralph_err!(codes::TASK_OPS, "No running session '{}'", id);
+        }
+        Ok(())
+    }
+
+    /// Record the git worktree a session runs in, before the agent starts.
+    pub fn set_agent_session_worktree(
+        &self,
+        id: &str,
+        branch: &str,
+        path: &str,
+        base_commit: &str,
+    ) -> Result<(), String> {
+        let affected = self
+            .conn
+            .execute(
+                "UPDATE agent_sessions SET worktree_branch = ?1, worktree_path = ?2, \
+                 base_commit = ?3 WHERE id = ?4",
+                rusqlite::params![branch, path, base_commit, id],
+            )
+            .ralph_err(codes::DB_WRITE, "Failed to record session worktree")?;
+
+        if affected == 0 {
+            return ralph_err!(codes::TASK_OPS, "Session '{}' does not exist", id);
+        }
+        Ok(())
+    }
+
+    /// Record the worktree HEAD a session ended on.
+    pub fn set_agent_session_head_commit(&self, id: &str, head_commit: &str) -> Result<(), String> {
+        self.conn
+            .execute(
+                "UPDATE agent_sessions SET head_commit = ?1 WHERE id = ?2",
+                rusqlite::params![head_commit, id],
+            )
+            .ralph_err(codes::DB_WRITE, "Failed to record session head commit")?;
+        Ok(())
+    }
+
+    /// Most recent session for a task that ran in a worktree.
+    pub fn get_latest_task_worktree_session(&self, task_id: u32) -> Option<AgentSession> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT id, kind, started_by, task_id, agent, model, launch_command, \
+                 post_start_preamble, init_prompt, started, ended, exit_code, closing_verb, \
+                 status, prompt_hash, output_bytes, error_text, session_number, pid, \
+                 worktree_branch, worktree_path, base_commit, head_commit \
+                 FROM agent_sessions WHERE task_id = ?1 AND worktree_branch IS NOT NULL \
+                 ORDER BY session_number DESC LIMIT 1",
+            )
+            .ok()?;
+
+        stmt.query_row([task_id], Self::row_to_agent_session).ok()
+    }
+
+    /// Mark every `running` session that `is_live` rejects as `crashed`, and put tasks they
+    /// left `in_progress` back to `pending` unless another live session still holds them.
+    pub fn reconcile_orphaned_agent_sessions<F>(
+        &self,
+        is_live: F,
+        error_text: &str,
+    ) -> Result<SessionRecovery, String>
+    where
+        F: Fn(&AgentSession) -> bool,
+    {
+        self.with_transaction(|db| {
+            let (live, orphaned): (Vec<_>, Vec<_>) = db
+                .list_running_agent_sessions()?
+                .into_iter()
+                .partition(&is_live);
+
+            let now = db.now();
+            let ended = now.format("%Y-%m-%dT%H:%M:%SZ").to_string();
+            let updated = now.format("%Y-%m-%d").to_string();
+            let mut recovery = SessionRecovery::default();
+
+            for session in &orphaned {
+                db.conn
+                    .execute(
+                        "UPDATE agent_sessions SET status = 'crashed', ended = ?1, \
+                         error_text = COALESCE(error_text, ?2) \
+                         WHERE id = ?3 AND status = 'running'",
+                        rusqlite::params![ended, error_text, session.id],
+                    )
+                    .ralph_err(codes::DB_WRITE, "Failed to mark orphaned session crashed")?;
+                recovery.crashed_session_ids.push(session.id.clone());
+
+                let Some(task_id) = session.task_id else {
+                    continue;
+                };
+                if recovery.requeued_task_ids.contains(&task_id)
+                    || live.iter().any(|s| s.task_id == Some(task_id))
+                {
+                    continue;
+                }
+                let requeued = db
+                    .conn
+                    .execute(
+                        "UPDATE runtime_tasks SET status = 'pending', updated = ?1 \
+                         WHERE id = ?2 AND status = 'in_progress'",
+                        rusqlite::params![updated, task_id],
+                    )
+                    .ralph_err(codes::DB_WRITE, "Failed to requeue orphaned task")?;
+                if requeued > 0 {
+                    recovery.requeued_task_ids.push(task_id);
+                }
+            }
+
+            Ok(recovery)
+        })
+    }
+
     pub fn delete_human_agent_session(&self, id: &str) -> Result<(), String> {
         if id.trim().is_empty() {
             return ralph_err!(codes::TASK_VALIDATION, "Session id cannot be empty");
@@ -133,7 +333,8 @@ impl SqliteDb {
             .prepare(
                 "SELECT id, kind, started_by, task_id, agent, model, launch_command, \
                  post_start_preamble, init_prompt, started, ended, exit_code, closing_verb, \
-                 status, prompt_hash, output_bytes, error_text, session_number \
+                 status, prompt_hash, output_bytes, error_text, session_number, pid, \
+                 worktree_branch, worktree_path, base_commit, head_commit \
                  FROM agent_sessions WHERE id = ?1",
             )
             .ok()?;
@@ -145,7 +346,8 @@ impl SqliteDb {
         let Ok(mut stmt) = self.conn.prepare(
             "SELECT id, kind, started_by, task_id, agent, model, launch_command, \
                  post_start_preamble, init_prompt, started, ended, exit_code, closing_verb, \
-                 status, prompt_hash, output_bytes, error_text, session_number \
+                 status, prompt_hash, output_bytes, error_text, session_number, pid, \
+                 worktree_branch, worktree_path, base_commit, head_commit \
                  FROM agent_sessions WHERE started_by = 'human' ORDER BY session_number DESC",
         ) else {
             return vec![];
@@ -157,6 +359,24 @@ impl SqliteDb {
         )
     }
 
+    pub(crate) fn list_running_agent_sessions(&self) -> Result<Vec<AgentSession>, String> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT id, kind, started_by, task_id, agent, model, launch_command, \
+                 post_start_preamble, init_prompt, started, ended, exit_code, closing_verb, \
+                 status, prompt_hash, output_bytes, error_text, session_number, pid, \
+                 worktree_branch, worktree_path, base_commit, head_commit \
+                 FROM agent_sessions WHERE status = 'running' ORDER BY session_number",
+            )
+            .ralph_err(codes::DB_READ, "Failed to prepare running sessions query")?;
+
+        stmt.query_map([], Self::row_to_agent_session)
+            .ralph_err(codes::DB_READ, "Failed to query running sessions")?
+            .collect::<Result<Vec<_>, _>>()
+            .ralph_err(codes::DB_READ, "Failed to read running sessions")
+    }
+
     fn row_to_agent_session(row: &rusqlite::Row<'_>) -> rusqlite::Result<AgentSession> {
         let output_bytes_i64: Option<i64> = row.get(15)?;
         let session_number_i64: i64 = row.get(17)?;
@@ -179,6 +399,11 @@ impl SqliteDb {
             prompt_hash: row.get(14)?,
             output_bytes: output_bytes_i64.map(|v| v as u32),
             error_text: row.get(16)?,
+            pid: row.get(18)?,
+            worktree_branch: row.get(19)?,
+            worktree_path: row.get(20)?,
+            base_commit: row.get(21)?,
+            head_commit: row.get(22)?,
         })
     }
 }
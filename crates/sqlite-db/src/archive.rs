@@ -0,0 +1,120 @@
+//! Archiving: subsystems and tasks that are done with but worth keeping.
+//!
+//! Archived entities stay in the database, so their history, comments and embeddings still
+//! serve search and RAG, but listings, prompt listings and execution runs leave them out.
+//! A task is archived through `runtime_tasks.archived_at`, a subsystem through its `status`;
+//! archiving a subsystem hides its tasks without touching their own archived state.
+
+use crate::types::{Subsystem, SubsystemStatus, Task, TaskStatus};
+use crate::SqliteDb;
+use ralph_errors::{codes, ralph_err, RalphResultExt};
+use rusqlite::OptionalExtension;
+use std::collections::HashSet;
+
+impl SqliteDb {
+    /// Archive or unarchive task `id`. A task that is in progress can't be archived.
+    pub fn set_task_archived(&self, id: u32, archived: bool) -> Result<(), String> {
+        let status: Option<String> = self
+            .conn
+            .query_row(
+                "SELECT status FROM runtime_tasks WHERE id = ?1",
+                [id],
+                |row| row.get(0),
+            )
+            .optional()
+            .ralph_err(codes::DB_READ, "Failed to query task")?;
+        let Some(status) = status else {
+            return ralph_err!(codes::TASK_OPS, "Task {id} does not exist");
+        };
+        if archived && status == TaskStatus::InProgress.as_str() {
+            return ralph_err!(
+                codes::TASK_OPS,
+                "Task {id} is in progress and can't be archived"
+            );
+        }
+
+        let now = self.now().format("%Y-%m-%d").to_string();
+        // Archiving an archived task keeps the date it was first archived.
+        self.conn
+            .execute(
+                "UPDATE runtime_tasks SET archived_at = ?1, updated = ?2 \
+                 WHERE id = ?3 AND (archived_at IS NULL) = ?4",
+                rusqlite::params![archived.then_some(&now), now, id, archived],
+            )
+            .ralph_err(codes::DB_WRITE, "Failed to archive task")?;
+        Ok(())
+    }
+
+    /// Archive or unarchive subsystem `name`. A subsystem with a task in progress can't be
+    /// archived.
+    pub fn set_subsystem_archived(&self, name: &str, archived: bool) -> Result<(), String> {
+        let subsystem_id = self.get_id_from_name("subsystems", name)?;
+        if archived {
+            let running: Option<u32> = self
+                .conn
+                .query_row(
+                    "SELECT id FROM runtime_tasks WHERE subsystem_id = ?1 AND status = ?2 LIMIT 1",
+                    rusqlite::params![subsystem_id, TaskStatus::InProgress.as_str()],
+                    |row| row.get(0),
+                )
+                .optional()
+                .ralph_err(codes::DB_READ, "Failed to query tasks")?;
+            if let Some(task_id) = running {
+                return ralph_err!(
+                    codes::FEATURE_OPS,
+                    "Cannot archive subsystem '{name}': task {task_id} is in progress"
+                );
+            }
+        }
+
+        let status = if archived {
+            SubsystemStatus::Archived
+        } else {
+            SubsystemStatus::Active
+        };
+        self.conn
+            .execute(
+                "UPDATE subsystems SET status = ?1 WHERE id = ?2",
+                rusqlite::params![status.as_str(), subsystem_id],
+            )
+            .ralph_err(codes::DB_WRITE, "Failed to archive subsystem")?;
+        Ok(())
+    }
+
+    /// Ids of the tasks archiving hides: archived themselves or in an archived subsystem.
+    pub fn archived_task_ids(&self) -> Result<HashSet<u32>, String> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT t.id FROM runtime_tasks t \
+                 JOIN subsystems s ON t.subsystem_id = s.id \
+                 WHERE t.archived_at IS NOT NULL OR s.status = ?1",
+            )
+            .ralph_err(codes::DB_READ, "Failed to prepare archived tasks query")?;
+        let ids = stmt
+            .query_map([SubsystemStatus::Archived.as_str()], |row| row.get(0))
+            .ralph_err(codes::DB_READ, "Failed to query archived tasks")?
+            .collect::<Result<HashSet<u32>, _>>()
+            .ralph_err(codes::DB_READ, "Failed to read archived tasks")?;
+        Ok(ids)
+    }
+
+    /// All tasks, leaving out archived ones unless `show_archived`.
+    pub fn list_tasks(&self, show_archived: bool) -> Result<Vec<Task>, String> {
+        let mut tasks = self.get_tasks();
+        if !show_archived {
+            let archived = self.archived_task_ids()?;
+            tasks.retain(|task| !archived.contains(&task.id));
+        }
+        Ok(tasks)
+    }
+
+    /// All subsystems, leaving out archived ones unless `show_archived`.
+    pub fn list_subsystems(&self, show_archived: bool) -> Vec<Subsystem> {
+        let mut subsystems = self.get_subsystems();
+        if !show_archived {
+            subsystems.retain(|subsystem| subsystem.status != SubsystemStatus::Archived);
+        }
+        subsystems
+    }
+}
@@ -0,0 +1,188 @@
+//! Snapshots of the project database in `.ralph/db/backups/`, and restoring from them.
+//!
+//! Backups are written with SQLite's online backup API, so they are consistent even while
+//! agents write to the database. A backup's reason and time are in its file name
+//! (`ralph-20260101T120000000Z-execution.db`); only the newest `BACKUP_RETENTION` are kept.
+
+use crate::types::{BackupReason, DbBackup};
+use crate::SqliteDb;
+use chrono::{DateTime, Duration, NaiveDateTime, Utc};
+use ralph_errors::{codes, ralph_err, RalphResultExt};
+use rusqlite::backup::Backup;
+use rusqlite::{Connection, DatabaseName, OpenFlags};
+use std::path::{Path, PathBuf};
+
+pub const BACKUPS_DIR: &str = "backups";
+
+/// Backups kept per project; the oldest are deleted as new ones are taken.
+pub const BACKUP_RETENTION: usize = 20;
+
+const NAME_PREFIX: &str = "ralph-";
+const NAME_SUFFIX: &str = ".db";
+const STAMP_FORMAT: &str = "%Y%m%dT%H%M%S%3fZ";
+
+/// Where backups of the database at `db_path` are kept.
+pub fn backups_dir(db_path: &Path) -> PathBuf {
+    db_path
+        .parent()
+        .unwrap_or_else(|| Path::new("."))
+        .join(BACKUPS_DIR)
+}
+
+/// `(time, reason)` of a backup file name, or `None` for any other file.
+fn parse_name(name: &str) -> Option<(DateTime<Utc>, BackupReason)> {
+    let (stamp, reason) = name
+        .strip_prefix(NAME_PREFIX)?
+        .strip_suffix(NAME_SUFFIX)?
+        .split_once('-')?;
+    let time = NaiveDateTime::parse_from_str(stamp, STAMP_FORMAT)
+        .ok()?
+        .and_utc();
+    Some((time, BackupReason::parse(reason)?))
+}
+
+fn backup_names(dir: &Path) -> Result<Vec<String>, String> {
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut names: Vec<String> = std::fs::read_dir(dir)
+        .ralph_err(codes::FILESYSTEM, "Failed to read backups directory")?
+        .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+        .filter(|name| parse_name(name).is_some())
+        .collect();
+    // The timestamp sorts lexically, so this is newest first.
+    names.sort_unstable_by(|a, b| b.cmp(a));
+    Ok(names)
+}
+
+fn task_count(path: &Path) -> Result<u32, String> {
+    let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .ralph_err(codes::DB_OPEN, "Failed to open backup")?;
+    conn.query_row("SELECT COUNT(*) FROM runtime_tasks", [], |row| row.get(0))
+        .ralph_err(codes::DB_READ, "Failed to count tasks in backup")
+}
+
+fn describe(dir: &Path, name: String) -> Option<DbBackup> {
+    let (time, reason) = parse_name(&name)?;
+    let path = dir.join(&name);
+    Some(DbBackup {
+        reason,
+        created: time.format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+        size_bytes: std::fs::metadata(&path).map_or(0, |meta| meta.len()),
+        task_count: task_count(&path).ok(),
+        name,
+    })
+}
+
+/// Copy the database `conn` has open into a new backup file. Each backup is stamped later
+/// than the newest existing one, so names sort in the order they were taken even when the
+/// clock doesn't move between them.
+pub(crate) fn write_backup(
+    conn: &Connection,
+    db_path: &Path,
+    reason: BackupReason,
+    now: DateTime<Utc>,
+) -> Result<DbBackup, String> {
+    let dir = backups_dir(db_path);
+    std::fs::create_dir_all(&dir)
+        .ralph_err(codes::FILESYSTEM, "Failed to create backups directory")?;
+    // Backups are local history; keep them out of a repo that tracks `.ralph/`.
+    let gitignore = dir.join(".gitignore");
+    if !gitignore.exists() {
+        std::fs::write(&gitignore, "*\n")
+            .ralph_err(codes::FILESYSTEM, "Failed to write backups .gitignore")?;
+    }
+    let time = backup_names(&dir)?
+        .first()
+        .and_then(|newest| parse_name(newest))
+        .map_or(now, |(newest, _)| {
+            now.max(newest + Duration::milliseconds(1))
+        });
+    let name = format!(
+        "{NAME_PREFIX}{}-{}{NAME_SUFFIX}",
+        time.format(STAMP_FORMAT),
+        reason.as_str()
+    );
+
+    let mut target =
+        Connection::open(dir.join(&name)).ralph_err(codes::DB_WRITE, "Failed to create backup")?;
+    Backup::new(conn, &mut target)
+        .and_then(|backup| backup.run_to_completion(256, std::time::Duration::ZERO, None))
+        .ralph_err(codes::DB_WRITE, "Failed to back up database")?;
+    // A backup is a plain file; in WAL mode it couldn't be opened read-only to list it.
+    target
+        .execute_batch("PRAGMA journal_mode = DELETE;")
+        .ralph_err(codes::DB_WRITE, "Failed to finish backup")?;
+    drop(target);
+
+    describe(&dir, name)
+        .ok_or_else(|| ralph_errors::err_string(codes::INTERNAL, "Backup name did not parse"))
+}
+
+/// Delete all but the newest `BACKUP_RETENTION` backups.
+pub(crate) fn prune_backups(db_path: &Path) -> Result<(), String> {
+    let dir = backups_dir(db_path);
+    for name in backup_names(&dir)?.iter().skip(BACKUP_RETENTION) {
+        std::fs::remove_file(dir.join(name))
+            .ralph_err(codes::FILESYSTEM, "Failed to remove old backup")?;
+    }
+    Ok(())
+}
+
+impl SqliteDb {
+    /// Take a backup now. Fails for in-memory databases.
+    pub fn create_backup(&self, reason: BackupReason) -> Result<DbBackup, String> {
+        let db_path = self.file_path()?;
+        let backup = write_backup(&self.conn, &db_path, reason, self.now())?;
+        prune_backups(&db_path)?;
+        Ok(backup)
+    }
+
+    /// Backups of this database, newest first.
+    pub fn list_backups(&self) -> Result<Vec<DbBackup>, String> {
+        let dir = backups_dir(&self.file_path()?);
+        Ok(backup_names(&dir)?
+            .into_iter()
+            .filter_map(|name| describe(&dir, name))
+            .collect())
+    }
+
+    /// Replace the whole database with backup `name`. The state it replaces is backed up
+    /// first, and that backup is returned. Anything that caches database contents should
+    /// reload afterwards.
+    pub fn restore_backup(&mut self, name: &str) -> Result<DbBackup, String> {
+        let db_path = self.file_path()?;
+        let source = backups_dir(&db_path).join(name);
+        if parse_name(name).is_none() || !source.is_file() {
+            return ralph_err!(codes::DB_OPEN, "Backup {name} not found");
+        }
+        task_count(&source).map_err(|e| {
+            ralph_errors::err_string(
+                codes::DB_OPEN,
+                format!("Backup {name} is not a project database: {e}"),
+            )
+        })?;
+
+        let replaced = write_backup(&self.conn, &db_path, BackupReason::Restore, self.now())?;
+        self.conn
+            .restore(
+                DatabaseName::Main,
+                &source,
+                None::<fn(rusqlite::backup::Progress)>,
+            )
+            .ralph_err(codes::DB_WRITE, "Failed to restore backup")?;
+        // Pruned only now, so the backup being restored can't be the one deleted.
+        prune_backups(&db_path)?;
+        Ok(replaced)
+    }
+
+    fn file_path(&self) -> Result<PathBuf, String> {
+        self.conn
+            .path()
+            .filter(|path| !path.is_empty())
+            .map(PathBuf::from)
+            .ok_or_else(|| {
+                ralph_errors::err_string(codes::DB_OPEN, "In-memory databases have no backups")
+            })
+    }
+}
@@ -173,6 +173,22 @@ impl SqliteDb {
         Ok(())
     }
 
+    /// Forget a discipline's portrait and its crops, e.g. when the image file is gone.
+    /// The image prompt is kept so the portrait can be generated again.
+    pub fn clear_discipline_image(&self, name: &str) -> Result<(), String> {
+        let affected = self
+            .conn
+            .execute(
+                "UPDATE disciplines SET image_path = NULL, crops = NULL WHERE name = ?1",
+                [name],
+            )
+            .ralph_err(codes::DB_WRITE, "Failed to clear discipline image")?;
+        if affected == 0 {
+            return ralph_err!(codes::DISCIPLINE_OPS, "Discipline '{name}' does not exist");
+        }
+        Ok(())
+    }
+
     pub fn delete_discipline(&self, name: String) -> Result<(), String> {
         let discipline_id = self.get_id_from_name("disciplines", &name)?;
 
@@ -0,0 +1,459 @@
+//! Undo/redo for human edits to tasks, subsystems and disciplines.
+//!
+//! An edit is recorded as before- and after-images of every row that belongs to the
+//! entity (a task with its tags, dependencies, signals and so on). Undoing writes the
+//! before-image back and redoing writes the after-image, but only while the rows still
+//! match the image the edit left behind; anything else changed them since, and replaying
+//! over it would lose that change, so it fails with `EDIT_CONFLICT` instead.
+//!
+//! The journal lives in memory, so it covers one project session.
+
+use crate::types::EditHistory;
+use crate::SqliteDb;
+use ralph_errors::{codes, ralph_err, RalphResultExt};
+use rusqlite::types::Value;
+use rusqlite::OptionalExtension;
+
+/// Edits kept for undo; the oldest are forgotten first.
+pub const EDIT_JOURNAL_LIMIT: usize = 100;
+
+/// The entity an edit changes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EditTarget {
+    Task(u32),
+    Subsystem(String),
+    Discipline(String),
+}
+
+/// Rows that belong to the entity, selected by `filter` with the entity id as `?1`.
+struct Owned {
+    table: &'static str,
+    key: &'static str,
+    filter: &'static str,
+    /// Whether rows missing from the target image are deleted. Task details outlive their
+    /// task (`delete_task` leaves them), so they are only ever written.
+    prune: bool,
+}
+
+/// Rows of other entities that point at this one and are set to NULL when it is deleted.
+struct Link {
+    table: &'static str,
+    key: &'static str,
+    column: &'static str,
+}
+
+/// What an entity consists of, parents before children.
+struct Footprint {
+    owned: &'static [Owned],
+    links: &'static [Link],
+}
+
+const fn owned(table: &'static str, key: &'static str, filter: &'static str) -> Owned {
+    Owned {
+        table,
+        key,
+        filter,
+        prune: true,
+    }
+}
+
+const TASK: Footprint = Footprint {
+    owned: &[
+        Owned {
+            table: "task_details",
+            key: "id",
+            filter: "id = (SELECT details_id FROM runtime_tasks WHERE id = ?1)",
+            prune: false,
+        },
+        owned("runtime_tasks", "id", "id = ?1"),
+        owned("task_tags", "id", "task_id = ?1"),
+        owned(
+            "task_dependencies",
+            "id",
+            "task_id = ?1 OR depends_on_task_id = ?1",
+        ),
+        owned("task_acceptance_criteria", "id", "task_id = ?1"),
+        owned("task_context_files", "id", "task_id = ?1"),
+        owned("task_output_artifacts", "id", "task_id = ?1"),
+        owned("task_signals", "id", "task_id = ?1"),
+        owned(
+            "task_signal_comments",
+            "id",
+            "signal_id IN (SELECT id FROM task_signals WHERE task_id = ?1)",
+        ),
+        owned("task_session_limits", "task_id", "task_id = ?1"),
+        owned("task_enrichment_proposals", "task_id", "task_id = ?1"),
+    ],
+    links: &[Link {
+        table: "agent_sessions",
+        key: "id",
+        column: "task_id",
+    }],
+};
+
+const SUBSYSTEM: Footprint = Footprint {
+    owned: &[
+        owned("subsystems", "id", "id = ?1"),
+        owned("subsystem_knowledge_paths", "id", "subsystem_id = ?1"),
+        owned("subsystem_context_files", "id", "subsystem_id = ?1"),
+        owned("subsystem_learnings", "id", "subsystem_id = ?1"),
+        owned(
+            "subsystem_dependencies",
+            "id",
+            "subsystem_id = ?1 OR depends_on_subsystem_id = ?1",
+        ),
+        owned("subsystem_comments", "id", "subsystem_id = ?1"),
+        owned(
+            "comment_embeddings",
+            "comment_id",
+            "comment_id IN (SELECT id FROM subsystem_comments WHERE subsystem_id = ?1)",
+        ),
+    ],
+    links: &[],
+};
+
+const DISCIPLINE: Footprint = Footprint {
+    owned: &[
+        owned("disciplines", "id", "id = ?1"),
+        owned("discipline_skills", "id", "discipline_id = ?1"),
+        owned("discipline_mcp_servers", "id", "discipline_id = ?1"),
+        owned(
+            "discipline_mcp_server_args",
+            "id",
+            "server_id IN (SELECT id FROM discipline_mcp_servers WHERE discipline_id = ?1)",
+        ),
+        owned(
+            "discipline_mcp_server_env",
+            "id",
+            "server_id IN (SELECT id FROM discipline_mcp_servers WHERE discipline_id = ?1)",
+        ),
+        owned(
+            "discipline_session_limits",
+            "discipline_id",
+            "discipline_id = ?1",
+        ),
+        owned(
+            "discipline_review_settings",
+            "discipline_id",
+            "discipline_id = ?1",
+        ),
+    ],
+    links: &[Link {
+        table: "subsystem_comments",
+        key: "id",
+        column: "discipline_id",
+    }],
+};
+
+#[derive(Debug, Clone, PartialEq)]
+struct Rows {
+    columns: Vec<String>,
+    rows: Vec<Vec<Value>>,
+}
+
+impl Rows {
+    fn key_index(&self, key: &str) -> usize {
+        self.columns.iter().position(|c| c == key).unwrap_or(0)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Image {
+    /// One entry per `Footprint::owned`, rows ordered by key.
+    owned: Vec<Rows>,
+    /// One entry per `Footprint::links`: keys of the rows pointing at the entity.
+    links: Vec<Vec<Value>>,
+}
+
+struct Edit {
+    label: String,
+    entity: String,
+    footprint: &'static Footprint,
+    id: i64,
+    before: Image,
+    after: Image,
+}
+
+#[derive(Default)]
+pub(crate) struct EditJournal {
+    undo: Vec<Edit>,
+    redo: Vec<Edit>,
+}
+
+enum Direction {
+    Undo,
+    Redo,
+}
+
+impl SqliteDb {
+    /// Run `f` as one undoable edit of `target`. Nothing is recorded if `f` fails or
+    /// leaves the entity as it was; a recorded edit clears the redo stack.
+    pub fn journaled<T, F>(&self, target: &EditTarget, label: &str, f: F) -> Result<T, String>
+    where
+        F: FnOnce(&Self) -> Result<T, String>,
+    {
+        let Some((footprint, id, entity)) = self.resolve_edit_target(target)? else {
+            // Nothing to capture; `f` reports the missing entity itself.
+            return f(self);
+        };
+        let before = self.capture(footprint, id)?;
+        let value = f(self)?;
+        let after = self.capture(footprint, id)?;
+        if before.owned != after.owned {
+            let mut journal = self.journal.borrow_mut();
+            journal.undo.push(Edit {
+                label: label.to_owned(),
+                entity,
+                footprint,
+                id,
+                before,
+                after,
+            });
+            if journal.undo.len() > EDIT_JOURNAL_LIMIT {
+                journal.undo.remove(0);
+            }
+            journal.redo.clear();
+        }
+        Ok(value)
+    }
+
+    /// Revert the most recent edit and return its label, or `None` if there is nothing to
+    /// undo.
+    pub fn undo_edit(&self) -> Result<Option<String>, String> {
+        self.replay(&Direction::Undo)
+    }
+
+    /// Re-apply the most recently undone edit and return its label, or `None` if there is
+    /// nothing to redo.
+    pub fn redo_edit(&self) -> Result<Option<String>, String> {
+        self.replay(&Direction::Redo)
+    }
+
+    pub fn edit_history(&self) -> EditHistory {
+        let journal = self.journal.borrow();
+        let labels = |edits: &[Edit]| edits.iter().rev().map(|e| e.label.clone()).collect();
+        EditHistory {
+            undo: labels(&journal.undo),
+            redo: labels(&journal.redo),
+        }
+    }
+
+    fn replay(&self, direction: &Direction) -> Result<Option<String>, String> {
+        let popped = {
+            let mut journal = self.journal.borrow_mut();
+            match direction {
+                Direction::Undo => journal.undo.pop(),
+                Direction::Redo => journal.redo.pop(),
+            }
+        };
+        let Some(mut edit) = popped else {
+            return Ok(None);
+        };
+        let (expected, target, verb) = match direction {
+            Direction::Undo => (&edit.after, &edit.before, "undone"),
+            Direction::Redo => (&edit.before, &edit.after, "redone"),
+        };
+
+        let result = self.with_transaction(|db| {
+            let current = db.capture(edit.footprint, edit.id)?;
+            if current.owned != expected.owned {
+                return ralph_err!(
+                    codes::EDIT_CONFLICT,
+                    "{} changed after \"{}\", so it can't be {verb}",
+                    edit.entity,
+                    edit.label
+                );
+            }
+            db.apply(edit.footprint, edit.id, &current, target)?;
+            // Timestamp triggers may touch rows as they are written; the next replay checks
+            // against what is actually there.
+            db.capture(edit.footprint, edit.id)
+        });
+
+        match result {
+            Ok(written) => {
+                match direction {
+                    Direction::Undo => edit.before = written,
+                    Direction::Redo => edit.after = written,
+                }
+                let label = edit.label.clone();
+                let mut journal = self.journal.borrow_mut();
+                match direction {
+                    Direction::Undo => journal.redo.push(edit),
+                    Direction::Redo => journal.undo.push(edit),
+                }
+                Ok(Some(label))
+            }
+            Err(err) => {
+                // A conflicting edit can never be replayed, so it is dropped; any other
+                // failure may be resolved (e.g. by deleting the tasks blocking a subsystem
+                // delete), so the edit stays where it was.
+                if !err.starts_with(&format!("[R-{}]", codes::EDIT_CONFLICT)) {
+                    let mut journal = self.journal.borrow_mut();
+                    match direction {
+                        Direction::Undo => journal.undo.push(edit),
+                        Direction::Redo => journal.redo.push(edit),
+                    }
+                }
+                Err(err)
+            }
+        }
+    }
+
+    fn resolve_edit_target(
+        &self,
+        target: &EditTarget,
+    ) -> Result<Option<(&'static Footprint, i64, String)>, String> {
+        let (footprint, table, name) = match target {
+            EditTarget::Task(id) => {
+                return Ok(self
+                    .conn
+                    .query_row("SELECT id FROM runtime_tasks WHERE id = ?1", [id], |row| {
+                        row.get(0)
+                    })
+                    .optional()
+                    .ralph_err(codes::DB_READ, "Failed to query task")?
+                    .map(|id| (&TASK, id, format!("Task #{id}"))));
+            }
+            EditTarget::Subsystem(name) => (&SUBSYSTEM, "subsystems", name),
+            EditTarget::Discipline(name) => (&DISCIPLINE, "disciplines", name),
+        };
+        let id = self
+            .conn
+            .query_row(
+                &format!("SELECT id FROM {table} WHERE name = ?1"),
+                [name],
+                |row| row.get(0),
+            )
+            .optional()
+            .ralph_err(codes::DB_READ, &format!("Failed to query {table}"))?;
+        let noun = if table == "subsystems" {
+            "Subsystem"
+        } else {
+            "Discipline"
+        };
+        Ok(id.map(|id| (footprint, id, format!("{noun} '{name}'"))))
+    }
+
+    fn capture(&self, footprint: &Footprint, id: i64) -> Result<Image, String> {
+        let mut owned = Vec::with_capacity(footprint.owned.len());
+        for spec in footprint.owned {
+            let mut stmt = self
+                .conn
+                .prepare(&format!(
+                    "SELECT * FROM {} WHERE {} ORDER BY {}",
+                    spec.table, spec.filter, spec.key
+                ))
+                .ralph_err(codes::DB_READ, "Failed to prepare edit capture")?;
+            let columns: Vec<String> = stmt.column_names().into_iter().map(String::from).collect();
+            let rows = stmt
+                .query_map([id], |row| {
+                    (0..columns.len())
+                        .map(|i| row.get::<_, Value>(i))
+                        .collect::<Result<Vec<_>, _>>()
+                })
+                .ralph_err(codes::DB_READ, "Failed to capture edit")?
+                .collect::<Result<Vec<_>, _>>()
+                .ralph_err(codes::DB_READ, "Failed to read captured row")?;
+            owned.push(Rows { columns, rows });
+        }
+
+        let mut links = Vec::with_capacity(footprint.links.len());
+        for link in footprint.links {
+            let mut stmt = self
+                .conn
+                .prepare(&format!(
+                    "SELECT {key} FROM {} WHERE {} = ?1 ORDER BY {key}",
+                    link.table,
+                    link.column,
+                    key = link.key
+                ))
+                .ralph_err(codes::DB_READ, "Failed to prepare edit capture")?;
+            let keys = stmt
+                .query_map([id], |row| row.get::<_, Value>(0))
+                .ralph_err(codes::DB_READ, "Failed to capture edit")?
+                .collect::<Result<Vec<_>, _>>()
+                .ralph_err(codes::DB_READ, "Failed to read captured row")?;
+            links.push(keys);
+        }
+
+        Ok(Image { owned, links })
+    }
+
+    /// Make the entity's rows match `target`. Rows it lacks are deleted children first,
+    /// then its rows are written parents first. Rows are upserted rather than replaced so
+    /// that rewriting a parent doesn't cascade to its children.
+    fn apply(
+        &self,
+        footprint: &Footprint,
+        id: i64,
+        current: &Image,
+        target: &Image,
+    ) -> Result<(), String> {
+        let tables = footprint
+            .owned
+            .iter()
+            .zip(&current.owned)
+            .zip(&target.owned);
+
+        for ((spec, now), want) in tables.clone().rev() {
+            if !spec.prune {
+                continue;
+            }
+            let key = now.key_index(spec.key);
+            let wanted: Vec<&Value> = want.rows.iter().map(|row| &row[key]).collect();
+            for row in &now.rows {
+                if !wanted.contains(&&row[key]) {
+                    self.conn
+                        .execute(
+                            &format!("DELETE FROM {} WHERE {} = ?1", spec.table, spec.key),
+                            [&row[key]],
+                        )
+                        .ralph_err(codes::DB_WRITE, "Failed to replay edit")?;
+                }
+            }
+        }
+
+        for ((spec, now), want) in tables {
+            let placeholders: Vec<String> =
+                (1..=want.columns.len()).map(|i| format!("?{i}")).collect();
+            let updates: Vec<String> = want
+                .columns
+                .iter()
+                .filter(|c| *c != spec.key)
+                .map(|c| format!("{c} = excluded.{c}"))
+                .collect();
+            let sql = format!(
+                "INSERT INTO {} ({}) VALUES ({}) ON CONFLICT({}) DO UPDATE SET {}",
+                spec.table,
+                want.columns.join(", "),
+                placeholders.join(", "),
+                spec.key,
+                updates.join(", ")
+            );
+            for row in want.rows.iter().filter(|row| !now.rows.contains(row)) {
+                self.conn
+                    .execute(&sql, rusqlite::params_from_iter(row))
+                    .ralph_err(codes::DB_WRITE, "Failed to replay edit")?;
+            }
+        }
+
+        // Deleting the entity nulled these references; only ever put them back.
+        for (link, keys) in footprint.links.iter().zip(&target.links) {
+            for key in keys {
+                self.conn
+                    .execute(
+                        &format!(
+                            "UPDATE {} SET {column} = ?1 WHERE {} = ?2 AND {column} IS NULL",
+                            link.table,
+                            link.key,
+                            column = link.column
+                        ),
+                        rusqlite::params![id, key],
+                    )
+                    .ralph_err(codes::DB_WRITE, "Failed to replay edit")?;
+            }
+        }
+        Ok(())
+    }
+}
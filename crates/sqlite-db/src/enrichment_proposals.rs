@@ -0,0 +1,142 @@
+use crate::types::{EnrichmentProposal, EnrichmentProposalInput};
+use crate::SqliteDb;
+use ralph_errors::{codes, ralph_err, RalphResultExt};
+
+impl SqliteDb {
+    /// Store a proposed enrichment for a draft task, replacing any earlier proposal for it.
+    pub fn save_enrichment_proposal(&self, input: EnrichmentProposalInput) -> Result<(), String> {
+        if input.pseudocode.trim().is_empty() {
+            return ralph_err!(codes::TASK_VALIDATION, "Pseudocode cannot be empty");
+        }
+        let status: String = self
+            .conn
+            .query_row(
+                "SELECT status FROM runtime_tasks WHERE id = ?1",
+                [input.task_id],
+                |row| row.get(0),
+            )
+            .ralph_err(codes::DB_READ, "Failed to read task")?;
+        if status != "draft" {
+            return ralph_err!(
+                codes::TASK_OPS,
+                "Task {} is not in draft status (current: {status})",
+                input.task_id
+            );
+        }
+
+        let criteria_json = serde_json::to_string(&input.acceptance_criteria)
+            .ralph_err(codes::DB_WRITE, "JSON error")?;
+        let files_json =
+            serde_json::to_string(&input.context_files).ralph_err(codes::DB_WRITE, "JSON error")?;
+        let now = self.now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+
+        self.conn
+            .execute(
+                "INSERT INTO task_enrichment_proposals \
+                 (task_id, pseudocode, acceptance_criteria, context_files, model, created) \
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6) \
+                 ON CONFLICT(task_id) DO UPDATE SET \
+                 pseudocode = excluded.pseudocode, \
+                 acceptance_criteria = excluded.acceptance_criteria, \
+                 context_files = excluded.context_files, model = excluded.model, \
+                 created = excluded.created",
+                rusqlite::params![
+                    input.task_id,
+                    input.pseudocode,
+                    criteria_json,
+                    files_json,
+                    input.model,
+                    now,
+                ],
+            )
+            .ralph_err(codes::DB_WRITE, "Failed to save enrichment proposal")?;
+        Ok(())
+    }
+
+    /// Proposals waiting for approval, oldest task first.
+    pub fn get_enrichment_proposals(&self) -> Result<Vec<EnrichmentProposal>, String> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT task_id, pseudocode, acceptance_criteria, context_files, model, created \
+                 FROM task_enrichment_proposals ORDER BY task_id",
+            )
+            .ralph_err(
+                codes::DB_READ,
+                "Failed to prepare enrichment proposal query",
+            )?;
+
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, u32>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, String>(3)?,
+                    row.get::<_, Option<String>>(4)?,
+                    row.get::<_, String>(5)?,
+                ))
+            })
+            .ralph_err(codes::DB_READ, "Failed to query enrichment proposals")?;
+
+        let mut proposals = Vec::new();
+        for row in rows {
+            let (task_id, pseudocode, criteria_json, files_json, model, created) =
+                row.ralph_err(codes::DB_READ, "Failed to read enrichment proposal")?;
+            proposals.push(EnrichmentProposal {
+                task_id,
+                pseudocode,
+                acceptance_criteria: serde_json::from_str(&criteria_json)
+                    .ralph_err(codes::DB_READ, "Failed to parse acceptance criteria")?,
+                context_files: serde_json::from_str(&files_json)
+                    .ralph_err(codes::DB_READ, "Failed to parse context files")?,
+                model,
+                created,
+            });
+        }
+        Ok(proposals)
+    }
+
+    /// Apply a task's proposal with `enrich_task` (promoting it to `pending`) and drop it.
+    pub fn approve_enrichment_proposal(&self, task_id: u32) -> Result<(), String> {
+        self.with_transaction(|db| db.apply_enrichment_proposal(task_id))
+    }
+
+    /// Approve several proposals at once. Either every proposal is applied or none is.
+    pub fn approve_enrichment_proposals(&self, task_ids: &[u32]) -> Result<(), String> {
+        self.with_transaction(|db| {
+            task_ids
+                .iter()
+                .try_for_each(|task_id| db.apply_enrichment_proposal(*task_id))
+        })
+    }
+
+    fn apply_enrichment_proposal(&self, task_id: u32) -> Result<(), String> {
+        let proposal = self
+            .get_enrichment_proposals()?
+            .into_iter()
+            .find(|proposal| proposal.task_id == task_id);
+        let Some(proposal) = proposal else {
+            return ralph_err!(codes::TASK_OPS, "Task {task_id} has no enrichment proposal");
+        };
+
+        self.enrich_task(
+            task_id,
+            &proposal.pseudocode,
+            Some(proposal.acceptance_criteria),
+            Some(proposal.context_files),
+        )?;
+        self.discard_enrichment_proposal(task_id)
+    }
+
+    /// Drop a task's proposal without touching the task. Missing proposals are not an error.
+    pub fn discard_enrichment_proposal(&self, task_id: u32) -> Result<(), String> {
+        self.conn
+            .execute(
+                "DELETE FROM task_enrichment_proposals WHERE task_id = ?1",
+                [task_id],
+            )
+            .ralph_err(codes::DB_WRITE, "Failed to discard enrichment proposal")?;
+        Ok(())
+    }
+}
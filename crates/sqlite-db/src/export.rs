@@ -1,13 +1,24 @@
 use crate::SqliteDb;
 
 /// Escape a string for safe inclusion in double-quoted YAML values.
-/// Handles: backslashes, double quotes, newlines, tabs.
+/// Handles: backslashes, double quotes, newlines, tabs, and as `\uXXXX` any other control
+/// character or Unicode line break, which YAML would reject or fold into a space.
 fn yaml_escape(s: &str) -> String {
-    s.replace('\\', "\\\\")
-        .replace('"', "\\\"")
-        .replace('\n', "\\n")
-        .replace('\r', "\\r")
-        .replace('\t', "\\t")
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if c.is_control() || matches!(c, '\u{2028}' | '\u{2029}' | '\u{feff}') => {
+                escaped.push_str(&format!("\\u{:04x}", u32::from(c)));
+            }
+            c => escaped.push(c),
+        }
+    }
+    escaped
 }
 
 impl SqliteDb {
@@ -0,0 +1,680 @@
+use crate::types::{PrdImportMode, PrdImportReport, Priority, TaskProvenance, TaskStatus};
+use crate::SqliteDb;
+use ralph_errors::{codes, ralph_err, RalphResultExt};
+use rusqlite::OptionalExtension;
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+
+const SCHEMA_VERSION: &str = "1.0";
+const SIGNAL_AUTHORS: [&str; 3] = ["agent", "human", "system"];
+const SUBSYSTEM_COMMENT_CATEGORIES: [&str; 7] = [
+    "architecture",
+    "boundary",
+    "learning",
+    "convention",
+    "dependency",
+    "design-decision",
+    "gotcha",
+];
+
+// The shape `export_prd_yaml` writes. Unknown keys are rejected so a typo doesn't
+// silently drop data.
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PrdDocument {
+    schema_version: String,
+    project: PrdProject,
+    #[serde(default)]
+    subsystems: Vec<PrdSubsystem>,
+    #[serde(default)]
+    disciplines: Vec<PrdDiscipline>,
+    #[serde(default)]
+    tasks: Vec<PrdTask>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PrdProject {
+    title: String,
+    description: Option<String>,
+    created: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PrdSubsystem {
+    name: String,
+    display_name: String,
+    acronym: Option<String>,
+    description: Option<String>,
+    created: Option<String>,
+    #[serde(default)]
+    comments: Vec<PrdSubsystemComment>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PrdSubsystemComment {
+    category: String,
+    discipline: Option<String>,
+    body: String,
+    reason: Option<String>,
+    created: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PrdDiscipline {
+    name: String,
+    display_name: String,
+    acronym: Option<String>,
+    icon: String,
+    color: String,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PrdTask {
+    id: u32,
+    subsystem: String,
+    discipline: String,
+    title: String,
+    description: Option<String>,
+    status: TaskStatus,
+    priority: Option<Priority>,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    depends_on: Vec<u32>,
+    created: Option<String>,
+    updated: Option<String>,
+    completed: Option<String>,
+    #[serde(default)]
+    acceptance_criteria: Vec<String>,
+    #[serde(default)]
+    context_files: Vec<String>,
+    #[serde(default)]
+    output_artifacts: Vec<String>,
+    hints: Option<String>,
+    estimated_turns: Option<u32>,
+    provenance: Option<TaskProvenance>,
+    #[serde(default)]
+    comments: Vec<PrdTaskComment>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PrdTaskComment {
+    author: String,
+    body: String,
+    created: Option<String>,
+}
+
+/// Names are matched the way the `COLLATE NOCASE` columns match them.
+fn name_key(name: &str) -> String {
+    name.to_ascii_lowercase()
+}
+
+/// Why an entity's acronym can't be imported, if it can't. `seen` holds the acronyms
+/// already used by entities of the same kind in the document.
+fn acronym_problem(
+    kind: &str,
+    name: &str,
+    acronym: Option<&str>,
+    seen: &mut HashSet<String>,
+) -> Option<String> {
+    let Some(acronym) = acronym else {
+        return Some(format!("{kind} '{name}' has no acronym"));
+    };
+    if crate::acronym::validate_acronym_format(acronym).is_err() {
+        return Some(format!(
+            "{kind} '{name}' has an invalid acronym '{acronym}'"
+        ));
+    }
+    if !seen.insert(acronym.to_owned()) {
+        return Some(format!("{kind} '{name}' reuses acronym '{acronym}'"));
+    }
+    None
+}
+
+/// Depth-first walk for `has_dependency_cycle`; `state` is 1 while a task is on the
+/// current path and 2 once it is fully explored.
+fn reaches_cycle(id: u32, edges: &HashMap<u32, &[u32]>, state: &mut HashMap<u32, u8>) -> bool {
+    match state.get(&id) {
+        Some(1) => return true,
+        Some(_) => return false,
+        None => {}
+    }
+    state.insert(id, 1);
+    let cyclic = edges
+        .get(&id)
+        .is_some_and(|deps| deps.iter().any(|dep| reaches_cycle(*dep, edges, state)));
+    state.insert(id, 2);
+    cyclic
+}
+
+/// A clash between an entity's acronym and one the database keeps for an entity the
+/// document doesn't mention. `taken` maps acronyms in the database to their owners.
+fn acronym_conflict(
+    kind: &str,
+    name: &str,
+    acronym: Option<&str>,
+    taken: &HashMap<&str, &str>,
+    listed: &HashSet<String>,
+) -> Option<String> {
+    let acronym = acronym?;
+    let owner = *taken.get(acronym)?;
+    if name_key(owner) == name_key(name) || listed.contains(&name_key(owner)) {
+        return None;
+    }
+    Some(format!(
+        "{kind} '{name}' uses acronym '{acronym}', which belongs to {kind} '{owner}'"
+    ))
+}
+
+/// Whether the `depends_on` edges (task id → ids it depends on) contain a cycle.
+pub(crate) fn has_dependency_cycle(edges: &HashMap<u32, &[u32]>) -> bool {
+    let mut state = HashMap::new();
+    edges.keys().any(|id| reaches_cycle(*id, edges, &mut state))
+}
+
+impl SqliteDb {
+    /// Import a project from the YAML `export_prd_yaml` writes, in one savepoint.
+    /// References between tasks, subsystems and disciplines are checked before anything
+    /// is written; a dry run reports what would change and rolls everything back.
+    ///
+    /// Subsystem comments and task comments are only imported for subsystems and tasks
+    /// the import creates, so re-importing an export never duplicates them. Task comments
+    /// come back as notes, since the export doesn't carry signal verbs.
+    pub fn import_prd_yaml(
+        &self,
+        yaml: &str,
+        mode: PrdImportMode,
+        dry_run: bool,
+    ) -> Result<PrdImportReport, String> {
+        let doc: PrdDocument = serde_yaml::from_str(yaml).map_err(|e| {
+            ralph_errors::err_string(codes::TASK_VALIDATION, format!("Invalid PRD YAML: {e}"))
+        })?;
+        self.validate_prd_document(&doc, mode)?;
+
+        self.conn
+            .execute_batch("SAVEPOINT prd_import;")
+            .ralph_err(codes::DB_WRITE, "Failed to start import")?;
+        let result = self.apply_prd_document(&doc, mode);
+        let finish = if result.is_ok() && !dry_run {
+            "RELEASE prd_import;"
+        } else {
+            "ROLLBACK TO prd_import; RELEASE prd_import;"
+        };
+        self.conn
+            .execute_batch(finish)
+            .ralph_err(codes::DB_WRITE, "Failed to finish import")?;
+        result
+    }
+
+    fn validate_prd_document(&self, doc: &PrdDocument, mode: PrdImportMode) -> Result<(), String> {
+        let mut problems = Vec::new();
+        if doc.schema_version != SCHEMA_VERSION {
+            problems.push(format!(
+                "unsupported schema_version '{}' (expected '{SCHEMA_VERSION}')",
+                doc.schema_version
+            ));
+        }
+        if doc.project.title.trim().is_empty() {
+            problems.push("project title is empty".to_owned());
+        }
+
+        // Disciplines are never removed, so existing ones stay referenceable in both modes.
+        let existing = self.get_disciplines();
+        let mut disciplines: HashSet<String> = existing.iter().map(|d| name_key(&d.name)).collect();
+        let taken: HashMap<&str, &str> = existing
+            .iter()
+            .map(|d| (d.acronym.as_str(), d.name.as_str()))
+            .collect();
+        let listed: HashSet<String> = doc.disciplines.iter().map(|d| name_key(&d.name)).collect();
+        let mut seen = HashSet::new();
+        let mut acronyms = HashSet::new();
+        for discipline in &doc.disciplines {
+            if !seen.insert(name_key(&discipline.name)) {
+                problems.push(format!("discipline '{}' is listed twice", discipline.name));
+            }
+            problems.extend(acronym_problem(
+                "discipline",
+                &discipline.name,
+                discipline.acronym.as_deref(),
+                &mut acronyms,
+            ));
+            problems.extend(acronym_conflict(
+                "discipline",
+                &discipline.name,
+                discipline.acronym.as_deref(),
+                &taken,
+                &listed,
+            ));
+            disciplines.insert(name_key(&discipline.name));
+        }
+
+        let existing = match mode {
+            PrdImportMode::Merge => self.get_subsystems(),
+            PrdImportMode::Replace => Vec::new(),
+        };
+        let mut subsystems: HashSet<String> = existing.iter().map(|s| name_key(&s.name)).collect();
+        let taken: HashMap<&str, &str> = existing
+            .iter()
+            .map(|s| (s.acronym.as_str(), s.name.as_str()))
+            .collect();
+        let listed: HashSet<String> = doc.subsystems.iter().map(|s| name_key(&s.name)).collect();
+        let mut seen = HashSet::new();
+        let mut acronyms = HashSet::new();
+        for subsystem in &doc.subsystems {
+            if !seen.insert(name_key(&subsystem.name)) {
+                problems.push(format!("subsystem '{}' is listed twice", subsystem.name));
+            }
+            problems.extend(acronym_problem(
+                "subsystem",
+                &subsystem.name,
+                subsystem.acronym.as_deref(),
+                &mut acronyms,
+            ));
+            problems.extend(acronym_conflict(
+                "subsystem",
+                &subsystem.name,
+                subsystem.acronym.as_deref(),
+                &taken,
+                &listed,
+            ));
+            for comment in &subsystem.comments {
+                if !SUBSYSTEM_COMMENT_CATEGORIES.contains(&comment.category.as_str()) {
+                    problems.push(format!(
+                        "subsystem '{}' has a comment with unknown category '{}'",
+                        subsystem.name, comment.category
+                    ));
+                }
+                if let Some(discipline) = &comment.discipline {
+                    if !disciplines.contains(&name_key(discipline)) {
+                        problems.push(format!(
+                            "subsystem '{}' has a comment from unknown discipline '{discipline}'",
+                            subsystem.name
+                        ));
+                    }
+                }
+            }
+            subsystems.insert(name_key(&subsystem.name));
+        }
+
+        let existing = match mode {
+            PrdImportMode::Merge => self.get_tasks(),
+            PrdImportMode::Replace => Vec::new(),
+        };
+        let mut task_ids: HashSet<u32> = existing.iter().map(|t| t.id).collect();
+        let mut seen = HashSet::new();
+        for task in &doc.tasks {
+            if !seen.insert(task.id) {
+                problems.push(format!("task {} is listed twice", task.id));
+            }
+            task_ids.insert(task.id);
+        }
+        for task in &doc.tasks {
+            let id = task.id;
+            if task.title.trim().is_empty() {
+                problems.push(format!("task {id} has an empty title"));
+            }
+            if !subsystems.contains(&name_key(&task.subsystem)) {
+                problems.push(format!(
+                    "task {id} references unknown subsystem '{}'",
+                    task.subsystem
+                ));
+            }
+            if !disciplines.contains(&name_key(&task.discipline)) {
+                problems.push(format!(
+                    "task {id} references unknown discipline '{}'",
+                    task.discipline
+                ));
+            }
+            if task.status == TaskStatus::Done && task.completed.is_none() {
+                problems.push(format!("task {id} is done but has no completed date"));
+            }
+            for dep in &task.depends_on {
+                if *dep == id {
+                    problems.push(format!("task {id} depends on itself"));
+                } else if !task_ids.contains(dep) {
+                    problems.push(format!("task {id} depends on unknown task {dep}"));
+                }
+            }
+            for comment in &task.comments {
+                if !SIGNAL_AUTHORS.contains(&comment.author.as_str()) {
+                    problems.push(format!(
+                        "task {id} has a comment with unknown author '{}'",
+                        comment.author
+                    ));
+                }
+            }
+        }
+        // Tasks the document doesn't list keep their dependencies, so a cycle can run
+        // through them; listed tasks get the document's.
+        let edges = existing
+            .iter()
+            .map(|task| (task.id, task.depends_on.as_slice()))
+            .chain(
+                doc.tasks
+                    .iter()
+                    .map(|task| (task.id, task.depends_on.as_slice())),
+            )
+            .collect();
+        if has_dependency_cycle(&edges) {
+            problems.push("task dependencies contain a cycle".to_owned());
+        }
+
+        if problems.is_empty() {
+            return Ok(());
+        }
+        ralph_err!(
+            codes::TASK_VALIDATION,
+            "Invalid PRD import:\n- {}",
+            problems.join("\n- ")
+        )
+    }
+
+    fn apply_prd_document(
+        &self,
+        doc: &PrdDocument,
+        mode: PrdImportMode,
+    ) -> Result<PrdImportReport, String> {
+        let mut report = PrdImportReport::default();
+
+        if mode == PrdImportMode::Replace {
+            report.tasks_removed = self.count_rows("runtime_tasks")?;
+            report.subsystems_removed = self.count_rows("subsystems")?;
+            self.conn
+                .execute_batch(
+                    "DELETE FROM runtime_tasks; \
+                     DELETE FROM task_details WHERE id NOT IN (SELECT details_id FROM task_templates); \
+                     DELETE FROM subsystems;",
+                )
+                .ralph_err(codes::DB_WRITE, "Failed to clear project for import")?;
+        }
+
+        self.conn
+            .execute(
+                "INSERT OR REPLACE INTO metadata (id, project_title, project_description, project_created) \
+                 VALUES (1, ?1, ?2, ?3)",
+                rusqlite::params![doc.project.title, doc.project.description, doc.project.created],
+            )
+            .ralph_err(codes::DB_WRITE, "Failed to import project metadata")?;
+
+        for discipline in &doc.disciplines {
+            let affected = self
+                .conn
+                .execute(
+                    "UPDATE disciplines SET display_name = ?1, acronym = ?2, icon = ?3, color = ?4 \
+                     WHERE name = ?5",
+                    rusqlite::params![
+                        discipline.display_name,
+                        discipline.acronym,
+                        discipline.icon,
+                        discipline.color,
+                        discipline.name,
+                    ],
+                )
+                .ralph_err(codes::DB_WRITE, "Failed to import discipline")?;
+            if affected > 0 {
+                report.disciplines_updated += 1;
+                continue;
+            }
+            self.conn
+                .execute(
+                    "INSERT INTO disciplines (name, display_name, acronym, icon, color) \
+                     VALUES (?1, ?2, ?3, ?4, ?5)",
+                    rusqlite::params![
+                        discipline.name,
+                        discipline.display_name,
+                        discipline.acronym,
+                        discipline.icon,
+                        discipline.color,
+                    ],
+                )
+                .ralph_err(codes::DB_WRITE, "Failed to import discipline")?;
+            report.disciplines_created += 1;
+        }
+
+        for subsystem in &doc.subsystems {
+            let affected = self
+                .conn
+                .execute(
+                    "UPDATE subsystems SET display_name = ?1, acronym = ?2, description = ?3, \
+                     created = ?4 WHERE name = ?5",
+                    rusqlite::params![
+                        subsystem.display_name,
+                        subsystem.acronym,
+                        subsystem.description,
+                        subsystem.created,
+                        subsystem.name,
+                    ],
+                )
+                .ralph_err(codes::DB_WRITE, "Failed to import subsystem")?;
+            if affected > 0 {
+                report.subsystems_updated += 1;
+                continue;
+            }
+            self.conn
+                .execute(
+                    "INSERT INTO subsystems (name, display_name, acronym, description, created, status) \
+                     VALUES (?1, ?2, ?3, ?4, ?5, 'active')",
+                    rusqlite::params![
+                        subsystem.name,
+                        subsystem.display_name,
+                        subsystem.acronym,
+                        subsystem.description,
+                        subsystem.created,
+                    ],
+                )
+                .ralph_err(codes::DB_WRITE, "Failed to import subsystem")?;
+            let subsystem_id = self.conn.last_insert_rowid();
+            // The export lists comments newest first.
+            for comment in subsystem.comments.iter().rev() {
+                let discipline_id = comment
+                    .discipline
+                    .as_deref()
+                    .map(|name| self.get_id_from_name("disciplines", name))
+                    .transpose()?;
+                self.conn
+                    .execute(
+                        "INSERT INTO subsystem_comments \
+                         (subsystem_id, category, discipline_id, body, reason, created) \
+                         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                        rusqlite::params![
+                            subsystem_id,
+                            comment.category,
+                            discipline_id,
+                            comment.body,
+                            comment.reason,
+                            comment.created,
+                        ],
+                    )
+                    .ralph_err(codes::DB_WRITE, "Failed to import subsystem comment")?;
+            }
+            report.subsystems_created += 1;
+        }
+
+        for task in &doc.tasks {
+            if self.import_prd_task(task)? {
+                report.tasks_created += 1;
+            } else {
+                report.tasks_updated += 1;
+            }
+        }
+        // Dependencies go in once every task they can point at exists.
+        for task in &doc.tasks {
+            for dep in &task.depends_on {
+                self.conn
+                    .execute(
+                        "INSERT INTO task_dependencies (task_id, depends_on_task_id) VALUES (?1, ?2)",
+                        rusqlite::params![task.id, dep],
+                    )
+                    .ralph_err(codes::DB_WRITE, "Failed to import dependency")?;
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Write one task, keeping its id. Returns whether it was created.
+    fn import_prd_task(&self, task: &PrdTask) -> Result<bool, String> {
+        let id = task.id;
+        let subsystem_id = self.get_id_from_name("subsystems", &task.subsystem)?;
+        let discipline_id = self.get_id_from_name("disciplines", &task.discipline)?;
+        let priority = task.priority.map(|p| p.as_str().to_owned());
+        let provenance = task.provenance.map(|p| p.as_str().to_owned());
+        let existing_details: Option<i64> = self
+            .conn
+            .query_row(
+                "SELECT details_id FROM runtime_tasks WHERE id = ?1",
+                [id],
+                |row| row.get(0),
+            )
+            .optional()
+            .ralph_err(codes::DB_READ, "Failed to look up task")?;
+
+        let created = if let Some(details_id) = existing_details {
+            // Agent, model and pseudocode aren't exported, so they are left as they are.
+            self.conn
+                .execute(
+                    "UPDATE task_details SET discipline_id = ?1, title = ?2, description = ?3, \
+                     priority = ?4, hints = ?5, estimated_turns = ?6 WHERE id = ?7",
+                    rusqlite::params![
+                        discipline_id,
+                        task.title,
+                        task.description,
+                        priority,
+                        task.hints,
+                        task.estimated_turns,
+                        details_id,
+                    ],
+                )
+                .ralph_err(codes::DB_WRITE, "Failed to import task details")?;
+            // Only touch rows that change: the timestamp trigger would otherwise bump
+            // `updated` on every re-import of an unchanged export.
+            self.conn
+                .execute(
+                    "UPDATE runtime_tasks SET subsystem_id = ?1, status = ?2, provenance = ?3, \
+                     created = ?4, updated = ?5, completed = ?6 WHERE id = ?7 \
+                     AND (subsystem_id IS NOT ?1 OR status IS NOT ?2 OR provenance IS NOT ?3 \
+                     OR created IS NOT ?4 OR updated IS NOT ?5 OR completed IS NOT ?6)",
+                    rusqlite::params![
+                        subsystem_id,
+                        task.status.as_str(),
+                        provenance,
+                        task.created,
+                        task.updated,
+                        task.completed,
+                        id,
+                    ],
+                )
+                .ralph_err(codes::DB_WRITE, "Failed to import task")?;
+            for table in [
+                "task_tags",
+                "task_dependencies",
+                "task_acceptance_criteria",
+                "task_context_files",
+                "task_output_artifacts",
+            ] {
+                self.delete_task_related_rows(table, id)?;
+            }
+            false
+        } else {
+            self.conn
+                .execute(
+                    "INSERT INTO task_details (discipline_id, title, description, priority, hints, \
+                     estimated_turns, created) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                    rusqlite::params![
+                        discipline_id,
+                        task.title,
+                        task.description,
+                        priority,
+                        task.hints,
+                        task.estimated_turns,
+                        task.created,
+                    ],
+                )
+                .ralph_err(codes::DB_WRITE, "Failed to import task details")?;
+            let details_id = self.conn.last_insert_rowid();
+            self.conn
+                .execute(
+                    "INSERT INTO runtime_tasks \
+                     (id, subsystem_id, details_id, status, provenance, created, updated, completed) \
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                    rusqlite::params![
+                        id,
+                        subsystem_id,
+                        details_id,
+                        task.status.as_str(),
+                        provenance,
+                        task.created,
+                        task.updated,
+                        task.completed,
+                    ],
+                )
+                .ralph_err(codes::DB_WRITE, "Failed to import task")?;
+            self.import_prd_task_comments(task)?;
+            true
+        };
+
+        self.insert_string_list("task_tags", "task_id", i64::from(id), "tag", &task.tags)?;
+        for (idx, criterion) in task.acceptance_criteria.iter().enumerate() {
+            self.conn
+                .execute(
+                    "INSERT INTO task_acceptance_criteria (task_id, criterion, criterion_order) VALUES (?1, ?2, ?3)",
+                    rusqlite::params![id, criterion, i64::try_from(idx).unwrap_or(0)],
+                )
+                .ralph_err(codes::DB_WRITE, "Failed to import acceptance criterion")?;
+        }
+        self.insert_string_list(
+            "task_context_files",
+            "task_id",
+            i64::from(id),
+            "file_path",
+            &task.context_files,
+        )?;
+        self.insert_string_list(
+            "task_output_artifacts",
+            "task_id",
+            i64::from(id),
+            "artifact_path",
+            &task.output_artifacts,
+        )?;
+
+        Ok(created)
+    }
+
+    /// Task comments become notes in a finished session per author, so `author` survives.
+    fn import_prd_task_comments(&self, task: &PrdTask) -> Result<(), String> {
+        let now = self.now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+        // The export lists comments newest first.
+        for comment in task.comments.iter().rev() {
+            let session_id = format!("prd-import-task-{}-{}", task.id, comment.author);
+            self.ensure_agent_session_exists(&session_id, task.id, &comment.author, "manual")?;
+            self.conn
+                .execute(
+                    "INSERT INTO task_signals (task_id, session_id, verb, text, kind, scope, created) \
+                     VALUES (?1, ?2, 'learned', ?3, 'discovery', 'task', ?4)",
+                    rusqlite::params![
+                        task.id,
+                        session_id,
+                        comment.body,
+                        comment.created.as_deref().unwrap_or(&now),
+                    ],
+                )
+                .ralph_err(codes::DB_WRITE, "Failed to import task comment")?;
+        }
+        Ok(())
+    }
+
+    fn count_rows(&self, table: &str) -> Result<u32, String> {
+        self.conn
+            .query_row(&format!("SELECT COUNT(*) FROM {table}"), [], |row| {
+                row.get(0)
+            })
+            .ralph_err(codes::DB_READ, "Failed to count rows")
+    }
+}
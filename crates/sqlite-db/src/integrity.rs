@@ -0,0 +1,40 @@
+//! Consistency checks on the database file itself, for `project_doctor`.
+
+use crate::SqliteDb;
+use ralph_errors::{codes, RalphResultExt};
+
+impl SqliteDb {
+    /// Problems SQLite finds in the database: corruption reported by `integrity_check` and
+    /// rows whose foreign keys point nowhere. Empty when the database is sound.
+    pub fn check_integrity(&self) -> Result<Vec<String>, String> {
+        let mut stmt = self
+            .conn
+            .prepare("PRAGMA integrity_check")
+            .ralph_err(codes::DB_READ, "Failed to prepare integrity check")?;
+        let mut problems = stmt
+            .query_map([], |row| row.get::<_, String>(0))
+            .ralph_err(codes::DB_READ, "Failed to run integrity check")?
+            .collect::<Result<Vec<_>, _>>()
+            .ralph_err(codes::DB_READ, "Failed to read integrity check")?;
+        problems.retain(|problem| problem != "ok");
+
+        let mut stmt = self
+            .conn
+            .prepare("PRAGMA foreign_key_check")
+            .ralph_err(codes::DB_READ, "Failed to prepare foreign key check")?;
+        let dangling = stmt
+            .query_map([], |row| {
+                Ok(format!(
+                    "{} row {} references a missing {} row",
+                    row.get::<_, String>(0)?,
+                    row.get::<_, Option<i64>>(1)?.unwrap_or_default(),
+                    row.get::<_, String>(2)?
+                ))
+            })
+            .ralph_err(codes::DB_READ, "Failed to run foreign key check")?
+            .collect::<Result<Vec<_>, _>>()
+            .ralph_err(codes::DB_READ, "Failed to read foreign key check")?;
+        problems.extend(dangling);
+        Ok(problems)
+    }
+}
@@ -1,37 +1,60 @@
 pub mod acronym;
+mod activity;
+mod agent_session_prompts;
 mod agent_sessions;
+mod archive;
+mod backups;
 mod comment_embeddings;
 mod disciplines;
+mod edit_journal;
+mod enrichment_proposals;
 mod export;
 mod helpers;
+mod import;
+mod integrity;
 mod metadata;
+mod mirror;
 mod prompt_builder_configs;
+mod reports;
+mod review_settings;
+mod session_limits;
 mod signals;
 mod subsystem_comments;
 mod subsystems;
+mod task_templates;
 mod tasks;
 pub mod types;
 
 // Re-export public types
+pub use agent_sessions::SessionRecovery;
+pub use backups::{backups_dir, BACKUPS_DIR, BACKUP_RETENTION};
 pub use comment_embeddings::ScoredCommentRow;
+pub use edit_journal::{EditTarget, EDIT_JOURNAL_LIMIT};
+pub use mirror::MIRROR_DIRS;
 pub use prompt_builder_configs::{
     PromptBuilderConfigData, PromptBuilderConfigInput, SectionSettingsData,
 };
+pub use session_limits::{DEFAULT_SESSION_IDLE_SECS, DEFAULT_SESSION_WALL_CLOCK_SECS};
 pub use signals::{
     AskSignalInput, BlockedSignalInput, DoneSignalInput, FlagSignalInput, LearnedSignalInput,
     PartialSignalInput, StuckSignalInput, SuggestSignalInput,
 };
 pub use subsystem_comments::AddSubsystemCommentInput;
 pub use types::{
-    AgentSession, AgentSessionCreateInput, AgentSessionUpdateInput, Discipline, DisciplineInput,
-    McpServerConfig, Priority, ProjectMetadata, Subsystem, SubsystemComment, SubsystemInput,
-    SubsystemStatus, Task, TaskInput, TaskListItem, TaskProvenance, TaskSignal, TaskSignalComment,
-    TaskSignalCommentCreateInput, TaskSignalSummary, TaskStatus, TaskTemplate,
+    AgentSession, AgentSessionCreateInput, AgentSessionFinishInput, AgentSessionPrompt,
+    AgentSessionPromptInput, AgentSessionUpdateInput, BackupReason, DbBackup, Discipline,
+    DisciplineInput, EditHistory, EnrichmentProposal, EnrichmentProposalInput, McpServerConfig,
+    MirrorConflict, MirrorImportReport, PrdImportMode, PrdImportReport, Priority, ProjectActivity,
+    ProjectMetadata, ReportFormat, ReviewSettings, SessionLimits, Subsystem, SubsystemComment,
+    SubsystemInput, SubsystemStatus, Task, TaskInput, TaskListItem, TaskProvenance, TaskSignal,
+    TaskSignalComment, TaskSignalCommentCreateInput, TaskSignalSummary, TaskStatus, TaskTemplate,
+    TaskTemplateInput, TaskTemplateInstantiateInput,
 };
 
-use ralph_errors::{codes, RalphResultExt};
-use rusqlite::Connection;
+use ralph_errors::{codes, ralph_err, RalphResultExt};
+use rusqlite::{Connection, OpenFlags};
 use rusqlite_migration::{Migrations, M};
+use std::cell::RefCell;
 use std::path::Path;
 
 pub trait Clock: Send + Sync {
@@ -52,9 +75,26 @@ impl Clock for FixedClock {
     }
 }
 
+const MIGRATIONS: [&str; 9] = [
+    include_str!("migrations/001_initial.sql"),
+    include_str!("migrations/002_session_limits.sql"),
+    include_str!("migrations/003_agent_session_pid.sql"),
+    include_str!("migrations/004_agent_session_worktree.sql"),
+    include_str!("migrations/005_agent_session_prompts.sql"),
+    include_str!("migrations/006_discipline_review_settings.sql"),
+    include_str!("migrations/007_task_enrichment_proposals.sql"),
+    include_str!("migrations/008_archived_tasks.sql"),
+    include_str!("migrations/009_mirror_base.sql"),
+];
+
+fn migrations() -> Migrations<'static> {
+    Migrations::new(MIGRATIONS.into_iter().map(M::up).collect())
+}
+
 pub struct SqliteDb {
     conn: Connection,
     clock: Box<dyn Clock>,
+    journal: RefCell<edit_journal::EditJournal>,
 }
 
 impl SqliteDb {
@@ -69,15 +109,49 @@ impl SqliteDb {
         )
         .ralph_err(codes::DB_OPEN, "Failed to set PRAGMAs")?;
 
-        let migrations = Migrations::new(vec![M::up(include_str!("migrations/001_initial.sql"))]);
+        let clock = clock.unwrap_or_else(|| Box::new(RealClock));
+        // A new database has nothing to lose; an existing one is backed up before it changes.
+        let version: usize = conn
+            .query_row("PRAGMA user_version", [], |row| row.get(0))
+            .ralph_err(codes::DB_OPEN, "Failed to read schema version")?;
+        if (1..MIGRATIONS.len()).contains(&version) {
+            backups::write_backup(&conn, path, BackupReason::Migration, clock.now())?;
+            backups::prune_backups(path)?;
+        }
 
-        migrations
+        migrations()
             .to_latest(&mut conn)
             .ralph_err(codes::DB_OPEN, "Failed to run migrations")?;
 
         Ok(Self {
             conn,
-            clock: clock.unwrap_or_else(|| Box::new(RealClock)),
+            clock,
+            journal: RefCell::default(),
+        })
+    }
+
+    /// Open a database only to read it, e.g. a project other than the locked one. Nothing is
+    /// written: no migrations, no backups and no change of journal mode. A database on another
+    /// schema version is refused rather than read with the wrong queries.
+    pub fn open_read_only(path: &Path) -> Result<Self, String> {
+        let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+            .ralph_err(codes::DB_OPEN, "Failed to open database")?;
+
+        let version: usize = conn
+            .query_row("PRAGMA user_version", [], |row| row.get(0))
+            .ralph_err(codes::DB_OPEN, "Failed to read schema version")?;
+        if version != MIGRATIONS.len() {
+            return ralph_err!(
+                codes::DB_OPEN,
+                "Database is at schema version {version}, expected {}; open the project to migrate it",
+                MIGRATIONS.len()
+            );
+        }
+
+        Ok(Self {
+            conn,
+            clock: Box::new(RealClock),
+            journal: RefCell::default(),
         })
     }
 
@@ -85,10 +159,15 @@ impl SqliteDb {
         self.clock.now()
     }
 
+    /// Run `f` in a transaction, rolled back if it fails. Inside an open transaction `f`
+    /// joins it, and the outer transaction decides whether it commits.
     pub fn with_transaction<T, F>(&self, f: F) -> Result<T, String>
     where
         F: FnOnce(&Self) -> Result<T, String>,
     {
+        if !self.conn.is_autocommit() {
+            return f(self);
+        }
         self.conn
             .execute_batch("BEGIN IMMEDIATE TRANSACTION;")
             .ralph_err(codes::DB_WRITE, "Failed to start transaction")?;
@@ -133,15 +212,14 @@ impl SqliteDb {
         conn.execute_batch("PRAGMA foreign_keys = ON;")
             .ralph_err(codes::DB_OPEN, "Failed to set PRAGMAs")?;
 
-        let migrations = Migrations::new(vec![M::up(include_str!("migrations/001_initial.sql"))]);
-
-        migrations
+        migrations()
             .to_latest(&mut conn)
             .ralph_err(codes::DB_OPEN, "Failed to run migrations")?;
 
         Ok(Self {
             conn,
             clock: clock.unwrap_or_else(|| Box::new(RealClock)),
+            journal: RefCell::default(),
         })
     }
 }
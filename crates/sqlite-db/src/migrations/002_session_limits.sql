@@ -0,0 +1,13 @@
+-- Session wall-clock and idle-output limits, in seconds.
+-- A missing row or NULL column means "inherit": task -> discipline -> built-in default.
+CREATE TABLE discipline_session_limits (
+  discipline_id INTEGER PRIMARY KEY REFERENCES disciplines(id) ON DELETE CASCADE,
+  wall_clock_secs INTEGER CHECK(wall_clock_secs > 0 OR wall_clock_secs IS NULL),
+  idle_secs INTEGER CHECK(idle_secs > 0 OR idle_secs IS NULL)
+) STRICT;
+
+CREATE TABLE task_session_limits (
+  task_id INTEGER PRIMARY KEY REFERENCES runtime_tasks(id) ON DELETE CASCADE,
+  wall_clock_secs INTEGER CHECK(wall_clock_secs > 0 OR wall_clock_secs IS NULL),
+  idle_secs INTEGER CHECK(idle_secs > 0 OR idle_secs IS NULL)
+) STRICT;
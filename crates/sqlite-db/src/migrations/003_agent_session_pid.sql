@@ -0,0 +1,3 @@
+-- OS process id of the agent behind a running session, so a restarted app can tell
+-- sessions that outlived it from sessions orphaned by a crash.
+ALTER TABLE agent_sessions ADD COLUMN pid INTEGER CHECK(pid > 0 OR pid IS NULL);
@@ -0,0 +1,5 @@
+-- Git worktree a task session ran in. `base_commit..head_commit` is the session's work.
+ALTER TABLE agent_sessions ADD COLUMN worktree_branch TEXT;
+ALTER TABLE agent_sessions ADD COLUMN worktree_path TEXT;
+ALTER TABLE agent_sessions ADD COLUMN base_commit TEXT;
+ALTER TABLE agent_sessions ADD COLUMN head_commit TEXT;
@@ -0,0 +1,10 @@
+-- Exact prompt an agent session was started with, so attempts can be compared later.
+CREATE TABLE agent_session_prompts (
+  session_id TEXT PRIMARY KEY REFERENCES agent_sessions(id) ON DELETE CASCADE,
+  recipe TEXT NOT NULL, -- e.g. task_execution
+  config_name TEXT, -- saved prompt builder config that overrode the recipe, if any
+  sections TEXT NOT NULL DEFAULT '[]', -- JSON array of section names that rendered
+  prompt TEXT NOT NULL,
+  mcp_config TEXT, -- generated MCP config JSON
+  created TEXT NOT NULL DEFAULT (datetime('now'))
+) STRICT;
@@ -0,0 +1,8 @@
+-- Review after a task reports done. A missing row means the discipline's tasks aren't reviewed.
+-- NULL agent/model mean "use the discipline's own".
+CREATE TABLE discipline_review_settings (
+  discipline_id INTEGER PRIMARY KEY REFERENCES disciplines(id) ON DELETE CASCADE,
+  required INTEGER NOT NULL DEFAULT 0 CHECK(required IN (0, 1)),
+  agent TEXT,
+  model TEXT
+) STRICT;
@@ -0,0 +1,10 @@
+-- Enrichment written for a draft task by a batch run, waiting for a human to approve it.
+-- Approving applies it with enrich_task (promoting the draft to pending) and deletes the row.
+CREATE TABLE task_enrichment_proposals (
+  task_id INTEGER PRIMARY KEY REFERENCES runtime_tasks(id) ON DELETE CASCADE,
+  pseudocode TEXT NOT NULL,
+  acceptance_criteria TEXT NOT NULL DEFAULT '[]', -- JSON array of strings
+  context_files TEXT NOT NULL DEFAULT '[]', -- JSON array of project-relative paths
+  model TEXT, -- model that wrote the proposal
+  created TEXT NOT NULL
+) STRICT;
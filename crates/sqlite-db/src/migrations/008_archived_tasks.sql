@@ -0,0 +1,3 @@
+-- Archived tasks are left out of listings, prompts and runs but kept for history.
+-- Subsystems archive through their existing `status` column.
+ALTER TABLE runtime_tasks ADD COLUMN archived_at TEXT;
@@ -0,0 +1,6 @@
+-- Text mirror files as last exported: the common ancestor import_mirror_files diffs the
+-- files and the database against. Local state, never mirrored itself.
+CREATE TABLE mirror_base (
+  path TEXT PRIMARY KEY,
+  text TEXT NOT NULL
+) STRICT;
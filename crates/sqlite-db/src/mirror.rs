@@ -0,0 +1,1444 @@
+//! Text mirror of the project: one YAML file per subsystem, discipline, task and template,
+//! so the project can be reviewed and merged with git. Paths are relative to the mirror
+//! directory:
+//!
+//! - `project.yaml`
+//! - `disciplines/<ACRONYM>.yaml`
+//! - `subsystems/<ACRONYM>.yaml`, with the subsystem's comments
+//! - `tasks/<id>.yaml`, with the task's signals and their comments
+//! - `templates/<id>.yaml`
+//!
+//! `import_mirror_files` is a three-way merge against the files as last exported
+//! (`record_mirror_export`): each file takes whichever side changed it, the files or the
+//! database, and a file both sides changed is a conflict. A database that was never
+//! exported is rebuilt from the files, so it can be thrown away and recreated from them.
+//! Sessions, limits, review settings and embeddings are local state and aren't mirrored.
+
+use crate::import::has_dependency_cycle;
+use crate::types::{
+    McpServerConfig, MirrorConflict, MirrorImportReport, Priority, SubsystemStatus, TaskProvenance,
+    TaskStatus,
+};
+use crate::SqliteDb;
+use ralph_errors::{codes, RalphResultExt};
+use rusqlite::OptionalExtension;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+const PROJECT_FILE: &str = "project.yaml";
+const DISCIPLINES_DIR: &str = "disciplines";
+const SUBSYSTEMS_DIR: &str = "subsystems";
+const TASKS_DIR: &str = "tasks";
+const TEMPLATES_DIR: &str = "templates";
+
+/// Directories `mirror_files` writes entity files into.
+pub const MIRROR_DIRS: [&str; 4] = [DISCIPLINES_DIR, SUBSYSTEMS_DIR, TASKS_DIR, TEMPLATES_DIR];
+
+#[derive(Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+struct MirrorProject {
+    title: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    created: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct MirrorDiscipline {
+    name: String,
+    display_name: String,
+    acronym: String,
+    icon: String,
+    color: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    system_prompt: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    agent: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    model: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    effort: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    thinking: Option<bool>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    skills: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    conventions: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    mcp_servers: Vec<MirrorMcpServer>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    image_path: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    crops: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    image_prompt: Option<String>,
+}
+
+/// `McpServerConfig` with its env sorted, so the file doesn't change between exports.
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct MirrorMcpServer {
+    name: String,
+    command: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    args: Vec<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    env: BTreeMap<String, String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct MirrorSubsystem {
+    name: String,
+    display_name: String,
+    acronym: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+    status: SubsystemStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    created: Option<String>,
+    /// Oldest first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    comments: Vec<MirrorSubsystemComment>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct MirrorSubsystemComment {
+    category: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    discipline: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    agent_task_id: Option<u32>,
+    body: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    summary: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    reason: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    source_iteration: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    created: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    updated: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct MirrorTask {
+    id: u32,
+    subsystem: String,
+    discipline: String,
+    title: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+    status: TaskStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    priority: Option<Priority>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    depends_on: Vec<u32>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    acceptance_criteria: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    context_files: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    output_artifacts: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    hints: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    estimated_turns: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    provenance: Option<TaskProvenance>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    agent: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    model: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    effort: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    thinking: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pseudocode: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    created: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    updated: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    completed: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    enriched_at: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    archived_at: Option<String>,
+    /// Oldest first. Signal ids aren't mirrored, so two branches adding signals don't clash.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    signals: Vec<MirrorSignal>,
+}
+
+/// A `task_signals` row; which fields are set depends on `verb`.
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct MirrorSignal {
+    verb: String,
+    session_id: String,
+    /// Who started the session, used to recreate it when the database is rebuilt.
+    started_by: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    text: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    summary: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    remaining: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    reason: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    question: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    what: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    on: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    blocking: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    severity: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    category: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    kind: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    scope: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    preferred: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    options: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    rationale: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    why: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    detail: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    answer: Option<String>,
+    created: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    comments: Vec<MirrorSignalComment>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct MirrorSignalComment {
+    author_type: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    session_id: Option<String>,
+    body: String,
+    created: String,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct MirrorTemplate {
+    id: u32,
+    discipline: String,
+    title: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    priority: Option<Priority>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    hints: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    estimated_turns: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    agent: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    model: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    effort: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    thinking: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pseudocode: Option<String>,
+    is_active: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    created: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    updated: Option<String>,
+}
+
+/// Every entity a set of mirror files describes, keyed by path.
+#[derive(Default)]
+struct MirrorSet {
+    project: Option<MirrorProject>,
+    disciplines: BTreeMap<String, MirrorDiscipline>,
+    subsystems: BTreeMap<String, MirrorSubsystem>,
+    tasks: BTreeMap<String, MirrorTask>,
+    templates: BTreeMap<String, MirrorTemplate>,
+}
+
+fn entity_path(dir: &str, key: impl std::fmt::Display) -> String {
+    format!("{dir}/{key}.yaml")
+}
+
+fn render<T: Serialize>(path: &str, entity: &T) -> Result<String, String> {
+    serde_yaml::to_string(entity).map_err(|e| {
+        ralph_errors::err_string(codes::INTERNAL, format!("Failed to render {path}: {e}"))
+    })
+}
+
+/// 1-based line of the first git conflict marker in `text`.
+fn conflict_marker_line(text: &str) -> Option<u32> {
+    text.lines()
+        .position(|line| {
+            line.starts_with("<<<<<<<") || line.starts_with(">>>>>>>") || line == "======="
+        })
+        .map(|index| u32::try_from(index + 1).unwrap_or(u32::MAX))
+}
+
+fn conflict(path: &str, line: Option<u32>, message: impl Into<String>) -> MirrorConflict {
+    MirrorConflict {
+        path: path.to_owned(),
+        line,
+        message: message.into(),
+    }
+}
+
+/// Three-way merge of the files read from disk (`files`) and the database's own
+/// (`current`) against `base`, the files as last exported. Each path keeps whichever
+/// side changed it. Paths both sides changed differently take the file's text and are
+/// returned separately.
+fn merge_files(
+    base: &BTreeMap<String, String>,
+    files: &BTreeMap<String, String>,
+    current: &BTreeMap<String, String>,
+) -> (BTreeMap<String, String>, Vec<String>) {
+    let mut merged = BTreeMap::new();
+    let mut contested = Vec::new();
+    let paths: BTreeSet<&String> = base
+        .keys()
+        .chain(files.keys())
+        .chain(current.keys())
+        .collect();
+    for path in paths {
+        let (ancestor, ours, theirs) = (base.get(path), current.get(path), files.get(path));
+        let text = if theirs == ancestor || theirs == ours {
+            ours
+        } else {
+            if ours != ancestor {
+                contested.push(path.clone());
+            }
+            theirs
+        };
+        if let Some(text) = text {
+            merged.insert(path.clone(), text.clone());
+        }
+    }
+    (merged, contested)
+}
+
+/// Parse one file, or say why it can't be.
+fn parse_file<T: DeserializeOwned>(path: &str, text: &str) -> Result<T, MirrorConflict> {
+    if let Some(line) = conflict_marker_line(text) {
+        return Err(conflict(path, Some(line), "unresolved merge conflict"));
+    }
+    serde_yaml::from_str(text).map_err(|e| {
+        let line = e
+            .location()
+            .map(|location| u32::try_from(location.line()).unwrap_or(u32::MAX));
+        conflict(path, line, format!("invalid YAML: {e}"))
+    })
+}
+
+/// Parse every recognised file; anything outside the mirror layout is ignored.
+fn parse_files(files: &BTreeMap<String, String>) -> (MirrorSet, Vec<MirrorConflict>) {
+    let mut set = MirrorSet::default();
+    let mut conflicts = Vec::new();
+    for (path, text) in files {
+        if path == PROJECT_FILE {
+            match parse_file(path, text) {
+                Ok(project) => set.project = Some(project),
+                Err(c) => conflicts.push(c),
+            }
+            continue;
+        }
+        let Some((dir, file_name)) = path.split_once('/') else {
+            continue;
+        };
+        let Some(stem) = file_name.strip_suffix(".yaml") else {
+            continue;
+        };
+        let key_problem = |key: String| {
+            (key != stem).then(|| {
+                conflict(
+                    path,
+                    None,
+                    format!("file name doesn't match its contents (expected {key}.yaml)"),
+                )
+            })
+        };
+        let path = path.clone();
+        match dir {
+            DISCIPLINES_DIR => match parse_file::<MirrorDiscipline>(&path, text) {
+                Ok(d) => {
+                    conflicts.extend(key_problem(d.acronym.clone()));
+                    set.disciplines.insert(path, d);
+                }
+                Err(c) => conflicts.push(c),
+            },
+            SUBSYSTEMS_DIR => match parse_file::<MirrorSubsystem>(&path, text) {
+                Ok(s) => {
+                    conflicts.extend(key_problem(s.acronym.clone()));
+                    set.subsystems.insert(path, s);
+                }
+                Err(c) => conflicts.push(c),
+            },
+            TASKS_DIR => match parse_file::<MirrorTask>(&path, text) {
+                Ok(t) => {
+                    conflicts.extend(key_problem(t.id.to_string()));
+                    set.tasks.insert(path, t);
+                }
+                Err(c) => conflicts.push(c),
+            },
+            TEMPLATES_DIR => match parse_file::<MirrorTemplate>(&path, text) {
+                Ok(t) => {
+                    conflicts.extend(key_problem(t.id.to_string()));
+                    set.templates.insert(path, t);
+                }
+                Err(c) => conflicts.push(c),
+            },
+            _ => {}
+        }
+    }
+    if set.project.is_none() && !files.contains_key(PROJECT_FILE) {
+        conflicts.push(conflict(PROJECT_FILE, None, "missing"));
+    }
+    (set, conflicts)
+}
+
+/// References between files that don't resolve, usually because two branches changed
+/// related entities differently.
+fn reference_conflicts(set: &MirrorSet) -> Vec<MirrorConflict> {
+    let mut conflicts = Vec::new();
+    let key = |name: &str| name.to_ascii_lowercase();
+
+    let mut disciplines = HashSet::new();
+    let mut acronyms = HashSet::new();
+    for (path, d) in &set.disciplines {
+        if !disciplines.insert(key(&d.name)) {
+            conflicts.push(conflict(
+                path,
+                None,
+                format!("discipline '{}' is defined twice", d.name),
+            ));
+        }
+        if crate::acronym::validate_acronym_format(&d.acronym).is_err()
+            || !acronyms.insert(&d.acronym)
+        {
+            conflicts.push(conflict(
+                path,
+                None,
+                format!("invalid or duplicate acronym '{}'", d.acronym),
+            ));
+        }
+    }
+
+    let mut subsystems = HashSet::new();
+    let mut acronyms = HashSet::new();
+    for (path, s) in &set.subsystems {
+        if !subsystems.insert(key(&s.name)) {
+            conflicts.push(conflict(
+                path,
+                None,
+                format!("subsystem '{}' is defined twice", s.name),
+            ));
+        }
+        if crate::acronym::validate_acronym_format(&s.acronym).is_err()
+            || !acronyms.insert(&s.acronym)
+        {
+            conflicts.push(conflict(
+                path,
+                None,
+                format!("invalid or duplicate acronym '{}'", s.acronym),
+            ));
+        }
+        for comment in &s.comments {
+            if let Some(discipline) = &comment.discipline {
+                if !disciplines.contains(&key(discipline)) {
+                    conflicts.push(conflict(
+                        path,
+                        None,
+                        format!("comment refers to unknown discipline '{discipline}'"),
+                    ));
+                }
+            }
+        }
+    }
+
+    for (path, t) in &set.templates {
+        if !disciplines.contains(&key(&t.discipline)) {
+            conflicts.push(conflict(
+                path,
+                None,
+                format!("unknown discipline '{}'", t.discipline),
+            ));
+        }
+    }
+
+    let task_ids: HashSet<u32> = set.tasks.values().map(|t| t.id).collect();
+    for (path, t) in &set.tasks {
+        if !subsystems.contains(&key(&t.subsystem)) {
+            conflicts.push(conflict(
+                path,
+                None,
+                format!("unknown subsystem '{}'", t.subsystem),
+            ));
+        }
+        if !disciplines.contains(&key(&t.discipline)) {
+            conflicts.push(conflict(
+                path,
+                None,
+                format!("unknown discipline '{}'", t.discipline),
+            ));
+        }
+        if t.status == TaskStatus::Done && t.completed.is_none() {
+            conflicts.push(conflict(
+                path,
+                None,
+                "task is done but has no completed date",
+            ));
+        }
+        for dep in &t.depends_on {
+            if *dep == t.id || !task_ids.contains(dep) {
+                conflicts.push(conflict(
+                    path,
+                    None,
+                    format!("depends on unknown task {dep}"),
+                ));
+            }
+        }
+    }
+    let edges = set
+        .tasks
+        .values()
+        .map(|t| (t.id, t.depends_on.as_slice()))
+        .collect();
+    if has_dependency_cycle(&edges) {
+        conflicts.push(conflict(
+            TASKS_DIR,
+            None,
+            "task dependencies contain a cycle",
+        ));
+    }
+    conflicts
+}
+
+impl SqliteDb {
+    /// The mirror as path → YAML text. Same database state, same files.
+    pub fn mirror_files(&self) -> Result<BTreeMap<String, String>, String> {
+        let mut files = BTreeMap::new();
+
+        let meta = self.get_project_info();
+        let project = MirrorProject {
+            title: meta.title,
+            description: meta.description,
+            created: meta.created,
+        };
+        files.insert(PROJECT_FILE.to_owned(), render(PROJECT_FILE, &project)?);
+
+        for d in self.get_disciplines() {
+            let path = entity_path(DISCIPLINES_DIR, &d.acronym);
+            let discipline = MirrorDiscipline {
+                name: d.name,
+                display_name: d.display_name,
+                acronym: d.acronym,
+                icon: d.icon,
+                color: d.color,
+                description: d.description,
+                system_prompt: d.system_prompt,
+                agent: d.agent,
+                model: d.model,
+                effort: d.effort,
+                thinking: d.thinking,
+                skills: d.skills,
+                conventions: d.conventions,
+                mcp_servers: d
+                    .mcp_servers
+                    .into_iter()
+                    .map(|server| MirrorMcpServer {
+                        name: server.name,
+                        command: server.command,
+                        args: server.args,
+                        env: server.env.into_iter().collect(),
+                    })
+                    .collect(),
+                image_path: d.image_path,
+                crops: d.crops,
+                image_prompt: d.image_prompt,
+            };
+            files.insert(path.clone(), render(&path, &discipline)?);
+        }
+
+        for s in self.get_subsystems() {
+            let path = entity_path(SUBSYSTEMS_DIR, &s.acronym);
+            let subsystem = MirrorSubsystem {
+                name: s.name,
+                display_name: s.display_name,
+                acronym: s.acronym,
+                description: s.description,
+                status: s.status,
+                created: s.created,
+                // Subsystems list their comments newest first.
+                comments: s
+                    .comments
+                    .into_iter()
+                    .rev()
+                    .map(|c| MirrorSubsystemComment {
+                        category: c.category,
+                        discipline: c.discipline,
+                        agent_task_id: c.agent_task_id,
+                        body: c.body,
+                        summary: c.summary,
+                        reason: c.reason,
+                        source_iteration: c.source_iteration,
+                        created: c.created,
+                        updated: c.updated,
+                    })
+                    .collect(),
+            };
+            files.insert(path.clone(), render(&path, &subsystem)?);
+        }
+
+        for t in self.get_tasks() {
+            let path = entity_path(TASKS_DIR, t.id);
+            let task = MirrorTask {
+                signals: self.mirror_signals(t.id)?,
+                id: t.id,
+                subsystem: t.subsystem,
+                discipline: t.discipline,
+                title: t.title,
+                description: t.description,
+                status: t.status,
+                priority: t.priority,
+                tags: t.tags,
+                depends_on: t.depends_on,
+                acceptance_criteria: t.acceptance_criteria,
+                context_files: t.context_files,
+                output_artifacts: t.output_artifacts,
+                hints: t.hints,
+                estimated_turns: t.estimated_turns,
+                provenance: t.provenance,
+                agent: t.agent,
+                model: t.model,
+                effort: t.effort,
+                thinking: t.thinking,
+                pseudocode: t.pseudocode,
+                created: t.created,
+                updated: t.updated,
+                completed: t.completed,
+                enriched_at: t.enriched_at,
+                archived_at: t.archived_at,
+            };
+            files.insert(path.clone(), render(&path, &task)?);
+        }
+
+        for template in self.mirror_templates()? {
+            let path = entity_path(TEMPLATES_DIR, template.id);
+            files.insert(path.clone(), render(&path, &template)?);
+        }
+
+        Ok(files)
+    }
+
+    fn mirror_signals(&self, task_id: u32) -> Result<Vec<MirrorSignal>, String> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT ts.id, ts.verb, ts.session_id, COALESCE(s.started_by, 'system'), \
+                 ts.text, ts.summary, ts.remaining, ts.reason, ts.question, ts.what, ts.\"on\", \
+                 ts.blocking, ts.severity, ts.category, ts.kind, ts.scope, ts.preferred, \
+                 ts.options, ts.rationale, ts.why, ts.detail, ts.answer, ts.created \
+                 FROM task_signals ts LEFT JOIN agent_sessions s ON ts.session_id = s.id \
+                 WHERE ts.task_id = ?1 ORDER BY ts.id",
+            )
+            .ralph_err(codes::DB_READ, "Failed to prepare signal query")?;
+        let rows: Vec<(i64, MirrorSignal)> = stmt
+            .query_map([task_id], |row| {
+                Ok((
+                    row.get(0)?,
+                    MirrorSignal {
+                        verb: row.get(1)?,
+                        session_id: row.get(2)?,
+                        started_by: row.get(3)?,
+                        text: row.get(4)?,
+                        summary: row.get(5)?,
+                        remaining: row.get(6)?,
+                        reason: row.get(7)?,
+                        question: row.get(8)?,
+                        what: row.get(9)?,
+                        on: row.get(10)?,
+                        blocking: row.get(11)?,
+                        severity: row.get(12)?,
+                        category: row.get(13)?,
+                        kind: row.get(14)?,
+                        scope: row.get(15)?,
+                        preferred: row.get(16)?,
+                        options: row.get(17)?,
+                        rationale: row.get(18)?,
+                        why: row.get(19)?,
+                        detail: row.get(20)?,
+                        answer: row.get(21)?,
+                        created: row.get(22)?,
+                        comments: Vec::new(),
+                    },
+                ))
+            })
+            .ralph_err(codes::DB_READ, "Failed to read signals")?
+            .collect::<Result<_, _>>()
+            .ralph_err(codes::DB_READ, "Failed to read signal")?;
+
+        let mut comments = self
+            .conn
+            .prepare(
+                "SELECT author_type, session_id, body, created FROM task_signal_comments \
+                 WHERE signal_id = ?1 ORDER BY id",
+            )
+            .ralph_err(codes::DB_READ, "Failed to prepare signal comment query")?;
+        let mut signals = Vec::with_capacity(rows.len());
+        for (signal_id, mut signal) in rows {
+            signal.comments = comments
+                .query_map([signal_id], |row| {
+                    Ok(MirrorSignalComment {
+                        author_type: row.get(0)?,
+                        session_id: row.get(1)?,
+                        body: row.get(2)?,
+                        created: row.get(3)?,
+                    })
+                })
+                .ralph_err(codes::DB_READ, "Failed to read signal comments")?
+                .collect::<Result<_, _>>()
+                .ralph_err(codes::DB_READ, "Failed to read signal comment")?;
+            signals.push(signal);
+        }
+        Ok(signals)
+    }
+
+    fn mirror_templates(&self) -> Result<Vec<MirrorTemplate>, String> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT tt.id, d.name, td.title, td.description, td.priority, td.hints, \
+                 td.estimated_turns, td.agent, td.model, td.effort, td.thinking, td.pseudocode, \
+                 tt.is_active, td.created, td.updated \
+                 FROM task_templates tt \
+                 JOIN task_details td ON tt.details_id = td.id \
+                 JOIN disciplines d ON td.discipline_id = d.id \
+                 ORDER BY tt.id",
+            )
+            .ralph_err(codes::DB_READ, "Failed to prepare template query")?;
+        let templates = stmt
+            .query_map([], |row| {
+                let priority: Option<String> = row.get(4)?;
+                Ok(MirrorTemplate {
+                    id: row.get(0)?,
+                    discipline: row.get(1)?,
+                    title: row.get(2)?,
+                    description: row.get(3)?,
+                    priority: priority.and_then(|p| Priority::parse(&p)),
+                    hints: row.get(5)?,
+                    estimated_turns: row.get(6)?,
+                    agent: row.get(7)?,
+                    model: row.get(8)?,
+                    effort: row.get(9)?,
+                    thinking: row.get(10)?,
+                    pseudocode: row.get(11)?,
+                    is_active: row.get(12)?,
+                    created: row.get(13)?,
+                    updated: row.get(14)?,
+                })
+            })
+            .ralph_err(codes::DB_READ, "Failed to read templates")?
+            .collect::<Result<_, _>>()
+            .ralph_err(codes::DB_READ, "Failed to read template")?;
+        Ok(templates)
+    }
+
+    /// Changes whenever this connection or another one writes, so a caller can tell
+    /// whether `mirror_files` may have changed since it last looked.
+    pub fn mirror_change_marker(&self) -> Result<(i64, i64), String> {
+        let version: i64 = self
+            .conn
+            .query_row("PRAGMA data_version", [], |row| row.get(0))
+            .ralph_err(codes::DB_READ, "Failed to read data version")?;
+        let changes: i64 = self
+            .conn
+            .query_row("SELECT total_changes()", [], |row| row.get(0))
+            .ralph_err(codes::DB_READ, "Failed to read change count")?;
+        Ok((version, changes))
+    }
+
+    /// Bring the database in line with mirror files read from disk, merging them with
+    /// the database's changes since the last export. Only entities whose text differs
+    /// from `mirror_files` are rewritten, and only entities whose file was removed are
+    /// removed. If any file has a conflict (changes on both sides, merge markers,
+    /// invalid YAML, broken references) nothing is imported and the conflicts are
+    /// reported instead.
+    pub fn import_mirror_files(
+        &self,
+        files: &BTreeMap<String, String>,
+    ) -> Result<MirrorImportReport, String> {
+        let mut report = MirrorImportReport::default();
+        // An empty mirror means the files haven't been written yet, not "delete everything".
+        if files.is_empty() {
+            return Ok(report);
+        }
+        let current = self.mirror_files()?;
+        // Never exported: the files describe the whole project.
+        let (target, contested) = self.mirror_base()?.map_or_else(
+            || (files.clone(), Vec::new()),
+            |base| merge_files(&base, files, &current),
+        );
+
+        let (set, mut conflicts) = parse_files(&target);
+        for path in contested {
+            if !conflicts.iter().any(|c| c.path == path) {
+                conflicts.push(conflict(
+                    &path,
+                    None,
+                    "changed in the file and in the database since the last export; \
+                     revert the file to keep the database's version",
+                ));
+            }
+        }
+        if conflicts.is_empty() {
+            conflicts = reference_conflicts(&set);
+        }
+        if !conflicts.is_empty() {
+            report.conflicts = conflicts;
+            return Ok(report);
+        }
+
+        let changed = |path: &String| current.get(path) != target.get(path);
+
+        self.conn
+            .execute_batch("SAVEPOINT mirror_import;")
+            .ralph_err(codes::DB_WRITE, "Failed to start mirror import")?;
+        let result = self.apply_mirror_set(&set, &changed, &mut report);
+        let finish = if result.is_ok() {
+            "RELEASE mirror_import;"
+        } else {
+            "ROLLBACK TO mirror_import; RELEASE mirror_import;"
+        };
+        self.conn
+            .execute_batch(finish)
+            .ralph_err(codes::DB_WRITE, "Failed to finish mirror import")?;
+        result.map(|()| report)
+    }
+
+    /// Remember `files` as written to disk: the base the next `import_mirror_files`
+    /// merges against.
+    pub fn record_mirror_export(&self, files: &BTreeMap<String, String>) -> Result<(), String> {
+        self.with_transaction(|db| {
+            db.conn
+                .execute("DELETE FROM mirror_base", [])
+                .ralph_err(codes::DB_WRITE, "Failed to clear mirror base")?;
+            let mut stmt = db
+                .conn
+                .prepare("INSERT INTO mirror_base (path, text) VALUES (?1, ?2)")
+                .ralph_err(codes::DB_WRITE, "Failed to prepare mirror base insert")?;
+            for (path, text) in files {
+                stmt.execute([path, text])
+                    .ralph_err(codes::DB_WRITE, "Failed to record mirror base")?;
+            }
+            Ok(())
+        })
+    }
+
+    /// The files `record_mirror_export` last saw, or `None` before the first export.
+    fn mirror_base(&self) -> Result<Option<BTreeMap<String, String>>, String> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT path, text FROM mirror_base")
+            .ralph_err(codes::DB_READ, "Failed to query mirror base")?;
+        let base: BTreeMap<String, String> = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .ralph_err(codes::DB_READ, "Failed to query mirror base")?
+            .collect::<Result<_, _>>()
+            .ralph_err(codes::DB_READ, "Failed to read mirror base")?;
+        Ok((!base.is_empty()).then_some(base))
+    }
+
+    fn apply_mirror_set(
+        &self,
+        set: &MirrorSet,
+        changed: &dyn Fn(&String) -> bool,
+        report: &mut MirrorImportReport,
+    ) -> Result<(), String> {
+        if let Some(project) = &set.project {
+            if changed(&PROJECT_FILE.to_owned()) {
+                self.conn
+                    .execute(
+                        "INSERT OR REPLACE INTO metadata (id, project_title, project_description, project_created) \
+                         VALUES (1, ?1, ?2, ?3)",
+                        rusqlite::params![project.title, project.description, project.created],
+                    )
+                    .ralph_err(codes::DB_WRITE, "Failed to import project metadata")?;
+                report.applied.push(PROJECT_FILE.to_owned());
+            }
+        }
+
+        // Tasks go first so nothing still points at what is removed further down.
+        let task_ids: HashSet<u32> = set.tasks.values().map(|t| t.id).collect();
+        for task in self.get_tasks() {
+            if !task_ids.contains(&task.id) {
+                let details_id: i64 = self
+                    .conn
+                    .query_row(
+                        "SELECT details_id FROM runtime_tasks WHERE id = ?1",
+                        [task.id],
+                        |row| row.get(0),
+                    )
+                    .ralph_err(codes::DB_READ, "Failed to look up task")?;
+                self.conn
+                    .execute("DELETE FROM runtime_tasks WHERE id = ?1", [task.id])
+                    .ralph_err(codes::DB_WRITE, "Failed to remove task")?;
+                // Details would otherwise keep the task's discipline from being removed.
+                self.conn
+                    .execute("DELETE FROM task_details WHERE id = ?1", [details_id])
+                    .ralph_err(codes::DB_WRITE, "Failed to remove task details")?;
+                report.removed.push(format!("task {}", task.id));
+            }
+        }
+
+        for (path, discipline) in &set.disciplines {
+            if changed(path) {
+                self.apply_mirror_discipline(discipline)?;
+                report.applied.push(path.clone());
+            }
+        }
+        for (path, subsystem) in &set.subsystems {
+            if changed(path) {
+                self.apply_mirror_subsystem(subsystem)?;
+                report.applied.push(path.clone());
+            }
+        }
+        for (path, template) in &set.templates {
+            if changed(path) {
+                self.apply_mirror_template(template)?;
+                report.applied.push(path.clone());
+            }
+        }
+        let mut changed_tasks = Vec::new();
+        for (path, task) in &set.tasks {
+            if changed(path) {
+                self.apply_mirror_task(task)?;
+                report.applied.push(path.clone());
+                changed_tasks.push(task);
+            }
+        }
+        // Dependencies go in once every task they can point at exists.
+        for task in changed_tasks {
+            for dep in &task.depends_on {
+                self.conn
+                    .execute(
+                        "INSERT INTO task_dependencies (task_id, depends_on_task_id) VALUES (?1, ?2)",
+                        rusqlite::params![task.id, dep],
+                    )
+                    .ralph_err(codes::DB_WRITE, "Failed to import dependency")?;
+            }
+        }
+
+        let template_ids: HashSet<u32> = set.templates.values().map(|t| t.id).collect();
+        for (id, details_id) in self.template_rows()? {
+            if !template_ids.contains(&id) {
+                self.conn
+                    .execute("DELETE FROM task_templates WHERE id = ?1", [id])
+                    .ralph_err(codes::DB_WRITE, "Failed to remove template")?;
+                self.conn
+                    .execute("DELETE FROM task_details WHERE id = ?1", [details_id])
+                    .ralph_err(codes::DB_WRITE, "Failed to remove template details")?;
+                report.removed.push(format!("template {id}"));
+            }
+        }
+
+        let subsystem_names: HashSet<String> = set
+            .subsystems
+            .values()
+            .map(|s| s.name.to_ascii_lowercase())
+            .collect();
+        for subsystem in self.get_subsystems() {
+            if !subsystem_names.contains(&subsystem.name.to_ascii_lowercase()) {
+                self.conn
+                    .execute("DELETE FROM subsystems WHERE id = ?1", [subsystem.id])
+                    .ralph_err(codes::DB_WRITE, "Failed to remove subsystem")?;
+                report.removed.push(format!("subsystem {}", subsystem.name));
+            }
+        }
+
+        let discipline_names: HashSet<String> = set
+            .disciplines
+            .values()
+            .map(|d| d.name.to_ascii_lowercase())
+            .collect();
+        for discipline in self.get_disciplines() {
+            if !discipline_names.contains(&discipline.name.to_ascii_lowercase()) {
+                self.conn
+                    .execute("DELETE FROM disciplines WHERE id = ?1", [discipline.id])
+                    .ralph_err(codes::DB_WRITE, "Failed to remove discipline")?;
+                report
+                    .removed
+                    .push(format!("discipline {}", discipline.name));
+            }
+        }
+        Ok(())
+    }
+
+    /// Id of the row named `name`, or else the one using `acronym` (a rename).
+    fn mirror_match(&self, table: &str, name: &str, acronym: &str) -> Result<Option<i64>, String> {
+        self.conn
+            .query_row(
+                &format!("SELECT id FROM {table} WHERE name = ?1 OR acronym = ?2 ORDER BY name = ?1 DESC LIMIT 1"),
+                [name, acronym],
+                |row| row.get(0),
+            )
+            .optional()
+            .ralph_err(codes::DB_READ, &format!("Failed to query {table}"))
+    }
+
+    fn apply_mirror_discipline(&self, d: &MirrorDiscipline) -> Result<(), String> {
+        let mcp_servers: Vec<McpServerConfig> = d
+            .mcp_servers
+            .iter()
+            .map(|server| McpServerConfig {
+                name: server.name.clone(),
+                command: server.command.clone(),
+                args: server.args.clone(),
+                env: server.env.clone().into_iter().collect(),
+            })
+            .collect();
+        let input = crate::types::DisciplineInput {
+            name: d.name.clone(),
+            display_name: d.display_name.clone(),
+            acronym: d.acronym.clone(),
+            icon: d.icon.clone(),
+            color: d.color.clone(),
+            description: d.description.clone(),
+            system_prompt: d.system_prompt.clone(),
+            agent: d.agent.clone(),
+            model: d.model.clone(),
+            effort: d.effort.clone(),
+            thinking: d.thinking,
+            skills: serde_json::to_string(&d.skills).ralph_err(codes::DB_WRITE, "JSON error")?,
+            conventions: d.conventions.clone(),
+            mcp_servers: serde_json::to_string(&mcp_servers)
+                .ralph_err(codes::DB_WRITE, "JSON error")?,
+            image_path: d.image_path.clone(),
+            crops: d.crops.clone(),
+            image_prompt: d.image_prompt.clone(),
+        };
+        match self.mirror_match("disciplines", &d.name, &d.acronym)? {
+            Some(id) => {
+                self.conn
+                    .execute(
+                        "UPDATE disciplines SET name = ?1 WHERE id = ?2",
+                        rusqlite::params![d.name, id],
+                    )
+                    .ralph_err(codes::DB_WRITE, "Failed to rename discipline")?;
+                self.update_discipline(input)
+            }
+            None => self.create_discipline(input),
+        }
+    }
+
+    fn apply_mirror_subsystem(&self, s: &MirrorSubsystem) -> Result<(), String> {
+        if let Some(id) = self.mirror_match("subsystems", &s.name, &s.acronym)? {
+            self.conn
+                .execute(
+                    "UPDATE subsystems SET name = ?1, display_name = ?2, acronym = ?3, \
+                     description = ?4, status = ?5, created = ?6 WHERE id = ?7",
+                    rusqlite::params![
+                        s.name,
+                        s.display_name,
+                        s.acronym,
+                        s.description,
+                        s.status.as_str(),
+                        s.created,
+                        id,
+                    ],
+                )
+                .ralph_err(codes::DB_WRITE, "Failed to import subsystem")?;
+            self.conn
+                .execute(
+                    "DELETE FROM subsystem_comments WHERE subsystem_id = ?1",
+                    [id],
+                )
+                .ralph_err(codes::DB_WRITE, "Failed to replace subsystem comments")?;
+        } else {
+            self.conn
+                .execute(
+                    "INSERT INTO subsystems (name, display_name, acronym, description, status, created) \
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                    rusqlite::params![
+                        s.name,
+                        s.display_name,
+                        s.acronym,
+                        s.description,
+                        s.status.as_str(),
+                        s.created,
+                    ],
+                )
+                .ralph_err(codes::DB_WRITE, "Failed to import subsystem")?;
+        }
+        let subsystem_id = self.get_id_from_name("subsystems", &s.name)?;
+        for c in &s.comments {
+            let discipline_id = c
+                .discipline
+                .as_deref()
+                .map(|name| self.get_id_from_name("disciplines", name))
+                .transpose()?;
+            self.conn
+                .execute(
+                    "INSERT INTO subsystem_comments (subsystem_id, category, discipline_id, \
+                     agent_task_id, body, summary, reason, source_iteration, created, updated) \
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+                    rusqlite::params![
+                        subsystem_id,
+                        c.category,
+                        discipline_id,
+                        c.agent_task_id,
+                        c.body,
+                        c.summary,
+                        c.reason,
+                        c.source_iteration,
+                        c.created,
+                        c.updated,
+                    ],
+                )
+                .ralph_err(codes::DB_WRITE, "Failed to import subsystem comment")?;
+        }
+        Ok(())
+    }
+
+    fn template_rows(&self) -> Result<Vec<(u32, i64)>, String> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT id, details_id FROM task_templates ORDER BY id")
+            .ralph_err(codes::DB_READ, "Failed to prepare template query")?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .ralph_err(codes::DB_READ, "Failed to read templates")?
+            .collect::<Result<_, _>>()
+            .ralph_err(codes::DB_READ, "Failed to read template")?;
+        Ok(rows)
+    }
+
+    fn apply_mirror_template(&self, t: &MirrorTemplate) -> Result<(), String> {
+        let discipline_id = self.get_id_from_name("disciplines", &t.discipline)?;
+        let priority = t.priority.map(|p| p.as_str().to_owned());
+        let details_id: Option<i64> = self
+            .conn
+            .query_row(
+                "SELECT details_id FROM task_templates WHERE id = ?1",
+                [t.id],
+                |row| row.get(0),
+            )
+            .optional()
+            .ralph_err(codes::DB_READ, "Failed to look up template")?;
+
+        if let Some(details_id) = details_id {
+            // Skip unchanged rows: the timestamp trigger would otherwise bump `updated`.
+            self.conn
+                .execute(
+                    "UPDATE task_details SET discipline_id = ?1, title = ?2, description = ?3, \
+                     priority = ?4, hints = ?5, estimated_turns = ?6, agent = ?7, model = ?8, \
+                     effort = ?9, thinking = ?10, pseudocode = ?11, created = ?12, updated = ?13 \
+                     WHERE id = ?14 AND (discipline_id IS NOT ?1 OR title IS NOT ?2 \
+                     OR description IS NOT ?3 OR priority IS NOT ?4 OR hints IS NOT ?5 \
+                     OR estimated_turns IS NOT ?6 OR agent IS NOT ?7 OR model IS NOT ?8 \
+                     OR effort IS NOT ?9 OR thinking IS NOT ?10 OR pseudocode IS NOT ?11 \
+                     OR created IS NOT ?12 OR updated IS NOT ?13)",
+                    rusqlite::params![
+                        discipline_id,
+                        t.title,
+                        t.description,
+                        priority,
+                        t.hints,
+                        t.estimated_turns,
+                        t.agent,
+                        t.model,
+                        t.effort,
+                        t.thinking,
+                        t.pseudocode,
+                        t.created,
+                        t.updated,
+                        details_id,
+                    ],
+                )
+                .ralph_err(codes::DB_WRITE, "Failed to import template details")?;
+            self.conn
+                .execute(
+                    "UPDATE task_templates SET is_active = ?1 WHERE id = ?2 AND is_active IS NOT ?1",
+                    rusqlite::params![t.is_active, t.id],
+                )
+                .ralph_err(codes::DB_WRITE, "Failed to import template")?;
+            return Ok(());
+        }
+
+        self.conn
+            .execute(
+                "INSERT INTO task_details (discipline_id, title, description, priority, hints, \
+                 estimated_turns, agent, model, effort, thinking, pseudocode, created, updated) \
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+                rusqlite::params![
+                    discipline_id,
+                    t.title,
+                    t.description,
+                    priority,
+                    t.hints,
+                    t.estimated_turns,
+                    t.agent,
+                    t.model,
+                    t.effort,
+                    t.thinking,
+                    t.pseudocode,
+                    t.created,
+                    t.updated,
+                ],
+            )
+            .ralph_err(codes::DB_WRITE, "Failed to import template details")?;
+        self.conn
+            .execute(
+                "INSERT INTO task_templates (id, details_id, is_active, created) VALUES (?1, ?2, ?3, ?4)",
+                rusqlite::params![t.id, self.conn.last_insert_rowid(), t.is_active, t.created],
+            )
+            .ralph_err(codes::DB_WRITE, "Failed to import template")?;
+        Ok(())
+    }
+
+    /// Write one task with its lists and signals; dependencies are left to the caller.
+    fn apply_mirror_task(&self, t: &MirrorTask) -> Result<(), String> {
+        let id = t.id;
+        let subsystem_id = self.get_id_from_name("subsystems", &t.subsystem)?;
+        let discipline_id = self.get_id_from_name("disciplines", &t.discipline)?;
+        let priority = t.priority.map(|p| p.as_str().to_owned());
+        let provenance = t.provenance.map(|p| p.as_str().to_owned());
+        let details_id: Option<i64> = self
+            .conn
+            .query_row(
+                "SELECT details_id FROM runtime_tasks WHERE id = ?1",
+                [id],
+                |row| row.get(0),
+            )
+            .optional()
+            .ralph_err(codes::DB_READ, "Failed to look up task")?;
+        let details = rusqlite::params![
+            discipline_id,
+            t.title,
+            t.description,
+            priority,
+            t.hints,
+            t.estimated_turns,
+            t.agent,
+            t.model,
+            t.effort,
+            t.thinking,
+            t.pseudocode,
+        ];
+
+        if let Some(details_id) = details_id {
+            self.conn
+                .execute(
+                    "UPDATE task_details SET discipline_id = ?1, title = ?2, description = ?3, \
+                     priority = ?4, hints = ?5, estimated_turns = ?6, agent = ?7, model = ?8, \
+                     effort = ?9, thinking = ?10, pseudocode = ?11 WHERE id = ?12",
+                    rusqlite::params![
+                        discipline_id,
+                        t.title,
+                        t.description,
+                        priority,
+                        t.hints,
+                        t.estimated_turns,
+                        t.agent,
+                        t.model,
+                        t.effort,
+                        t.thinking,
+                        t.pseudocode,
+                        details_id,
+                    ],
+                )
+                .ralph_err(codes::DB_WRITE, "Failed to import task details")?;
+            // Skip unchanged rows: the timestamp trigger would otherwise bump `updated`.
+            self.conn
+                .execute(
+                    "UPDATE runtime_tasks SET subsystem_id = ?1, status = ?2, provenance = ?3, \
+                     created = ?4, updated = ?5, completed = ?6, enriched_at = ?7, \
+                     archived_at = ?8 WHERE id = ?9 \
+                     AND (subsystem_id IS NOT ?1 OR status IS NOT ?2 OR provenance IS NOT ?3 \
+                     OR created IS NOT ?4 OR updated IS NOT ?5 OR completed IS NOT ?6 \
+                     OR enriched_at IS NOT ?7 OR archived_at IS NOT ?8)",
+                    rusqlite::params![
+                        subsystem_id,
+                        t.status.as_str(),
+                        provenance,
+                        t.created,
+                        t.updated,
+                        t.completed,
+                        t.enriched_at,
+                        t.archived_at,
+                        id,
+                    ],
+                )
+                .ralph_err(codes::DB_WRITE, "Failed to import task")?;
+            for table in [
+                "task_tags",
+                "task_dependencies",
+                "task_acceptance_criteria",
+                "task_context_files",
+                "task_output_artifacts",
+                "task_signals",
+            ] {
+                self.delete_task_related_rows(table, id)?;
+            }
+        } else {
+            self.conn
+                .execute(
+                    "INSERT INTO task_details (discipline_id, title, description, priority, hints, \
+                     estimated_turns, agent, model, effort, thinking, pseudocode) \
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+                    details,
+                )
+                .ralph_err(codes::DB_WRITE, "Failed to import task details")?;
+            self.conn
+                .execute(
+                    "INSERT INTO runtime_tasks (id, subsystem_id, details_id, status, provenance, \
+                     created, updated, completed, enriched_at, archived_at) \
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+                    rusqlite::params![
+                        id,
+                        subsystem_id,
+                        self.conn.last_insert_rowid(),
+                        t.status.as_str(),
+                        provenance,
+                        t.created,
+                        t.updated,
+                        t.completed,
+                        t.enriched_at,
+                        t.archived_at,
+                    ],
+                )
+                .ralph_err(codes::DB_WRITE, "Failed to import task")?;
+        }
+
+        self.insert_string_list("task_tags", "task_id", i64::from(id), "tag", &t.tags)?;
+        for (idx, criterion) in t.acceptance_criteria.iter().enumerate() {
+            self.conn
+                .execute(
+                    "INSERT INTO task_acceptance_criteria (task_id, criterion, criterion_order) VALUES (?1, ?2, ?3)",
+                    rusqlite::params![id, criterion, i64::try_from(idx).unwrap_or(0)],
+                )
+                .ralph_err(codes::DB_WRITE, "Failed to import acceptance criterion")?;
+        }
+        self.insert_string_list(
+            "task_context_files",
+            "task_id",
+            i64::from(id),
+            "file_path",
+            &t.context_files,
+        )?;
+        self.insert_string_list(
+            "task_output_artifacts",
+            "task_id",
+            i64::from(id),
+            "artifact_path",
+            &t.output_artifacts,
+        )?;
+        for signal in &t.signals {
+            self.apply_mirror_signal(id, signal)?;
+        }
+        Ok(())
+    }
+
+    fn apply_mirror_signal(&self, task_id: u32, s: &MirrorSignal) -> Result<(), String> {
+        self.ensure_agent_session_exists(&s.session_id, task_id, &s.started_by, "manual")?;
+        self.conn
+            .execute(
+                "INSERT INTO task_signals (task_id, session_id, verb, text, summary, remaining, \
+                 reason, question, what, \"on\", blocking, severity, category, kind, scope, \
+                 preferred, options, rationale, why, detail, answer, created) \
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, \
+                 ?17, ?18, ?19, ?20, ?21, ?22)",
+                rusqlite::params![
+                    task_id,
+                    s.session_id,
+                    s.verb,
+                    s.text,
+                    s.summary,
+                    s.remaining,
+                    s.reason,
+                    s.question,
+                    s.what,
+                    s.on,
+                    s.blocking,
+                    s.severity,
+                    s.category,
+                    s.kind,
+                    s.scope,
+                    s.preferred,
+                    s.options,
+                    s.rationale,
+                    s.why,
+                    s.detail,
+                    s.answer,
+                    s.created,
+                ],
+            )
+            .ralph_err(codes::DB_WRITE, "Failed to import signal")?;
+        let signal_id = self.conn.last_insert_rowid();
+        for c in &s.comments {
+            if let Some(session_id) = &c.session_id {
+                self.ensure_agent_session_exists(session_id, task_id, &c.author_type, "manual")?;
+            }
+            self.conn
+                .execute(
+                    "INSERT INTO task_signal_comments (signal_id, session_id, author_type, body, created) \
+                     VALUES (?1, ?2, ?3, ?4, ?5)",
+                    rusqlite::params![signal_id, c.session_id, c.author_type, c.body, c.created],
+                )
+                .ralph_err(codes::DB_WRITE, "Failed to import signal comment")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_git_conflict_markers() {
+        let text = "id: 3\n<<<<<<< HEAD\ntitle: a\n=======\ntitle: b\n>>>>>>> topic\n";
+        assert_eq!(conflict_marker_line(text), Some(2));
+        assert_eq!(conflict_marker_line("title: \"=======\"\n"), None);
+    }
+
+    #[test]
+    fn reports_files_named_after_another_entity() {
+        let files = BTreeMap::from([
+            (PROJECT_FILE.to_owned(), "title: P\n".to_owned()),
+            (
+                "tasks/4.yaml".to_owned(),
+                "id: 5\nsubsystem: s\ndiscipline: d\ntitle: t\nstatus: pending\n".to_owned(),
+            ),
+            ("notes/readme.md".to_owned(), "ignored".to_owned()),
+        ]);
+        let (set, conflicts) = parse_files(&files);
+        assert_eq!(set.tasks.len(), 1);
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].path, "tasks/4.yaml");
+        assert!(conflicts[0].message.contains("5.yaml"));
+    }
+}
@@ -0,0 +1,353 @@
+//! Read-only renderings of the task plan for people outside the app: a Markdown report,
+//! a CSV sheet and the dependency graph as Graphviz DOT or Mermaid.
+//! Like `export_prd_yaml`, the same database state always renders the same text.
+
+use crate::types::{ReportFormat, Task, TaskStatus};
+use crate::SqliteDb;
+
+/// Signals shown per task in the Markdown report, newest first.
+const LATEST_SIGNALS: usize = 3;
+
+/// Order statuses are listed in: work that needs attention first.
+const STATUS_ORDER: [TaskStatus; 6] = [
+    TaskStatus::InProgress,
+    TaskStatus::Blocked,
+    TaskStatus::Pending,
+    TaskStatus::Draft,
+    TaskStatus::Done,
+    TaskStatus::Skipped,
+];
+
+const CSV_HEADER: [&str; 13] = [
+    "id",
+    "subsystem",
+    "discipline",
+    "title",
+    "status",
+    "priority",
+    "depends_on",
+    "tags",
+    "acceptance_criteria",
+    "estimated_turns",
+    "created",
+    "updated",
+    "completed",
+];
+
+fn status_label(status: TaskStatus) -> &'static str {
+    match status {
+        TaskStatus::Draft => "Draft",
+        TaskStatus::Pending => "Pending",
+        TaskStatus::InProgress => "In progress",
+        TaskStatus::Done => "Done",
+        TaskStatus::Blocked => "Blocked",
+        TaskStatus::Skipped => "Skipped",
+    }
+}
+
+/// The app's light-theme status colors (`--status-*` in index.css) as hex.
+fn status_color(status: TaskStatus) -> &'static str {
+    match status {
+        TaskStatus::Pending => "#638fd8",
+        TaskStatus::InProgress => "#ca8c00",
+        TaskStatus::Done => "#139948",
+        TaskStatus::Blocked => "#de3b3d",
+        TaskStatus::Draft | TaskStatus::Skipped => "#686ca0",
+    }
+}
+
+/// Collapse line breaks and runs of whitespace so text fits on one Markdown line.
+fn inline(s: &str) -> String {
+    s.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_owned()
+    }
+}
+
+fn dot_escape(s: &str) -> String {
+    inline(s).replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Mermaid labels are quoted and take `#code;` entities, so `#` itself must be encoded.
+fn mermaid_escape(s: &str) -> String {
+    let mut escaped = String::new();
+    for c in inline(s).chars() {
+        match c {
+            '#' => escaped.push_str("#35;"),
+            '"' => escaped.push_str("#quot;"),
+            '<' => escaped.push_str("#lt;"),
+            '>' => escaped.push_str("#gt;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn join_ids(ids: &[u32], separator: &str) -> String {
+    ids.iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+fn markdown_task(output: &mut String, task: &Task) {
+    let mut facts = vec![format!("`{}`", task.discipline)];
+    if let Some(priority) = task.priority {
+        facts.push(format!("{} priority", priority.as_str()));
+    }
+    if !task.depends_on.is_empty() {
+        let deps: Vec<String> = task.depends_on.iter().map(|id| format!("#{id}")).collect();
+        facts.push(format!("depends on {}", deps.join(", ")));
+    }
+    output.push_str(&format!(
+        "- **#{} {}** · {}\n",
+        task.id,
+        inline(&task.title),
+        facts.join(" · ")
+    ));
+
+    if !task.acceptance_criteria.is_empty() {
+        output.push_str("  - Acceptance criteria:\n");
+        let check = if task.status == TaskStatus::Done {
+            'x'
+        } else {
+            ' '
+        };
+        for criterion in &task.acceptance_criteria {
+            output.push_str(&format!("    - [{check}] {}\n", inline(criterion)));
+        }
+    }
+    if !task.signals.is_empty() {
+        output.push_str("  - Latest signals:\n");
+        for signal in task.signals.iter().rev().take(LATEST_SIGNALS) {
+            let verb = signal.signal_verb.as_deref().unwrap_or("signal");
+            let created = signal
+                .created
+                .as_deref()
+                .map(|created| format!(" ({created})"))
+                .unwrap_or_default();
+            output.push_str(&format!(
+                "    - {verb}{created}: {}\n",
+                inline(&signal.body)
+            ));
+        }
+    }
+}
+
+impl SqliteDb {
+    pub fn export_report(&self, format: ReportFormat) -> String {
+        match format {
+            ReportFormat::Markdown => self.export_markdown_report(),
+            ReportFormat::Csv => self.export_tasks_csv(),
+            ReportFormat::Dot => self.export_dependency_dot(),
+            ReportFormat::Mermaid => self.export_dependency_mermaid(),
+        }
+    }
+
+    /// Tasks grouped by subsystem (by name) and status, with acceptance criteria and the
+    /// latest signals.
+    pub fn export_markdown_report(&self) -> String {
+        let meta = self.get_project_info();
+        let tasks = self.get_tasks();
+        let mut output = format!("# {}\n\n", inline(&meta.title));
+        if let Some(description) = &meta.description {
+            output.push_str(&format!("{}\n\n", description.trim()));
+        }
+
+        let counts: Vec<String> = STATUS_ORDER
+            .iter()
+            .filter_map(|status| {
+                let count = tasks.iter().filter(|t| t.status == *status).count();
+                (count > 0).then(|| format!("{count} {}", status_label(*status).to_lowercase()))
+            })
+            .collect();
+        output.push_str(&format!("{} tasks", tasks.len()));
+        if !counts.is_empty() {
+            output.push_str(&format!(": {}", counts.join(", ")));
+        }
+        output.push_str(".\n");
+
+        for subsystem in self.get_subsystems() {
+            output.push_str(&format!(
+                "\n## {} ({})\n",
+                inline(&subsystem.display_name),
+                subsystem.acronym
+            ));
+            if let Some(description) = &subsystem.description {
+                output.push_str(&format!("\n{}\n", description.trim()));
+            }
+            let subsystem_tasks: Vec<&Task> = tasks
+                .iter()
+                .filter(|t| t.subsystem == subsystem.name)
+                .collect();
+            if subsystem_tasks.is_empty() {
+                output.push_str("\n_No tasks._\n");
+                continue;
+            }
+            for status in STATUS_ORDER {
+                let group: Vec<&&Task> = subsystem_tasks
+                    .iter()
+                    .filter(|t| t.status == status)
+                    .collect();
+                if group.is_empty() {
+                    continue;
+                }
+                output.push_str(&format!("\n### {}\n\n", status_label(status)));
+                for task in group {
+                    markdown_task(&mut output, task);
+                }
+            }
+        }
+        output
+    }
+
+    /// One RFC 4180 row per task, ordered by id. List columns are joined with `; `.
+    pub fn export_tasks_csv(&self) -> String {
+        let mut output = CSV_HEADER.join(",");
+        output.push_str("\r\n");
+        for task in self.get_tasks() {
+            let row = [
+                task.id.to_string(),
+                task.subsystem.clone(),
+                task.discipline.clone(),
+                task.title.clone(),
+                task.status.as_str().to_owned(),
+                task.priority
+                    .map(|p| p.as_str().to_owned())
+                    .unwrap_or_default(),
+                join_ids(&task.depends_on, "; "),
+                task.tags.join("; "),
+                task.acceptance_criteria.join("; "),
+                task.estimated_turns
+                    .map(|turns| turns.to_string())
+                    .unwrap_or_default(),
+                task.created.clone().unwrap_or_default(),
+                task.updated.clone().unwrap_or_default(),
+                task.completed.clone().unwrap_or_default(),
+            ];
+            let fields: Vec<String> = row.iter().map(|field| csv_field(field)).collect();
+            output.push_str(&fields.join(","));
+            output.push_str("\r\n");
+        }
+        output
+    }
+
+    /// Graphviz digraph with one cluster per subsystem. Edges point from a dependency to
+    /// the task waiting on it.
+    pub fn export_dependency_dot(&self) -> String {
+        let tasks = self.get_tasks();
+        let mut output = String::from("digraph tasks {\n");
+        output.push_str("  rankdir=LR;\n");
+        output.push_str(
+            "  node [shape=box, style=\"rounded,filled\", fontname=\"Helvetica\", fontcolor=\"#ffffff\"];\n",
+        );
+        for subsystem in self.get_subsystems() {
+            let members: Vec<&Task> = tasks
+                .iter()
+                .filter(|t| t.subsystem == subsystem.name)
+                .collect();
+            if members.is_empty() {
+                continue;
+            }
+            output.push_str(&format!("  subgraph cluster_{} {{\n", subsystem.id));
+            output.push_str(&format!(
+                "    label=\"{}\";\n",
+                dot_escape(&subsystem.display_name)
+            ));
+            for task in members {
+                output.push_str(&format!(
+                    "    t{} [label=\"#{} {}\", fillcolor=\"{}\"];\n",
+                    task.id,
+                    task.id,
+                    dot_escape(&task.title),
+                    status_color(task.status)
+                ));
+            }
+            output.push_str("  }\n");
+        }
+        for task in &tasks {
+            for dep in &task.depends_on {
+                output.push_str(&format!("  t{dep} -> t{};\n", task.id));
+            }
+        }
+        output.push_str("}\n");
+        output
+    }
+
+    /// Mermaid flowchart with one subgraph per subsystem and a class per status.
+    pub fn export_dependency_mermaid(&self) -> String {
+        let tasks = self.get_tasks();
+        let mut output = String::from("flowchart LR\n");
+        for subsystem in self.get_subsystems() {
+            let members: Vec<&Task> = tasks
+                .iter()
+                .filter(|t| t.subsystem == subsystem.name)
+                .collect();
+            if members.is_empty() {
+                continue;
+            }
+            output.push_str(&format!(
+                "  subgraph s{}[\"{}\"]\n",
+                subsystem.id,
+                mermaid_escape(&subsystem.display_name)
+            ));
+            for task in members {
+                output.push_str(&format!(
+                    "    t{}[\"#35;{} {}\"]\n",
+                    task.id,
+                    task.id,
+                    mermaid_escape(&task.title)
+                ));
+            }
+            output.push_str("  end\n");
+        }
+        for task in &tasks {
+            for dep in &task.depends_on {
+                output.push_str(&format!("  t{dep} --> t{}\n", task.id));
+            }
+        }
+        for status in STATUS_ORDER {
+            let ids: Vec<String> = tasks
+                .iter()
+                .filter(|t| t.status == status)
+                .map(|t| format!("t{}", t.id))
+                .collect();
+            if ids.is_empty() {
+                continue;
+            }
+            output.push_str(&format!(
+                "  classDef {} fill:{},color:#fff\n",
+                status.as_str(),
+                status_color(status)
+            ));
+            output.push_str(&format!("  class {} {}\n", ids.join(","), status.as_str()));
+        }
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn csv_fields_are_quoted_only_when_needed() {
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field("a, b"), "\"a, b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("two\nlines"), "\"two\nlines\"");
+    }
+
+    #[test]
+    fn mermaid_labels_encode_entity_and_quote_characters() {
+        assert_eq!(
+            mermaid_escape("Fix #12 \"now\"\n<b>"),
+            "Fix #35;12 #quot;now#quot; #lt;b#gt;"
+        );
+    }
+}
@@ -0,0 +1,73 @@
+use crate::types::ReviewSettings;
+use crate::SqliteDb;
+use ralph_errors::{codes, RalphResultExt};
+
+/// Blank agent or model names mean "use the discipline's own".
+fn normalize(value: Option<String>) -> Option<String> {
+    value
+        .map(|value| value.trim().to_owned())
+        .filter(|value| !value.is_empty())
+}
+
+impl SqliteDb {
+    /// Set (or clear, when nothing is set) the review settings for a discipline.
+    pub fn set_discipline_review_settings(
+        &self,
+        discipline: &str,
+        settings: ReviewSettings,
+    ) -> Result<(), String> {
+        let settings = ReviewSettings {
+            required: settings.required,
+            agent: normalize(settings.agent),
+            model: normalize(settings.model),
+        };
+        let discipline_id = self.get_id_from_name("disciplines", discipline)?;
+
+        if settings == ReviewSettings::default() {
+            self.conn
+                .execute(
+                    "DELETE FROM discipline_review_settings WHERE discipline_id = ?1",
+                    [discipline_id],
+                )
+                .ralph_err(
+                    codes::DB_WRITE,
+                    "Failed to clear discipline review settings",
+                )?;
+            return Ok(());
+        }
+
+        self.conn
+            .execute(
+                "INSERT INTO discipline_review_settings (discipline_id, required, agent, model) \
+                 VALUES (?1, ?2, ?3, ?4) \
+                 ON CONFLICT(discipline_id) DO UPDATE SET \
+                 required = excluded.required, agent = excluded.agent, model = excluded.model",
+                rusqlite::params![
+                    discipline_id,
+                    settings.required,
+                    settings.agent,
+                    settings.model
+                ],
+            )
+            .ralph_err(codes::DB_WRITE, "Failed to set discipline review settings")?;
+        Ok(())
+    }
+
+    /// Review settings for a discipline; an unknown or unconfigured one requires no review.
+    pub fn get_discipline_review_settings(&self, discipline: &str) -> ReviewSettings {
+        self.conn
+            .query_row(
+                "SELECT r.required, r.agent, r.model FROM discipline_review_settings r \
+                 JOIN disciplines d ON d.id = r.discipline_id WHERE d.name = ?1",
+                [discipline],
+                |row| {
+                    Ok(ReviewSettings {
+                        required: row.get(0)?,
+                        agent: row.get(1)?,
+                        model: row.get(2)?,
+                    })
+                },
+            )
+            .unwrap_or_default()
+    }
+}
@@ -0,0 +1,185 @@
+use crate::types::SessionLimits;
+use crate::SqliteDb;
+use ralph_errors::{codes, ralph_err, RalphResultExt};
+use rusqlite::OptionalExtension;
+
+/// Idle-output limit when neither the task nor its discipline sets one.
+pub const DEFAULT_SESSION_IDLE_SECS: u32 = 10 * 60;
+/// Wall-clock limit when neither the task nor its discipline sets one and the
+/// task has no `estimated_turns`.
+pub const DEFAULT_SESSION_WALL_CLOCK_SECS: u32 = 60 * 60;
+/// Budget per estimated turn when deriving a wall-clock default from `estimated_turns`.
+const SESSION_SECS_PER_ESTIMATED_TURN: u32 = 3 * 60;
+/// Floor for a wall-clock default derived from `estimated_turns`.
+const MIN_ESTIMATED_SESSION_WALL_CLOCK_SECS: u32 = 15 * 60;
+
+fn validate_limits(limits: SessionLimits) -> Result<(), String> {
+    if limits.wall_clock_secs == Some(0) || limits.idle_secs == Some(0) {
+        return ralph_err!(
+            codes::TASK_VALIDATION,
+            "Session limits must be positive (omit a limit to inherit it)"
+        );
+    }
+    Ok(())
+}
+
+fn default_wall_clock_secs(estimated_turns: Option<u32>) -> u32 {
+    estimated_turns.map_or(DEFAULT_SESSION_WALL_CLOCK_SECS, |turns| {
+        turns
+            .saturating_mul(SESSION_SECS_PER_ESTIMATED_TURN)
+            .max(MIN_ESTIMATED_SESSION_WALL_CLOCK_SECS)
+    })
+}
+
+impl SqliteDb {
+    /// Set (or clear, when both limits are `None`) the session limits for a discipline.
+    pub fn set_discipline_session_limits(
+        &self,
+        discipline: &str,
+        limits: SessionLimits,
+    ) -> Result<(), String> {
+        validate_limits(limits)?;
+        let discipline_id = self.get_id_from_name("disciplines", discipline)?;
+
+        if limits == SessionLimits::default() {
+            self.conn
+                .execute(
+                    "DELETE FROM discipline_session_limits WHERE discipline_id = ?1",
+                    [discipline_id],
+                )
+                .ralph_err(codes::DB_WRITE, "Failed to clear discipline session limits")?;
+            return Ok(());
+        }
+
+        self.conn
+            .execute(
+                "INSERT INTO discipline_session_limits (discipline_id, wall_clock_secs, idle_secs) \
+                 VALUES (?1, ?2, ?3) \
+                 ON CONFLICT(discipline_id) DO UPDATE SET \
+                 wall_clock_secs = excluded.wall_clock_secs, idle_secs = excluded.idle_secs",
+                rusqlite::params![discipline_id, limits.wall_clock_secs, limits.idle_secs],
+            )
+            .ralph_err(codes::DB_WRITE, "Failed to set discipline session limits")?;
+        Ok(())
+    }
+
+    /// Set (or clear, when both limits are `None`) the session limits for a task.
+    pub fn set_task_session_limits(
+        &self,
+        task_id: u32,
+        limits: SessionLimits,
+    ) -> Result<(), String> {
+        validate_limits(limits)?;
+        if !self.check_exists("runtime_tasks", "id", &task_id.to_string())? {
+            return ralph_err!(codes::TASK_OPS, "Task {task_id} does not exist");
+        }
+
+        if limits == SessionLimits::default() {
+            self.conn
+                .execute(
+                    "DELETE FROM task_session_limits WHERE task_id = ?1",
+                    [task_id],
+                )
+                .ralph_err(codes::DB_WRITE, "Failed to clear task session limits")?;
+            return Ok(());
+        }
+
+        self.conn
+            .execute(
+                "INSERT INTO task_session_limits (task_id, wall_clock_secs, idle_secs) \
+                 VALUES (?1, ?2, ?3) \
+                 ON CONFLICT(task_id) DO UPDATE SET \
+                 wall_clock_secs = excluded.wall_clock_secs, idle_secs = excluded.idle_secs",
+                rusqlite::params![task_id, limits.wall_clock_secs, limits.idle_secs],
+            )
+            .ralph_err(codes::DB_WRITE, "Failed to set task session limits")?;
+        Ok(())
+    }
+
+    /// Overrides stored for a discipline (no inheritance applied).
+    pub fn get_discipline_session_limits(&self, discipline: &str) -> Result<SessionLimits, String> {
+        self.conn
+            .query_row(
+                "SELECT l.wall_clock_secs, l.idle_secs FROM discipline_session_limits l \
+                 JOIN disciplines d ON d.id = l.discipline_id WHERE d.name = ?1",
+                [discipline],
+                |row| {
+                    Ok(SessionLimits {
+                        wall_clock_secs: row.get(0)?,
+                        idle_secs: row.get(1)?,
+                    })
+                },
+            )
+            .optional()
+            .ralph_err(codes::DB_READ, "Failed to read discipline session limits")
+            .map(Option::unwrap_or_default)
+    }
+
+    /// Overrides stored for a task (no inheritance applied).
+    pub fn get_task_session_limits(&self, task_id: u32) -> Result<SessionLimits, String> {
+        self.conn
+            .query_row(
+                "SELECT wall_clock_secs, idle_secs FROM task_session_limits WHERE task_id = ?1",
+                [task_id],
+                |row| {
+                    Ok(SessionLimits {
+                        wall_clock_secs: row.get(0)?,
+                        idle_secs: row.get(1)?,
+                    })
+                },
+            )
+            .optional()
+            .ralph_err(codes::DB_READ, "Failed to read task session limits")
+            .map(Option::unwrap_or_default)
+    }
+
+    /// Limits a session for this task should run with.
+    ///
+    /// Each limit resolves independently: task override, then discipline override, then the
+    /// built-in default. The wall-clock default scales with `estimated_turns` when it is set.
+    pub fn effective_session_limits(&self, task_id: u32) -> Result<SessionLimits, String> {
+        type Row = (
+            Option<u32>,
+            Option<u32>,
+            Option<u32>,
+            Option<u32>,
+            Option<u32>,
+        );
+        let row: Option<Row> = self
+            .conn
+            .query_row(
+                "SELECT tl.wall_clock_secs, tl.idle_secs, dl.wall_clock_secs, dl.idle_secs, \
+                 td.estimated_turns \
+                 FROM runtime_tasks rt \
+                 JOIN task_details td ON td.id = rt.details_id \
+                 LEFT JOIN task_session_limits tl ON tl.task_id = rt.id \
+                 LEFT JOIN discipline_session_limits dl ON dl.discipline_id = td.discipline_id \
+                 WHERE rt.id = ?1",
+                [task_id],
+                |row| {
+                    Ok((
+                        row.get(0)?,
+                        row.get(1)?,
+                        row.get(2)?,
+                        row.get(3)?,
+                        row.get(4)?,
+                    ))
+                },
+            )
+            .optional()
+            .ralph_err(codes::DB_READ, "Failed to read session limits")?;
+
+        let Some((task_wall, task_idle, disc_wall, disc_idle, estimated_turns)) = row else {
+            return ralph_err!(codes::TASK_OPS, "Task {task_id} does not exist");
+        };
+
+        Ok(SessionLimits {
+            wall_clock_secs: Some(
+                task_wall
+                    .or(disc_wall)
+                    .unwrap_or_else(|| default_wall_clock_secs(estimated_turns)),
+            ),
+            idle_secs: Some(task_idle.or(disc_idle).unwrap_or(DEFAULT_SESSION_IDLE_SECS)),
+        })
+    }
+}
@@ -67,7 +67,7 @@ pub struct BlockedSignalInput {
 }
 
 impl SqliteDb {
-    fn ensure_agent_session_exists(
+    pub(crate) fn ensure_agent_session_exists(
         &self,
         session_id: &str,
         task_id: u32,
@@ -166,4 +166,34 @@ impl SqliteDb {
             },
         )
     }
+
+    /// Reference documents (specs, designs) attached to a subsystem.
+    pub fn get_subsystem_knowledge_paths(&self, name: &str) -> Vec<String> {
+        self.get_subsystem_paths(
+            "SELECT kp.path FROM subsystem_knowledge_paths kp \
+             JOIN subsystems s ON kp.subsystem_id = s.id \
+             WHERE s.name = ?1 ORDER BY kp.id",
+            name,
+        )
+    }
+
+    /// Source files an agent working on the subsystem should always see.
+    pub fn get_subsystem_context_files(&self, name: &str) -> Vec<String> {
+        self.get_subsystem_paths(
+            "SELECT cf.file_path FROM subsystem_context_files cf \
+             JOIN subsystems s ON cf.subsystem_id = s.id \
+             WHERE s.name = ?1 ORDER BY cf.id",
+            name,
+        )
+    }
+
+    fn get_subsystem_paths(&self, sql: &str, name: &str) -> Vec<String> {
+        let Ok(mut stmt) = self.conn.prepare(sql) else {
+            return vec![];
+        };
+        stmt.query_map([name], |row| row.get(0)).map_or_else(
+            |_| vec![],
+            |rows| rows.filter_map(std::result::Result::ok).collect(),
+        )
+    }
 }
@@ -0,0 +1,398 @@
+use crate::types::{
+    Priority, TaskInput, TaskTemplate, TaskTemplateInput, TaskTemplateInstantiateInput,
+};
+use crate::SqliteDb;
+use minijinja::{Environment, UndefinedBehavior};
+use ralph_errors::{codes, ralph_err, RalphResultExt};
+use std::collections::{BTreeSet, HashMap};
+
+/// Placeholders are minijinja variables, the same syntax prompt section templates use.
+fn placeholder_env() -> Environment<'static> {
+    let mut env = Environment::new();
+    env.set_undefined_behavior(UndefinedBehavior::Strict);
+    env.set_keep_trailing_newline(true);
+    env
+}
+
+fn invalid_template(err: &minijinja::Error) -> String {
+    ralph_errors::err_string(
+        codes::TASK_VALIDATION,
+        format!("Invalid template placeholders: {err}"),
+    )
+}
+
+/// Placeholder names used in `texts`, sorted. Fails when a text isn't a valid template.
+fn placeholders<'a>(texts: impl IntoIterator<Item = &'a str>) -> Result<Vec<String>, String> {
+    let env = placeholder_env();
+    let mut names = BTreeSet::new();
+    for text in texts {
+        let template = env
+            .template_from_str(text)
+            .map_err(|err| invalid_template(&err))?;
+        names.extend(template.undeclared_variables(false));
+    }
+    Ok(names.into_iter().collect())
+}
+
+/// `text` rendered with `params` as its variables.
+fn fill_placeholders(text: &str, params: &HashMap<String, String>) -> Result<String, String> {
+    placeholder_env()
+        .render_str(text, params)
+        .map_err(|err| invalid_template(&err))
+}
+
+/// `text` as a template that renders back to itself, with every `{{`, `{%` and `{#`
+/// turned into a string expression so none of it reads as a placeholder.
+fn escape_placeholders(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, chars.peek()) {
+            ('{', Some(&next @ ('{' | '%' | '#'))) => {
+                chars.next();
+                escaped.push_str(&format!("{{{{ \"{{{next}\" }}}}"));
+            }
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+impl SqliteDb {
+    fn template_discipline_id(&self, discipline: &str) -> Result<i64, String> {
+        if discipline.trim().is_empty() {
+            return ralph_err!(codes::TASK_VALIDATION, "Discipline name cannot be empty");
+        }
+        self.get_id_from_name("disciplines", discipline)
+            .map_err(|_| {
+                format!(
+                    "[R-{}] Discipline '{discipline}' does not exist. Create it first.",
+                    codes::TASK_VALIDATION
+                )
+            })
+    }
+
+    fn template_details_id(&self, id: u32) -> Result<i64, String> {
+        self.conn
+            .query_row(
+                "SELECT details_id FROM task_templates WHERE id = ?1",
+                [id],
+                |row| row.get(0),
+            )
+            .map_err(|_| {
+                ralph_errors::err_string(
+                    codes::TASK_OPS,
+                    format!("Task template {id} does not exist"),
+                )
+            })
+    }
+
+    fn query_task_templates<P: rusqlite::Params>(
+        &self,
+        filter: &str,
+        params: P,
+    ) -> Vec<TaskTemplate> {
+        let Ok(mut stmt) = self.conn.prepare(&format!(
+            "SELECT tt.id, td.discipline_id, td.title, td.description, td.priority, td.hints, \
+             td.estimated_turns, td.agent, td.model, td.effort, td.thinking, td.pseudocode, \
+             td.created, td.updated, \
+             (SELECT COUNT(*) FROM runtime_tasks rt WHERE rt.template_id = tt.id) AS pulled_count, \
+             tt.is_active \
+             FROM task_templates tt \
+             JOIN task_details td ON tt.details_id = td.id \
+             WHERE {filter} \
+             ORDER BY tt.id"
+        )) else {
+            return vec![];
+        };
+
+        let Ok(rows) = stmt.query_map(params, |row| {
+            let priority_str: Option<String> = row.get(4).ok();
+            let title: String = row.get(2)?;
+            let description: Option<String> = row.get(3)?;
+            Ok(TaskTemplate {
+                id: row.get(0)?,
+                discipline_id: row.get(1)?,
+                placeholders: placeholders(
+                    std::iter::once(title.as_str()).chain(description.as_deref()),
+                )
+                .unwrap_or_default(),
+                title,
+                description,
+                priority: priority_str.and_then(|s| Priority::parse(&s)),
+                hints: row.get(5)?,
+                estimated_turns: row.get(6)?,
+                agent: row.get(7)?,
+                model: row.get(8)?,
+                effort: row.get(9)?,
+                thinking: row.get(10)?,
+                pseudocode: row.get(11)?,
+                created: row.get(12)?,
+                updated: row.get(13)?,
+                pulled_count: row.get(14)?,
+                is_active: row.get(15)?,
+            })
+        }) else {
+            return vec![];
+        };
+
+        rows.filter_map(std::result::Result::ok).collect()
+    }
+
+    pub fn get_active_task_templates_for_discipline(
+        &self,
+        discipline_id: u32,
+    ) -> Vec<TaskTemplate> {
+        self.query_task_templates(
+            "tt.is_active = 1 AND td.discipline_id = ?1",
+            [discipline_id],
+        )
+    }
+
+    /// Every template, active or not.
+    pub fn get_task_templates(&self) -> Vec<TaskTemplate> {
+        self.query_task_templates("1 = 1", [])
+    }
+
+    pub fn get_task_template(&self, id: u32) -> Option<TaskTemplate> {
+        self.query_task_templates("tt.id = ?1", [id]).pop()
+    }
+
+    pub fn create_task_template(&self, input: TaskTemplateInput) -> Result<u32, String> {
+        if input.title.trim().is_empty() {
+            return ralph_err!(codes::TASK_VALIDATION, "Template title cannot be empty");
+        }
+        placeholders(std::iter::once(input.title.as_str()).chain(input.description.as_deref()))?;
+        let discipline_id = self.template_discipline_id(&input.discipline)?;
+        let now = self.now().format("%Y-%m-%d").to_string();
+
+        self.with_transaction(|db| db.insert_task_template(discipline_id, &input, &now))
+    }
+
+    fn insert_task_template(
+        &self,
+        discipline_id: i64,
+        input: &TaskTemplateInput,
+        now: &str,
+    ) -> Result<u32, String> {
+        self.conn
+            .execute(
+                "INSERT INTO task_details (discipline_id, title, description, priority, hints, \
+                 estimated_turns, agent, model, effort, thinking, pseudocode, created) \
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+                rusqlite::params![
+                    discipline_id,
+                    input.title,
+                    input.description,
+                    input.priority.map(|p| p.as_str().to_owned()),
+                    input.hints,
+                    input.estimated_turns,
+                    input.agent,
+                    input.model,
+                    input.effort,
+                    input.thinking,
+                    input.pseudocode,
+                    now,
+                ],
+            )
+            .ralph_err(codes::DB_WRITE, "Failed to insert template details")?;
+        let details_id = self.conn.last_insert_rowid();
+
+        self.conn
+            .execute(
+                "INSERT INTO task_templates (details_id, is_active, created) VALUES (?1, 1, ?2)",
+                rusqlite::params![details_id, now],
+            )
+            .ralph_err(codes::DB_WRITE, "Failed to insert task template")?;
+
+        Ok(self.conn.last_insert_rowid() as u32)
+    }
+
+    /// Replace a template's details. Tasks instantiated from it keep their own copy.
+    pub fn update_task_template(&self, id: u32, input: TaskTemplateInput) -> Result<(), String> {
+        if input.title.trim().is_empty() {
+            return ralph_err!(codes::TASK_VALIDATION, "Template title cannot be empty");
+        }
+        placeholders(std::iter::once(input.title.as_str()).chain(input.description.as_deref()))?;
+        let details_id = self.template_details_id(id)?;
+        let discipline_id = self.template_discipline_id(&input.discipline)?;
+        let now = self.now().format("%Y-%m-%d").to_string();
+
+        self.conn
+            .execute(
+                "UPDATE task_details SET discipline_id = ?1, title = ?2, description = ?3, \
+                 priority = ?4, hints = ?5, estimated_turns = ?6, agent = ?7, model = ?8, \
+                 effort = ?9, thinking = ?10, pseudocode = ?11, updated = ?12 WHERE id = ?13",
+                rusqlite::params![
+                    discipline_id,
+                    input.title,
+                    input.description,
+                    input.priority.map(|p| p.as_str().to_owned()),
+                    input.hints,
+                    input.estimated_turns,
+                    input.agent,
+                    input.model,
+                    input.effort,
+                    input.thinking,
+                    input.pseudocode,
+                    now,
+                    details_id,
+                ],
+            )
+            .ralph_err(codes::DB_WRITE, "Failed to update template details")?;
+        Ok(())
+    }
+
+    /// Inactive templates are hidden from disciplines and can't be instantiated.
+    pub fn set_task_template_active(&self, id: u32, active: bool) -> Result<(), String> {
+        let affected = self
+            .conn
+            .execute(
+                "UPDATE task_templates SET is_active = ?1 WHERE id = ?2",
+                rusqlite::params![active, id],
+            )
+            .ralph_err(codes::DB_WRITE, "Failed to update task template")?;
+        if affected == 0 {
+            return ralph_err!(codes::TASK_OPS, "Task template {id} does not exist");
+        }
+        Ok(())
+    }
+
+    /// Create a runtime task from an active template, rendering its title and description
+    /// with `params` as their `{{ name }}` variables. Every placeholder needs a value.
+    pub fn instantiate_task_template(
+        &self,
+        input: TaskTemplateInstantiateInput,
+    ) -> Result<u32, String> {
+        let template_id = input.template_id;
+        let Some(template) = self.get_task_template(template_id) else {
+            return ralph_err!(
+                codes::TASK_OPS,
+                "Task template {template_id} does not exist"
+            );
+        };
+        if !template.is_active {
+            return ralph_err!(codes::TASK_OPS, "Task template {template_id} is inactive");
+        }
+        let missing: Vec<&str> = template
+            .placeholders
+            .iter()
+            .filter(|name| !input.params.contains_key(*name))
+            .map(String::as_str)
+            .collect();
+        if !missing.is_empty() {
+            return ralph_err!(
+                codes::TASK_VALIDATION,
+                "Missing template parameters: {}",
+                missing.join(", ")
+            );
+        }
+
+        let discipline: String = self
+            .conn
+            .query_row(
+                "SELECT name FROM disciplines WHERE id = ?1",
+                [template.discipline_id],
+                |row| row.get(0),
+            )
+            .ralph_err(codes::DB_READ, "Failed to read template discipline")?;
+
+        let title = fill_placeholders(&template.title, &input.params)?;
+        let description = template
+            .description
+            .as_deref()
+            .map(|description| fill_placeholders(description, &input.params))
+            .transpose()?;
+
+        self.with_transaction(|db| {
+            let task_id = db.create_task(TaskInput {
+                subsystem: input.subsystem,
+                discipline,
+                title,
+                description,
+                status: input.status,
+                priority: template.priority,
+                hints: template.hints,
+                estimated_turns: template.estimated_turns,
+                agent: template.agent,
+                model: template.model,
+                effort: template.effort,
+                thinking: template.thinking,
+                ..Default::default()
+            })?;
+
+            db.conn
+                .execute(
+                    "UPDATE runtime_tasks SET template_id = ?1 WHERE id = ?2",
+                    rusqlite::params![template_id, task_id],
+                )
+                .ralph_err(codes::DB_WRITE, "Failed to link task to template")?;
+            db.conn
+                .execute(
+                    "UPDATE task_details SET pseudocode = ?1 \
+                     WHERE id = (SELECT details_id FROM runtime_tasks WHERE id = ?2)",
+                    rusqlite::params![template.pseudocode, task_id],
+                )
+                .ralph_err(codes::DB_WRITE, "Failed to copy template pseudocode")?;
+
+            Ok(task_id)
+        })
+    }
+
+    /// Copy a task's details into a new active template. Template syntax already in the
+    /// title or description is escaped, so the template reproduces the task's text.
+    pub fn save_task_as_template(&self, task_id: u32) -> Result<u32, String> {
+        let Some(task) = self.get_task_by_id(task_id) else {
+            return ralph_err!(codes::TASK_OPS, "Task {task_id} does not exist");
+        };
+        self.create_task_template(TaskTemplateInput {
+            discipline: task.discipline,
+            title: escape_placeholders(&task.title),
+            description: task.description.as_deref().map(escape_placeholders),
+            priority: task.priority,
+            hints: task.hints,
+            estimated_turns: task.estimated_turns,
+            agent: task.agent,
+            model: task.model,
+            effort: task.effort,
+            thinking: task.thinking,
+            pseudocode: task.pseudocode,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_placeholders_sorted_without_duplicates() {
+        let names = placeholders(["Add {{ endpoint }} for {{service}}", "Call {{endpoint}}"]);
+        assert_eq!(names.unwrap(), vec!["endpoint", "service"]);
+    }
+
+    #[test]
+    fn fills_placeholders_with_the_template_engine() {
+        let params = HashMap::from([("name".to_owned(), "login".to_owned())]);
+        assert_eq!(
+            fill_placeholders("{{ name | upper }} {% if name %}ok{% endif %}\n", &params).unwrap(),
+            "LOGIN ok\n"
+        );
+        assert!(fill_placeholders("{{ other }}", &params).is_err());
+    }
+
+    #[test]
+    fn escaped_text_renders_back_to_itself() {
+        let text = "<div style={{ color: 'red' }}>{% if %} {# x #} {{{a}}} {\n";
+        let escaped = escape_placeholders(text);
+        assert!(placeholders([escaped.as_str()]).unwrap().is_empty());
+        assert_eq!(fill_placeholders(&escaped, &HashMap::new()).unwrap(), text);
+    }
+
+    #[test]
+    fn rejects_invalid_template_syntax() {
+        let err = placeholders(["{{ not a name }}"]).unwrap_err();
+        assert!(err.contains("Invalid template placeholders"), "{err}");
+        assert!(placeholders(["{{"]).is_err());
+    }
+}
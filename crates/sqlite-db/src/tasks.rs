@@ -4,7 +4,7 @@ use ralph_errors::{codes, ralph_err, RalphResultExt};
 use std::collections::{HashMap, HashSet};
 
 impl SqliteDb {
-    fn delete_task_related_rows(&self, table: &str, task_id: u32) -> Result<(), String> {
+    pub(crate) fn delete_task_related_rows(&self, table: &str, task_id: u32) -> Result<(), String> {
         self.conn
             .execute(
                 &format!("DELETE FROM {table} WHERE task_id = ?1"),
@@ -471,7 +471,7 @@ impl SqliteDb {
                  td.priority, t.created, t.updated, t.completed, td.hints, td.estimated_turns, \
                  t.provenance, td.agent, td.model, td.effort, td.thinking, td.pseudocode, t.enriched_at, \
                  f.name, f.display_name, f.acronym, \
-                 d.name, d.display_name, d.acronym, d.icon, d.color \
+                 d.name, d.display_name, d.acronym, d.icon, d.color, t.archived_at \
                  FROM runtime_tasks t \
                  JOIN task_details td ON t.details_id = td.id \
                  JOIN subsystems f ON t.subsystem_id = f.id \
@@ -502,7 +502,9 @@ impl SqliteDb {
         Some(task)
     }
 
-    pub fn get_task_list_items(&self) -> Result<Vec<TaskListItem>, String> {
+    /// Summaries of all tasks for list views. Archived tasks, and tasks in archived
+    /// subsystems, are only included with `show_archived`.
+    pub fn get_task_list_items(&self, show_archived: bool) -> Result<Vec<TaskListItem>, String> {
         let mut stmt = self
             .conn
             .prepare(
@@ -519,17 +521,19 @@ impl SqliteDb {
                  d.display_name, \
                  d.acronym, \
                  d.icon, \
-                 d.color \
+                 d.color, \
+                 t.archived_at \
                  FROM runtime_tasks t \
                  JOIN task_details td ON t.details_id = td.id \
                  JOIN subsystems f ON t.subsystem_id = f.id \
                  JOIN disciplines d ON td.discipline_id = d.id \
+                 WHERE ?1 OR (t.archived_at IS NULL AND f.status != 'archived') \
                  ORDER BY t.id",
             )
             .ralph_err(codes::DB_READ, "Failed to prepare task list query")?;
 
         let rows = stmt
-            .query_map([], |row| self.row_to_task_list_item(row))
+            .query_map([show_archived], |row| self.row_to_task_list_item(row))
             .ralph_err(codes::DB_READ, "Failed to query task list rows")?;
 
         let mut tasks: Vec<TaskListItem> = Vec::new();
@@ -616,7 +620,7 @@ impl SqliteDb {
              td.priority, t.created, t.updated, t.completed, td.hints, td.estimated_turns, \
              t.provenance, td.agent, td.model, td.effort, td.thinking, td.pseudocode, t.enriched_at, \
              f.name, f.display_name, f.acronym, \
-             d.name, d.display_name, d.acronym, d.icon, d.color \
+             d.name, d.display_name, d.acronym, d.icon, d.color, t.archived_at \
              FROM runtime_tasks t \
              JOIN task_details td ON t.details_id = td.id \
              JOIN subsystems f ON t.subsystem_id = f.id \
@@ -661,49 +665,6 @@ impl SqliteDb {
             .collect()
     }
 
-    pub fn get_active_task_templates_for_discipline(
-        &self,
-        discipline_id: u32,
-    ) -> Vec<TaskTemplate> {
-        let Ok(mut stmt) = self.conn.prepare(
-            "SELECT tt.id, td.discipline_id, td.title, td.description, td.priority, td.hints, \
-             td.estimated_turns, td.agent, td.model, td.effort, td.thinking, td.pseudocode, \
-             td.created, td.updated, \
-             (SELECT COUNT(*) FROM runtime_tasks rt WHERE rt.template_id = tt.id) AS pulled_count \
-             FROM task_templates tt \
-             JOIN task_details td ON tt.details_id = td.id \
-             WHERE tt.is_active = 1 AND td.discipline_id = ?1 \
-             ORDER BY tt.id",
-        ) else {
-            return vec![];
-        };
-
-        let Ok(rows) = stmt.query_map([discipline_id], |row| {
-            let priority_str: Option<String> = row.get(4).ok();
-            Ok(TaskTemplate {
-                id: row.get(0)?,
-                discipline_id: row.get(1)?,
-                title: row.get(2)?,
-                description: row.get(3)?,
-                priority: priority_str.and_then(|s| Priority::parse(&s)),
-                hints: row.get(5)?,
-                estimated_turns: row.get(6)?,
-                agent: row.get(7)?,
-                model: row.get(8)?,
-                effort: row.get(9)?,
-                thinking: row.get(10)?,
-                pseudocode: row.get(11)?,
-                created: row.get(12)?,
-                updated: row.get(13)?,
-                pulled_count: row.get(14)?,
-            })
-        }) else {
-            return vec![];
-        };
-
-        rows.filter_map(std::result::Result::ok).collect()
-    }
-
     #[allow(clippy::unused_self)]
     fn row_to_task(&self, row: &rusqlite::Row) -> Task {
         let status_str: String = row.get(5).unwrap_or_else(|_| "pending".to_owned());
@@ -735,6 +696,7 @@ impl SqliteDb {
             thinking: row.get(16).ok(),
             pseudocode: row.get(17).ok(),
             enriched_at: row.get(18).ok(),
+            archived_at: row.get(27).ok(),
             signals: vec![],
             subsystem_display_name: row.get(20).unwrap_or_default(),
             subsystem_acronym: row.get(21).unwrap_or_default(),
@@ -804,6 +766,7 @@ impl SqliteDb {
             acceptance_criteria_count: 0,
             signal_count: 0,
             provenance,
+            archived_at: row.get(14)?,
             subsystem_display_name: row.get(8)?,
             subsystem_acronym: row.get(9)?,
             discipline_display_name: row.get(10)?,
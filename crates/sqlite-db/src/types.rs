@@ -211,6 +211,9 @@ pub struct Task {
     pub pseudocode: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub enriched_at: Option<String>,
+    /// When the task was archived. Tasks in an archived subsystem are hidden as well.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub archived_at: Option<String>,
     #[serde(default)]
     pub signals: Vec<TaskSignal>,
     pub subsystem_display_name: String,
@@ -242,6 +245,8 @@ pub struct TaskListItem {
     pub signal_count: u32,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub provenance: Option<TaskProvenance>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub archived_at: Option<String>,
     pub subsystem_display_name: String,
     pub subsystem_acronym: String,
     pub discipline_display_name: String,
@@ -280,6 +285,34 @@ pub struct TaskTemplate {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub updated: Option<String>,
     pub pulled_count: u32,
+    pub is_active: bool,
+    /// `{{ name }}` variables used in the title and description, sorted.
+    pub placeholders: Vec<String>,
+}
+
+/// Creates or replaces the details of a task template.
+#[derive(Debug, Clone, Default)]
+pub struct TaskTemplateInput {
+    pub discipline: String,
+    pub title: String,
+    pub description: Option<String>,
+    pub priority: Option<Priority>,
+    pub hints: Option<String>,
+    pub estimated_turns: Option<u32>,
+    pub agent: Option<String>,
+    pub model: Option<String>,
+    pub effort: Option<String>,
+    pub thinking: Option<bool>,
+    pub pseudocode: Option<String>,
+}
+
+/// Creates a runtime task in `subsystem` from a template, filling its placeholders.
+#[derive(Debug, Clone, Default)]
+pub struct TaskTemplateInstantiateInput {
+    pub template_id: u32,
+    pub subsystem: String,
+    pub params: HashMap<String, String>,
+    pub status: Option<TaskStatus>,
 }
 
 #[ipc_type]
@@ -515,6 +548,16 @@ pub struct AgentSession {
     pub output_bytes: Option<u32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error_text: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pid: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub worktree_branch: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub worktree_path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub base_commit: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub head_commit: Option<String>,
 }
 
 #[ipc_type]
@@ -552,6 +595,234 @@ pub struct AgentSessionUpdateInput {
     pub error_text: Option<String>,
 }
 
+/// The rendered prompt a session started with.
+#[ipc_type]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentSessionPrompt {
+    pub session_id: String,
+    pub session_number: u32,
+    pub recipe: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub config_name: Option<String>,
+    /// Sections that rendered, in prompt order.
+    pub sections: Vec<String>,
+    pub prompt: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prompt_hash: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mcp_config: Option<String>,
+    pub created: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct AgentSessionPromptInput {
+    pub session_id: String,
+    pub recipe: String,
+    pub config_name: Option<String>,
+    pub sections: Vec<String>,
+    pub prompt: String,
+    pub prompt_hash: String,
+    pub mcp_config: Option<String>,
+}
+
+/// Closes a running session row, whoever started it.
+#[derive(Debug, Clone)]
+pub struct AgentSessionFinishInput {
+    pub id: String,
+    /// One of `finished`, `crashed`, `timed_out`, `cancelled`.
+    pub status: String,
+    pub exit_code: Option<i32>,
+    pub output_bytes: Option<u32>,
+    pub error_text: Option<String>,
+}
+
+/// Wall-clock and idle-output limits for an agent session, in seconds.
+/// `None` means no override at this level (or no limit, once resolved).
+#[ipc_type]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionLimits {
+    pub wall_clock_secs: Option<u32>,
+    pub idle_secs: Option<u32>,
+}
+
+/// Whether a discipline's tasks get a review session after they report `done`, and who reviews
+/// them. `None` agent or model means the discipline's own.
+#[ipc_type]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReviewSettings {
+    #[serde(default)]
+    pub required: bool,
+    pub agent: Option<String>,
+    pub model: Option<String>,
+}
+
+/// Formats `export_report` renders the task plan in, for readers outside the app.
+#[ipc_type]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportFormat {
+    /// Tasks grouped by subsystem and status, with acceptance criteria and latest signals.
+    Markdown,
+    /// One row per task, for spreadsheets.
+    Csv,
+    /// Graphviz dependency graph, colored by status.
+    Dot,
+    /// Mermaid flowchart of the dependency graph, colored by status.
+    Mermaid,
+}
+
+/// Why a database backup was taken; part of the backup's file name.
+#[ipc_type]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackupReason {
+    /// Asked for by the user.
+    Manual,
+    /// Before an execution run started.
+    Execution,
+    /// Before schema migrations ran on open.
+    Migration,
+    /// The state a restore replaced, so the restore itself can be undone.
+    Restore,
+}
+
+impl BackupReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Manual => "manual",
+            Self::Execution => "execution",
+            Self::Migration => "migration",
+            Self::Restore => "restore",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "manual" => Some(Self::Manual),
+            "execution" => Some(Self::Execution),
+            "migration" => Some(Self::Migration),
+            "restore" => Some(Self::Restore),
+            _ => None,
+        }
+    }
+}
+
+/// A snapshot in `.ralph/db/backups/`.
+#[ipc_type]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DbBackup {
+    /// File name; what `restore_backup` takes.
+    pub name: String,
+    pub reason: BackupReason,
+    pub created: String,
+    pub size_bytes: u64,
+    /// `None` when the file can't be read as a project database.
+    pub task_count: Option<u32>,
+}
+
+/// Counts of what is waiting on a person in a project.
+#[ipc_type]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectActivity {
+    /// Asks from agents that have no answer yet.
+    pub pending_asks: u32,
+    pub running_sessions: u32,
+    pub blocked_tasks: u32,
+}
+
+/// Labels of the edits `undo_edit` and `redo_edit` would replay, most recent first.
+#[ipc_type]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EditHistory {
+    pub undo: Vec<String>,
+    pub redo: Vec<String>,
+}
+
+/// How `import_prd_yaml` treats what is already in the database.
+#[ipc_type]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PrdImportMode {
+    /// Subsystems and disciplines are matched by name and tasks by id; matches are
+    /// overwritten, everything else is created, and nothing is removed.
+    #[default]
+    Merge,
+    /// Every task and subsystem is removed first. Disciplines are kept, since the
+    /// export does not carry their configuration.
+    Replace,
+}
+
+/// What an import changed, or would change on a dry run.
+#[ipc_type]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrdImportReport {
+    pub disciplines_created: u32,
+    pub disciplines_updated: u32,
+    pub subsystems_created: u32,
+    pub subsystems_updated: u32,
+    pub subsystems_removed: u32,
+    pub tasks_created: u32,
+    pub tasks_updated: u32,
+    pub tasks_removed: u32,
+}
+
+/// A text mirror file that can't be imported as it stands, e.g. one a git merge left
+/// with conflict markers.
+#[ipc_type]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MirrorConflict {
+    /// Path relative to the mirror directory, e.g. `tasks/12.yaml`.
+    pub path: String,
+    /// 1-based line the problem is on, when it has one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<u32>,
+    pub message: String,
+}
+
+/// What `import_mirror_files` changed. When there are conflicts nothing was imported.
+#[ipc_type]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MirrorImportReport {
+    /// Files whose entity was created or updated from the text.
+    pub applied: Vec<String>,
+    /// Entities removed because their file is gone, e.g. `task 12`.
+    pub removed: Vec<String>,
+    pub conflicts: Vec<MirrorConflict>,
+}
+
+/// Enrichment written for a draft task by a batch run, waiting for a human to approve it.
+#[ipc_type]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnrichmentProposal {
+    pub task_id: u32,
+    pub pseudocode: String,
+    pub acceptance_criteria: Vec<String>,
+    pub context_files: Vec<String>,
+    /// Model that wrote the proposal.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    pub created: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct EnrichmentProposalInput {
+    pub task_id: u32,
+    pub pseudocode: String,
+    pub acceptance_criteria: Vec<String>,
+    pub context_files: Vec<String>,
+    pub model: Option<String>,
+}
+
 #[ipc_type]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -607,6 +878,7 @@ mod tests {
             thinking: None,
             pseudocode: None,
             enriched_at: None,
+            archived_at: None,
             signals: vec![],
             subsystem_display_name: "Core".to_owned(),
             subsystem_acronym: "CORE".to_owned(),
@@ -0,0 +1,221 @@
+//! Database backups: taken on demand and before migrations, pruned to the retention limit,
+//! and restored with the replaced state kept as a backup of its own.
+
+use sqlite_db::{
+    backups_dir, BackupReason, Clock, DisciplineInput, FixedClock, SqliteDb, SubsystemInput,
+    TaskInput, BACKUP_RETENTION,
+};
+use std::path::{Path, PathBuf};
+
+fn clock() -> Box<dyn Clock> {
+    Box::new(FixedClock(
+        chrono::NaiveDate::from_ymd_opt(2026, 1, 1)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap()
+            .and_utc(),
+    ))
+}
+
+fn db_path(dir: &Path) -> PathBuf {
+    let db_dir = dir.join(".ralph").join("db");
+    std::fs::create_dir_all(&db_dir).unwrap();
+    db_dir.join("ralph.db")
+}
+
+fn project(path: &Path) -> SqliteDb {
+    let db = SqliteDb::open(path, Some(clock())).unwrap();
+    db.create_discipline(DisciplineInput {
+        name: "backend".to_owned(),
+        display_name: "Backend".to_owned(),
+        acronym: "BACK".to_owned(),
+        icon: "Server".to_owned(),
+        color: "#8b5cf6".to_owned(),
+        description: None,
+        system_prompt: None,
+        agent: None,
+        model: None,
+        effort: None,
+        thinking: None,
+        skills: "[]".to_owned(),
+        conventions: None,
+        mcp_servers: "[]".to_owned(),
+        image_path: None,
+        crops: None,
+        image_prompt: None,
+    })
+    .unwrap();
+    db.create_subsystem(SubsystemInput {
+        name: "auth".to_owned(),
+        display_name: "Auth".to_owned(),
+        acronym: "AUTH".to_owned(),
+        description: None,
+    })
+    .unwrap();
+    add_task(&db, "Login");
+    db
+}
+
+fn add_task(db: &SqliteDb, title: &str) {
+    db.create_task(TaskInput {
+        subsystem: "auth".to_owned(),
+        discipline: "backend".to_owned(),
+        title: title.to_owned(),
+        ..Default::default()
+    })
+    .unwrap();
+}
+
+#[test]
+fn restore_rolls_back_and_keeps_the_replaced_state() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = db_path(dir.path());
+    let mut db = project(&path);
+
+    let backup = db.create_backup(BackupReason::Manual).unwrap();
+    assert_eq!(backup.name, "ralph-20260101T120000000Z-manual.db");
+    assert_eq!(backup.created, "2026-01-01T12:00:00Z");
+    assert_eq!(backup.task_count, Some(1));
+    add_task(&db, "Logout");
+
+    let replaced = db.restore_backup(&backup.name).unwrap();
+    assert_eq!(replaced.reason, BackupReason::Restore);
+    assert_eq!(replaced.task_count, Some(2));
+    assert_eq!(db.get_tasks().len(), 1);
+    drop(db);
+    assert_eq!(
+        SqliteDb::open(&path, None).unwrap().get_tasks()[0].title,
+        "Login"
+    );
+
+    let db = SqliteDb::open(&path, Some(clock())).unwrap();
+    let listed: Vec<(String, Option<u32>)> = db
+        .list_backups()
+        .unwrap()
+        .into_iter()
+        .map(|backup| (backup.name, backup.task_count))
+        .collect();
+    assert_eq!(
+        listed,
+        vec![
+            ("ralph-20260101T120000001Z-restore.db".to_owned(), Some(2)),
+            ("ralph-20260101T120000000Z-manual.db".to_owned(), Some(1)),
+        ]
+    );
+}
+
+#[test]
+fn restore_only_accepts_backup_names() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = db_path(dir.path());
+    let mut db = project(&path);
+    db.create_backup(BackupReason::Manual).unwrap();
+
+    for name in [
+        "../ralph.db",
+        "ralph-20260101T120000000Z-manual.db/..",
+        "missing.db",
+    ] {
+        assert!(db.restore_backup(name).is_err(), "{name}");
+    }
+    std::fs::write(
+        backups_dir(&path).join("ralph-20250101T000000000Z-manual.db"),
+        "not a database",
+    )
+    .unwrap();
+    let error = db
+        .restore_backup("ralph-20250101T000000000Z-manual.db")
+        .unwrap_err();
+    assert!(error.contains("not a project database"), "{error}");
+    assert_eq!(db.get_tasks().len(), 1);
+}
+
+#[test]
+fn only_the_newest_backups_are_kept() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = db_path(dir.path());
+    let db = project(&path);
+    std::fs::create_dir_all(backups_dir(&path)).unwrap();
+    std::fs::write(backups_dir(&path).join("notes.txt"), "kept").unwrap();
+
+    for _ in 0..BACKUP_RETENTION + 3 {
+        db.create_backup(BackupReason::Execution).unwrap();
+    }
+    let backups = db.list_backups().unwrap();
+    assert_eq!(backups.len(), BACKUP_RETENTION);
+    assert_eq!(
+        backups[0].name,
+        format!(
+            "ralph-20260101T1200000{}Z-execution.db",
+            BACKUP_RETENTION + 2
+        )
+    );
+    assert_eq!(
+        backups.last().unwrap().name,
+        "ralph-20260101T120000003Z-execution.db"
+    );
+    assert!(backups_dir(&path).join("notes.txt").exists());
+}
+
+#[test]
+fn read_only_open_writes_nothing() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = db_path(dir.path());
+    drop(project(&path));
+
+    let db = SqliteDb::open_read_only(&path).unwrap();
+    assert_eq!(db.get_tasks()[0].title, "Login");
+    assert!(db
+        .create_subsystem(SubsystemInput {
+            name: "billing".to_owned(),
+            display_name: "Billing".to_owned(),
+            acronym: "BILL".to_owned(),
+            description: None,
+        })
+        .is_err());
+    drop(db);
+
+    let conn = rusqlite::Connection::open(&path).unwrap();
+    conn.execute_batch("DROP TABLE mirror_base; PRAGMA user_version = 8;")
+        .unwrap();
+    drop(conn);
+
+    let error = SqliteDb::open_read_only(&path).err().unwrap();
+    assert!(error.contains("schema version 8"), "{error}");
+    assert!(!backups_dir(&path).exists());
+    let conn = rusqlite::Connection::open(&path).unwrap();
+    let version: u32 = conn
+        .query_row("PRAGMA user_version", [], |row| row.get(0))
+        .unwrap();
+    assert_eq!(version, 8);
+}
+
+#[test]
+fn opening_an_outdated_database_backs_it_up_first() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = db_path(dir.path());
+    drop(project(&path));
+    assert!(!backups_dir(&path).exists());
+
+    let conn = rusqlite::Connection::open(&path).unwrap();
+    conn.execute_batch("DROP TABLE mirror_base; PRAGMA user_version = 8;")
+        .unwrap();
+    drop(conn);
+
+    let db = SqliteDb::open(&path, Some(clock())).unwrap();
+    let backups = db.list_backups().unwrap();
+    assert_eq!(backups.len(), 1);
+    assert_eq!(backups[0].reason, BackupReason::Migration);
+    assert_eq!(backups[0].task_count, Some(1));
+
+    drop(db);
+    SqliteDb::open(&path, Some(clock())).unwrap();
+    assert_eq!(
+        SqliteDb::open(&path, None)
+            .unwrap()
+            .list_backups()
+            .unwrap()
+            .len(),
+        1
+    );
+}
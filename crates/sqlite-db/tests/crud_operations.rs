@@ -1,5 +1,9 @@
 use sqlite_db::{
-    AddSubsystemCommentInput, FixedClock, Priority, SqliteDb, SubsystemInput, TaskInput, TaskStatus,
+    AddSubsystemCommentInput, AgentSessionCreateInput, AgentSessionFinishInput,
+    AgentSessionPromptInput, AgentSessionUpdateInput, AskSignalInput, EnrichmentProposalInput,
+    FixedClock, Priority, ProjectActivity, ReviewSettings, SessionLimits, SqliteDb, SubsystemInput,
+    TaskInput, TaskStatus, TaskTemplateInput, TaskTemplateInstantiateInput,
+    DEFAULT_SESSION_IDLE_SECS, DEFAULT_SESSION_WALL_CLOCK_SECS,
 };
 
 fn comment(subsystem: &str, category: &str, body: &str) -> AddSubsystemCommentInput {
@@ -604,6 +608,152 @@ fn test_delete_subsystem_with_tasks_rejected() {
     assert!(result.unwrap_err().contains("Cannot delete subsystem"));
 }
 
+// === Archive tests ===
+
+fn archive_task(db: &SqliteDb, subsystem: &str, title: &str) -> u32 {
+    db.create_task(TaskInput {
+        subsystem: subsystem.into(),
+        discipline: "backend".into(),
+        title: title.into(),
+        ..Default::default()
+    })
+    .unwrap()
+}
+
+#[test]
+fn test_archived_task_is_hidden_but_kept() {
+    let db = create_test_db();
+    db.create_subsystem(subsystem("auth", "Auth", "AUTH"))
+        .unwrap();
+    let login = archive_task(&db, "auth", "Login");
+    let logout = archive_task(&db, "auth", "Logout");
+
+    db.set_task_archived(login, true).unwrap();
+    let listed: Vec<u32> = db.list_tasks(false).unwrap().iter().map(|t| t.id).collect();
+    assert_eq!(listed, vec![logout]);
+    assert_eq!(db.list_tasks(true).unwrap().len(), 2);
+    assert_eq!(db.get_task_list_items(false).unwrap().len(), 1);
+    let items = db.get_task_list_items(true).unwrap();
+    assert_eq!(items[0].archived_at.as_deref(), Some("2026-01-01"));
+    assert_eq!(
+        db.get_task_by_id(login).unwrap().archived_at.as_deref(),
+        Some("2026-01-01")
+    );
+
+    db.set_task_archived(login, false).unwrap();
+    assert!(db.get_task_by_id(login).unwrap().archived_at.is_none());
+    assert_eq!(db.list_tasks(false).unwrap().len(), 2);
+    assert!(db.set_task_archived(999, true).is_err());
+}
+
+#[test]
+fn test_archived_subsystem_hides_its_tasks() {
+    let db = create_test_db();
+    db.create_subsystem(subsystem("auth", "Auth", "AUTH"))
+        .unwrap();
+    db.create_subsystem(subsystem("billing", "Billing", "BILL"))
+        .unwrap();
+    archive_task(&db, "auth", "Login");
+    let invoice = archive_task(&db, "billing", "Invoice");
+
+    db.set_subsystem_archived("billing", true).unwrap();
+    let names: Vec<String> = db
+        .list_subsystems(false)
+        .into_iter()
+        .map(|s| s.name)
+        .collect();
+    assert_eq!(names, vec!["auth".to_owned()]);
+    assert_eq!(db.list_subsystems(true).len(), 2);
+    assert!(db.archived_task_ids().unwrap().contains(&invoice));
+    assert_eq!(db.list_tasks(false).unwrap().len(), 1);
+    assert_eq!(db.get_task_list_items(false).unwrap().len(), 1);
+    assert!(db.get_task_by_id(invoice).unwrap().archived_at.is_none());
+
+    db.set_subsystem_archived("billing", false).unwrap();
+    assert_eq!(db.list_tasks(false).unwrap().len(), 2);
+    assert!(db.set_subsystem_archived("nope", true).is_err());
+}
+
+#[test]
+fn test_archive_rejects_work_in_progress() {
+    let db = create_test_db();
+    db.create_subsystem(subsystem("auth", "Auth", "AUTH"))
+        .unwrap();
+    let login = archive_task(&db, "auth", "Login");
+    db.set_task_status(login, TaskStatus::InProgress).unwrap();
+
+    assert!(db
+        .set_task_archived(login, true)
+        .unwrap_err()
+        .contains("in progress"));
+    assert!(db
+        .set_subsystem_archived("auth", true)
+        .unwrap_err()
+        .contains("in progress"));
+    assert_eq!(db.list_tasks(false).unwrap().len(), 1);
+}
+
+#[test]
+fn test_project_activity_counts_open_work() {
+    let db = create_test_db();
+    db.create_subsystem(subsystem("auth", "Auth", "AUTH"))
+        .unwrap();
+    let login = archive_task(&db, "auth", "Login");
+    let logout = archive_task(&db, "auth", "Logout");
+    assert_eq!(
+        db.project_activity(|_| true).unwrap(),
+        ProjectActivity::default()
+    );
+
+    for (task_id, question) in [(login, "Which provider?"), (logout, "Keep sessions?")] {
+        db.insert_ask_signal(
+            None,
+            AskSignalInput {
+                task_id,
+                session_id: format!("session-{task_id}"),
+                question: question.to_owned(),
+                blocking: true,
+                options: None,
+                preferred: None,
+            },
+        )
+        .unwrap();
+    }
+    db.set_task_status(logout, TaskStatus::Blocked).unwrap();
+    db.create_human_agent_session(AgentSessionCreateInput {
+        id: "live".into(),
+        kind: "task_execution".into(),
+        task_id: Some(login),
+        agent: None,
+        model: None,
+        launch_command: None,
+        post_start_preamble: None,
+        init_prompt: None,
+    })
+    .unwrap();
+    let ask_id = db.get_task_by_id(login).unwrap().signals[0].id;
+    db.answer_ask(ask_id, "GitHub".into()).unwrap();
+
+    let expected = ProjectActivity {
+        pending_asks: 1,
+        running_sessions: 1,
+        blocked_tasks: 1,
+    };
+    assert_eq!(db.project_activity(|_| true).unwrap(), expected);
+    // A `running` row whose agent is gone isn't running.
+    assert_eq!(
+        db.project_activity(|session| session.id != "live")
+            .unwrap()
+            .running_sessions,
+        0
+    );
+
+    // Archived work doesn't ask for attention.
+    db.set_task_archived(logout, true).unwrap();
+    let activity = db.project_activity(|_| true).unwrap();
+    assert_eq!((activity.pending_asks, activity.blocked_tasks), (0, 0));
+}
+
 // === FEATURE fields tests ===
 
 #[test]
@@ -1019,6 +1169,46 @@ fn test_delete_discipline() {
     assert_eq!(db.get_disciplines().len(), initial - 1);
 }
 
+#[test]
+fn test_clear_discipline_image() {
+    let db = create_test_db();
+    db.create_discipline(sqlite_db::DisciplineInput {
+        name: "custom".to_owned(),
+        display_name: "Custom".to_owned(),
+        acronym: "CUST".to_owned(),
+        icon: "Wrench".to_owned(),
+        color: "#ff0000".to_owned(),
+        system_prompt: None,
+        agent: None,
+        model: None,
+        effort: None,
+        thinking: None,
+        skills: "[]".to_owned(),
+        conventions: None,
+        mcp_servers: "[]".to_owned(),
+        image_path: Some("images/disciplines/custom.png".to_owned()),
+        crops: None,
+        description: None,
+        image_prompt: None,
+    })
+    .unwrap();
+
+    db.clear_discipline_image("custom").unwrap();
+    let custom = db
+        .get_disciplines()
+        .into_iter()
+        .find(|d| d.name == "custom")
+        .unwrap();
+    assert!(custom.image_path.is_none());
+    assert!(db.clear_discipline_image("nope").is_err());
+}
+
+#[test]
+fn test_fresh_database_passes_integrity_check() {
+    let db = create_test_db();
+    assert!(db.check_integrity().unwrap().is_empty());
+}
+
 #[test]
 fn test_delete_discipline_nonexistent() {
     let db = create_test_db();
@@ -1449,3 +1639,600 @@ fn test_export_yaml_escapes_special_chars() {
     // Verify no unescaped quotes mid-string
     assert!(!yaml.contains("\"bug\""));
 }
+
+fn create_limits_task(db: &SqliteDb, estimated_turns: Option<u32>) -> u32 {
+    db.create_subsystem(subsystem("runner", "Runner", "RUNR"))
+        .unwrap();
+    db.create_task(TaskInput {
+        subsystem: "runner".into(),
+        discipline: "backend".into(),
+        title: "Long running task".into(),
+        estimated_turns,
+        ..Default::default()
+    })
+    .unwrap()
+}
+
+#[test]
+fn test_effective_session_limits_defaults() {
+    let db = create_test_db();
+    let task_id = create_limits_task(&db, None);
+
+    let limits = db.effective_session_limits(task_id).unwrap();
+    assert_eq!(
+        limits.wall_clock_secs,
+        Some(DEFAULT_SESSION_WALL_CLOCK_SECS)
+    );
+    assert_eq!(limits.idle_secs, Some(DEFAULT_SESSION_IDLE_SECS));
+}
+
+#[test]
+fn test_effective_session_limits_scale_with_estimated_turns() {
+    let db = create_test_db();
+    let task_id = create_limits_task(&db, Some(40));
+
+    let limits = db.effective_session_limits(task_id).unwrap();
+    assert_eq!(limits.wall_clock_secs, Some(40 * 3 * 60));
+}
+
+#[test]
+fn test_effective_session_limits_task_overrides_discipline() {
+    let db = create_test_db();
+    let task_id = create_limits_task(&db, None);
+
+    db.set_discipline_session_limits(
+        "backend",
+        SessionLimits {
+            wall_clock_secs: Some(1200),
+            idle_secs: Some(120),
+        },
+    )
+    .unwrap();
+    db.set_task_session_limits(
+        task_id,
+        SessionLimits {
+            wall_clock_secs: Some(300),
+            idle_secs: None,
+        },
+    )
+    .unwrap();
+
+    let limits = db.effective_session_limits(task_id).unwrap();
+    assert_eq!(limits.wall_clock_secs, Some(300));
+    assert_eq!(limits.idle_secs, Some(120));
+
+    db.set_task_session_limits(task_id, SessionLimits::default())
+        .unwrap();
+    assert_eq!(
+        db.get_task_session_limits(task_id).unwrap(),
+        SessionLimits::default()
+    );
+    assert_eq!(
+        db.effective_session_limits(task_id)
+            .unwrap()
+            .wall_clock_secs,
+        Some(1200)
+    );
+}
+
+#[test]
+fn test_session_limits_reject_zero_and_missing_targets() {
+    let db = create_test_db();
+    let task_id = create_limits_task(&db, None);
+
+    let zero = SessionLimits {
+        wall_clock_secs: Some(0),
+        idle_secs: None,
+    };
+    assert!(db.set_task_session_limits(task_id, zero).is_err());
+    assert!(db
+        .set_task_session_limits(999, SessionLimits::default())
+        .is_err());
+    assert!(db
+        .set_discipline_session_limits("nonexistent", SessionLimits::default())
+        .is_err());
+    assert!(db.effective_session_limits(999).is_err());
+}
+
+#[test]
+fn test_discipline_review_settings_round_trip_and_clear() {
+    let db = create_test_db();
+    assert_eq!(
+        db.get_discipline_review_settings("backend"),
+        ReviewSettings::default()
+    );
+
+    db.set_discipline_review_settings(
+        "backend",
+        ReviewSettings {
+            required: true,
+            agent: Some("claude".into()),
+            model: Some("  ".into()),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        db.get_discipline_review_settings("backend"),
+        ReviewSettings {
+            required: true,
+            agent: Some("claude".into()),
+            model: None,
+        }
+    );
+
+    db.set_discipline_review_settings("backend", ReviewSettings::default())
+        .unwrap();
+    assert!(!db.get_discipline_review_settings("backend").required);
+    assert!(db
+        .set_discipline_review_settings("nonexistent", ReviewSettings::default())
+        .is_err());
+}
+
+#[test]
+fn test_enrichment_proposal_approve_promotes_draft() {
+    let db = create_test_db();
+    db.create_subsystem(subsystem("auth", "Auth", "AUTH"))
+        .unwrap();
+    let task_id = db
+        .create_task(TaskInput {
+            subsystem: "auth".into(),
+            discipline: "backend".into(),
+            title: "Implement login".into(),
+            status: Some(TaskStatus::Draft),
+            ..Default::default()
+        })
+        .unwrap();
+    let proposal = |pseudocode: &str| EnrichmentProposalInput {
+        task_id,
+        pseudocode: pseudocode.into(),
+        acceptance_criteria: vec!["Login returns a session".into()],
+        context_files: vec!["src/auth.rs".into()],
+        model: Some("llama3".into()),
+    };
+
+    db.save_enrichment_proposal(proposal("first")).unwrap();
+    db.save_enrichment_proposal(proposal("second")).unwrap();
+    let proposals = db.get_enrichment_proposals().unwrap();
+    assert_eq!(proposals.len(), 1);
+    assert_eq!(proposals[0].pseudocode, "second");
+    assert_eq!(proposals[0].created, "2026-01-01T00:00:00Z");
+
+    db.approve_enrichment_proposal(task_id).unwrap();
+    let task = db.get_task_by_id(task_id).unwrap();
+    assert_eq!(task.status, TaskStatus::Pending);
+    assert_eq!(task.pseudocode.as_deref(), Some("second"));
+    assert_eq!(task.acceptance_criteria, vec!["Login returns a session"]);
+    assert_eq!(task.context_files, vec!["src/auth.rs"]);
+    assert!(db.get_enrichment_proposals().unwrap().is_empty());
+
+    assert!(db.save_enrichment_proposal(proposal("third")).is_err());
+    assert!(db.approve_enrichment_proposal(task_id).is_err());
+}
+
+#[test]
+fn test_enrichment_proposals_approve_is_all_or_nothing() {
+    let db = create_test_db();
+    db.create_subsystem(subsystem("auth", "Auth", "AUTH"))
+        .unwrap();
+    let draft = |title: &str| {
+        db.create_task(TaskInput {
+            subsystem: "auth".into(),
+            discipline: "backend".into(),
+            title: title.into(),
+            status: Some(TaskStatus::Draft),
+            ..Default::default()
+        })
+        .unwrap()
+    };
+    let first = draft("Implement login");
+    let second = draft("Implement logout");
+    db.save_enrichment_proposal(EnrichmentProposalInput {
+        task_id: first,
+        pseudocode: "check credentials".into(),
+        acceptance_criteria: vec!["Login returns a session".into()],
+        context_files: vec![],
+        model: None,
+    })
+    .unwrap();
+
+    // `second` has no proposal, so the batch fails and `first` stays a draft.
+    assert!(db.approve_enrichment_proposals(&[first, second]).is_err());
+    assert_eq!(db.get_task_by_id(first).unwrap().status, TaskStatus::Draft);
+    assert_eq!(db.get_enrichment_proposals().unwrap().len(), 1);
+
+    db.approve_enrichment_proposals(&[first]).unwrap();
+    assert_eq!(
+        db.get_task_by_id(first).unwrap().status,
+        TaskStatus::Pending
+    );
+    assert!(db.get_enrichment_proposals().unwrap().is_empty());
+}
+
+#[test]
+fn test_task_template_instantiate_fills_placeholders() {
+    let db = create_test_db();
+    db.create_subsystem(subsystem("auth", "Auth", "AUTH"))
+        .unwrap();
+    let template_id = db
+        .create_task_template(TaskTemplateInput {
+            discipline: "backend".into(),
+            title: "Add {{endpoint}} endpoint".into(),
+            description: Some("Expose {{ endpoint }} for {{client}}".into()),
+            priority: Some(Priority::High),
+            pseudocode: Some("1. Add route".into()),
+            ..Default::default()
+        })
+        .unwrap();
+    let template = db.get_task_template(template_id).unwrap();
+    assert_eq!(template.placeholders, vec!["client", "endpoint"]);
+    assert!(template.is_active);
+    assert!(db
+        .create_task_template(TaskTemplateInput {
+            discipline: "backend".into(),
+            title: "Add {{ not a name }}".into(),
+            ..Default::default()
+        })
+        .is_err());
+
+    let instantiate = |params: &[(&str, &str)]| {
+        db.instantiate_task_template(TaskTemplateInstantiateInput {
+            template_id,
+            subsystem: "auth".into(),
+            params: params
+                .iter()
+                .map(|(k, v)| ((*k).to_owned(), (*v).to_owned()))
+                .collect(),
+            status: None,
+        })
+    };
+    let err = instantiate(&[("endpoint", "login")]).unwrap_err();
+    assert!(err.contains("client"));
+
+    let task_id = instantiate(&[("endpoint", "login"), ("client", "web")]).unwrap();
+    let task = db.get_task_by_id(task_id).unwrap();
+    assert_eq!(task.title, "Add login endpoint");
+    assert_eq!(task.description.as_deref(), Some("Expose login for web"));
+    assert_eq!(task.discipline, "backend");
+    assert_eq!(task.priority, Some(Priority::High));
+    assert_eq!(task.pseudocode.as_deref(), Some("1. Add route"));
+    assert_eq!(db.get_task_template(template_id).unwrap().pulled_count, 1);
+
+    db.set_task_template_active(template_id, false).unwrap();
+    assert!(db
+        .get_active_task_templates_for_discipline(template.discipline_id)
+        .is_empty());
+    assert_eq!(db.get_task_templates().len(), 1);
+    assert!(instantiate(&[("endpoint", "a"), ("client", "b")]).is_err());
+
+    // Inside a caller's transaction the task is rolled back with it.
+    db.set_task_template_active(template_id, true).unwrap();
+    let tasks = db.get_tasks().len();
+    let result: Result<(), String> = db.with_transaction(|_| {
+        instantiate(&[("endpoint", "a"), ("client", "b")])?;
+        Err("abort".into())
+    });
+    assert!(result.is_err());
+    assert_eq!(db.get_tasks().len(), tasks);
+}
+
+#[test]
+fn test_save_task_as_template_copies_details() {
+    let db = create_test_db();
+    db.create_subsystem(subsystem("auth", "Auth", "AUTH"))
+        .unwrap();
+    let task_id = db
+        .create_task(TaskInput {
+            subsystem: "auth".into(),
+            discipline: "backend".into(),
+            title: "Rotate keys".into(),
+            hints: Some("Use the vault".into()),
+            ..Default::default()
+        })
+        .unwrap();
+
+    let template_id = db.save_task_as_template(task_id).unwrap();
+    db.update_task_template(
+        template_id,
+        TaskTemplateInput {
+            discipline: "backend".into(),
+            title: "Rotate {{service}} keys".into(),
+            hints: Some("Use the vault".into()),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+    let template = db.get_task_template(template_id).unwrap();
+    assert_eq!(template.title, "Rotate {{service}} keys");
+    assert_eq!(template.hints.as_deref(), Some("Use the vault"));
+    assert_eq!(db.get_task_by_id(task_id).unwrap().title, "Rotate keys");
+    assert!(db.save_task_as_template(999).is_err());
+    assert!(db
+        .update_task_template(999, TaskTemplateInput::default())
+        .is_err());
+}
+
+#[test]
+fn test_save_task_as_template_keeps_braces_literal() {
+    let db = create_test_db();
+    db.create_subsystem(subsystem("auth", "Auth", "AUTH"))
+        .unwrap();
+    let description = "Render <div style={{ color: 'red' }}> and keep {% raw %}{{ name }}";
+    let task_id = db
+        .create_task(TaskInput {
+            subsystem: "auth".into(),
+            discipline: "backend".into(),
+            title: "Fix {{#each}} loop".into(),
+            description: Some(description.into()),
+            ..Default::default()
+        })
+        .unwrap();
+
+    let template_id = db.save_task_as_template(task_id).unwrap();
+    assert!(db
+        .get_task_template(template_id)
+        .unwrap()
+        .placeholders
+        .is_empty());
+
+    let copy_id = db
+        .instantiate_task_template(TaskTemplateInstantiateInput {
+            template_id,
+            subsystem: "auth".into(),
+            params: std::collections::HashMap::new(),
+            status: None,
+        })
+        .unwrap();
+    let copy = db.get_task_by_id(copy_id).unwrap();
+    assert_eq!(copy.title, "Fix {{#each}} loop");
+    assert_eq!(copy.description.as_deref(), Some(description));
+}
+
+#[test]
+fn test_finish_agent_session_records_end_once() {
+    let db = create_test_db();
+    db.create_human_agent_session(AgentSessionCreateInput {
+        id: "session-1".into(),
+        kind: "task_execution".into(),
+        task_id: None,
+        agent: Some("claude".into()),
+        model: None,
+        launch_command: None,
+        post_start_preamble: None,
+        init_prompt: None,
+    })
+    .unwrap();
+
+    let finished = db
+        .finish_agent_session(AgentSessionFinishInput {
+            id: "session-1".into(),
+            status: "timed_out".into(),
+            exit_code: Some(130),
+            output_bytes: Some(2048),
+            error_text: Some("Idle for 600s".into()),
+        })
+        .unwrap();
+    assert!(finished);
+
+    let session = db.get_agent_session_by_id("session-1").unwrap();
+    assert_eq!(session.status, "timed_out");
+    assert_eq!(session.exit_code, Some(130));
+    assert_eq!(session.output_bytes, Some(2048));
+    assert_eq!(session.ended, Some("2026-01-01T00:00:00Z".into()));
+
+    let again = db
+        .finish_agent_session(AgentSessionFinishInput {
+            id: "session-1".into(),
+            status: "finished".into(),
+            exit_code: Some(0),
+            output_bytes: None,
+            error_text: None,
+        })
+        .unwrap();
+    assert!(!again);
+    assert_eq!(
+        db.get_agent_session_by_id("session-1").unwrap().status,
+        "timed_out"
+    );
+
+    assert!(db
+        .finish_agent_session(AgentSessionFinishInput {
+            id: "session-1".into(),
+            status: "running".into(),
+            exit_code: None,
+            output_bytes: None,
+            error_text: None,
+        })
+        .is_err());
+}
+
+#[test]
+fn test_system_agent_session_is_not_human() {
+    let db = create_test_db();
+    let task_id = create_limits_task(&db, None);
+    db.create_system_agent_session(AgentSessionCreateInput {
+        id: "worker-1".into(),
+        kind: "task_execution".into(),
+        task_id: Some(task_id),
+        agent: Some("codex".into()),
+        model: None,
+        launch_command: None,
+        post_start_preamble: None,
+        init_prompt: None,
+    })
+    .unwrap();
+
+    let session = db.get_agent_session_by_id("worker-1").unwrap();
+    assert_eq!(session.started_by, "system");
+    assert_eq!(session.status, "running");
+    assert_eq!(session.session_number, 1);
+    assert!(db.list_human_agent_sessions().is_empty());
+    assert!(db
+        .update_human_agent_session(AgentSessionUpdateInput {
+            id: "worker-1".into(),
+            kind: None,
+            task_id: None,
+            agent: None,
+            model: None,
+            launch_command: None,
+            post_start_preamble: None,
+            init_prompt: None,
+            ended: None,
+            exit_code: None,
+            closing_verb: None,
+            status: Some("finished".into()),
+            prompt_hash: None,
+            output_bytes: None,
+            error_text: None,
+        })
+        .is_err());
+}
+
+fn running_session(db: &SqliteDb, id: &str, task_id: Option<u32>) {
+    db.create_human_agent_session(AgentSessionCreateInput {
+        id: id.into(),
+        kind: "task_execution".into(),
+        task_id,
+        agent: Some("claude".into()),
+        model: None,
+        launch_command: None,
+        post_start_preamble: None,
+        init_prompt: None,
+    })
+    .unwrap();
+}
+
+#[test]
+fn test_reconcile_orphaned_agent_sessions() {
+    let db = create_test_db();
+    let orphaned_task = create_limits_task(&db, None);
+    let shared_task = db
+        .create_task(TaskInput {
+            subsystem: "runner".into(),
+            discipline: "backend".into(),
+            title: "Shared task".into(),
+            ..Default::default()
+        })
+        .unwrap();
+    db.set_task_status(orphaned_task, TaskStatus::InProgress)
+        .unwrap();
+    db.set_task_status(shared_task, TaskStatus::InProgress)
+        .unwrap();
+
+    running_session(&db, "orphan", Some(orphaned_task));
+    running_session(&db, "orphan-shared", Some(shared_task));
+    running_session(&db, "live", Some(shared_task));
+    db.set_agent_session_pid("live", 4242).unwrap();
+
+    let recovery = db
+        .reconcile_orphaned_agent_sessions(|s| s.pid == Some(4242), "App exited mid-session")
+        .unwrap();
+
+    assert_eq!(
+        recovery.crashed_session_ids,
+        vec!["orphan".to_owned(), "orphan-shared".to_owned()]
+    );
+    assert_eq!(recovery.requeued_task_ids, vec![orphaned_task]);
+
+    let orphan = db.get_agent_session_by_id("orphan").unwrap();
+    assert_eq!(orphan.status, "crashed");
+    assert_eq!(orphan.error_text.as_deref(), Some("App exited mid-session"));
+    assert!(orphan.ended.is_some());
+    assert_eq!(
+        db.get_agent_session_by_id("live").unwrap().status,
+        "running"
+    );
+    assert_eq!(
+        db.get_task_by_id(orphaned_task).unwrap().status,
+        TaskStatus::Pending
+    );
+    assert_eq!(
+        db.get_task_by_id(shared_task).unwrap().status,
+        TaskStatus::InProgress
+    );
+
+    let again = db
+        .reconcile_orphaned_agent_sessions(|s| s.pid == Some(4242), "App exited mid-session")
+        .unwrap();
+    assert!(again.is_empty());
+}
+
+#[test]
+fn test_agent_session_worktree_recording() {
+    let db = create_test_db();
+    let task_id = create_limits_task(&db, None);
+    assert!(db.get_latest_task_worktree_session(task_id).is_none());
+
+    running_session(&db, "plain", Some(task_id));
+    running_session(&db, "isolated", Some(task_id));
+    db.set_agent_session_worktree(
+        "isolated",
+        "ralph/task-1",
+        "/p/.ralph/worktrees/task-1",
+        "abc",
+    )
+    .unwrap();
+    db.set_agent_session_head_commit("isolated", "def").unwrap();
+    assert!(db
+        .set_agent_session_worktree("missing", "b", "p", "c")
+        .is_err());
+
+    let session = db.get_latest_task_worktree_session(task_id).unwrap();
+    assert_eq!(session.id, "isolated");
+    assert_eq!(session.worktree_branch.as_deref(), Some("ralph/task-1"));
+    assert_eq!(session.base_commit.as_deref(), Some("abc"));
+    assert_eq!(session.head_commit.as_deref(), Some("def"));
+}
+
+fn session_prompt(session_id: &str, prompt: &str) -> AgentSessionPromptInput {
+    AgentSessionPromptInput {
+        session_id: session_id.into(),
+        recipe: "task_execution".into(),
+        config_name: None,
+        sections: vec!["task_details".into(), "task_exec_instructions".into()],
+        prompt: prompt.into(),
+        prompt_hash: format!("hash-{prompt}"),
+        mcp_config: Some("{}".into()),
+    }
+}
+
+#[test]
+fn test_agent_session_prompt_recording() {
+    let db = create_test_db();
+    let task_id = create_limits_task(&db, None);
+    running_session(&db, "first", Some(task_id));
+    assert!(db.get_agent_session_prompt(1).unwrap().is_none());
+
+    db.record_agent_session_prompt(session_prompt("first", "v1"))
+        .unwrap();
+    db.record_agent_session_prompt(AgentSessionPromptInput {
+        config_name: Some("task_execution".into()),
+        ..session_prompt("first", "v2")
+    })
+    .unwrap();
+    assert!(db
+        .record_agent_session_prompt(session_prompt("missing", "v1"))
+        .is_err());
+
+    let prompt = db.get_agent_session_prompt(1).unwrap().unwrap();
+    assert_eq!(prompt.session_id, "first");
+    assert_eq!(prompt.session_number, 1);
+    assert_eq!(prompt.prompt, "v2");
+    assert_eq!(prompt.config_name.as_deref(), Some("task_execution"));
+    assert_eq!(
+        prompt.sections,
+        vec![
+            "task_details".to_owned(),
+            "task_exec_instructions".to_owned()
+        ]
+    );
+    assert_eq!(prompt.prompt_hash.as_deref(), Some("hash-v2"));
+    assert_eq!(
+        db.get_agent_session_by_id("first")
+            .unwrap()
+            .prompt_hash
+            .as_deref(),
+        Some("hash-v2")
+    );
+    assert!(db.get_agent_session_prompt(2).unwrap().is_none());
+}
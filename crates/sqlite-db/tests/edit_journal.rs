@@ -0,0 +1,262 @@
+//! Undo/redo of journaled edits: updates and deletes are reverted with everything they
+//! touched, and replaying over a later change fails with a conflict.
+
+use sqlite_db::{
+    AddSubsystemCommentInput, Clock, DisciplineInput, EditTarget, FixedClock, SqliteDb, Subsystem,
+    SubsystemInput, TaskInput,
+};
+
+fn clock() -> Box<dyn Clock> {
+    Box::new(FixedClock(
+        chrono::NaiveDate::from_ymd_opt(2026, 1, 1)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap()
+            .and_utc(),
+    ))
+}
+
+fn discipline(name: &str, display_name: &str) -> DisciplineInput {
+    DisciplineInput {
+        name: name.to_owned(),
+        display_name: display_name.to_owned(),
+        acronym: name[..4].to_uppercase(),
+        icon: "Server".to_owned(),
+        color: "#8b5cf6".to_owned(),
+        description: None,
+        system_prompt: None,
+        agent: None,
+        model: None,
+        effort: None,
+        thinking: None,
+        skills: r#"["rust"]"#.to_owned(),
+        conventions: None,
+        mcp_servers: r#"[{"name":"db","command":"db-mcp","args":["--ro"],"env":{"A":"1"}}]"#
+            .to_owned(),
+        image_path: None,
+        crops: None,
+        image_prompt: None,
+    }
+}
+
+fn subsystem(name: &str) -> SubsystemInput {
+    SubsystemInput {
+        name: name.to_owned(),
+        display_name: name.to_uppercase(),
+        acronym: name[..4].to_uppercase(),
+        description: None,
+    }
+}
+
+fn task(title: &str, tags: &[&str]) -> TaskInput {
+    TaskInput {
+        subsystem: "auth".to_owned(),
+        discipline: "backend".to_owned(),
+        title: title.to_owned(),
+        tags: tags.iter().map(|&tag| tag.to_owned()).collect(),
+        acceptance_criteria: Some(vec!["It works".to_owned()]),
+        ..Default::default()
+    }
+}
+
+fn project() -> SqliteDb {
+    let db = SqliteDb::open_in_memory(Some(clock())).unwrap();
+    db.create_discipline(discipline("backend", "Backend"))
+        .unwrap();
+    db.create_subsystem(subsystem("auth")).unwrap();
+    db
+}
+
+/// The task as the UI sees it, minus `updated`, which the timestamp trigger bumps whenever a
+/// row is written back.
+fn task_json(db: &SqliteDb, id: u32) -> Option<serde_json::Value> {
+    db.get_task_by_id(id).map(|task| {
+        let mut json = serde_json::to_value(task).unwrap();
+        json.as_object_mut().unwrap().remove("updated");
+        json
+    })
+}
+
+fn subsystem_named(db: &SqliteDb, name: &str) -> Option<Subsystem> {
+    db.get_subsystems().into_iter().find(|s| s.name == name)
+}
+
+fn comment(subsystem_name: &str, discipline: Option<&str>) -> AddSubsystemCommentInput {
+    AddSubsystemCommentInput {
+        subsystem_name: subsystem_name.to_owned(),
+        category: "gotcha".to_owned(),
+        discipline: discipline.map(str::to_owned),
+        agent_task_id: None,
+        body: "Tokens expire after an hour".to_owned(),
+        summary: None,
+        reason: None,
+        source_iteration: None,
+    }
+}
+
+#[test]
+fn task_updates_undo_and_redo_in_order() {
+    let db = project();
+    let id = db.create_task(task("Login", &["api"])).unwrap();
+    let original = task_json(&db, id);
+
+    db.journaled(&EditTarget::Task(id), "Rename task", |db| {
+        db.update_task(id, task("Sign in", &["api", "ui"]))
+    })
+    .unwrap();
+    let renamed = task_json(&db, id);
+    db.journaled(&EditTarget::Task(id), "Retag task", |db| {
+        db.update_task(id, task("Sign in", &[]))
+    })
+    .unwrap();
+    assert_eq!(db.edit_history().undo, ["Retag task", "Rename task"]);
+
+    assert_eq!(db.undo_edit().unwrap().as_deref(), Some("Retag task"));
+    assert_eq!(task_json(&db, id), renamed);
+    assert_eq!(db.undo_edit().unwrap().as_deref(), Some("Rename task"));
+    assert_eq!(task_json(&db, id), original);
+    assert_eq!(db.undo_edit().unwrap(), None);
+    assert_eq!(db.edit_history().redo, ["Rename task", "Retag task"]);
+
+    assert_eq!(db.redo_edit().unwrap().as_deref(), Some("Rename task"));
+    assert_eq!(task_json(&db, id), renamed);
+    assert_eq!(db.get_task_by_id(id).unwrap().tags, ["api", "ui"]);
+
+    // A new edit forgets what was undone.
+    db.journaled(&EditTarget::Task(id), "Rename again", |db| {
+        db.update_task(id, task("Log in", &["api"]))
+    })
+    .unwrap();
+    assert!(db.edit_history().redo.is_empty());
+    assert_eq!(db.redo_edit().unwrap(), None);
+}
+
+#[test]
+fn a_deleted_task_comes_back_with_its_rows() {
+    let db = project();
+    let id = db.create_task(task("Login", &["api"])).unwrap();
+    let original = task_json(&db, id);
+
+    db.journaled(&EditTarget::Task(id), "Delete task", |db| {
+        db.delete_task(id)
+    })
+    .unwrap();
+    assert!(db.get_task_by_id(id).is_none());
+
+    db.undo_edit().unwrap();
+    assert_eq!(task_json(&db, id), original);
+    db.redo_edit().unwrap();
+    assert!(db.get_task_by_id(id).is_none());
+    db.undo_edit().unwrap();
+    assert_eq!(task_json(&db, id), original);
+}
+
+#[test]
+fn a_deleted_subsystem_comes_back_with_comments_and_embeddings() {
+    let db = project();
+    db.create_subsystem(subsystem("billing")).unwrap();
+    db.add_subsystem_comment(comment("billing", Some("backend")))
+        .unwrap();
+    let comment_id = subsystem_named(&db, "billing").unwrap().comments[0].id;
+    db.upsert_comment_embedding(comment_id, &[0.5, 0.25], "test-model", "abc")
+        .unwrap();
+
+    db.journaled(
+        &EditTarget::Subsystem("billing".to_owned()),
+        "Delete subsystem",
+        |db| db.delete_subsystem("billing".to_owned()),
+    )
+    .unwrap();
+    assert!(subsystem_named(&db, "billing").is_none());
+    assert!(!db.has_comment_embedding(comment_id));
+
+    db.undo_edit().unwrap();
+    let restored = subsystem_named(&db, "billing").unwrap();
+    assert_eq!(restored.comments.len(), 1);
+    assert_eq!(restored.comments[0].discipline.as_deref(), Some("backend"));
+    assert_eq!(db.get_embedding_hash(comment_id).as_deref(), Some("abc"));
+}
+
+#[test]
+fn discipline_edits_restore_nested_rows_and_comment_links() {
+    let db = project();
+    db.create_discipline(discipline("frontend", "Frontend"))
+        .unwrap();
+    db.add_subsystem_comment(comment("auth", Some("frontend")))
+        .unwrap();
+    let frontend = |db: &SqliteDb| {
+        db.get_disciplines()
+            .into_iter()
+            .find(|d| d.name == "frontend")
+    };
+    let original = serde_json::to_value(frontend(&db).unwrap()).unwrap();
+
+    let mut update = discipline("frontend", "Web");
+    update.skills = "[]".to_owned();
+    update.mcp_servers = "[]".to_owned();
+    let target = EditTarget::Discipline("frontend".to_owned());
+    db.journaled(&target, "Update discipline", |db| {
+        db.update_discipline(update)
+    })
+    .unwrap();
+    db.journaled(&target, "Delete discipline", |db| {
+        db.delete_discipline("frontend".to_owned())
+    })
+    .unwrap();
+    assert!(frontend(&db).is_none());
+    assert_eq!(
+        subsystem_named(&db, "auth").unwrap().comments[0].discipline,
+        None
+    );
+
+    db.undo_edit().unwrap();
+    assert_eq!(frontend(&db).unwrap().display_name, "Web");
+    assert_eq!(
+        subsystem_named(&db, "auth").unwrap().comments[0]
+            .discipline
+            .as_deref(),
+        Some("frontend")
+    );
+    db.undo_edit().unwrap();
+    assert_eq!(
+        serde_json::to_value(frontend(&db).unwrap()).unwrap(),
+        original
+    );
+}
+
+#[test]
+fn replaying_over_a_later_change_is_a_conflict() {
+    let db = project();
+    let id = db.create_task(task("Login", &[])).unwrap();
+    db.journaled(&EditTarget::Task(id), "Rename task", |db| {
+        db.update_task(id, task("Sign in", &[]))
+    })
+    .unwrap();
+    // Not journaled, like an agent's change.
+    db.update_task(id, task("Sign in with SSO", &[])).unwrap();
+
+    let error = db.undo_edit().unwrap_err();
+    assert!(error.starts_with("[R-2300]"), "{error}");
+    assert!(error.contains("Task #1"), "{error}");
+    assert_eq!(db.get_task_by_id(id).unwrap().title, "Sign in with SSO");
+    assert!(db.edit_history().undo.is_empty());
+}
+
+#[test]
+fn failed_edits_are_not_recorded() {
+    let db = project();
+    let id = db.create_task(task("Login", &[])).unwrap();
+    db.create_task(TaskInput {
+        depends_on: vec![id],
+        ..task("Logout", &[])
+    })
+    .unwrap();
+
+    for id in [id, 99] {
+        let deleted = db.journaled(&EditTarget::Task(id), "Delete task", |db| {
+            db.delete_task(id)
+        });
+        assert!(deleted.is_err());
+    }
+    assert!(db.edit_history().undo.is_empty());
+}
@@ -0,0 +1,386 @@
+//! Property tests for `export_prd_yaml` / `import_prd_yaml`: random projects full of
+//! awkward strings must survive export → import → export byte for byte.
+
+use sqlite_db::{
+    AddSubsystemCommentInput, DisciplineInput, FixedClock, PrdImportMode, Priority, SqliteDb,
+    SubsystemInput, TaskInput, TaskProvenance, TaskStatus,
+};
+
+const SEEDS: u64 = 40;
+
+/// Strings YAML likes to misread: quotes, escapes, indicators, control characters,
+/// Unicode line separators and non-ASCII text.
+const FRAGMENTS: [&str; 24] = [
+    "plain",
+    "two words",
+    "\"quoted\"",
+    "'single'",
+    "back\\slash",
+    "line\nbreak",
+    "tab\there",
+    "cr\r\n",
+    "bell\u{7}",
+    "nul-ish\u{1}",
+    "sep\u{2028}",
+    "para\u{2029}",
+    "bom\u{feff}",
+    "nel\u{85}",
+    "key: value",
+    "# not a comment",
+    "- not a list",
+    "{ not: a map }",
+    "[not, a, list]",
+    "&anchor *alias",
+    "!tag",
+    "yes",
+    "42",
+    "héllo wörld ✓ 🦀",
+];
+
+/// xorshift64*, so failures reproduce from the seed alone.
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        Self(seed.wrapping_mul(0x9e37_79b9_7f4a_7c15) | 1)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    fn chance(&mut self) -> bool {
+        self.next() % 2 == 0
+    }
+
+    fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len())]
+    }
+
+    /// One to three fragments; never blank, since most inputs reject blank text.
+    fn text(&mut self) -> String {
+        let mut text = String::from("x");
+        for _ in 0..=self.below(3) {
+            let fragment = *self.pick(&FRAGMENTS);
+            text.push_str(fragment);
+        }
+        text
+    }
+
+    fn maybe_text(&mut self) -> Option<String> {
+        self.chance().then(|| self.text())
+    }
+
+    fn texts(&mut self) -> Vec<String> {
+        (0..self.below(3)).map(|_| self.text()).collect()
+    }
+}
+
+fn empty_db() -> SqliteDb {
+    let clock = Box::new(FixedClock(
+        chrono::NaiveDate::from_ymd_opt(2026, 1, 1)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap()
+            .and_utc(),
+    ));
+    SqliteDb::open_in_memory(Some(clock)).unwrap()
+}
+
+/// A project with random disciplines, subsystems, comments, tasks and signals.
+fn random_project(seed: u64) -> SqliteDb {
+    let mut rng = Rng::new(seed);
+    let db = empty_db();
+    db.initialize_metadata(rng.text(), rng.maybe_text())
+        .unwrap();
+
+    let disciplines: Vec<String> = (0..=rng.below(3))
+        .map(|i| format!("disc {i} {}", rng.text()))
+        .collect();
+    for (i, name) in disciplines.iter().enumerate() {
+        db.create_discipline(DisciplineInput {
+            name: name.clone(),
+            display_name: rng.text(),
+            // Unique across seeds, so one project can be replaced by another.
+            acronym: format!("D{:03}", seed * 4 + i as u64),
+            icon: rng.text(),
+            color: rng.text(),
+            description: None,
+            system_prompt: None,
+            agent: None,
+            model: None,
+            effort: None,
+            thinking: None,
+            skills: "[]".to_owned(),
+            conventions: None,
+            mcp_servers: "[]".to_owned(),
+            image_path: None,
+            crops: None,
+            image_prompt: None,
+        })
+        .unwrap();
+    }
+
+    let subsystems: Vec<String> = (0..=rng.below(3))
+        .map(|i| format!("sub {i} {}", rng.text()))
+        .collect();
+    for (i, name) in subsystems.iter().enumerate() {
+        db.create_subsystem(SubsystemInput {
+            name: name.clone(),
+            display_name: rng.text(),
+            acronym: format!("S{i:03}"),
+            description: rng.maybe_text(),
+        })
+        .unwrap();
+        for _ in 0..rng.below(3) {
+            db.add_subsystem_comment(AddSubsystemCommentInput {
+                subsystem_name: name.clone(),
+                category: (*rng.pick(&["architecture", "gotcha", "design-decision"])).to_owned(),
+                discipline: rng.chance().then(|| rng.pick(&disciplines).clone()),
+                agent_task_id: None,
+                body: rng.text(),
+                summary: None,
+                reason: rng.maybe_text(),
+                source_iteration: None,
+            })
+            .unwrap();
+        }
+    }
+
+    let mut task_ids = Vec::new();
+    for _ in 0..rng.below(8) {
+        let depends_on = task_ids
+            .iter()
+            .copied()
+            .filter(|_| rng.below(3) == 0)
+            .collect();
+        let id = db
+            .create_task(TaskInput {
+                subsystem: rng.pick(&subsystems).clone(),
+                discipline: rng.pick(&disciplines).clone(),
+                title: rng.text(),
+                description: rng.maybe_text(),
+                priority: rng
+                    .chance()
+                    .then(|| *rng.pick(&[Priority::Low, Priority::High, Priority::Critical])),
+                tags: rng.texts(),
+                depends_on,
+                acceptance_criteria: Some(rng.texts()),
+                context_files: rng.texts(),
+                output_artifacts: rng.texts(),
+                hints: rng.maybe_text(),
+                estimated_turns: rng.chance().then(|| rng.below(20) as u32),
+                provenance: rng
+                    .chance()
+                    .then(|| *rng.pick(&[TaskProvenance::Agent, TaskProvenance::Human])),
+                ..Default::default()
+            })
+            .unwrap();
+        let status = *rng.pick(&[
+            TaskStatus::Draft,
+            TaskStatus::Pending,
+            TaskStatus::InProgress,
+            TaskStatus::Blocked,
+            TaskStatus::Done,
+        ]);
+        db.set_task_status(id, status).unwrap();
+        for _ in 0..rng.below(3) {
+            db.add_signal(id, None, None, None, rng.text()).unwrap();
+        }
+        task_ids.push(id);
+    }
+    db
+}
+
+#[test]
+fn export_import_export_is_identical() {
+    for seed in 0..SEEDS {
+        let exported = random_project(seed).export_prd_yaml().unwrap();
+
+        let imported = empty_db();
+        imported
+            .import_prd_yaml(&exported, PrdImportMode::Replace, false)
+            .unwrap_or_else(|e| panic!("seed {seed}: import failed: {e}\n{exported}"));
+        assert_eq!(
+            imported.export_prd_yaml().unwrap(),
+            exported,
+            "seed {seed}: re-export differs"
+        );
+    }
+}
+
+#[test]
+fn merging_an_export_into_its_own_project_changes_nothing() {
+    for seed in 0..SEEDS {
+        let db = random_project(seed);
+        let exported = db.export_prd_yaml().unwrap();
+        let task_count = db.get_tasks().len() as u32;
+
+        let report = db
+            .import_prd_yaml(&exported, PrdImportMode::Merge, false)
+            .unwrap_or_else(|e| panic!("seed {seed}: merge failed: {e}"));
+        assert_eq!(report.tasks_created, 0, "seed {seed}");
+        assert_eq!(report.tasks_updated, task_count, "seed {seed}");
+        assert_eq!(report.subsystems_created, 0, "seed {seed}");
+        assert_eq!(db.export_prd_yaml().unwrap(), exported, "seed {seed}");
+    }
+}
+
+#[test]
+fn replace_into_a_populated_project_matches_the_source() {
+    for seed in 0..SEEDS {
+        let exported = random_project(seed).export_prd_yaml().unwrap();
+        let target = random_project(seed + SEEDS);
+
+        target
+            .import_prd_yaml(&exported, PrdImportMode::Replace, false)
+            .unwrap_or_else(|e| panic!("seed {seed}: replace failed: {e}"));
+        let reexported = target.export_prd_yaml().unwrap();
+        // The target keeps its own disciplines, so compare everything but that section.
+        let without_disciplines = |yaml: &str| -> String {
+            yaml.split("\n\n")
+                .filter(|section| !section.starts_with("disciplines:"))
+                .collect::<Vec<_>>()
+                .join("\n\n")
+        };
+        assert_eq!(
+            without_disciplines(&reexported),
+            without_disciplines(&exported),
+            "seed {seed}"
+        );
+    }
+}
+
+#[test]
+fn dry_run_reports_without_writing() {
+    for seed in 0..SEEDS {
+        let source = random_project(seed);
+        let exported = source.export_prd_yaml().unwrap();
+
+        let target = empty_db();
+        let before = target.export_prd_yaml().unwrap();
+        let report = target
+            .import_prd_yaml(&exported, PrdImportMode::Replace, true)
+            .unwrap();
+        assert_eq!(
+            report.tasks_created,
+            source.get_tasks().len() as u32,
+            "seed {seed}"
+        );
+        assert_eq!(target.export_prd_yaml().unwrap(), before, "seed {seed}");
+    }
+}
+
+#[test]
+fn rejects_dangling_references_and_cycles() {
+    let db = random_project(7);
+    let before = db.export_prd_yaml().unwrap();
+
+    let yaml = r##"schema_version: "1.0"
+project:
+  title: "Broken"
+
+subsystems:
+- name: "core"
+  display_name: "Core"
+  acronym: "CORE"
+
+disciplines:
+- name: "backend"
+  display_name: "Backend"
+  acronym: "BACK"
+  icon: "Server"
+  color: "#8b5cf6"
+
+tasks:
+- id: 1
+  subsystem: "core"
+  discipline: "backend"
+  title: "One"
+  status: "pending"
+  depends_on:
+  - 2
+- id: 2
+  subsystem: "missing"
+  discipline: "nobody"
+  title: "Two"
+  status: "done"
+  depends_on:
+  - 1
+  - 9
+"##;
+    let err = db
+        .import_prd_yaml(yaml, PrdImportMode::Replace, false)
+        .unwrap_err();
+    assert!(err.contains("unknown subsystem 'missing'"), "{err}");
+    assert!(err.contains("unknown discipline 'nobody'"), "{err}");
+    assert!(
+        err.contains("task 2 is done but has no completed date"),
+        "{err}"
+    );
+    assert!(err.contains("unknown task 9"), "{err}");
+    assert!(err.contains("cycle"), "{err}");
+    assert_eq!(db.export_prd_yaml().unwrap(), before);
+
+    let err = db
+        .import_prd_yaml(
+            "schema_version: \"1.0\"\nproject:\n  title: \"x\"\n  owner: \"me\"\n",
+            PrdImportMode::Merge,
+            false,
+        )
+        .unwrap_err();
+    assert!(err.contains("owner"), "{err}");
+}
+
+#[test]
+fn merge_rejects_cycles_through_existing_dependencies() {
+    let header = r##"schema_version: "1.0"
+project:
+  title: "Cycle"
+
+subsystems:
+- name: "core"
+  display_name: "Core"
+  acronym: "CORE"
+
+disciplines:
+- name: "backend"
+  display_name: "Backend"
+  acronym: "BACK"
+  icon: "Server"
+  color: "#8b5cf6"
+
+tasks:
+"##;
+    let task = |id: u32, depends_on: &str| {
+        format!(
+            "- id: {id}\n  subsystem: \"core\"\n  discipline: \"backend\"\n  title: \"Task {id}\"\n  \
+             status: \"pending\"\n  depends_on: {depends_on}\n"
+        )
+    };
+    let db = empty_db();
+    let seeded = format!("{header}{}{}", task(1, "[2]"), task(2, "[]"));
+    db.import_prd_yaml(&seeded, PrdImportMode::Replace, false)
+        .unwrap();
+    let before = db.export_prd_yaml().unwrap();
+
+    // Task 1 isn't listed, but it still depends on task 2.
+    let merged = format!("{header}{}", task(2, "[1]"));
+    let err = db
+        .import_prd_yaml(&merged, PrdImportMode::Merge, false)
+        .unwrap_err();
+    assert!(err.contains("cycle"), "{err}");
+    assert_eq!(db.export_prd_yaml().unwrap(), before);
+
+    // Listing task 1 without the dependency breaks the cycle.
+    let merged = format!("{header}{}{}", task(1, "[]"), task(2, "[1]"));
+    db.import_prd_yaml(&merged, PrdImportMode::Merge, false)
+        .unwrap();
+}
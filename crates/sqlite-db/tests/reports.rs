@@ -0,0 +1,115 @@
+//! `export_report`: Markdown, CSV, DOT and Mermaid renderings of the task plan.
+
+use sqlite_db::{
+    DisciplineInput, FixedClock, Priority, ReportFormat, SqliteDb, SubsystemInput, TaskInput,
+    TaskStatus,
+};
+
+fn project() -> SqliteDb {
+    let clock = Box::new(FixedClock(
+        chrono::NaiveDate::from_ymd_opt(2026, 1, 1)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap()
+            .and_utc(),
+    ));
+    let db = SqliteDb::open_in_memory(Some(clock)).unwrap();
+    db.initialize_metadata("Shop".to_owned(), Some("Online store".to_owned()))
+        .unwrap();
+    db.create_discipline(DisciplineInput {
+        name: "backend".to_owned(),
+        display_name: "Backend".to_owned(),
+        acronym: "BACK".to_owned(),
+        icon: "Server".to_owned(),
+        color: "#8b5cf6".to_owned(),
+        description: None,
+        system_prompt: None,
+        agent: None,
+        model: None,
+        effort: None,
+        thinking: None,
+        skills: "[]".to_owned(),
+        conventions: None,
+        mcp_servers: "[]".to_owned(),
+        image_path: None,
+        crops: None,
+        image_prompt: None,
+    })
+    .unwrap();
+    for (name, acronym) in [("auth", "AUTH"), ("billing", "BILL")] {
+        db.create_subsystem(SubsystemInput {
+            name: name.to_owned(),
+            display_name: format!("{name} \"core\""),
+            acronym: acronym.to_owned(),
+            description: None,
+        })
+        .unwrap();
+    }
+    let login = db
+        .create_task(TaskInput {
+            subsystem: "auth".to_owned(),
+            discipline: "backend".to_owned(),
+            title: "Login, with #sessions".to_owned(),
+            priority: Some(Priority::High),
+            acceptance_criteria: Some(vec!["Returns a \"session\"".to_owned()]),
+            ..Default::default()
+        })
+        .unwrap();
+    db.create_task(TaskInput {
+        subsystem: "auth".to_owned(),
+        discipline: "backend".to_owned(),
+        title: "Logout".to_owned(),
+        depends_on: vec![login],
+        ..Default::default()
+    })
+    .unwrap();
+    db.set_task_status(login, TaskStatus::Done).unwrap();
+    db.add_signal(login, None, None, None, "Used\nsigned cookies".to_owned())
+        .unwrap();
+    db
+}
+
+#[test]
+fn markdown_groups_tasks_by_subsystem_and_status() {
+    let report = project().export_report(ReportFormat::Markdown);
+    assert!(report.starts_with("# Shop\n\nOnline store\n\n2 tasks: 1 pending, 1 done.\n"));
+    let pending = report.find("### Pending").unwrap();
+    let done = report.find("### Done").unwrap();
+    assert!(pending < done);
+    assert!(report.contains("- **#2 Logout** · `backend` · depends on #1\n"));
+    assert!(report.contains("    - [x] Returns a \"session\"\n"));
+    assert!(report.contains(
+        "  - Latest signals:\n    - learned (2026-01-01T00:00:00Z): Used signed cookies\n"
+    ));
+    assert!(report.contains("## billing \"core\" (BILL)\n\n_No tasks._\n"));
+}
+
+#[test]
+fn csv_quotes_fields_and_has_one_row_per_task() {
+    let csv = project().export_report(ReportFormat::Csv);
+    let rows: Vec<&str> = csv.split("\r\n").collect();
+    assert_eq!(rows[0], "id,subsystem,discipline,title,status,priority,depends_on,tags,acceptance_criteria,estimated_turns,created,updated,completed");
+    assert!(rows[1].starts_with(
+        "1,auth,backend,\"Login, with #sessions\",done,high,,,\"Returns a \"\"session\"\"\","
+    ));
+    assert!(rows[2].starts_with("2,auth,backend,Logout,pending,,1,,,"));
+    assert_eq!(rows.len(), 4);
+}
+
+#[test]
+fn graphs_link_dependencies_and_color_by_status() {
+    let db = project();
+    let dot = db.export_report(ReportFormat::Dot);
+    assert!(dot.starts_with("digraph tasks {\n"));
+    assert!(dot.contains("label=\"auth \\\"core\\\"\";"));
+    assert!(dot.contains("t1 [label=\"#1 Login, with #sessions\", fillcolor=\"#139948\"];"));
+    assert!(dot.contains("  t1 -> t2;\n"));
+    assert!(!dot.contains("cluster_2"));
+
+    let mermaid = db.export_report(ReportFormat::Mermaid);
+    assert!(mermaid.starts_with("flowchart LR\n"));
+    assert!(mermaid.contains("    t1[\"#35;1 Login, with #35;sessions\"]\n"));
+    assert!(mermaid.contains("  t1 --> t2\n"));
+    assert!(mermaid.contains("  classDef done fill:#139948,color:#fff\n  class t1 done\n"));
+    assert!(mermaid.contains("  class t2 pending\n"));
+}
@@ -0,0 +1,271 @@
+//! `mirror_files` / `import_mirror_files`: the text mirror must rebuild an identical
+//! database, apply edits made to the files without losing the database's own, and refuse
+//! files left conflicted by a merge or changed on both sides.
+
+use sqlite_db::{
+    AddSubsystemCommentInput, AskSignalInput, DisciplineInput, FixedClock, Priority, SqliteDb,
+    SubsystemInput, TaskInput, TaskSignalCommentCreateInput, TaskStatus, TaskTemplateInput,
+};
+use std::collections::BTreeMap;
+
+fn empty_db() -> SqliteDb {
+    let clock = Box::new(FixedClock(
+        chrono::NaiveDate::from_ymd_opt(2026, 1, 1)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap()
+            .and_utc(),
+    ));
+    SqliteDb::open_in_memory(Some(clock)).unwrap()
+}
+
+fn populated_db() -> SqliteDb {
+    let db = empty_db();
+    db.initialize_metadata("Mirror".to_owned(), Some("line one\nline two".to_owned()))
+        .unwrap();
+    db.create_discipline(DisciplineInput {
+        name: "backend".to_owned(),
+        display_name: "Backend".to_owned(),
+        acronym: "BACK".to_owned(),
+        icon: "Server".to_owned(),
+        color: "#8b5cf6".to_owned(),
+        description: Some("APIs: \"quoted\"".to_owned()),
+        system_prompt: None,
+        agent: Some("claude".to_owned()),
+        model: None,
+        effort: Some("high".to_owned()),
+        thinking: Some(true),
+        skills: r#"["rust", "sql"]"#.to_owned(),
+        conventions: None,
+        mcp_servers: r#"[{"name": "db", "command": "db-mcp", "args": ["--ro"], "env": {"B": "2", "A": "1"}}]"#
+            .to_owned(),
+        image_path: None,
+        crops: None,
+        image_prompt: None,
+    })
+    .unwrap();
+    db.create_subsystem(SubsystemInput {
+        name: "auth".to_owned(),
+        display_name: "Auth".to_owned(),
+        acronym: "AUTH".to_owned(),
+        description: None,
+    })
+    .unwrap();
+    for body in ["Sessions live in redis", "Tokens: never log them"] {
+        db.add_subsystem_comment(AddSubsystemCommentInput {
+            subsystem_name: "auth".to_owned(),
+            category: "gotcha".to_owned(),
+            discipline: Some("backend".to_owned()),
+            agent_task_id: None,
+            body: body.to_owned(),
+            summary: None,
+            reason: None,
+            source_iteration: None,
+        })
+        .unwrap();
+    }
+
+    let login = db
+        .create_task(TaskInput {
+            subsystem: "auth".to_owned(),
+            discipline: "backend".to_owned(),
+            title: "Implement login".to_owned(),
+            priority: Some(Priority::High),
+            tags: vec!["api".to_owned()],
+            acceptance_criteria: Some(vec!["Returns a session".to_owned()]),
+            ..Default::default()
+        })
+        .unwrap();
+    let logout = db
+        .create_task(TaskInput {
+            subsystem: "auth".to_owned(),
+            discipline: "backend".to_owned(),
+            title: "Implement logout".to_owned(),
+            depends_on: vec![login],
+            ..Default::default()
+        })
+        .unwrap();
+    db.set_task_status(login, TaskStatus::Done).unwrap();
+    db.add_signal(logout, None, None, None, "Check the cookie path".to_owned())
+        .unwrap();
+    db.insert_ask_signal(
+        None,
+        AskSignalInput {
+            task_id: logout,
+            session_id: "session-a".to_owned(),
+            question: "Revoke all devices?".to_owned(),
+            blocking: true,
+            options: Some(vec!["yes".to_owned(), "no".to_owned()]),
+            preferred: Some("yes".to_owned()),
+        },
+    )
+    .unwrap();
+    let ask = db
+        .get_task_by_id(logout)
+        .unwrap()
+        .signals
+        .last()
+        .unwrap()
+        .id;
+    db.add_task_signal_comment(TaskSignalCommentCreateInput {
+        signal_id: ask,
+        session_id: None,
+        author_type: "human".to_owned(),
+        body: "Only this device".to_owned(),
+    })
+    .unwrap();
+
+    db.create_task_template(TaskTemplateInput {
+        discipline: "backend".to_owned(),
+        title: "Add {{endpoint}} endpoint".to_owned(),
+        pseudocode: Some("1. Add route".to_owned()),
+        ..Default::default()
+    })
+    .unwrap();
+    db
+}
+
+#[test]
+fn mirror_rebuilds_an_identical_database() {
+    let files = populated_db().mirror_files().unwrap();
+    assert!(files.contains_key("project.yaml"));
+    assert!(files.contains_key("disciplines/BACK.yaml"));
+    assert!(files.contains_key("subsystems/AUTH.yaml"));
+    assert!(files.contains_key("tasks/1.yaml"));
+    assert!(files.contains_key("tasks/2.yaml"));
+    assert!(files.contains_key("templates/1.yaml"));
+
+    let rebuilt = empty_db();
+    let report = rebuilt.import_mirror_files(&files).unwrap();
+    assert!(report.conflicts.is_empty(), "{:?}", report.conflicts);
+    assert_eq!(report.applied.len(), files.len());
+    assert_eq!(rebuilt.mirror_files().unwrap(), files);
+
+    let report = rebuilt.import_mirror_files(&files).unwrap();
+    assert!(report.applied.is_empty());
+    assert!(report.removed.is_empty());
+}
+
+#[test]
+fn mirror_applies_edited_and_deleted_files() {
+    let db = populated_db();
+    let mut files = db.mirror_files().unwrap();
+    let task = files.get_mut("tasks/1.yaml").unwrap();
+    *task = task.replace("title: Implement login", "title: Implement sign-in");
+    let logout = files.remove("tasks/2.yaml").unwrap();
+    files.remove("templates/1.yaml");
+
+    let report = db.import_mirror_files(&files).unwrap();
+    assert!(report.conflicts.is_empty(), "{:?}", report.conflicts);
+    assert_eq!(report.applied, vec!["tasks/1.yaml"]);
+    assert_eq!(report.removed, vec!["task 2", "template 1"]);
+    assert_eq!(db.get_task_by_id(1).unwrap().title, "Implement sign-in");
+    assert!(db.get_task_by_id(2).is_none());
+    assert!(db.get_task_templates().is_empty());
+
+    // A deleted task comes back with its signals when its file returns.
+    files.insert("tasks/2.yaml".to_owned(), logout.clone());
+    db.import_mirror_files(&files).unwrap();
+    assert_eq!(db.mirror_files().unwrap()["tasks/2.yaml"], logout);
+}
+
+#[test]
+fn mirror_reports_merge_conflicts_without_importing() {
+    let db = populated_db();
+    let before = db.mirror_files().unwrap();
+
+    let mut files = before.clone();
+    let task = files.get_mut("tasks/1.yaml").unwrap();
+    *task = task.replace(
+        "title: Implement login\n",
+        "<<<<<<< HEAD\ntitle: Implement login\n=======\ntitle: Build login\n>>>>>>> feature\n",
+    );
+    files.insert(
+        "tasks/3.yaml".to_owned(),
+        "id: 3\nsubsystem: billing\ndiscipline: backend\ntitle: Invoice\nstatus: pending\n"
+            .to_owned(),
+    );
+    files.insert(
+        "tasks/4.yaml".to_owned(),
+        "id: 4\nsubsystem: auth\ndiscipline: backend\ntitle: x\nstatus: pending\nowner: me\n"
+            .to_owned(),
+    );
+
+    let report = db.import_mirror_files(&files).unwrap();
+    let conflicts: BTreeMap<_, _> = report
+        .conflicts
+        .iter()
+        .map(|c| (c.path.as_str(), (c.line, c.message.as_str())))
+        .collect();
+    assert!(report.applied.is_empty());
+    assert_eq!(conflicts["tasks/1.yaml"].1, "unresolved merge conflict");
+    assert!(conflicts["tasks/1.yaml"].0.is_some());
+    assert!(conflicts["tasks/4.yaml"].1.contains("owner"));
+    assert_eq!(db.mirror_files().unwrap(), before);
+
+    // Once the files parse, broken references are reported the same way.
+    files.insert("tasks/1.yaml".to_owned(), before["tasks/1.yaml"].clone());
+    files.remove("tasks/4.yaml");
+    let report = db.import_mirror_files(&files).unwrap();
+    assert_eq!(report.conflicts.len(), 1);
+    assert_eq!(report.conflicts[0].path, "tasks/3.yaml");
+    assert!(report.conflicts[0].message.contains("billing"));
+    assert_eq!(db.mirror_files().unwrap(), before);
+}
+
+#[test]
+fn mirror_keeps_database_changes_made_since_the_last_export() {
+    let db = populated_db();
+    let mut files = db.mirror_files().unwrap();
+    db.record_mirror_export(&files).unwrap();
+
+    // The database gains a task while the files change task 1 and lose the template.
+    let added = db
+        .create_task(TaskInput {
+            subsystem: "auth".to_owned(),
+            discipline: "backend".to_owned(),
+            title: "Rotate tokens".to_owned(),
+            ..Default::default()
+        })
+        .unwrap();
+    let task = files.get_mut("tasks/1.yaml").unwrap();
+    *task = task.replace("title: Implement login", "title: Implement sign-in");
+    files.remove("templates/1.yaml");
+
+    let report = db.import_mirror_files(&files).unwrap();
+    assert!(report.conflicts.is_empty(), "{:?}", report.conflicts);
+    assert_eq!(report.applied, vec!["tasks/1.yaml"]);
+    assert_eq!(report.removed, vec!["template 1"]);
+    assert_eq!(db.get_task_by_id(1).unwrap().title, "Implement sign-in");
+    assert_eq!(db.get_task_by_id(added).unwrap().title, "Rotate tokens");
+}
+
+#[test]
+fn mirror_reports_files_changed_on_both_sides() {
+    let db = populated_db();
+    let mut files = db.mirror_files().unwrap();
+    db.record_mirror_export(&files).unwrap();
+
+    // Both sides drop task 2, which agrees; only the database adds a signal to task 1.
+    db.delete_task(2).unwrap();
+    db.add_signal(1, None, None, None, "Use argon2".to_owned())
+        .unwrap();
+    let before = db.mirror_files().unwrap();
+    files.remove("tasks/2.yaml");
+    let task = files.get_mut("tasks/1.yaml").unwrap();
+    *task = task.replace("title: Implement login", "title: Implement sign-in");
+
+    let report = db.import_mirror_files(&files).unwrap();
+    assert_eq!(report.conflicts.len(), 1, "{:?}", report.conflicts);
+    assert_eq!(report.conflicts[0].path, "tasks/1.yaml");
+    assert!(report.conflicts[0].message.contains("database"));
+    assert!(report.applied.is_empty());
+    assert_eq!(db.mirror_files().unwrap(), before);
+
+    // Reverting the file keeps the database's version.
+    files.insert("tasks/1.yaml".to_owned(), before["tasks/1.yaml"].clone());
+    let report = db.import_mirror_files(&files).unwrap();
+    assert!(report.conflicts.is_empty(), "{:?}", report.conflicts);
+    assert!(report.applied.is_empty());
+    assert!(report.removed.is_empty());
+}
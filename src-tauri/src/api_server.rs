@@ -6,10 +6,10 @@ use tokio::sync::RwLock;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SignalRequest {
-    task_id: u32,
-    session_id: String,
-    verb: String,
-    payload: serde_json::Value,
+    pub(crate) task_id: u32,
+    pub(crate) session_id: String,
+    pub(crate) verb: String,
+    pub(crate) payload: serde_json::Value,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -127,7 +127,10 @@ async fn handle_signal(
     )
 }
 
-fn insert_signal(db: &sqlite_db::SqliteDb, request: &SignalRequest) -> Result<(), String> {
+pub(crate) fn insert_signal(
+    db: &sqlite_db::SqliteDb,
+    request: &SignalRequest,
+) -> Result<(), String> {
     match request.verb.as_str() {
         "done" => {
             let summary = request
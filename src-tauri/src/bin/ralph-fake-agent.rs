@@ -0,0 +1,6 @@
+use std::env;
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    std::process::exit(ralph4days_lib::run_fake_agent(&args));
+}
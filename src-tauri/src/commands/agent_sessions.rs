@@ -1,4 +1,5 @@
 use super::state::{AppState, CommandContext};
+use ralph_errors::codes;
 use ralph_macros::ipc_type;
 use serde::{Deserialize, Serialize};
 use tauri::State;
@@ -15,7 +16,7 @@ pub fn agent_sessions_create_human(
     state: State<'_, AppState>,
     args: sqlite_db::AgentSessionCreateInput,
 ) -> Result<(), String> {
-    CommandContext::from_tauri_state(&state).db(|db| db.create_human_agent_session(args))
+    CommandContext::new(&state).db(|db| db.create_human_agent_session(args))
 }
 
 #[tauri::command]
@@ -23,7 +24,7 @@ pub fn agent_sessions_update_human(
     state: State<'_, AppState>,
     args: sqlite_db::AgentSessionUpdateInput,
 ) -> Result<(), String> {
-    CommandContext::from_tauri_state(&state).db(|db| db.update_human_agent_session(args))
+    CommandContext::new(&state).db(|db| db.update_human_agent_session(args))
 }
 
 #[tauri::command]
@@ -31,7 +32,7 @@ pub fn agent_sessions_delete_human(
     state: State<'_, AppState>,
     args: AgentSessionsByIdArgs,
 ) -> Result<(), String> {
-    CommandContext::from_tauri_state(&state).db(|db| db.delete_human_agent_session(&args.id))
+    CommandContext::new(&state).db(|db| db.delete_human_agent_session(&args.id))
 }
 
 #[tauri::command]
@@ -39,12 +40,127 @@ pub fn agent_sessions_get(
     state: State<'_, AppState>,
     args: AgentSessionsByIdArgs,
 ) -> Result<Option<sqlite_db::AgentSession>, String> {
-    CommandContext::from_tauri_state(&state).db(|db| Ok(db.get_agent_session_by_id(&args.id)))
+    CommandContext::new(&state).db(|db| Ok(db.get_agent_session_by_id(&args.id)))
 }
 
 #[tauri::command]
 pub fn agent_sessions_list_human(
     state: State<'_, AppState>,
 ) -> Result<Vec<sqlite_db::AgentSession>, String> {
-    CommandContext::from_tauri_state(&state).db(|db| Ok(db.list_human_agent_sessions()))
+    CommandContext::new(&state).db(|db| Ok(db.list_human_agent_sessions()))
+}
+
+#[ipc_type]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentSessionsPromptGetArgs {
+    pub session_number: u32,
+}
+
+/// The prompt session `#sessionNumber` started with, if it recorded one.
+#[tauri::command]
+pub fn agent_sessions_prompt_get(
+    state: State<'_, AppState>,
+    args: AgentSessionsPromptGetArgs,
+) -> Result<Option<sqlite_db::AgentSessionPrompt>, String> {
+    CommandContext::new(&state).db(|db| db.get_agent_session_prompt(args.session_number))
+}
+
+#[ipc_type]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentSessionsPromptDiffArgs {
+    pub from_session_number: u32,
+    pub to_session_number: u32,
+}
+
+#[ipc_type]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentSessionPromptDiff {
+    pub from: sqlite_db::AgentSessionPrompt,
+    pub to: sqlite_db::AgentSessionPrompt,
+    /// Sections only the `to` prompt rendered.
+    pub sections_added: Vec<String>,
+    /// Sections only the `from` prompt rendered.
+    pub sections_removed: Vec<String>,
+    pub mcp_config_changed: bool,
+    /// Unified line diff of the prompt text; empty when identical.
+    pub diff: String,
+}
+
+fn diff_session_prompts(
+    from: sqlite_db::AgentSessionPrompt,
+    to: sqlite_db::AgentSessionPrompt,
+) -> AgentSessionPromptDiff {
+    let only_in = |a: &[String], b: &[String]| -> Vec<String> {
+        a.iter().filter(|name| !b.contains(name)).cloned().collect()
+    };
+    AgentSessionPromptDiff {
+        sections_added: only_in(&to.sections, &from.sections),
+        sections_removed: only_in(&from.sections, &to.sections),
+        mcp_config_changed: from.mcp_config != to.mcp_config,
+        diff: prompt_builder::diff::unified_diff(&from.prompt, &to.prompt),
+        from,
+        to,
+    }
+}
+
+/// Compare the prompts two sessions started with.
+#[tauri::command]
+pub fn agent_sessions_prompt_diff(
+    state: State<'_, AppState>,
+    args: AgentSessionsPromptDiffArgs,
+) -> Result<AgentSessionPromptDiff, String> {
+    let (from, to) = CommandContext::new(&state).db(|db| {
+        let load = |session_number: u32| {
+            db.get_agent_session_prompt(session_number)?.ok_or_else(|| {
+                ralph_errors::err_string(
+                    codes::TASK_OPS,
+                    format!("Session #{session_number} has no recorded prompt"),
+                )
+            })
+        };
+        Ok((
+            load(args.from_session_number)?,
+            load(args.to_session_number)?,
+        ))
+    })?;
+    Ok(diff_session_prompts(from, to))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn prompt(session_number: u32, sections: &[&str], text: &str) -> sqlite_db::AgentSessionPrompt {
+        sqlite_db::AgentSessionPrompt {
+            session_id: format!("session-{session_number}"),
+            session_number,
+            recipe: "task_execution".to_owned(),
+            config_name: None,
+            sections: sections.iter().map(|s| (*s).to_owned()).collect(),
+            prompt: text.to_owned(),
+            prompt_hash: None,
+            mcp_config: Some("{}".to_owned()),
+            created: "2026-01-01T00:00:00Z".to_owned(),
+        }
+    }
+
+    #[test]
+    fn diff_reports_section_and_text_changes() {
+        let diff = diff_session_prompts(
+            prompt(
+                1,
+                &["task_details", "previous_attempts"],
+                "## Your Task\n\nold",
+            ),
+            prompt(2, &["task_details", "repo_map"], "## Your Task\n\nnew"),
+        );
+
+        assert_eq!(diff.sections_added, vec!["repo_map".to_owned()]);
+        assert_eq!(diff.sections_removed, vec!["previous_attempts".to_owned()]);
+        assert!(!diff.mcp_config_changed);
+        assert!(diff.diff.contains("-old\n+new\n"));
+    }
 }
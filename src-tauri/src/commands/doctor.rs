@@ -0,0 +1,24 @@
+use super::state::AppState;
+use ralph_backend::doctor::{run_doctor, DoctorCheck};
+use ralph_macros::ipc_type;
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+#[ipc_type]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectDoctorArgs {
+    /// Apply the safe automatic fixes.
+    #[serde(default)]
+    pub fix: bool,
+}
+
+/// Run every check on the locked project. With `fix`, problems that have a safe fix are
+/// fixed and reported as such.
+#[tauri::command]
+pub async fn project_doctor(
+    state: State<'_, AppState>,
+    args: Option<ProjectDoctorArgs>,
+) -> Result<Vec<DoctorCheck>, String> {
+    run_doctor(&state, args.unwrap_or_default().fix).await
+}
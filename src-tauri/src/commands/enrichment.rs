@@ -0,0 +1,238 @@
+use super::state::{AppState, CommandContext};
+use prompt_builder::enrichment;
+use ralph_errors::{codes, ralph_err};
+use ralph_macros::ipc_type;
+use serde::{Deserialize, Serialize};
+use sqlite_db::{EnrichmentProposal, EnrichmentProposalInput, TaskStatus};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tauri::{AppHandle, Emitter, State};
+
+const ENRICHMENT_PROGRESS_EVENT: &str = "enrichment-progress";
+
+#[ipc_type]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnrichmentBatchRunArgs {
+    /// Only enrich drafts in this subsystem.
+    pub subsystem: Option<String>,
+    /// Only enrich drafts of this discipline.
+    pub discipline: Option<String>,
+    /// Promote valid enrichments straight to `pending` instead of waiting for approval.
+    pub auto_approve: Option<bool>,
+}
+
+#[ipc_type]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnrichmentProposalsDecideArgs {
+    pub task_ids: Vec<u32>,
+}
+
+#[ipc_type]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum EnrichmentOutcome {
+    /// Saved as a proposal waiting for approval.
+    Proposed,
+    /// Applied; the task is now `pending`.
+    Promoted,
+    /// The model's answer failed validation; the task stays a draft.
+    Rejected,
+    /// The prompt, the model call or the database write failed.
+    Failed,
+}
+
+/// One task of a batch run, emitted as `enrichment-progress` when it finishes.
+#[ipc_type]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnrichmentProgress {
+    pub task_id: u32,
+    /// 1-based position in the batch.
+    pub index: u32,
+    pub total: u32,
+    pub outcome: EnrichmentOutcome,
+    /// Why the task was rejected or failed.
+    pub problems: Vec<String>,
+}
+
+/// Holds the "batch running" flag for the lifetime of a run.
+struct RunningBatch<'a>(&'a AtomicBool);
+
+impl<'a> RunningBatch<'a> {
+    fn acquire(flag: &'a AtomicBool) -> Result<Self, String> {
+        if flag.swap(true, Ordering::SeqCst) {
+            return ralph_err!(codes::TASK_OPS, "An enrichment batch is already running");
+        }
+        Ok(Self(flag))
+    }
+}
+
+impl Drop for RunningBatch<'_> {
+    fn drop(&mut self) {
+        self.0.store(false, Ordering::SeqCst);
+    }
+}
+
+/// The enrichment prompt for one task, with instructions asking for a JSON answer.
+fn render_enrichment_prompt(
+    state: &AppState,
+    project_path: &Path,
+    task_id: u32,
+) -> Result<String, String> {
+    let overrides = HashMap::from([(
+        enrichment::INSTRUCTIONS_SECTION.to_owned(),
+        enrichment::response_instructions(),
+    )]);
+    let mut ctx = state.build_prompt_context(project_path, None, overrides, Some(task_id))?;
+    // A project template for the instructions would ask for something other than JSON.
+    ctx.section_templates
+        .remove(enrichment::INSTRUCTIONS_SECTION);
+    Ok(prompt_builder::build(prompt_builder::PromptType::Enrichment, &ctx).prompt)
+}
+
+async fn enrich_task(
+    state: &AppState,
+    project_path: &Path,
+    ollama: &ralph_external::OllamaConfig,
+    task_id: u32,
+    auto_approve: bool,
+) -> (EnrichmentOutcome, Vec<String>) {
+    let prompt = match render_enrichment_prompt(state, project_path, task_id) {
+        Ok(prompt) => prompt,
+        Err(error) => return (EnrichmentOutcome::Failed, vec![error]),
+    };
+    let response = match ralph_external::generate_text(ollama, prompt).await {
+        Ok(response) => response,
+        Err(error) => return (EnrichmentOutcome::Failed, vec![error]),
+    };
+    let proposal = match enrichment::parse_response(&response) {
+        Ok(proposal) => proposal,
+        Err(error) => return (EnrichmentOutcome::Rejected, vec![error]),
+    };
+
+    let mut problems = proposal.problems();
+    if problems.is_empty() {
+        problems.extend(
+            proposal
+                .context_files
+                .iter()
+                .filter(|file| !project_path.join(file).is_file())
+                .map(|file| format!("context file `{file}` does not exist")),
+        );
+    }
+    if !problems.is_empty() {
+        return (EnrichmentOutcome::Rejected, problems);
+    }
+
+    let saved = CommandContext::new(state).db(|db| {
+        db.save_enrichment_proposal(EnrichmentProposalInput {
+            task_id,
+            pseudocode: proposal.pseudocode,
+            acceptance_criteria: proposal.acceptance_criteria,
+            context_files: proposal.context_files,
+            model: Some(ollama.llm_model.clone()),
+        })?;
+        if auto_approve {
+            db.approve_enrichment_proposal(task_id)?;
+        }
+        Ok(())
+    });
+    match saved {
+        Ok(()) if auto_approve => (EnrichmentOutcome::Promoted, Vec::new()),
+        Ok(()) => (EnrichmentOutcome::Proposed, Vec::new()),
+        Err(error) => (EnrichmentOutcome::Failed, vec![error]),
+    }
+}
+
+/// Enrich every draft task (optionally only one subsystem's or discipline's) with the local
+/// LLM, one at a time. Valid answers become proposals unless `autoApprove` is set.
+#[tauri::command]
+pub async fn enrichment_batch_run(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    args: EnrichmentBatchRunArgs,
+) -> Result<Vec<EnrichmentProgress>, String> {
+    let _running = RunningBatch::acquire(&state.enrichment_running)?;
+    let command_ctx = CommandContext::new(&state);
+    let project_path = command_ctx.locked_project_path()?;
+    let drafts: Vec<u32> = command_ctx.db(|db| {
+        Ok(db
+            .list_tasks(false)?
+            .into_iter()
+            .filter(|task| task.status == TaskStatus::Draft)
+            .filter(|task| {
+                args.subsystem
+                    .as_ref()
+                    .map_or(true, |name| &task.subsystem == name)
+            })
+            .filter(|task| {
+                args.discipline
+                    .as_ref()
+                    .map_or(true, |name| &task.discipline == name)
+            })
+            .map(|task| task.id)
+            .collect())
+    })?;
+    if drafts.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let ext_config = ralph_external::ExternalServicesConfig::load()?;
+    let auto_approve = args.auto_approve.unwrap_or(false);
+    let total = u32::try_from(drafts.len()).unwrap_or(u32::MAX);
+    let mut results = Vec::with_capacity(drafts.len());
+    for (index, task_id) in (1..).zip(drafts) {
+        let (outcome, problems) = enrich_task(
+            &state,
+            &project_path,
+            &ext_config.ollama,
+            task_id,
+            auto_approve,
+        )
+        .await;
+        let progress = EnrichmentProgress {
+            task_id,
+            index,
+            total,
+            outcome,
+            problems,
+        };
+        if let Err(error) = app.emit(ENRICHMENT_PROGRESS_EVENT, &progress) {
+            tracing::warn!(error = %error, "Failed to emit enrichment progress");
+        }
+        results.push(progress);
+    }
+    Ok(results)
+}
+
+#[tauri::command]
+pub fn enrichment_proposals_list(
+    state: State<'_, AppState>,
+) -> Result<Vec<EnrichmentProposal>, String> {
+    CommandContext::new(&state).db(sqlite_db::SqliteDb::get_enrichment_proposals)
+}
+
+/// Apply the proposals, promoting their tasks to `pending`. One failure rolls back the batch.
+#[tauri::command]
+pub fn enrichment_proposals_approve(
+    state: State<'_, AppState>,
+    args: EnrichmentProposalsDecideArgs,
+) -> Result<(), String> {
+    CommandContext::new(&state).db(|db| db.approve_enrichment_proposals(&args.task_ids))
+}
+
+/// Drop the proposals; their tasks stay drafts.
+#[tauri::command]
+pub fn enrichment_proposals_reject(
+    state: State<'_, AppState>,
+    args: EnrichmentProposalsDecideArgs,
+) -> Result<(), String> {
+    CommandContext::new(&state).db(|db| {
+        args.task_ids
+            .iter()
+            .try_for_each(|task_id| db.discard_enrichment_proposal(*task_id))
+    })
+}
@@ -0,0 +1,57 @@
+use super::state::AppState;
+use crate::session_host::TauriHost;
+use ralph_backend::execution::{
+    dispatch, start_execution, stop_execution, ExecutionConcurrencySetArgs, ExecutionStartArgs,
+};
+use ralph_backend::session_host::SessionHost;
+use ralph_backend::worker_pool::ExecutionState;
+use ralph_errors::{codes, ToStringErr};
+use tauri::{AppHandle, State};
+
+#[tauri::command]
+pub fn execution_start(
+    app: AppHandle,
+    args: Option<ExecutionStartArgs>,
+) -> Result<ExecutionState, String> {
+    start_execution(&TauriHost(app), args.unwrap_or_default())
+}
+
+#[tauri::command]
+pub fn execution_pause(app: AppHandle, state: State<'_, AppState>) -> Result<(), String> {
+    let mut pool = state.execution.lock().err_str(codes::INTERNAL)?;
+    pool.pause()?;
+    TauriHost(app).emit_execution_state(&pool.state());
+    Ok(())
+}
+
+#[tauri::command]
+pub fn execution_resume(app: AppHandle, state: State<'_, AppState>) -> Result<(), String> {
+    state.execution.lock().err_str(codes::INTERNAL)?.resume()?;
+    dispatch(&TauriHost(app));
+    Ok(())
+}
+
+#[tauri::command]
+pub fn execution_stop(app: AppHandle) -> Result<(), String> {
+    stop_execution(&TauriHost(app))
+}
+
+#[tauri::command]
+pub fn execution_state_get(state: State<'_, AppState>) -> Result<ExecutionState, String> {
+    Ok(state.execution.lock().err_str(codes::INTERNAL)?.state())
+}
+
+#[tauri::command]
+pub fn execution_concurrency_set(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    args: ExecutionConcurrencySetArgs,
+) -> Result<(), String> {
+    state
+        .execution
+        .lock()
+        .err_str(codes::INTERNAL)?
+        .set_concurrency(args.concurrency)?;
+    dispatch(&TauriHost(app));
+    Ok(())
+}
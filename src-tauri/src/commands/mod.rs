@@ -1,12 +1,17 @@
 pub(crate) mod agent_sessions;
+pub(crate) mod doctor;
+pub(crate) mod enrichment;
+pub(crate) mod execution;
 pub(crate) mod project;
 pub(crate) mod prompts;
 pub(crate) mod protocol;
 pub(crate) mod remote;
-mod state;
+pub(crate) mod state;
 pub(crate) mod subsystems;
+pub(crate) mod task_templates;
 pub(crate) mod tasks;
 pub(crate) mod terminal_bridge;
+pub(crate) mod workspace;
 
-pub use project::{project_lock_validated, project_validate_path};
+pub use project::project_validate_path;
 pub use state::AppState;
@@ -1,8 +1,9 @@
 use super::state::{AppState, CommandContext};
-use ralph_errors::{codes, ralph_err, RalphResultExt, ToStringErr};
+use ralph_backend::project::{import_prd, project_lock_validated, restore_backup};
+use ralph_errors::{codes, RalphResultExt};
 use ralph_macros::ipc_type;
 use serde::{Deserialize, Serialize};
-use sqlite_db::SqliteDb;
+use sqlite_db::{BackupReason, DbBackup, EditHistory, SqliteDb};
 use std::path::PathBuf;
 use tauri::{Manager, State};
 
@@ -45,6 +46,44 @@ pub struct ProjectInfo {
     pub created: Option<String>,
 }
 
+#[ipc_type]
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MirrorStatus {
+    /// Whether `.ralph/mirror/` is kept in sync with the database.
+    pub enabled: bool,
+    /// Files that couldn't be imported; syncing is paused until they are fixed.
+    pub conflicts: Vec<sqlite_db::MirrorConflict>,
+}
+
+#[ipc_type]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectPrdImportArgs {
+    /// YAML in the format of the PRD export.
+    pub yaml: String,
+    #[serde(default)]
+    pub mode: sqlite_db::PrdImportMode,
+    /// Validate and report without writing anything.
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+#[ipc_type]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectReportExportArgs {
+    pub format: sqlite_db::ReportFormat,
+}
+
+#[ipc_type]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectBackupRestoreArgs {
+    /// File name from `project_backup_list`.
+    pub name: String,
+}
+
 #[tauri::command]
 #[tracing::instrument]
 pub fn project_validate_path(args: ProjectValidatePathArgs) -> Result<(), String> {
@@ -59,154 +98,14 @@ pub struct ProjectValidatePathArgs {
     pub path: String,
 }
 
-fn seed_disciplines_for_stack(
-    db: &SqliteDb,
-    stack: u8,
-    ralph_dir: Option<&std::path::Path>,
-) -> Result<(), String> {
-    let defs = predefined_disciplines::get_disciplines_for_stack(stack);
-    if defs.is_empty() && stack != 0 {
-        return ralph_err!(
-            codes::DISCIPLINE_OPS,
-            "No disciplines defined for stack {stack}"
-        );
-    }
-
-    if let Some(ralph_dir) = ralph_dir {
-        let images_dir = ralph_dir.join("images").join("disciplines");
-        let _ = std::fs::create_dir_all(&images_dir);
-    }
-
-    for d in &defs {
-        let skills_json = serde_json::to_string(&d.skills).map_err(|error| {
-            format!(
-                "Failed to serialize skills for discipline '{}': {error}",
-                d.name
-            )
-        })?;
-
-        let image_path = ralph_dir.and_then(|ralph_dir| {
-            predefined_disciplines::get_discipline_image(stack, &d.name).and_then(|bytes| {
-                let rel = format!("images/disciplines/{}.png", d.name);
-                let abs = ralph_dir.join(&rel);
-                std::fs::write(&abs, bytes).is_ok().then_some(rel)
-            })
-        });
-
-        let crops_json = d.crops.as_ref().and_then(|crops| {
-            serde_json::to_string(crops).ok().or_else(|| {
-                tracing::warn!(
-                    discipline = %d.name,
-                    "Failed to serialize crops; storing no crops"
-                );
-                None
-            })
-        });
-        let image_prompt_json = d.image_prompt.as_ref().and_then(|prompt| {
-            serde_json::to_string(prompt).ok().or_else(|| {
-                tracing::warn!(
-                    discipline = %d.name,
-                    "Failed to serialize image_prompt; storing no prompt"
-                );
-                None
-            })
-        });
-
-        db.create_discipline(sqlite_db::DisciplineInput {
-            name: d.name.clone(),
-            display_name: d.display_name.clone(),
-            acronym: d.acronym.clone(),
-            icon: d.icon.clone(),
-            color: d.color.clone(),
-            description: d.description.clone(),
-            system_prompt: Some(d.system_prompt.clone()),
-            agent: None,
-            model: None,
-            effort: None,
-            thinking: None,
-            skills: skills_json,
-            conventions: Some(d.conventions.clone()),
-            mcp_servers: "[]".to_owned(),
-            image_path,
-            crops: crops_json,
-            image_prompt: image_prompt_json,
-        })?;
-    }
-    Ok(())
-}
-
 #[tauri::command]
 #[tracing::instrument]
 pub fn project_initialize(args: ProjectInitializeArgs) -> Result<(), String> {
-    let stack = args.stack;
-    tracing::info!("Initializing Ralph project with stack {}", stack);
-    let project_title = args.project_title.clone();
-    let path = PathBuf::from(&args.path);
-
-    if !path.exists() {
-        return ralph_err!(
-            codes::PROJECT_PATH,
-            "Directory not found: {}",
-            path.display()
-        );
-    }
-    if !path.is_dir() {
-        return ralph_err!(codes::PROJECT_PATH, "Not a directory: {}", path.display());
-    }
-
-    let ralph_dir = path.join(".ralph");
-    if ralph_dir.exists() {
-        return ralph_err!(
-            codes::PROJECT_INIT,
-            ".ralph/ already exists at {}",
-            path.display()
-        );
-    }
-
-    std::fs::create_dir(&ralph_dir)
-        .ralph_err(codes::PROJECT_INIT, "Failed to create .ralph/ directory")?;
-
-    let db_dir = ralph_dir.join("db");
-    std::fs::create_dir(&db_dir)
-        .ralph_err(codes::PROJECT_INIT, "Failed to create .ralph/db/ directory")?;
-
-    let db_path = db_dir.join("ralph.db");
-    let db = SqliteDb::open(&db_path, None)?;
-    seed_disciplines_for_stack(&db, stack, Some(&ralph_dir))?;
-    db.initialize_metadata(
-        project_title.clone(),
-        Some("Add project description here".to_owned()),
-    )?;
-    let claude_path = ralph_dir.join("CLAUDE.RALPH.md");
-    let claude_template = format!(
-        "# {project_title} - Ralph Context
-
-## Project Overview
-
-Add context about this project that Claude should know when working on it.
-
-## Architecture
-
-Describe the architecture, tech stack, and key components.
-
-## Coding Standards
-
-- List any coding conventions
-- Style guides
-- Best practices
-
-## Important Notes
-
-- Any gotchas or things to watch out for
-- Known issues or limitations
-- Dependencies or external services
-"
-    );
-
-    std::fs::write(&claude_path, claude_template)
-        .ralph_err(codes::FILESYSTEM, "Failed to create CLAUDE.RALPH.md")?;
-
-    Ok(())
+    ralph_backend::project::initialize_project(
+        &PathBuf::from(&args.path),
+        &args.project_title,
+        args.stack,
+    )
 }
 
 #[ipc_type]
@@ -218,37 +117,6 @@ pub struct ProjectInitializeArgs {
     pub stack: u8,
 }
 
-pub fn project_lock_validated(state: &AppState, path: String) -> Result<(), String> {
-    let canonical_path =
-        std::fs::canonicalize(&path).ralph_err(codes::PROJECT_PATH, "Failed to resolve path")?;
-
-    let mut locked = state.locked_project.lock().err_str(codes::INTERNAL)?;
-    if locked.is_some() {
-        return ralph_err!(
-            codes::PROJECT_LOCK,
-            "Project already locked for this session"
-        );
-    }
-
-    let db_path = canonical_path.join(".ralph").join("db").join("ralph.db");
-    let db = SqliteDb::open(&db_path, None)?;
-
-    let mut db_guard = state.db.lock().err_str(codes::INTERNAL)?;
-    *db_guard = Some(db);
-
-    let project_name = canonical_path
-        .file_name()
-        .map_or_else(|| "Unknown".to_owned(), |n| n.to_string_lossy().to_string());
-    let _ = crate::recent_projects::add(
-        &state.xdg,
-        canonical_path.to_string_lossy().to_string(),
-        project_name,
-    );
-
-    *locked = Some(canonical_path);
-    Ok(())
-}
-
 #[tauri::command]
 pub fn project_lock_set(
     state: State<'_, AppState>,
@@ -262,40 +130,15 @@ pub fn project_lock_set(
 
 #[tauri::command]
 pub fn project_lock_get(state: State<'_, AppState>) -> Result<Option<String>, String> {
-    let locked = CommandContext::from_tauri_state(&state).maybe_locked_project_path()?;
+    let locked = CommandContext::new(&state).maybe_locked_project_path()?;
     Ok(locked.as_ref().map(|p| p.to_string_lossy().to_string()))
 }
 
 #[tauri::command]
 pub fn project_recent_list(
     state: State<'_, AppState>,
-) -> Result<Vec<crate::recent_projects::RecentProject>, String> {
-    crate::recent_projects::load(&state.xdg)
-}
-
-#[tauri::command]
-pub fn execution_start() -> Result<(), String> {
-    ralph_err!(codes::LOOP_ENGINE, "Not implemented")
-}
-
-#[tauri::command]
-pub fn execution_pause() -> Result<(), String> {
-    ralph_err!(codes::LOOP_ENGINE, "Not implemented")
-}
-
-#[tauri::command]
-pub fn execution_resume() -> Result<(), String> {
-    ralph_err!(codes::LOOP_ENGINE, "Not implemented")
-}
-
-#[tauri::command]
-pub fn execution_stop() -> Result<(), String> {
-    ralph_err!(codes::LOOP_ENGINE, "Not implemented")
-}
-
-#[tauri::command]
-pub fn execution_state_get() -> Result<(), String> {
-    ralph_err!(codes::LOOP_ENGINE, "Not implemented")
+) -> Result<Vec<ralph_backend::recent_projects::RecentProject>, String> {
+    ralph_backend::recent_projects::load(&state.xdg)
 }
 
 #[tauri::command]
@@ -381,7 +224,7 @@ pub fn system_home_dir_get() -> Result<String, String> {
 
 #[tauri::command]
 pub fn project_info_get(state: State<'_, AppState>) -> Result<ProjectInfo, String> {
-    let info = CommandContext::from_tauri_state(&state).db(|db| Ok(db.get_project_info()))?;
+    let info = CommandContext::new(&state).db(|db| Ok(db.get_project_info()))?;
     Ok(ProjectInfo {
         title: info.title.clone(),
         description: info.description.clone(),
@@ -389,6 +232,83 @@ pub fn project_info_get(state: State<'_, AppState>) -> Result<ProjectInfo, Strin
     })
 }
 
+/// Import a project from PRD YAML into the locked project's database.
+#[tauri::command]
+pub fn project_prd_import(
+    state: State<'_, AppState>,
+    args: ProjectPrdImportArgs,
+) -> Result<sqlite_db::PrdImportReport, String> {
+    import_prd(&state, &args.yaml, args.mode, args.dry_run)
+}
+
+/// Render the locked project's tasks as a report or dependency graph.
+#[tauri::command]
+pub fn project_report_export(
+    state: State<'_, AppState>,
+    args: ProjectReportExportArgs,
+) -> Result<String, String> {
+    CommandContext::new(&state).db(|db| Ok(db.export_report(args.format)))
+}
+
+#[tauri::command]
+pub fn project_backup_list(state: State<'_, AppState>) -> Result<Vec<DbBackup>, String> {
+    CommandContext::new(&state).db(SqliteDb::list_backups)
+}
+
+#[tauri::command]
+pub fn project_backup_create(state: State<'_, AppState>) -> Result<DbBackup, String> {
+    CommandContext::new(&state).db(|db| db.create_backup(BackupReason::Manual))
+}
+
+/// Undo the most recent task, subsystem or discipline edit made in this session.
+/// Returns the history after it, so `redo[0]` is the edit that was undone.
+#[tauri::command]
+pub fn project_edit_undo(state: State<'_, AppState>) -> Result<EditHistory, String> {
+    CommandContext::new(&state).db(|db| {
+        db.undo_edit()?;
+        Ok(db.edit_history())
+    })
+}
+
+#[tauri::command]
+pub fn project_edit_redo(state: State<'_, AppState>) -> Result<EditHistory, String> {
+    CommandContext::new(&state).db(|db| {
+        db.redo_edit()?;
+        Ok(db.edit_history())
+    })
+}
+
+#[tauri::command]
+pub fn project_edit_history_get(state: State<'_, AppState>) -> Result<EditHistory, String> {
+    CommandContext::new(&state).db(|db| Ok(db.edit_history()))
+}
+
+/// Roll the locked project back to a backup. Returns the backup of the state it replaced.
+#[tauri::command]
+pub fn project_backup_restore(
+    state: State<'_, AppState>,
+    args: ProjectBackupRestoreArgs,
+) -> Result<DbBackup, String> {
+    restore_backup(&state, &args.name)
+}
+
+/// Start mirroring the locked project into `.ralph/mirror/`, one YAML file per entity.
+#[tauri::command]
+pub fn project_mirror_enable(state: State<'_, AppState>) -> Result<MirrorStatus, String> {
+    let ctx = CommandContext::new(&state);
+    let project_path = ctx.locked_project_path()?;
+    ctx.db(|db| state.text_mirror.enable(&project_path, db))?;
+    project_mirror_status(state)
+}
+
+#[tauri::command]
+pub fn project_mirror_status(state: State<'_, AppState>) -> Result<MirrorStatus, String> {
+    Ok(MirrorStatus {
+        enabled: state.text_mirror.is_enabled()?,
+        conflicts: state.text_mirror.conflicts()?,
+    })
+}
+
 #[tauri::command]
 pub fn window_splash_close(app: tauri::AppHandle) {
     if let Some(splash) = app.get_webview_window("splash") {
@@ -46,7 +46,7 @@ pub fn prompt_builder_preview(
         sections,
         user_input,
     } = args;
-    let ctx = CommandContext::from_tauri_state(&state);
+    let ctx = CommandContext::new(&state);
     let project_path = ctx.locked_project_path()?;
 
     let overrides: std::collections::HashMap<String, String> = sections
@@ -93,7 +93,7 @@ pub fn prompt_builder_preview(
 
 #[tauri::command]
 pub fn prompt_builder_config_list(state: State<'_, AppState>) -> Result<Vec<String>, String> {
-    CommandContext::from_tauri_state(&state).db(sqlite_db::SqliteDb::list_prompt_builder_configs)
+    CommandContext::new(&state).db(sqlite_db::SqliteDb::list_prompt_builder_configs)
 }
 
 #[ipc_type]
@@ -108,7 +108,7 @@ pub fn prompt_builder_config_get(
     state: State<'_, AppState>,
     args: PromptBuilderConfigGetArgs,
 ) -> Result<Option<PromptBuilderConfigData>, String> {
-    CommandContext::from_tauri_state(&state).db(|db| db.get_prompt_builder_config(&args.name))
+    CommandContext::new(&state).db(|db| db.get_prompt_builder_config(&args.name))
 }
 
 #[ipc_type]
@@ -123,7 +123,7 @@ pub fn prompt_builder_config_save(
     state: State<'_, AppState>,
     args: PromptBuilderConfigSaveArgs,
 ) -> Result<(), String> {
-    CommandContext::from_tauri_state(&state).db(|db| db.save_prompt_builder_config(args.config))
+    CommandContext::new(&state).db(|db| db.save_prompt_builder_config(args.config))
 }
 
 #[ipc_type]
@@ -138,5 +138,5 @@ pub fn prompt_builder_config_delete(
     state: State<'_, AppState>,
     args: PromptBuilderConfigDeleteArgs,
 ) -> Result<(), String> {
-    CommandContext::from_tauri_state(&state).db(|db| db.delete_prompt_builder_config(&args.name))
+    CommandContext::new(&state).db(|db| db.delete_prompt_builder_config(&args.name))
 }
@@ -1,10 +1,15 @@
-use super::state::AppState;
+use crate::remote::RemoteWireFrameConnection;
 use ralph_contracts::protocol::ProtocolVersionInfo;
 use ralph_errors::{codes, err_string};
 use ralph_macros::ipc_type;
 use std::sync::Arc;
 use tauri::{AppHandle, State};
 
+/// The app's connection to a remote backend, if any. Managed next to `AppState`, which the
+/// CLI shares and which has no use for it.
+#[derive(Default)]
+pub struct RemoteState(tokio::sync::Mutex<Option<RemoteWireFrameConnection>>);
+
 #[ipc_type]
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "camelCase", deny_unknown_fields)]
@@ -32,10 +37,10 @@ pub struct RemoteStatus {
 #[tauri::command]
 pub async fn remote_connect(
     app: AppHandle,
-    state: State<'_, AppState>,
+    state: State<'_, RemoteState>,
     args: RemoteConnectArgs,
 ) -> Result<RemoteConnectResult, String> {
-    let mut guard = state.remote.lock().await;
+    let mut guard = state.0.lock().await;
 
     if let Some(existing) = guard.as_ref() {
         if existing.is_connected() {
@@ -55,7 +60,7 @@ pub async fn remote_connect(
     }
 
     let sink = Arc::new(crate::event_sink::TauriEventSink::new(app));
-    let conn = crate::remote::RemoteWireFrameConnection::connect(args.ws_url.clone(), sink).await?;
+    let conn = RemoteWireFrameConnection::connect(args.ws_url.clone(), sink).await?;
     let protocol = conn.remote_protocol();
 
     *guard = Some(conn);
@@ -67,8 +72,8 @@ pub async fn remote_connect(
 }
 
 #[tauri::command]
-pub async fn remote_disconnect(state: State<'_, AppState>) -> Result<(), String> {
-    let conn = { state.remote.lock().await.take() };
+pub async fn remote_disconnect(state: State<'_, RemoteState>) -> Result<(), String> {
+    let conn = { state.0.lock().await.take() };
     if let Some(conn) = conn {
         conn.shutdown().await?;
     }
@@ -76,8 +81,8 @@ pub async fn remote_disconnect(state: State<'_, AppState>) -> Result<(), String>
 }
 
 #[tauri::command]
-pub async fn remote_status_get(state: State<'_, AppState>) -> Result<RemoteStatus, String> {
-    let guard = state.remote.lock().await;
+pub async fn remote_status_get(state: State<'_, RemoteState>) -> Result<RemoteStatus, String> {
+    let guard = state.0.lock().await;
     let status = guard.as_ref().map_or(
         RemoteStatus {
             connected: false,
@@ -1,21 +1,10 @@
 use super::state::{AppState, CommandContext};
-use crate::diagnostics;
+use ralph_backend::{build_embedding_config, diagnostics};
 use ralph_errors::{codes, RalphResultExt};
 use ralph_macros::ipc_type;
+use sqlite_db::EditTarget;
 use tauri::State;
 
-fn build_embedding_config(
-    ext_config: &ralph_external::ExternalServicesConfig,
-) -> ralph_external::comment_embeddings::CommentEmbeddingConfig<'_> {
-    ralph_external::comment_embeddings::CommentEmbeddingConfig {
-        ollama: &ext_config.ollama,
-        document_prefix: "search_document: ",
-        query_prefix: "search_query: ",
-        min_search_score: 0.4,
-        max_search_results: 10,
-    }
-}
-
 fn db_path(ctx: &CommandContext<'_>) -> Result<std::path::PathBuf, String> {
     let project_path = ctx.locked_project_path()?;
     Ok(project_path.join(".ralph").join("db").join("ralph.db"))
@@ -182,7 +171,7 @@ fn get_discipline_config_or_error(
 
 #[tauri::command]
 pub fn disciplines_list(state: State<'_, AppState>) -> Result<Vec<DisciplineConfig>, String> {
-    CommandContext::from_tauri_state(&state).db(|db| {
+    CommandContext::new(&state).db(|db| {
         Ok(db
             .get_disciplines()
             .iter()
@@ -267,10 +256,58 @@ fn get_subsystem_data_or_error(
     Ok(to_subsystem_data(subsystem))
 }
 
+#[ipc_type]
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubsystemsListArgs {
+    /// Also list archived subsystems.
+    #[serde(default)]
+    pub show_archived: bool,
+}
+
+#[tauri::command]
+pub fn subsystems_list(
+    state: State<'_, AppState>,
+    args: Option<SubsystemsListArgs>,
+) -> Result<Vec<SubsystemData>, String> {
+    let args = args.unwrap_or_default();
+    CommandContext::new(&state).db(|db| {
+        Ok(db
+            .list_subsystems(args.show_archived)
+            .iter()
+            .map(to_subsystem_data)
+            .collect())
+    })
+}
+
+#[ipc_type]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubsystemsSetArchivedArgs {
+    pub name: String,
+    pub archived: bool,
+}
+
+/// Archive or unarchive a subsystem. Its tasks are hidden along with it; nothing is
+/// deleted, so its comments stay searchable.
 #[tauri::command]
-pub fn subsystems_list(state: State<'_, AppState>) -> Result<Vec<SubsystemData>, String> {
-    CommandContext::from_tauri_state(&state)
-        .db(|db| Ok(db.get_subsystems().iter().map(to_subsystem_data).collect()))
+pub fn subsystems_set_archived(
+    state: State<'_, AppState>,
+    args: SubsystemsSetArchivedArgs,
+) -> Result<SubsystemData, String> {
+    let verb = if args.archived {
+        "Archive"
+    } else {
+        "Unarchive"
+    };
+    CommandContext::new(&state).db(|db| {
+        db.journaled(
+            &EditTarget::Subsystem(args.name.clone()),
+            &format!("{verb} subsystem '{}'", args.name),
+            |db| db.set_subsystem_archived(&args.name, args.archived),
+        )?;
+        get_subsystem_data_or_error(db, &args.name)
+    })
 }
 
 #[ipc_type]
@@ -289,7 +326,7 @@ pub fn subsystems_create(
     args: SubsystemsCreateArgs,
 ) -> Result<SubsystemData, String> {
     let subsystem_name = args.name.clone();
-    CommandContext::from_tauri_state(&state).db(|db| {
+    CommandContext::new(&state).db(|db| {
         db.create_subsystem(sqlite_db::SubsystemInput {
             name: args.name,
             display_name: args.display_name,
@@ -316,13 +353,19 @@ pub fn subsystems_update(
     args: SubsystemsUpdateArgs,
 ) -> Result<SubsystemData, String> {
     let subsystem_name = args.name.clone();
-    CommandContext::from_tauri_state(&state).db(|db| {
-        db.update_subsystem(sqlite_db::SubsystemInput {
-            name: args.name,
-            display_name: args.display_name,
-            acronym: args.acronym,
-            description: args.description,
-        })?;
+    CommandContext::new(&state).db(|db| {
+        db.journaled(
+            &EditTarget::Subsystem(subsystem_name.clone()),
+            &format!("Edit subsystem '{subsystem_name}'"),
+            |db| {
+                db.update_subsystem(sqlite_db::SubsystemInput {
+                    name: args.name,
+                    display_name: args.display_name,
+                    acronym: args.acronym,
+                    description: args.description,
+                })
+            },
+        )?;
         get_subsystem_data_or_error(db, &subsystem_name)
     })
 }
@@ -346,7 +389,7 @@ pub async fn subsystems_comment_add(
     state: State<'_, AppState>,
     args: SubsystemsCommentAddArgs,
 ) -> Result<SubsystemData, String> {
-    let command_ctx = CommandContext::from_tauri_state(&state);
+    let command_ctx = CommandContext::new(&state);
     let path = db_path(&command_ctx)?;
     let (comment_id, embedding_text, subsystem) = command_ctx.db_tx(|db| {
         db.add_subsystem_comment(sqlite_db::AddSubsystemCommentInput {
@@ -401,7 +444,7 @@ pub async fn subsystems_comment_update(
     state: State<'_, AppState>,
     args: SubsystemsCommentUpdateArgs,
 ) -> Result<SubsystemData, String> {
-    let command_ctx = CommandContext::from_tauri_state(&state);
+    let command_ctx = CommandContext::new(&state);
     let path = db_path(&command_ctx)?;
     let (embedding_text, needs_embed, subsystem) = command_ctx.db_tx(|db| {
         db.update_subsystem_comment(
@@ -463,8 +506,12 @@ pub fn subsystems_comment_delete(
     state: State<'_, AppState>,
     args: SubsystemsCommentDeleteArgs,
 ) -> Result<SubsystemData, String> {
-    CommandContext::from_tauri_state(&state).db(|db| {
-        db.delete_subsystem_comment(&args.subsystem_name, args.comment_id)?;
+    CommandContext::new(&state).db(|db| {
+        db.journaled(
+            &EditTarget::Subsystem(args.subsystem_name.clone()),
+            &format!("Delete comment on subsystem '{}'", args.subsystem_name),
+            |db| db.delete_subsystem_comment(&args.subsystem_name, args.comment_id),
+        )?;
         get_subsystem_data_or_error(db, &args.subsystem_name)
     })
 }
@@ -517,7 +564,7 @@ pub fn disciplines_create(
     let mcp_json = serde_json::to_string(&mcp_servers)
         .ralph_err(codes::DISCIPLINE_OPS, "Failed to serialize mcp_servers")?;
 
-    CommandContext::from_tauri_state(&state).db(|db| {
+    CommandContext::new(&state).db(|db| {
         db.create_discipline(sqlite_db::DisciplineInput {
             name: normalized_name,
             display_name: args.display_name,
@@ -583,26 +630,32 @@ pub fn disciplines_update(
         .ralph_err(codes::DISCIPLINE_OPS, "Failed to serialize mcp_servers")?;
 
     let discipline_name = args.name.clone();
-    CommandContext::from_tauri_state(&state).db(|db| {
-        db.update_discipline(sqlite_db::DisciplineInput {
-            name: args.name,
-            display_name: args.display_name,
-            acronym: args.acronym,
-            icon: args.icon,
-            color: args.color,
-            description: None,
-            system_prompt: args.system_prompt,
-            agent: args.agent,
-            model: args.model,
-            effort: args.effort,
-            thinking: args.thinking,
-            skills: skills_json,
-            conventions: args.conventions,
-            mcp_servers: mcp_json,
-            image_path: None,
-            crops: None,
-            image_prompt: None,
-        })?;
+    CommandContext::new(&state).db(|db| {
+        db.journaled(
+            &EditTarget::Discipline(discipline_name.clone()),
+            &format!("Edit discipline '{discipline_name}'"),
+            |db| {
+                db.update_discipline(sqlite_db::DisciplineInput {
+                    name: args.name,
+                    display_name: args.display_name,
+                    acronym: args.acronym,
+                    icon: args.icon,
+                    color: args.color,
+                    description: None,
+                    system_prompt: args.system_prompt,
+                    agent: args.agent,
+                    model: args.model,
+                    effort: args.effort,
+                    thinking: args.thinking,
+                    skills: skills_json,
+                    conventions: args.conventions,
+                    mcp_servers: mcp_json,
+                    image_path: None,
+                    crops: None,
+                    image_prompt: None,
+                })
+            },
+        )?;
         get_discipline_config_or_error(db, &discipline_name)
     })
 }
@@ -612,7 +665,13 @@ pub fn subsystems_delete(
     state: State<'_, AppState>,
     args: SubsystemsDeleteArgs,
 ) -> Result<(), String> {
-    CommandContext::from_tauri_state(&state).db(|db| db.delete_subsystem(args.name))
+    CommandContext::new(&state).db(|db| {
+        db.journaled(
+            &EditTarget::Subsystem(args.name.clone()),
+            &format!("Delete subsystem '{}'", args.name),
+            |db| db.delete_subsystem(args.name),
+        )
+    })
 }
 
 #[tauri::command]
@@ -621,8 +680,12 @@ pub fn disciplines_delete(
     args: DisciplinesDeleteArgs,
 ) -> Result<String, String> {
     let deleted_name = args.name.clone();
-    CommandContext::from_tauri_state(&state).db(|db| {
-        db.delete_discipline(args.name)?;
+    CommandContext::new(&state).db(|db| {
+        db.journaled(
+            &EditTarget::Discipline(args.name.clone()),
+            &format!("Delete discipline '{}'", args.name),
+            |db| db.delete_discipline(args.name),
+        )?;
         Ok(deleted_name)
     })
 }
@@ -642,49 +705,71 @@ pub struct DisciplinesDeleteArgs {
 }
 
 #[ipc_type]
-#[derive(Debug, Clone, serde::Serialize)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
-pub struct VisualIdentityData {
-    pub style: String,
-    pub theme: String,
-    pub tone: String,
-    pub references: String,
+pub struct DisciplinesSessionLimitsGetArgs {
+    pub name: String,
 }
 
 #[ipc_type]
-#[derive(Debug, Clone, serde::Serialize)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
-pub struct StackMetadataData {
-    pub stack_id: u8,
+pub struct DisciplinesSessionLimitsSetArgs {
     pub name: String,
-    pub description: String,
-    pub philosophy: String,
-    pub visual_identity: VisualIdentityData,
-    pub when_to_use: Vec<String>,
-    pub discipline_count: u8,
-    pub characteristics: Vec<String>,
+    pub limits: sqlite_db::SessionLimits,
 }
 
 #[tauri::command]
-pub fn stacks_metadata_list() -> Vec<StackMetadataData> {
-    predefined_disciplines::get_all_stack_metadata()
-        .iter()
-        .map(|m| StackMetadataData {
-            stack_id: m.stack_id,
-            name: m.name.clone(),
-            description: m.description.clone(),
-            philosophy: m.philosophy.clone(),
-            visual_identity: VisualIdentityData {
-                style: m.visual_identity.style.clone(),
-                theme: m.visual_identity.theme.clone(),
-                tone: m.visual_identity.tone.clone(),
-                references: m.visual_identity.references.clone(),
-            },
-            when_to_use: m.when_to_use.clone(),
-            discipline_count: m.discipline_count,
-            characteristics: m.characteristics.clone(),
-        })
-        .collect()
+pub fn disciplines_session_limits_get(
+    state: State<'_, AppState>,
+    args: DisciplinesSessionLimitsGetArgs,
+) -> Result<sqlite_db::SessionLimits, String> {
+    CommandContext::new(&state).db(|db| db.get_discipline_session_limits(&args.name))
+}
+
+#[tauri::command]
+pub fn disciplines_session_limits_set(
+    state: State<'_, AppState>,
+    args: DisciplinesSessionLimitsSetArgs,
+) -> Result<(), String> {
+    CommandContext::new(&state).db(|db| db.set_discipline_session_limits(&args.name, args.limits))
+}
+
+#[ipc_type]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DisciplinesReviewSettingsGetArgs {
+    pub name: String,
+}
+
+#[ipc_type]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DisciplinesReviewSettingsSetArgs {
+    pub name: String,
+    pub settings: sqlite_db::ReviewSettings,
+}
+
+#[tauri::command]
+pub fn disciplines_review_settings_get(
+    state: State<'_, AppState>,
+    args: DisciplinesReviewSettingsGetArgs,
+) -> Result<sqlite_db::ReviewSettings, String> {
+    CommandContext::new(&state).db(|db| Ok(db.get_discipline_review_settings(&args.name)))
+}
+
+#[tauri::command]
+pub fn disciplines_review_settings_set(
+    state: State<'_, AppState>,
+    args: DisciplinesReviewSettingsSetArgs,
+) -> Result<(), String> {
+    CommandContext::new(&state)
+        .db(|db| db.set_discipline_review_settings(&args.name, args.settings))
+}
+
+#[tauri::command]
+pub fn stacks_metadata_list() -> Vec<ralph_backend::stacks::StackMetadataData> {
+    ralph_backend::stacks::stacks_metadata()
 }
 
 #[tauri::command]
@@ -694,7 +779,7 @@ pub fn disciplines_image_data_get(
 ) -> Result<Option<String>, String> {
     use base64::Engine;
 
-    let ctx = CommandContext::from_tauri_state(&state);
+    let ctx = CommandContext::new(&state);
     let disc = ctx.db(|db| {
         Ok(db
             .get_disciplines()
@@ -734,7 +819,7 @@ pub fn disciplines_cropped_image_get(
     use base64::Engine;
     use std::io::Cursor;
 
-    let ctx = CommandContext::from_tauri_state(&state);
+    let ctx = CommandContext::new(&state);
     let disc = ctx.db(|db| {
         Ok(db
             .get_disciplines()
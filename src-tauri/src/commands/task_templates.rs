@@ -0,0 +1,179 @@
+use super::state::{AppState, CommandContext};
+use ralph_errors::codes;
+use ralph_macros::ipc_type;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tauri::State;
+
+fn get_template_or_error(
+    db: &sqlite_db::SqliteDb,
+    id: u32,
+) -> Result<sqlite_db::TaskTemplate, String> {
+    db.get_task_template(id).ok_or_else(|| {
+        ralph_errors::err_string(
+            codes::TASK_OPS,
+            format!("Task template {id} not found after mutation"),
+        )
+    })
+}
+
+#[ipc_type]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskTemplatesCreateArgs {
+    pub discipline: String,
+    pub title: String,
+    pub description: Option<String>,
+    pub priority: Option<sqlite_db::Priority>,
+    pub hints: Option<String>,
+    pub estimated_turns: Option<u32>,
+    pub agent: Option<String>,
+    pub model: Option<String>,
+    pub effort: Option<String>,
+    pub thinking: Option<bool>,
+    pub pseudocode: Option<String>,
+}
+
+#[ipc_type]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskTemplatesUpdateArgs {
+    pub id: u32,
+    pub discipline: String,
+    pub title: String,
+    pub description: Option<String>,
+    pub priority: Option<sqlite_db::Priority>,
+    pub hints: Option<String>,
+    pub estimated_turns: Option<u32>,
+    pub agent: Option<String>,
+    pub model: Option<String>,
+    pub effort: Option<String>,
+    pub thinking: Option<bool>,
+    pub pseudocode: Option<String>,
+}
+
+#[ipc_type]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskTemplatesSetActiveArgs {
+    pub id: u32,
+    pub active: bool,
+}
+
+#[ipc_type]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskTemplatesInstantiateArgs {
+    pub template_id: u32,
+    pub subsystem: String,
+    /// Values for the template's `{{name}}` placeholders.
+    #[serde(default)]
+    pub params: HashMap<String, String>,
+}
+
+#[ipc_type]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskTemplatesSaveFromTaskArgs {
+    pub task_id: u32,
+}
+
+#[tauri::command]
+pub fn task_templates_list(
+    state: State<'_, AppState>,
+) -> Result<Vec<sqlite_db::TaskTemplate>, String> {
+    CommandContext::new(&state).db(|db| Ok(db.get_task_templates()))
+}
+
+#[tauri::command]
+pub fn task_templates_create(
+    state: State<'_, AppState>,
+    args: TaskTemplatesCreateArgs,
+) -> Result<sqlite_db::TaskTemplate, String> {
+    let input = sqlite_db::TaskTemplateInput {
+        discipline: args.discipline,
+        title: args.title,
+        description: args.description,
+        priority: args.priority,
+        hints: args.hints,
+        estimated_turns: args.estimated_turns,
+        agent: args.agent,
+        model: args.model,
+        effort: args.effort,
+        thinking: args.thinking,
+        pseudocode: args.pseudocode,
+    };
+    CommandContext::new(&state).db_tx(|db| {
+        let id = db.create_task_template(input)?;
+        get_template_or_error(db, id)
+    })
+}
+
+#[tauri::command]
+pub fn task_templates_update(
+    state: State<'_, AppState>,
+    args: TaskTemplatesUpdateArgs,
+) -> Result<sqlite_db::TaskTemplate, String> {
+    let id = args.id;
+    let input = sqlite_db::TaskTemplateInput {
+        discipline: args.discipline,
+        title: args.title,
+        description: args.description,
+        priority: args.priority,
+        hints: args.hints,
+        estimated_turns: args.estimated_turns,
+        agent: args.agent,
+        model: args.model,
+        effort: args.effort,
+        thinking: args.thinking,
+        pseudocode: args.pseudocode,
+    };
+    CommandContext::new(&state).db(|db| {
+        db.update_task_template(id, input)?;
+        get_template_or_error(db, id)
+    })
+}
+
+#[tauri::command]
+pub fn task_templates_set_active(
+    state: State<'_, AppState>,
+    args: TaskTemplatesSetActiveArgs,
+) -> Result<sqlite_db::TaskTemplate, String> {
+    CommandContext::new(&state).db(|db| {
+        db.set_task_template_active(args.id, args.active)?;
+        get_template_or_error(db, args.id)
+    })
+}
+
+/// Create a task in `subsystem` from the template; returns the new task.
+#[tauri::command]
+pub fn task_templates_instantiate(
+    state: State<'_, AppState>,
+    args: TaskTemplatesInstantiateArgs,
+) -> Result<sqlite_db::Task, String> {
+    CommandContext::new(&state).db_tx(|db| {
+        let task_id = db.instantiate_task_template(sqlite_db::TaskTemplateInstantiateInput {
+            template_id: args.template_id,
+            subsystem: args.subsystem,
+            params: args.params,
+            status: None,
+        })?;
+        db.get_task_by_id(task_id).ok_or_else(|| {
+            ralph_errors::err_string(
+                codes::TASK_OPS,
+                format!("Task {task_id} not found after mutation"),
+            )
+        })
+    })
+}
+
+#[tauri::command]
+pub fn task_templates_save_from_task(
+    state: State<'_, AppState>,
+    args: TaskTemplatesSaveFromTaskArgs,
+) -> Result<sqlite_db::TaskTemplate, String> {
+    CommandContext::new(&state).db_tx(|db| {
+        let id = db.save_task_as_template(args.task_id)?;
+        get_template_or_error(db, id)
+    })
+}
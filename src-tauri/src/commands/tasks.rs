@@ -2,6 +2,7 @@ use super::state::{AppState, CommandContext};
 use ralph_errors::codes;
 use ralph_macros::ipc_type;
 use serde::{Deserialize, Serialize};
+use sqlite_db::EditTarget;
 use tauri::State;
 
 fn get_task_or_error(db: &sqlite_db::SqliteDb, id: u32) -> Result<sqlite_db::Task, String> {
@@ -82,6 +83,23 @@ pub struct TasksGetArgs {
     pub id: u32,
 }
 
+#[ipc_type]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TasksListArgs {
+    /// Also list archived tasks and the tasks of archived subsystems.
+    #[serde(default)]
+    pub show_archived: bool,
+}
+
+#[ipc_type]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TasksSetArchivedArgs {
+    pub id: u32,
+    pub archived: bool,
+}
+
 #[ipc_type]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -157,9 +175,81 @@ pub struct TasksSignalCommentsListArgs {
     pub signal_id: u32,
 }
 
+#[ipc_type]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TasksSessionLimitsGetArgs {
+    pub task_id: u32,
+}
+
+#[ipc_type]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TasksSessionLimitsGetResult {
+    /// Limits stored on the task itself.
+    pub overrides: sqlite_db::SessionLimits,
+    /// Limits a session for the task will run with after discipline and default fallback.
+    pub effective: sqlite_db::SessionLimits,
+}
+
+#[ipc_type]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TasksSessionLimitsSetArgs {
+    pub task_id: u32,
+    pub limits: sqlite_db::SessionLimits,
+}
+
+#[ipc_type]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TasksWorktreeGetArgs {
+    pub task_id: u32,
+}
+
+#[ipc_type]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TasksWorktreeMergeArgs {
+    pub task_id: u32,
+}
+
+#[ipc_type]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TasksWorktreeDiscardArgs {
+    pub task_id: u32,
+}
+
+#[ipc_type]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskWorktreeInfo {
+    /// Latest agent session that ran in the worktree.
+    pub agent_session_id: String,
+    pub branch: String,
+    pub path: String,
+    pub base_commit: String,
+    /// Worktree HEAD when that session ended; `None` while it is still running.
+    pub head_commit: Option<String>,
+    /// Whether the worktree is still on disk (it is gone after a merge or discard).
+    pub exists: bool,
+}
+
+/// Refuse to touch a worktree an agent is still running in.
+fn ensure_worktree_idle(db: &sqlite_db::SqliteDb, task_id: u32) -> Result<(), String> {
+    match db.get_latest_task_worktree_session(task_id) {
+        Some(session) if session.status == "running" => ralph_errors::ralph_err!(
+            codes::GIT_OPS,
+            "Task {task_id} has a session running in its worktree"
+        ),
+        _ => Ok(()),
+    }
+}
+
 #[tauri::command]
 pub fn tasks_create(state: State<'_, AppState>, args: TasksCreateArgs) -> Result<String, String> {
-    let ctx = CommandContext::from_tauri_state(&state);
+    let ctx = CommandContext::new(&state);
     let task_input = sqlite_db::TaskInput {
         subsystem: args.subsystem,
         discipline: args.discipline,
@@ -190,7 +280,7 @@ pub fn tasks_update(
     state: State<'_, AppState>,
     args: TasksUpdateArgs,
 ) -> Result<sqlite_db::Task, String> {
-    let ctx = CommandContext::from_tauri_state(&state);
+    let ctx = CommandContext::new(&state);
     let task_id = args.id;
     let task_input = sqlite_db::TaskInput {
         subsystem: args.subsystem,
@@ -214,7 +304,11 @@ pub fn tasks_update(
     };
 
     ctx.db(|db| {
-        db.update_task(task_id, task_input)?;
+        db.journaled(
+            &EditTarget::Task(task_id),
+            &format!("Edit task #{task_id}"),
+            |db| db.update_task(task_id, task_input),
+        )?;
         get_task_or_error(db, task_id)
     })
 }
@@ -224,7 +318,7 @@ pub fn tasks_set_status(
     state: State<'_, AppState>,
     args: TasksSetStatusArgs,
 ) -> Result<sqlite_db::Task, String> {
-    let ctx = CommandContext::from_tauri_state(&state);
+    let ctx = CommandContext::new(&state);
     let status = sqlite_db::TaskStatus::parse(&args.status).ok_or_else(|| {
         ralph_errors::err_string(
             codes::TASK_VALIDATION,
@@ -232,14 +326,46 @@ pub fn tasks_set_status(
         )
     })?;
     ctx.db(|db| {
-        db.set_task_status(args.id, status)?;
+        db.journaled(
+            &EditTarget::Task(args.id),
+            &format!("Set task #{} to {}", args.id, status.as_str()),
+            |db| db.set_task_status(args.id, status),
+        )?;
+        get_task_or_error(db, args.id)
+    })
+}
+
+/// Archive or unarchive a task. Archived tasks are kept, but left out of listings,
+/// prompts and execution runs.
+#[tauri::command]
+pub fn tasks_set_archived(
+    state: State<'_, AppState>,
+    args: TasksSetArchivedArgs,
+) -> Result<sqlite_db::Task, String> {
+    let verb = if args.archived {
+        "Archive"
+    } else {
+        "Unarchive"
+    };
+    CommandContext::new(&state).db(|db| {
+        db.journaled(
+            &EditTarget::Task(args.id),
+            &format!("{verb} task #{}", args.id),
+            |db| db.set_task_archived(args.id, args.archived),
+        )?;
         get_task_or_error(db, args.id)
     })
 }
 
 #[tauri::command]
 pub fn tasks_delete(state: State<'_, AppState>, args: TasksDeleteArgs) -> Result<(), String> {
-    CommandContext::from_tauri_state(&state).db(|db| db.delete_task(args.id))
+    CommandContext::new(&state).db(|db| {
+        db.journaled(
+            &EditTarget::Task(args.id),
+            &format!("Delete task #{}", args.id),
+            |db| db.delete_task(args.id),
+        )
+    })
 }
 
 #[tauri::command]
@@ -247,7 +373,7 @@ pub fn tasks_signal_add(
     state: State<'_, AppState>,
     args: TasksSignalAddArgs,
 ) -> Result<sqlite_db::Task, String> {
-    CommandContext::from_tauri_state(&state).db(|db| {
+    CommandContext::new(&state).db(|db| {
         db.add_signal(
             args.task_id,
             args.discipline,
@@ -264,8 +390,12 @@ pub fn tasks_signal_update(
     state: State<'_, AppState>,
     args: TasksSignalUpdateArgs,
 ) -> Result<sqlite_db::Task, String> {
-    CommandContext::from_tauri_state(&state).db(|db| {
-        db.update_signal(args.task_id, args.signal_id, args.body)?;
+    CommandContext::new(&state).db(|db| {
+        db.journaled(
+            &EditTarget::Task(args.task_id),
+            &format!("Edit signal on task #{}", args.task_id),
+            |db| db.update_signal(args.task_id, args.signal_id, args.body),
+        )?;
         get_task_or_error(db, args.task_id)
     })
 }
@@ -275,15 +405,85 @@ pub fn tasks_signal_delete(
     state: State<'_, AppState>,
     args: TasksSignalDeleteArgs,
 ) -> Result<sqlite_db::Task, String> {
-    CommandContext::from_tauri_state(&state).db(|db| {
-        db.delete_signal(args.task_id, args.signal_id)?;
+    CommandContext::new(&state).db(|db| {
+        db.journaled(
+            &EditTarget::Task(args.task_id),
+            &format!("Delete signal on task #{}", args.task_id),
+            |db| db.delete_signal(args.task_id, args.signal_id),
+        )?;
         get_task_or_error(db, args.task_id)
     })
 }
 
 #[tauri::command]
-pub fn tasks_list(state: State<'_, AppState>) -> Result<Vec<sqlite_db::Task>, String> {
-    CommandContext::from_tauri_state(&state).db(|db| Ok(db.get_tasks()))
+pub fn tasks_session_limits_get(
+    state: State<'_, AppState>,
+    args: TasksSessionLimitsGetArgs,
+) -> Result<TasksSessionLimitsGetResult, String> {
+    CommandContext::new(&state).db(|db| {
+        Ok(TasksSessionLimitsGetResult {
+            effective: db.effective_session_limits(args.task_id)?,
+            overrides: db.get_task_session_limits(args.task_id)?,
+        })
+    })
+}
+
+#[tauri::command]
+pub fn tasks_session_limits_set(
+    state: State<'_, AppState>,
+    args: TasksSessionLimitsSetArgs,
+) -> Result<(), String> {
+    CommandContext::new(&state).db(|db| db.set_task_session_limits(args.task_id, args.limits))
+}
+
+#[tauri::command]
+pub fn tasks_worktree_get(
+    state: State<'_, AppState>,
+    args: TasksWorktreeGetArgs,
+) -> Result<Option<TaskWorktreeInfo>, String> {
+    let session = CommandContext::new(&state)
+        .db(|db| Ok(db.get_latest_task_worktree_session(args.task_id)))?;
+    Ok(session.and_then(|session| {
+        let branch = session.worktree_branch?;
+        let path = session.worktree_path?;
+        Some(TaskWorktreeInfo {
+            agent_session_id: session.id,
+            exists: std::path::Path::new(&path).exists(),
+            branch,
+            path,
+            base_commit: session.base_commit.unwrap_or_default(),
+            head_commit: session.head_commit,
+        })
+    }))
+}
+
+#[tauri::command]
+pub fn tasks_worktree_merge(
+    state: State<'_, AppState>,
+    args: TasksWorktreeMergeArgs,
+) -> Result<(), String> {
+    let ctx = CommandContext::new(&state);
+    ctx.db(|db| ensure_worktree_idle(db, args.task_id))?;
+    ralph_backend::worktree::merge_task_worktree(&ctx.locked_project_path()?, args.task_id)
+}
+
+#[tauri::command]
+pub fn tasks_worktree_discard(
+    state: State<'_, AppState>,
+    args: TasksWorktreeDiscardArgs,
+) -> Result<(), String> {
+    let ctx = CommandContext::new(&state);
+    ctx.db(|db| ensure_worktree_idle(db, args.task_id))?;
+    ralph_backend::worktree::discard_task_worktree(&ctx.locked_project_path()?, args.task_id)
+}
+
+#[tauri::command]
+pub fn tasks_list(
+    state: State<'_, AppState>,
+    args: Option<TasksListArgs>,
+) -> Result<Vec<sqlite_db::Task>, String> {
+    let args = args.unwrap_or_default();
+    CommandContext::new(&state).db(|db| db.list_tasks(args.show_archived))
 }
 
 #[tauri::command]
@@ -291,14 +491,16 @@ pub fn tasks_get(
     state: State<'_, AppState>,
     args: TasksGetArgs,
 ) -> Result<sqlite_db::Task, String> {
-    CommandContext::from_tauri_state(&state).db(|db| get_task_or_error(db, args.id))
+    CommandContext::new(&state).db(|db| get_task_or_error(db, args.id))
 }
 
 #[tauri::command]
 pub fn tasks_list_items(
     state: State<'_, AppState>,
+    args: Option<TasksListArgs>,
 ) -> Result<Vec<sqlite_db::TaskListItem>, String> {
-    CommandContext::from_tauri_state(&state).db(sqlite_db::SqliteDb::get_task_list_items)
+    let args = args.unwrap_or_default();
+    CommandContext::new(&state).db(|db| db.get_task_list_items(args.show_archived))
 }
 
 #[tauri::command]
@@ -306,7 +508,7 @@ pub fn tasks_signal_summaries_get(
     state: State<'_, AppState>,
     args: TasksSignalSummariesGetArgs,
 ) -> Result<std::collections::HashMap<u32, sqlite_db::TaskSignalSummary>, String> {
-    CommandContext::from_tauri_state(&state).db(|db| db.get_signal_summaries(&args.task_ids))
+    CommandContext::new(&state).db(|db| db.get_signal_summaries(&args.task_ids))
 }
 
 #[tauri::command]
@@ -314,7 +516,7 @@ pub fn tasks_ask_answer(
     state: State<'_, AppState>,
     args: TasksAskAnswerArgs,
 ) -> Result<(), String> {
-    CommandContext::from_tauri_state(&state).db(|db| db.answer_ask(args.signal_id, args.answer))
+    CommandContext::new(&state).db(|db| db.answer_ask(args.signal_id, args.answer))
 }
 
 #[tauri::command]
@@ -322,7 +524,7 @@ pub fn tasks_comment_reply_add(
     state: State<'_, AppState>,
     args: TasksCommentReplyAddArgs,
 ) -> Result<sqlite_db::Task, String> {
-    CommandContext::from_tauri_state(&state).db(|db| {
+    CommandContext::new(&state).db(|db| {
         db.add_signal_with_parent(
             args.task_id,
             None,
@@ -339,7 +541,7 @@ pub fn tasks_signal_comment_add(
     state: State<'_, AppState>,
     args: sqlite_db::TaskSignalCommentCreateInput,
 ) -> Result<u32, String> {
-    CommandContext::from_tauri_state(&state).db(|db| db.add_task_signal_comment(args))
+    CommandContext::new(&state).db(|db| db.add_task_signal_comment(args))
 }
 
 #[tauri::command]
@@ -347,8 +549,7 @@ pub fn tasks_signal_comment_update(
     state: State<'_, AppState>,
     args: TasksSignalCommentUpdateArgs,
 ) -> Result<(), String> {
-    CommandContext::from_tauri_state(&state)
-        .db(|db| db.update_task_signal_comment(args.comment_id, args.body))
+    CommandContext::new(&state).db(|db| db.update_task_signal_comment(args.comment_id, args.body))
 }
 
 #[tauri::command]
@@ -356,7 +557,7 @@ pub fn tasks_signal_comment_delete(
     state: State<'_, AppState>,
     args: TasksSignalCommentDeleteArgs,
 ) -> Result<(), String> {
-    CommandContext::from_tauri_state(&state).db(|db| db.delete_task_signal_comment(args.comment_id))
+    CommandContext::new(&state).db(|db| db.delete_task_signal_comment(args.comment_id))
 }
 
 #[tauri::command]
@@ -364,6 +565,5 @@ pub fn tasks_signal_comments_list(
     state: State<'_, AppState>,
     args: TasksSignalCommentsListArgs,
 ) -> Result<Vec<sqlite_db::TaskSignalComment>, String> {
-    CommandContext::from_tauri_state(&state)
-        .db(|db| Ok(db.get_task_signal_comments(args.signal_id)))
+    CommandContext::new(&state).db(|db| Ok(db.get_task_signal_comments(args.signal_id)))
 }
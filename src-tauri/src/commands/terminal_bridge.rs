@@ -1,199 +1,23 @@
-use super::state::{AppState, CommandContext, ProjectSessionService};
-use crate::terminal::providers::{
-    list_model_entries_for_agent, resolve_agent_provider, resolve_post_start_preamble,
-    resolve_session_effort_for_agent, resolve_session_model_for_agent, shell_agent_enabled,
-    AGENT_CLAUDE, AGENT_CODEX, AGENT_SHELL,
+use super::state::AppState;
+use crate::session_host::TauriHost;
+use ralph_backend::session_host::SessionHost;
+use ralph_backend::session_launch::{
+    emit_system_message, launch_agent_session, start_session, SessionStarter,
 };
-use crate::terminal::{
-    PtyOutputEvent, SessionConfig, SessionInitSettings, SessionStreamMode,
-    TerminalBridgeEmitSystemMessageArgs, TerminalBridgeListModelFormTreeResult,
+use ralph_backend::terminal::providers::{
+    list_model_entries_for_agent, resolve_agent_provider, shell_agent_enabled, AGENT_CLAUDE,
+    AGENT_CODEX, AGENT_SHELL,
+};
+use ralph_backend::terminal::{
+    SessionStreamMode, TerminalBridgeEmitSystemMessageArgs, TerminalBridgeListModelFormTreeResult,
     TerminalBridgeListModelsResult, TerminalBridgeModelOption, TerminalBridgeReplayOutputArgs,
     TerminalBridgeReplayOutputResult, TerminalBridgeResizeArgs, TerminalBridgeSendInputArgs,
     TerminalBridgeSetStreamModeArgs, TerminalBridgeStartHumanSessionArgs,
     TerminalBridgeStartHumanSessionResult, TerminalBridgeStartSessionArgs,
     TerminalBridgeStartTaskSessionArgs, TerminalBridgeTerminateArgs,
 };
-use base64::{engine::general_purpose::STANDARD, Engine};
-use ralph_contracts::transport::EventSink;
-use std::path::PathBuf;
-use std::sync::atomic::{AtomicU64, Ordering};
-use std::time::{SystemTime, UNIX_EPOCH};
 use tauri::{AppHandle, State};
 
-static AGENT_SESSION_COUNTER: AtomicU64 = AtomicU64::new(0);
-
-fn locked_project_path(state: &AppState) -> Result<PathBuf, String> {
-    ProjectSessionService::new(state).locked_project_path()
-}
-
-fn build_system_message_event(session_id: String, text: String) -> PtyOutputEvent {
-    PtyOutputEvent {
-        session_id,
-        seq: 0,
-        data: STANDARD.encode(text.as_bytes()),
-    }
-}
-
-fn emit_system_message(
-    sink: &dyn EventSink,
-    session_id: String,
-    text: String,
-) -> Result<(), String> {
-    let event = build_system_message_event(session_id, text);
-    sink.emit_terminal_output(event)
-}
-
-fn resolve_start_session_context(
-    state: &AppState,
-    mcp_mode: Option<&str>,
-) -> Result<(PathBuf, Option<PathBuf>), String> {
-    let project_path = locked_project_path(state)?;
-    let mcp_config = if let Some(mode) = mcp_mode {
-        Some(state.generate_mcp_config(mode, &project_path)?)
-    } else {
-        None
-    };
-    Ok((project_path, mcp_config))
-}
-
-fn resolve_start_task_session_context(
-    state: &AppState,
-    task_id: u32,
-) -> Result<(PathBuf, PathBuf), String> {
-    let project_path = locked_project_path(state)?;
-    let mcp_config = state.generate_mcp_config_for_task(task_id, &project_path)?;
-    Ok((project_path, mcp_config))
-}
-
-fn build_session_config(
-    agent: Option<String>,
-    selected_model: Option<String>,
-    effort: Option<String>,
-    thinking: Option<bool>,
-    permission_level: Option<String>,
-    post_start_preamble: Option<String>,
-) -> Result<SessionConfig, String> {
-    let provider_id = resolve_agent_provider(agent.as_deref()).id();
-    if provider_id == AGENT_SHELL {
-        if !shell_agent_enabled() {
-            return Err("Shell terminal sessions are disabled in production builds".to_owned());
-        }
-        if selected_model
-            .as_deref()
-            .map(str::trim)
-            .is_some_and(|value| !value.is_empty())
-        {
-            return Err("Shell terminal sessions do not support model selection".to_owned());
-        }
-        if effort
-            .as_deref()
-            .map(str::trim)
-            .is_some_and(|value| !value.is_empty())
-        {
-            return Err("Shell terminal sessions do not support effort selection".to_owned());
-        }
-        let resolved_preamble = resolve_session_post_start_preamble(
-            agent.as_deref(),
-            None,
-            None,
-            thinking,
-            post_start_preamble,
-        );
-        return Ok(SessionConfig {
-            agent,
-            model: None,
-            effort: None,
-            thinking,
-            permission_level,
-            init_settings: SessionInitSettings::default(),
-            post_start_preamble: resolved_preamble,
-        });
-    }
-
-    let runtime_model = resolve_session_model_for_agent(agent.as_deref(), selected_model.clone())?;
-    let runtime_effort =
-        resolve_session_effort_for_agent(agent.as_deref(), selected_model.as_deref(), effort)?;
-    let resolved_preamble = resolve_session_post_start_preamble(
-        agent.as_deref(),
-        runtime_model.clone(),
-        runtime_effort.clone(),
-        thinking,
-        post_start_preamble,
-    );
-    Ok(SessionConfig {
-        agent,
-        model: runtime_model,
-        effort: runtime_effort,
-        thinking,
-        permission_level,
-        init_settings: SessionInitSettings::default(),
-        post_start_preamble: resolved_preamble,
-    })
-}
-
-fn start_session_impl(
-    app: AppHandle,
-    state: &AppState,
-    args: TerminalBridgeStartSessionArgs,
-) -> Result<(), String> {
-    tracing::debug!(
-        session_id = %args.session_id,
-        agent = ?args.agent,
-        mcp_mode = ?args.mcp_mode,
-        model = ?args.model,
-        thinking = ?args.thinking,
-        "terminal_start_session"
-    );
-    let (project_path, mcp_config) =
-        resolve_start_session_context(state, args.mcp_mode.as_deref())?;
-    let config = build_session_config(
-        args.agent,
-        args.model,
-        args.effort,
-        args.thinking,
-        args.permission_level,
-        args.post_start_preamble,
-    )?;
-
-    let sink = std::sync::Arc::new(crate::event_sink::TauriEventSink::new(app));
-    state
-        .pty_manager
-        .create_session(sink, args.session_id, &project_path, mcp_config, config)
-}
-
-fn start_task_session_impl(
-    app: AppHandle,
-    state: &AppState,
-    args: TerminalBridgeStartTaskSessionArgs,
-) -> Result<(), String> {
-    tracing::debug!(
-        session_id = %args.session_id,
-        task_id = args.task_id,
-        agent = ?args.agent,
-        model = ?args.model,
-        thinking = ?args.thinking,
-        "terminal_start_task_session"
-    );
-    let (project_path, mcp_config) = resolve_start_task_session_context(state, args.task_id)?;
-    let config = build_session_config(
-        args.agent,
-        args.model,
-        args.effort,
-        args.thinking,
-        args.permission_level,
-        args.post_start_preamble,
-    )?;
-
-    let sink = std::sync::Arc::new(crate::event_sink::TauriEventSink::new(app));
-    state.pty_manager.create_session(
-        sink,
-        args.session_id,
-        &project_path,
-        Some(mcp_config),
-        config,
-    )
-}
-
 fn send_input_impl(state: &AppState, args: TerminalBridgeSendInputArgs) -> Result<(), String> {
     tracing::debug!(
         session_id = %args.session_id,
@@ -262,99 +86,13 @@ fn emit_system_message_impl(
     emit_system_message(&sink, args.session_id, args.text)
 }
 
-fn generate_agent_session_id() -> String {
-    let millis = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .map_or(0u128, |d| d.as_millis());
-    let counter = AGENT_SESSION_COUNTER.fetch_add(1, Ordering::Relaxed);
-    format!("agent-session-{millis}-{counter}")
-}
-
-fn resolve_session_post_start_preamble(
-    agent: Option<&str>,
-    model: Option<String>,
-    effort: Option<String>,
-    thinking: Option<bool>,
-    user_preamble: Option<String>,
-) -> Option<String> {
-    let config = SessionConfig {
-        agent: agent.map(str::to_owned),
-        model,
-        effort,
-        thinking,
-        permission_level: None,
-        init_settings: SessionInitSettings::default(),
-        post_start_preamble: None,
-    };
-    resolve_post_start_preamble(agent, &config, user_preamble)
-}
-
-fn build_launch_command(config: &SessionConfig) -> String {
-    let agent = resolve_agent_provider(config.agent.as_deref()).id();
-    if agent == AGENT_SHELL {
-        return "shell -i".to_owned();
-    }
-
-    let mut parts = vec![agent.to_owned()];
-
-    if let Some(model) = config.model.as_deref() {
-        parts.push("--model".to_owned());
-        parts.push(model.to_owned());
-    }
-    if let Some(effort) = config.effort.as_deref() {
-        if agent == AGENT_CODEX {
-            parts.push("--config".to_owned());
-            parts.push(format!("model_reasoning_effort={effort}"));
-        } else {
-            parts.push("--effort".to_owned());
-            parts.push(effort.to_owned());
-        }
-    }
-
-    if agent == AGENT_CODEX {
-        match config.permission_level.as_deref().map(str::trim) {
-            Some("safe") => {
-                parts.push("--sandbox".to_owned());
-                parts.push("workspace-write".to_owned());
-                parts.push("--ask-for-approval".to_owned());
-                parts.push("untrusted".to_owned());
-            }
-            Some("auto") => {
-                parts.push("--full-auto".to_owned());
-            }
-            Some("full_auto") => {
-                parts.push("--dangerously-bypass-approvals-and-sandbox".to_owned());
-            }
-            _ => {
-                parts.push("--sandbox".to_owned());
-                parts.push("workspace-write".to_owned());
-                parts.push("--ask-for-approval".to_owned());
-                parts.push("on-request".to_owned());
-            }
-        }
-    } else {
-        parts.push("--permission-mode".to_owned());
-        parts.push(
-            match config.permission_level.as_deref().map(str::trim) {
-                Some("safe") => "default",
-                Some("auto") => "dontAsk",
-                Some("full_auto") => "bypassPermissions",
-                _ => "delegate",
-            }
-            .to_owned(),
-        );
-    }
-
-    parts.join(" ")
-}
-
 #[tauri::command]
 pub fn terminal_start_session(
     app: AppHandle,
     state: State<'_, AppState>,
     args: TerminalBridgeStartSessionArgs,
 ) -> Result<(), String> {
-    start_session_impl(app, state.inner(), args)
+    start_session(TauriHost(app).event_sink(), state.inner(), args, None)
 }
 
 #[tauri::command]
@@ -397,13 +135,28 @@ pub fn terminal_replay_output(
     replay_output_impl(state.inner(), args)
 }
 
+/// Runs the task like a pool worker does, with an `agent_sessions` row that records how the
+/// session ended, including when the watchdog ends it.
 #[tauri::command]
 pub fn terminal_start_task_session(
     app: AppHandle,
-    state: State<'_, AppState>,
     args: TerminalBridgeStartTaskSessionArgs,
 ) -> Result<(), String> {
-    start_task_session_impl(app, state.inner(), args)
+    let args = TerminalBridgeStartHumanSessionArgs {
+        terminal_session_id: args.session_id,
+        kind: "task_execution".to_owned(),
+        task_id: Some(args.task_id),
+        agent: args.agent,
+        model: args.model,
+        effort: args.effort,
+        permission_level: args.permission_level,
+        post_start_preamble: args.post_start_preamble,
+        init_prompt: None,
+        mcp_mode: None,
+        thinking: args.thinking,
+        use_worktree: None,
+    };
+    launch_agent_session(&TauriHost(app), args, SessionStarter::Human, None).map(|_| ())
 }
 
 #[tauri::command]
@@ -430,111 +183,7 @@ pub fn terminal_start_human_session(
         thinking = ?args.thinking,
         "terminal_start_human_session"
     );
-
-    let session_config = build_session_config(
-        args.agent.clone(),
-        args.model.clone(),
-        args.effort.clone(),
-        args.thinking,
-        args.permission_level.clone(),
-        args.post_start_preamble.clone(),
-    )?;
-    let launch_command = build_launch_command(&session_config);
-
-    let resolved_post_start_preamble = session_config.post_start_preamble;
-
-    let agent_session_id = generate_agent_session_id();
-    tracing::debug!(
-        terminal_session_id = %args.terminal_session_id,
-        agent_session_id = %agent_session_id,
-        "terminal_start_human_session.created_agent_session_id"
-    );
-
-    let command_ctx = CommandContext::from_tauri_state(&state);
-
-    let agent_session_number = command_ctx.db_tx(|db| {
-        db.create_human_agent_session(sqlite_db::AgentSessionCreateInput {
-            id: agent_session_id.clone(),
-            kind: args.kind.clone(),
-            task_id: args.task_id,
-            agent: args.agent.clone(),
-            model: args.model.clone(),
-            launch_command: Some(launch_command),
-            post_start_preamble: resolved_post_start_preamble,
-            init_prompt: args.init_prompt.clone(),
-        })?;
-
-        db.get_agent_session_by_id(&agent_session_id)
-            .map(|s| s.session_number)
-            .ok_or_else(|| {
-                format!("Failed to load newly created agent session '{agent_session_id}'")
-            })
-    })?;
-
-    let start_result = if let Some(task_id) = args.task_id {
-        start_task_session_impl(
-            app.clone(),
-            state.inner(),
-            TerminalBridgeStartTaskSessionArgs {
-                session_id: args.terminal_session_id.clone(),
-                task_id,
-                agent: args.agent.clone(),
-                model: args.model.clone(),
-                effort: args.effort.clone(),
-                permission_level: args.permission_level.clone(),
-                thinking: args.thinking,
-                post_start_preamble: args.post_start_preamble.clone(),
-            },
-        )
-    } else {
-        start_session_impl(
-            app.clone(),
-            state.inner(),
-            TerminalBridgeStartSessionArgs {
-                session_id: args.terminal_session_id.clone(),
-                agent: args.agent.clone(),
-                mcp_mode: args.mcp_mode.clone(),
-                model: args.model.clone(),
-                effort: args.effort.clone(),
-                permission_level: args.permission_level.clone(),
-                thinking: args.thinking,
-                post_start_preamble: args.post_start_preamble.clone(),
-            },
-        )
-    };
-
-    if let Err(err) = start_result {
-        let _ = command_ctx.db(|db| {
-            db.update_human_agent_session(sqlite_db::AgentSessionUpdateInput {
-                id: agent_session_id.clone(),
-                kind: None,
-                task_id: None,
-                agent: None,
-                model: None,
-                launch_command: None,
-                post_start_preamble: None,
-                init_prompt: None,
-                ended: Some(chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string()),
-                exit_code: Some(1),
-                closing_verb: None,
-                status: Some("crashed".to_owned()),
-                prompt_hash: None,
-                output_bytes: None,
-                error_text: Some(err.clone()),
-            })
-        });
-        return Err(err);
-    }
-
-    let connected_line =
-        format!("\x1b[2m[connected to agent_session #{agent_session_number:03}]\x1b[0m\r\n");
-    let sink = crate::event_sink::TauriEventSink::new(app);
-    emit_system_message(&sink, args.terminal_session_id, connected_line)?;
-
-    Ok(TerminalBridgeStartHumanSessionResult {
-        agent_session_id,
-        agent_session_number,
-    })
+    launch_agent_session(&TauriHost(app), args, SessionStarter::Human, None)
 }
 
 fn list_models_for_agent(agent: &str) -> TerminalBridgeListModelsResult {
@@ -565,64 +214,11 @@ pub fn terminal_list_model_form_tree() -> TerminalBridgeListModelFormTreeResult
 #[cfg(test)]
 mod tests {
     use super::*;
-    use base64::engine::general_purpose::STANDARD;
+    use base64::{engine::general_purpose::STANDARD, Engine};
     use ralph_contracts::terminal::TERMINAL_OUTPUT_EVENT;
-    use std::path::PathBuf;
     use std::sync::mpsc;
     use std::time::Duration;
     use tauri::Listener;
-    use tempfile::tempdir;
-
-    #[test]
-    fn locked_project_path_errors_when_unset() {
-        let state = AppState::default();
-        let err = locked_project_path(&state).unwrap_err();
-        assert!(err.contains("No project locked"));
-    }
-
-    #[test]
-    fn locked_project_path_returns_value_when_set() {
-        let state = AppState::default();
-        let expected = PathBuf::from("/tmp/ralph4days-test-project");
-        *state.locked_project.lock().unwrap() = Some(expected.clone());
-        let actual = locked_project_path(&state).unwrap();
-        assert_eq!(actual, expected);
-    }
-
-    #[test]
-    fn build_system_message_event_encodes_text_as_base64() {
-        let event =
-            build_system_message_event("session-1".to_owned(), "[session started]\r\n".to_owned());
-        assert_eq!(event.session_id, "session-1");
-        assert_eq!(event.seq, 0);
-        let decoded = STANDARD.decode(event.data).unwrap();
-        assert_eq!(String::from_utf8(decoded).unwrap(), "[session started]\r\n");
-    }
-
-    #[test]
-    fn build_system_message_event_preserves_ansi_and_newlines() {
-        let text = "\u{1b}[2m[session #001 started]\u{1b}[0m\r\n".to_owned();
-        let event = build_system_message_event("session-ansi".to_owned(), text.clone());
-        let decoded = STANDARD.decode(event.data).unwrap();
-        assert_eq!(String::from_utf8(decoded).unwrap(), text);
-    }
-
-    #[test]
-    fn build_launch_command_for_codex_uses_reasoning_effort_config_override() {
-        let config = SessionConfig {
-            agent: Some(AGENT_CODEX.to_owned()),
-            model: Some("gpt-5.3-codex".to_owned()),
-            effort: Some("high".to_owned()),
-            thinking: None,
-            permission_level: None,
-            init_settings: SessionInitSettings::default(),
-            post_start_preamble: None,
-        };
-
-        let launch = build_launch_command(&config);
-        assert!(launch.contains("--config model_reasoning_effort=high"));
-        assert!(!launch.contains("--effort "));
-    }
 
     #[test]
     fn terminal_emit_system_message_emits_output_event() {
@@ -733,52 +329,4 @@ mod tests {
         .unwrap_err();
         assert!(err.contains("No terminal bridge session: missing-session"));
     }
-
-    fn app_state_with_locked_project(path: PathBuf) -> AppState {
-        let state = AppState::default();
-        *state.locked_project.lock().unwrap() = Some(path);
-        state
-    }
-
-    #[test]
-    fn resolve_start_session_context_errors_when_project_unlocked() {
-        let state = AppState::default();
-        let err = resolve_start_session_context(&state, Some("interactive")).unwrap_err();
-        assert!(err.contains("No project locked"));
-    }
-
-    #[test]
-    fn resolve_start_task_session_context_errors_when_project_unlocked() {
-        let state = AppState::default();
-        let err = resolve_start_task_session_context(&state, 7).unwrap_err();
-        assert!(err.contains("No project locked"));
-    }
-
-    #[test]
-    fn resolve_start_session_context_allows_no_mcp_config_without_db() {
-        let dir = tempdir().unwrap();
-        let state = app_state_with_locked_project(dir.path().to_path_buf());
-
-        let (project_path, mcp_config) = resolve_start_session_context(&state, None).unwrap();
-        assert_eq!(project_path, dir.path().to_path_buf());
-        assert!(mcp_config.is_none());
-    }
-
-    #[test]
-    fn resolve_start_session_context_requires_open_db_when_generating_mcp() {
-        let dir = tempdir().unwrap();
-        let state = app_state_with_locked_project(dir.path().to_path_buf());
-
-        let err = resolve_start_session_context(&state, Some("interactive")).unwrap_err();
-        assert!(err.contains("database not open"));
-    }
-
-    #[test]
-    fn resolve_start_task_session_context_requires_open_db() {
-        let dir = tempdir().unwrap();
-        let state = app_state_with_locked_project(dir.path().to_path_buf());
-
-        let err = resolve_start_task_session_context(&state, 42).unwrap_err();
-        assert!(err.contains("database not open"));
-    }
 }
@@ -0,0 +1,73 @@
+use super::state::AppState;
+use ralph_backend::project::{close_project, open_project, switch_project};
+use ralph_backend::workspace::{
+    add_project, remove_project, workspace_dashboard, WorkspaceProject, WorkspaceProjectSummary,
+};
+use ralph_macros::ipc_type;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tauri::State;
+
+#[ipc_type]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceProjectArgs {
+    pub path: String,
+}
+
+#[tauri::command]
+pub fn workspace_project_list(state: State<'_, AppState>) -> Result<Vec<WorkspaceProject>, String> {
+    ralph_backend::workspace::load(&state.xdg)
+}
+
+#[tauri::command]
+pub fn workspace_project_add(
+    state: State<'_, AppState>,
+    args: WorkspaceProjectArgs,
+) -> Result<Vec<WorkspaceProject>, String> {
+    add_project(&state, Path::new(&args.path))
+}
+
+#[tauri::command]
+pub fn workspace_project_remove(
+    state: State<'_, AppState>,
+    args: WorkspaceProjectArgs,
+) -> Result<Vec<WorkspaceProject>, String> {
+    remove_project(&state, Path::new(&args.path))
+}
+
+/// Open a project alongside the locked one, registering it in the workspace.
+#[tauri::command]
+pub fn workspace_project_open(
+    state: State<'_, AppState>,
+    args: WorkspaceProjectArgs,
+) -> Result<(), String> {
+    add_project(&state, Path::new(&args.path))?;
+    open_project(&state, Path::new(&args.path))
+}
+
+#[tauri::command]
+pub fn workspace_project_close(
+    state: State<'_, AppState>,
+    args: WorkspaceProjectArgs,
+) -> Result<(), String> {
+    close_project(&state, Path::new(&args.path))
+}
+
+/// Lock another project, keeping the current one open, and register it in the workspace.
+#[tauri::command]
+pub fn workspace_project_switch(
+    state: State<'_, AppState>,
+    args: WorkspaceProjectArgs,
+) -> Result<(), String> {
+    add_project(&state, Path::new(&args.path))?;
+    switch_project(&state, args.path)
+}
+
+/// What is waiting on a person in each workspace project.
+#[tauri::command]
+pub fn workspace_dashboard_get(
+    state: State<'_, AppState>,
+) -> Result<Vec<WorkspaceProjectSummary>, String> {
+    workspace_dashboard(&state)
+}
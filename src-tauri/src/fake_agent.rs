@@ -0,0 +1,401 @@
+//! Deterministic scripted agent used by end-to-end tests in place of a real LLM CLI.
+//!
+//! A scenario file (YAML or JSON) lists steps that are played back in order:
+//!
+//! ```yaml
+//! steps:
+//!   - output: "Reading task context...\n"
+//!   - touch: { path: src/lib.rs, content: "pub fn answer() -> u32 { 42 }\n" }
+//!   - signal: { verb: done, payload: { summary: "Implemented answer()" } }
+//!   - sleep_ms: 50
+//! exit_code: 0
+//! ```
+//!
+//! Signals are delivered the same way `task_signals_server.ts` delivers them: through the
+//! Ralph API server when `RALPH_API_PORT` is present in the `ralph-signals` MCP env, otherwise
+//! straight into the project database at `RALPH_DB_PATH`.
+
+use crate::api_server::{insert_signal, SignalRequest};
+use serde::Deserialize;
+use std::io::{Read, Write};
+use std::path::{Component, Path, PathBuf};
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FakeScenario {
+    #[serde(default)]
+    pub steps: Vec<FakeStep>,
+    #[serde(default)]
+    pub exit_code: i32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FakeStep {
+    /// Write text to stdout (the PTY).
+    Output(String),
+    /// Call an MCP signal verb with the given payload.
+    Signal {
+        verb: String,
+        #[serde(default = "empty_payload")]
+        payload: serde_json::Value,
+    },
+    /// Create or overwrite a file relative to the working directory.
+    Touch {
+        path: String,
+        #[serde(default)]
+        content: Option<String>,
+    },
+    /// Pause playback, e.g. to exercise idle detection.
+    SleepMs(u64),
+}
+
+fn empty_payload() -> serde_json::Value {
+    serde_json::Value::Object(serde_json::Map::new())
+}
+
+/// Signal routing extracted from the `ralph-signals` entry of an MCP config.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FakeSignalTarget {
+    pub task_id: u32,
+    pub session_id: String,
+    pub db_path: Option<String>,
+    pub api_port: Option<u16>,
+}
+
+/// Parse YAML (or JSON) scenario text.
+///
+/// Goes through `serde_json::Value` so steps can be written as plain single-key maps
+/// (`- output: "..."`) instead of serde_yaml's `!tag` enum syntax.
+pub fn parse_scenario(text: &str) -> Result<FakeScenario, String> {
+    serde_yaml::from_str::<serde_json::Value>(text)
+        .map_err(|e| e.to_string())
+        .and_then(|value| serde_json::from_value(value).map_err(|e| e.to_string()))
+        .map_err(|e| format!("Invalid fake agent scenario: {e}"))
+}
+
+pub fn load_scenario(path: &Path) -> Result<FakeScenario, String> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read fake agent scenario {}: {e}", path.display()))?;
+    parse_scenario(&text)
+}
+
+/// Read the `ralph-signals` server env out of a generated `--mcp-config` JSON document.
+pub fn parse_signal_target(mcp_config_json: &str) -> Result<Option<FakeSignalTarget>, String> {
+    let config: serde_json::Value = serde_json::from_str(mcp_config_json)
+        .map_err(|e| format!("Invalid MCP config JSON: {e}"))?;
+    let Some(env) = config
+        .get("mcpServers")
+        .and_then(|servers| servers.get("ralph-signals"))
+        .and_then(|server| server.get("env"))
+    else {
+        return Ok(None);
+    };
+
+    let get = |key: &str| env.get(key).and_then(|v| v.as_str()).map(str::to_owned);
+
+    let task_id = get("RALPH_TASK_ID")
+        .ok_or("ralph-signals env is missing RALPH_TASK_ID")?
+        .parse::<u32>()
+        .map_err(|e| format!("Invalid RALPH_TASK_ID: {e}"))?;
+    let session_id =
+        get("RALPH_SESSION_ID").ok_or("ralph-signals env is missing RALPH_SESSION_ID")?;
+    let api_port = get("RALPH_API_PORT")
+        .map(|port| {
+            port.parse::<u16>()
+                .map_err(|e| format!("Invalid RALPH_API_PORT: {e}"))
+        })
+        .transpose()?;
+
+    Ok(Some(FakeSignalTarget {
+        task_id,
+        session_id,
+        db_path: get("RALPH_DB_PATH"),
+        api_port,
+    }))
+}
+
+/// Resolve a scenario-relative path, refusing anything that escapes the working directory.
+fn resolve_touch_path(working_dir: &Path, relative: &str) -> Result<PathBuf, String> {
+    let rel = Path::new(relative);
+    if rel
+        .components()
+        .any(|c| !matches!(c, Component::Normal(_) | Component::CurDir))
+    {
+        return Err(format!(
+            "Touch path must stay inside the working directory: {relative}"
+        ));
+    }
+    Ok(working_dir.join(rel))
+}
+
+fn touch_file(working_dir: &Path, relative: &str, content: Option<&str>) -> Result<(), String> {
+    let path = resolve_touch_path(working_dir, relative)?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {e}", parent.display()))?;
+    }
+    content
+        .map_or_else(
+            || {
+                std::fs::OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(&path)
+                    .map(|_| ())
+            },
+            |text| std::fs::write(&path, text),
+        )
+        .map_err(|e| format!("Failed to touch {}: {e}", path.display()))
+}
+
+fn post_json(port: u16, route: &str, body: &serde_json::Value) -> Result<(), String> {
+    let body = body.to_string();
+    let mut stream = std::net::TcpStream::connect(("127.0.0.1", port))
+        .map_err(|e| format!("Failed to connect to API server on port {port}: {e}"))?;
+    let request = format!(
+        "POST {route} HTTP/1.1\r\nHost: 127.0.0.1:{port}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    );
+    stream
+        .write_all(request.as_bytes())
+        .map_err(|e| format!("Failed to send request to {route}: {e}"))?;
+
+    let mut response = String::new();
+    stream
+        .read_to_string(&mut response)
+        .map_err(|e| format!("Failed to read response from {route}: {e}"))?;
+    let status_line = response.lines().next().unwrap_or_default();
+    if status_line.split_whitespace().nth(1) == Some("200") {
+        Ok(())
+    } else {
+        Err(format!("{route} returned '{status_line}'"))
+    }
+}
+
+fn send_signal(
+    target: &FakeSignalTarget,
+    verb: &str,
+    payload: &serde_json::Value,
+) -> Result<(), String> {
+    if let Some(port) = target.api_port {
+        if let Some(db_path) = &target.db_path {
+            post_json(
+                port,
+                "/api/set-db-path",
+                &serde_json::json!({ "db_path": db_path }),
+            )?;
+        }
+        return post_json(
+            port,
+            "/api/task-signal",
+            &serde_json::json!({
+                "task_id": target.task_id,
+                "session_id": target.session_id,
+                "verb": verb,
+                "payload": payload,
+            }),
+        );
+    }
+
+    let db_path = target
+        .db_path
+        .as_deref()
+        .ok_or("ralph-signals env has neither RALPH_API_PORT nor RALPH_DB_PATH")?;
+    let db = sqlite_db::SqliteDb::open(Path::new(db_path), None)?;
+    insert_signal(
+        &db,
+        &SignalRequest {
+            task_id: target.task_id,
+            session_id: target.session_id.clone(),
+            verb: verb.to_owned(),
+            payload: payload.clone(),
+        },
+    )
+}
+
+/// Play a scenario to completion and return the exit code the process should use.
+///
+/// Step failures are reported on stderr and end playback with exit code 1, so a broken
+/// scenario shows up as a crashed session rather than a silent pass.
+pub fn run_scenario(
+    scenario: &FakeScenario,
+    working_dir: &Path,
+    signal_target: Option<&FakeSignalTarget>,
+) -> i32 {
+    let mut stdout = std::io::stdout();
+    for (index, step) in scenario.steps.iter().enumerate() {
+        let result = match step {
+            FakeStep::Output(text) => stdout
+                .write_all(text.as_bytes())
+                .and_then(|()| stdout.flush())
+                .map_err(|e| format!("Failed to write output: {e}")),
+            FakeStep::Signal { verb, payload } => signal_target
+                .ok_or_else(|| {
+                    "Signal step requires an MCP config with a ralph-signals server".to_owned()
+                })
+                .and_then(|target| send_signal(target, verb, payload)),
+            FakeStep::Touch { path, content } => touch_file(working_dir, path, content.as_deref()),
+            FakeStep::SleepMs(ms) => {
+                std::thread::sleep(std::time::Duration::from_millis(*ms));
+                Ok(())
+            }
+        };
+        if let Err(error) = result {
+            eprintln!("fake agent: step {} failed: {error}", index + 1);
+            return 1;
+        }
+    }
+    scenario.exit_code
+}
+
+/// Entry point for the `ralph-fake-agent` binary.
+///
+/// Accepts `--scenario <path>` (required), `--mcp-config <path>` (optional) and
+/// `--prompt <text>` (optional, ignored: the scenario decides what happens).
+pub fn run_cli(args: &[String]) -> i32 {
+    let mut scenario_path: Option<PathBuf> = None;
+    let mut mcp_config_path: Option<PathBuf> = None;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--scenario" => scenario_path = iter.next().map(PathBuf::from),
+            "--mcp-config" => mcp_config_path = iter.next().map(PathBuf::from),
+            "--prompt" => {
+                iter.next();
+            }
+            other => {
+                eprintln!("fake agent: unexpected argument '{other}'");
+                return 2;
+            }
+        }
+    }
+
+    let Some(scenario_path) = scenario_path else {
+        eprintln!("fake agent: --scenario <path> is required");
+        return 2;
+    };
+
+    let prepared = load_scenario(&scenario_path).and_then(|scenario| {
+        let target = mcp_config_path
+            .as_deref()
+            .map(|path| {
+                std::fs::read_to_string(path)
+                    .map_err(|e| format!("Failed to read MCP config {}: {e}", path.display()))
+                    .and_then(|json| parse_signal_target(&json))
+            })
+            .transpose()?
+            .flatten();
+        let working_dir = std::env::current_dir()
+            .map_err(|e| format!("Failed to resolve working directory: {e}"))?;
+        Ok((scenario, target, working_dir))
+    });
+
+    prepared.map_or_else(
+        |error| {
+            eprintln!("fake agent: {error}");
+            2
+        },
+        |(scenario, target, working_dir)| run_scenario(&scenario, &working_dir, target.as_ref()),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn parses_all_step_kinds() {
+        let scenario = parse_scenario(
+            r#"
+steps:
+  - output: "hello\n"
+  - touch: { path: src/lib.rs, content: "fn main() {}" }
+  - touch: { path: notes.txt }
+  - signal: { verb: done, payload: { summary: "ok" } }
+  - sleep_ms: 5
+exit_code: 3
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(scenario.steps.len(), 5);
+        assert_eq!(scenario.exit_code, 3);
+        assert!(matches!(&scenario.steps[0], FakeStep::Output(text) if text == "hello\n"));
+        assert!(matches!(&scenario.steps[3], FakeStep::Signal { verb, .. } if verb == "done"));
+        assert!(matches!(scenario.steps[4], FakeStep::SleepMs(5)));
+    }
+
+    #[test]
+    fn exit_code_defaults_to_zero() {
+        let scenario = parse_scenario("steps: []").unwrap();
+        assert_eq!(scenario.exit_code, 0);
+    }
+
+    #[test]
+    fn rejects_unknown_step() {
+        let err = parse_scenario("steps:\n  - explode: true\n").unwrap_err();
+        assert!(err.contains("Invalid fake agent scenario"));
+    }
+
+    #[test]
+    fn extracts_signal_target_from_mcp_config() {
+        let json = r#"{"mcpServers":{"ralph-signals":{"command":"bun","args":["x.ts"],"env":{"RALPH_TASK_ID":"7","RALPH_SESSION_ID":"abc","RALPH_DB_PATH":"/tmp/r.db","RALPH_API_PORT":"4321"}}}}"#;
+        let target = parse_signal_target(json).unwrap().unwrap();
+        assert_eq!(
+            target,
+            FakeSignalTarget {
+                task_id: 7,
+                session_id: "abc".to_owned(),
+                db_path: Some("/tmp/r.db".to_owned()),
+                api_port: Some(4321),
+            }
+        );
+    }
+
+    #[test]
+    fn missing_signal_server_yields_no_target() {
+        let target = parse_signal_target(r#"{"mcpServers":{}}"#).unwrap();
+        assert!(target.is_none());
+    }
+
+    #[test]
+    fn touch_rejects_escaping_paths() {
+        let dir = tempdir().unwrap();
+        assert!(resolve_touch_path(dir.path(), "../outside.txt").is_err());
+        assert!(resolve_touch_path(dir.path(), "/etc/passwd").is_err());
+        assert!(resolve_touch_path(dir.path(), "src/ok.rs").is_ok());
+    }
+
+    #[test]
+    fn run_touches_files_and_returns_exit_code() {
+        let dir = tempdir().unwrap();
+        let scenario = parse_scenario(
+            r#"
+steps:
+  - touch: { path: src/new.rs, content: "pub fn f() {}" }
+  - touch: { path: empty.txt }
+exit_code: 4
+"#,
+        )
+        .unwrap();
+
+        let code = run_scenario(&scenario, dir.path(), None);
+        assert_eq!(code, 4);
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("src/new.rs")).unwrap(),
+            "pub fn f() {}"
+        );
+        assert!(dir.path().join("empty.txt").exists());
+    }
+
+    #[test]
+    fn signal_without_target_fails_playback() {
+        let dir = tempdir().unwrap();
+        let scenario =
+            parse_scenario("steps:\n  - signal: { verb: done, payload: { summary: x } }\n")
+                .unwrap();
+        assert_eq!(run_scenario(&scenario, dir.path(), None), 1);
+    }
+}
@@ -1,13 +1,13 @@
 mod api_server;
 mod commands;
-mod diagnostics;
 mod event_sink;
-mod recent_projects;
+mod fake_agent;
 mod remote;
-mod terminal;
-mod xdg;
+mod session_host;
 
 use commands::AppState;
+pub use ralph_backend::PromptInputs;
+use ralph_backend::{diagnostics, terminal};
 use tauri::Manager;
 use tauri_plugin_cli::CliExt;
 
@@ -38,6 +38,21 @@ pub fn list_provider_model_entries(agent: Option<&str>) -> Vec<terminal::provide
     terminal::providers::list_model_entries_for_agent(agent)
 }
 
+/// Play back a scripted fake agent scenario; used by the `ralph-fake-agent` binary.
+pub fn run_fake_agent(args: &[String]) -> i32 {
+    fake_agent::run_cli(args)
+}
+
+/// Assemble a prompt context the way task sessions do, without the RAG comment search; used by
+/// the prompt snapshot test.
+pub fn assemble_prompt_context(
+    db: &sqlite_db::SqliteDb,
+    project_path: &std::path::Path,
+    inputs: PromptInputs,
+) -> Result<prompt_builder::PromptContext, String> {
+    ralph_backend::assemble_prompt_context(db, project_path, inputs, None)
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     let _start = std::time::Instant::now();
@@ -60,6 +75,7 @@ pub fn run() {
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_cli::init())
         .manage(AppState::default())
+        .manage(commands::remote::RemoteState::default())
         .setup(|app| {
             // Start API server for MCP signal communication
             let app_handle = app.handle().clone();
@@ -139,7 +155,7 @@ pub fn run() {
                         }
 
                         let state: &AppState = app.state::<AppState>().inner();
-                        if let Err(e) = commands::project_lock_validated(state, path_str.clone()) {
+                        if let Err(e) = ralph_backend::project::project_lock_validated(state, path_str.clone()) {
                             eprintln!("Error: {e}");
                             std::process::exit(1);
                         }
@@ -150,11 +166,16 @@ pub fn run() {
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
-            commands::project::execution_start,
-            commands::project::execution_pause,
-            commands::project::execution_resume,
-            commands::project::execution_stop,
-            commands::project::execution_state_get,
+            commands::execution::execution_start,
+            commands::execution::execution_pause,
+            commands::execution::execution_resume,
+            commands::execution::execution_stop,
+            commands::execution::execution_state_get,
+            commands::execution::execution_concurrency_set,
+            commands::enrichment::enrichment_batch_run,
+            commands::enrichment::enrichment_proposals_list,
+            commands::enrichment::enrichment_proposals_approve,
+            commands::enrichment::enrichment_proposals_reject,
             commands::project::project_scan,
             commands::project::system_home_dir_get,
             commands::project::project_validate_path,
@@ -163,11 +184,30 @@ pub fn run() {
             commands::project::project_lock_get,
             commands::project::project_recent_list,
             commands::project::project_info_get,
+            commands::project::project_prd_import,
+            commands::project::project_report_export,
+            commands::project::project_backup_list,
+            commands::project::project_backup_create,
+            commands::project::project_backup_restore,
+            commands::project::project_edit_undo,
+            commands::project::project_edit_redo,
+            commands::project::project_edit_history_get,
+            commands::project::project_mirror_enable,
+            commands::project::project_mirror_status,
+            commands::doctor::project_doctor,
+            commands::workspace::workspace_project_list,
+            commands::workspace::workspace_project_add,
+            commands::workspace::workspace_project_remove,
+            commands::workspace::workspace_project_open,
+            commands::workspace::workspace_project_close,
+            commands::workspace::workspace_project_switch,
+            commands::workspace::workspace_dashboard_get,
             commands::project::window_splash_close,
             commands::project::window_open_new,
             commands::tasks::tasks_create,
             commands::tasks::tasks_update,
             commands::tasks::tasks_set_status,
+            commands::tasks::tasks_set_archived,
             commands::tasks::tasks_delete,
             commands::tasks::tasks_signal_add,
             commands::tasks::tasks_signal_update,
@@ -182,15 +222,29 @@ pub fn run() {
             commands::tasks::tasks_signal_comment_update,
             commands::tasks::tasks_signal_comment_delete,
             commands::tasks::tasks_signal_comments_list,
+            commands::tasks::tasks_session_limits_get,
+            commands::tasks::tasks_session_limits_set,
+            commands::tasks::tasks_worktree_get,
+            commands::tasks::tasks_worktree_merge,
+            commands::tasks::tasks_worktree_discard,
+            commands::task_templates::task_templates_list,
+            commands::task_templates::task_templates_create,
+            commands::task_templates::task_templates_update,
+            commands::task_templates::task_templates_set_active,
+            commands::task_templates::task_templates_instantiate,
+            commands::task_templates::task_templates_save_from_task,
             commands::agent_sessions::agent_sessions_create_human,
             commands::agent_sessions::agent_sessions_update_human,
             commands::agent_sessions::agent_sessions_delete_human,
             commands::agent_sessions::agent_sessions_get,
             commands::agent_sessions::agent_sessions_list_human,
+            commands::agent_sessions::agent_sessions_prompt_get,
+            commands::agent_sessions::agent_sessions_prompt_diff,
             commands::subsystems::disciplines_list,
             commands::subsystems::subsystems_list,
             commands::subsystems::subsystems_create,
             commands::subsystems::subsystems_update,
+            commands::subsystems::subsystems_set_archived,
             commands::subsystems::subsystems_delete,
             commands::subsystems::subsystems_comment_add,
             commands::subsystems::subsystems_comment_update,
@@ -198,6 +252,10 @@ pub fn run() {
             commands::subsystems::disciplines_create,
             commands::subsystems::disciplines_update,
             commands::subsystems::disciplines_delete,
+            commands::subsystems::disciplines_session_limits_get,
+            commands::subsystems::disciplines_session_limits_set,
+            commands::subsystems::disciplines_review_settings_get,
+            commands::subsystems::disciplines_review_settings_set,
             commands::subsystems::stacks_metadata_list,
             commands::subsystems::disciplines_image_data_get,
             commands::subsystems::disciplines_cropped_image_get,
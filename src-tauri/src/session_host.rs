@@ -0,0 +1,33 @@
+//! The desktop app as a `SessionHost`: sessions and the execution loop report to the window.
+
+use crate::commands::AppState;
+use ralph_backend::session_host::{SessionHost, EXECUTION_STATE_EVENT};
+use ralph_backend::worker_pool::ExecutionState;
+use ralph_contracts::transport::EventSink;
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter, Manager};
+
+pub struct TauriHost<R: tauri::Runtime = tauri::Wry>(pub AppHandle<R>);
+
+// A derive would require `R: Clone`; the handle is cloneable for every runtime.
+impl<R: tauri::Runtime> Clone for TauriHost<R> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<R: tauri::Runtime> SessionHost for TauriHost<R> {
+    fn app_state(&self) -> &AppState {
+        self.0.state::<AppState>().inner()
+    }
+
+    fn event_sink(&self) -> Arc<dyn EventSink> {
+        Arc::new(crate::event_sink::TauriEventSink::new(self.0.clone()))
+    }
+
+    fn emit_execution_state(&self, state: &ExecutionState) {
+        if let Err(error) = self.0.emit(EXECUTION_STATE_EVENT, state) {
+            tracing::warn!(error = %error, "Failed to emit execution state");
+        }
+    }
+}
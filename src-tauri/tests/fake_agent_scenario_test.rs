@@ -0,0 +1,161 @@
+//! Drives the `ralph-fake-agent` binary against a real project database, the same way a
+//! task execution PTY session would, without any network or LLM.
+
+mod test_support;
+
+use sqlite_db::{SqliteDb, SubsystemInput, TaskInput};
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use tempfile::tempdir;
+
+use crate::test_support::fixture_project;
+
+const FAKE_AGENT_BIN: &str = env!("CARGO_BIN_EXE_ralph-fake-agent");
+
+fn setup_project(root: &Path) -> (SqliteDb, u32) {
+    fixture_project::initialize_project_for_fixture(
+        root.to_path_buf(),
+        "Fake Agent Project".to_owned(),
+        false,
+        None,
+    )
+    .unwrap();
+
+    let db = SqliteDb::open(&root.join(".ralph/db/ralph.db"), None).unwrap();
+    db.create_subsystem(SubsystemInput {
+        name: "core".to_owned(),
+        display_name: "Core".to_owned(),
+        acronym: "CORE".to_owned(),
+        description: None,
+    })
+    .unwrap();
+    let task_id = db
+        .create_task(TaskInput {
+            subsystem: "core".to_owned(),
+            discipline: "backend".to_owned(),
+            title: "Implement answer()".to_owned(),
+            ..Default::default()
+        })
+        .unwrap();
+    (db, task_id)
+}
+
+fn write_mcp_config(root: &Path, task_id: u32, session_id: &str) -> std::path::PathBuf {
+    let config = serde_json::json!({
+        "mcpServers": {
+            "ralph-signals": {
+                "command": "bun",
+                "args": ["task_signals_server.ts"],
+                "env": {
+                    "RALPH_TASK_ID": task_id.to_string(),
+                    "RALPH_SESSION_ID": session_id,
+                    "RALPH_DB_PATH": root.join(".ralph/db/ralph.db").to_string_lossy(),
+                }
+            }
+        }
+    });
+    let path = root.join("mcp-task.json");
+    fs::write(&path, config.to_string()).unwrap();
+    path
+}
+
+#[test]
+fn fake_agent_plays_back_output_files_signals_and_exit_code() {
+    let dir = tempdir().unwrap();
+    let root = dir.path();
+    let (db, task_id) = setup_project(root);
+    let mcp_config = write_mcp_config(root, task_id, "fake-session-1");
+
+    let scenario = root.join("scenario.yaml");
+    fs::write(
+        &scenario,
+        r#"
+steps:
+  - output: "Reading task context\n"
+  - touch: { path: src/answer.rs, content: "pub fn answer() -> u32 { 42 }\n" }
+  - signal:
+      verb: learned
+      payload: { text: "answer lives in src/answer.rs", kind: discovery, scope: task }
+  - signal:
+      verb: done
+      payload: { summary: "Implemented answer()" }
+  - output: "<promise>COMPLETE</promise>\n"
+exit_code: 0
+"#,
+    )
+    .unwrap();
+
+    let output = Command::new(FAKE_AGENT_BIN)
+        .current_dir(root)
+        .arg("--scenario")
+        .arg(&scenario)
+        .arg("--mcp-config")
+        .arg(&mcp_config)
+        .output()
+        .unwrap();
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(
+        output.status.code(),
+        Some(0),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(stdout.contains("Reading task context"));
+    assert!(prompt_builder::check_completion(&stdout));
+    assert_eq!(
+        fs::read_to_string(root.join("src/answer.rs")).unwrap(),
+        "pub fn answer() -> u32 { 42 }\n"
+    );
+
+    let task = db.get_task_by_id(task_id).unwrap();
+    let verbs: Vec<_> = task
+        .signals
+        .iter()
+        .filter_map(|s| s.signal_verb.as_deref())
+        .collect();
+    // Signals are listed newest first.
+    assert_eq!(verbs, vec!["done", "learned"]);
+    assert!(task
+        .signals
+        .iter()
+        .all(|s| s.session_id.as_deref() == Some("fake-session-1")));
+}
+
+#[test]
+fn fake_agent_reports_scenario_exit_code() {
+    let dir = tempdir().unwrap();
+    let scenario = dir.path().join("scenario.yaml");
+    fs::write(&scenario, "steps:\n  - output: \"boom\\n\"\nexit_code: 7\n").unwrap();
+
+    let output = Command::new(FAKE_AGENT_BIN)
+        .current_dir(dir.path())
+        .arg("--scenario")
+        .arg(&scenario)
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(7));
+}
+
+#[test]
+fn fake_agent_fails_when_signal_cannot_be_delivered() {
+    let dir = tempdir().unwrap();
+    let scenario = dir.path().join("scenario.yaml");
+    fs::write(
+        &scenario,
+        "steps:\n  - signal: { verb: done, payload: { summary: x } }\n",
+    )
+    .unwrap();
+
+    let output = Command::new(FAKE_AGENT_BIN)
+        .current_dir(dir.path())
+        .arg("--scenario")
+        .arg(&scenario)
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("ralph-signals"));
+}
@@ -4,7 +4,7 @@ const LIB_RS: &str = include_str!("../src/lib.rs");
 
 // Update this intentionally when the frontend-facing invoke command surface changes.
 const EXPECTED_INVOKE_COMMAND_LIST_SHA256: &str =
-    "81f7ea0dfb290f18676ba900b328d28f356846e699444091e31bdbfdedde9903";
+    "b41efc36dea3998c7bc67d9cb96e9bfcb27b96f01036f55453dc4c29c1b2d1ad";
 
 fn sha256_hex(text: &str) -> String {
     hex::encode(Sha256::digest(text.as_bytes()))
@@ -0,0 +1,164 @@
+//! Prompt regression snapshots
+//!
+//! Renders every built-in recipe against the checked-in fixture projects and compares the
+//! result with the golden files in `tests/prompt_snapshots/<fixture>/<recipe>.md`. The
+//! context is assembled the way task sessions assemble it, minus the RAG comment search.
+//!
+//! - On a mismatch the new rendering is written next to the golden file as `<recipe>.md.new`
+//!   and the test fails with a unified diff of every changed prompt.
+//! - Review the diffs, then accept them with `just update-prompt-snapshots`
+//!   (`RALPH_UPDATE_PROMPT_SNAPSHOTS=1`), which rewrites the golden files.
+//! - A missing golden file fails the test too; the update command writes it.
+
+use prompt_builder::PromptType;
+use ralph4days_lib::PromptInputs;
+use sqlite_db::{FixedClock, SqliteDb};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const FIXTURES: &[&str] = &[
+    "01-desktop-blank",
+    "02-desktop-feature",
+    "03-desktop-tasks",
+    "04-desktop-dev",
+    "05-desktop-templates",
+];
+
+const UPDATE_ENV: &str = "RALPH_UPDATE_PROMPT_SNAPSHOTS";
+
+/// Stand-in paths so the goldens don't depend on where the fixture was copied to.
+const PROJECT_PATH: &str = "/project";
+const SCRIPT_DIR: &str = "/tmp/ralph-mcp";
+
+const USER_INPUT: &str = "Add a settings page where users can change their display name.";
+
+fn repo_root() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .unwrap()
+        .to_path_buf()
+}
+
+fn fixed_clock() -> Box<dyn sqlite_db::Clock> {
+    Box::new(FixedClock(
+        chrono::NaiveDate::from_ymd_opt(2026, 1, 1)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap()
+            .and_utc(),
+    ))
+}
+
+/// Copies the fixture into `target` with its `.undetect-ralph` dir as `.ralph`, so the
+/// copy looks like a live project and opening its database (which migrates it) leaves the
+/// checked-in file alone.
+fn copy_fixture(from: &Path, target: &Path) {
+    fs::create_dir_all(target).unwrap();
+    for entry in fs::read_dir(from).unwrap() {
+        let entry = entry.unwrap();
+        let name = entry.file_name();
+        let to = if name == ".undetect-ralph" {
+            target.join(".ralph")
+        } else {
+            target.join(&name)
+        };
+        if entry.file_type().unwrap().is_dir() {
+            copy_fixture(&entry.path(), &to);
+        } else {
+            fs::copy(entry.path(), to).unwrap();
+        }
+    }
+}
+
+fn build_context(
+    project: &Path,
+    db: &SqliteDb,
+    prompt_type: PromptType,
+) -> prompt_builder::PromptContext {
+    // Task-focused recipes target the fixture's first task; the others ignore it.
+    let target_task_id = matches!(
+        prompt_type,
+        PromptType::TaskExecution | PromptType::OpusReview | PromptType::Enrichment
+    )
+    .then(|| db.get_tasks().iter().map(|task| task.id).min())
+    .flatten();
+
+    let inputs = PromptInputs {
+        user_input: Some(USER_INPUT.to_owned()),
+        instruction_overrides: HashMap::new(),
+        target_task_id,
+        script_dir: PathBuf::from(SCRIPT_DIR),
+        api_server_port: None,
+        codebase_snapshot: Some(prompt_builder::snapshot::analyze(project)),
+    };
+    ralph4days_lib::assemble_prompt_context(db, project, inputs).unwrap()
+}
+
+/// Compares `rendered` with the golden file. Returns a failure report, or `None` when the
+/// golden matches (or was just updated).
+fn check_snapshot(golden: &Path, rendered: &str, update: bool) -> Option<String> {
+    let pending = golden.with_extension("md.new");
+    let expected = fs::read_to_string(golden).ok();
+    if expected.as_deref() == Some(rendered) {
+        let _ = fs::remove_file(&pending);
+        return None;
+    }
+
+    fs::create_dir_all(golden.parent().unwrap()).unwrap();
+    if update {
+        fs::write(golden, rendered).unwrap();
+        let _ = fs::remove_file(&pending);
+        println!("✓ Wrote prompt snapshot {}", golden.display());
+        return None;
+    }
+
+    fs::write(&pending, rendered).unwrap();
+    let diff = expected.map_or_else(
+        || "(no golden file)\n".to_owned(),
+        |expected| prompt_builder::diff::unified_diff(&expected, rendered),
+    );
+    Some(format!(
+        "--- {}\n+++ {}\n{diff}",
+        golden.display(),
+        pending.display()
+    ))
+}
+
+#[test]
+fn built_in_recipes_match_prompt_snapshots() {
+    let update = std::env::var_os(UPDATE_ENV).is_some();
+    let root = repo_root();
+    let snapshots_dir = root.join("src-tauri/tests/prompt_snapshots");
+    let scratch = tempfile::tempdir().unwrap();
+
+    let mut failures = Vec::new();
+    for fixture in FIXTURES {
+        let project = scratch.path().join(fixture);
+        copy_fixture(&root.join("fixtures").join(fixture), &project);
+        let db = SqliteDb::open(&project.join(".ralph/db/ralph.db"), Some(fixed_clock())).unwrap();
+        let project_path = project.to_string_lossy().into_owned();
+
+        for prompt_type in PromptType::ALL {
+            let ctx = build_context(&project, &db, prompt_type);
+            let mut rendered = prompt_builder::build(prompt_type, &ctx)
+                .prompt
+                .replace(&project_path, PROJECT_PATH);
+            rendered.push('\n');
+
+            let recipe = prompt_builder::recipes::get(prompt_type).name;
+            let golden = snapshots_dir.join(fixture).join(format!("{recipe}.md"));
+            if let Some(failure) = check_snapshot(&golden, &rendered, update) {
+                failures.push(failure);
+            }
+        }
+    }
+
+    assert!(
+        failures.is_empty(),
+        "{} prompt snapshot(s) changed or missing. Review the diffs below (new renderings are \
+         saved as *.md.new) and run `just update-prompt-snapshots` to accept them.\n\n{}",
+        failures.len(),
+        failures.join("\n")
+    );
+}
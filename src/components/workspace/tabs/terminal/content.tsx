@@ -33,7 +33,8 @@ export function TerminalTabContent({ tab, params }: { tab: WorkspaceTab; params:
       humanSession: {
         kind: params.kind,
         agent: params.agent,
-        initPrompt: params.initPrompt ?? undefined
+        initPrompt: params.initPrompt ?? undefined,
+        useWorktree: params.useWorktree
       }
     },
     {
@@ -22,7 +22,8 @@ export function createTerminalTab(input: TerminalTabInput = {}): Omit<WorkspaceT
       thinking: input.thinking,
       permissionLevel: input.permissionLevel,
       taskId: input.taskId,
-      initPrompt: input.initPrompt
+      initPrompt: input.initPrompt,
+      useWorktree: input.useWorktree
     }
   }
 }
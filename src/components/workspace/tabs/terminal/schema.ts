@@ -9,6 +9,7 @@ const terminalTabInputSchema = optionalLaunchOptionsSchema
     agent: terminalTabAgentSchema.optional(),
     taskId: z.number().int().positive().optional(),
     initPrompt: z.string().optional(),
+    useWorktree: z.boolean().optional(),
     title: z.string().trim().min(1).optional()
   })
   .strict()
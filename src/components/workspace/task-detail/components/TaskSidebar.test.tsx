@@ -16,6 +16,10 @@ vi.mock('../hooks/useResolvedTaskLaunch', () => ({
   useResolvedTaskLaunch: (...args: unknown[]) => useResolvedTaskLaunchMock(...args)
 }))
 
+vi.mock('./TaskWorktreeSection', () => ({
+  TaskWorktreeSection: () => null
+}))
+
 vi.mock('./DisciplineSelect', () => ({
   DisciplineSelect: ({ value }: { value: string }) => <span data-testid="discipline-select">{value}</span>
 }))
@@ -1,4 +1,4 @@
-import { Bot, Brain, Check, Cog, Play, Radio, Sparkles, User, WandSparkles } from 'lucide-react'
+import { Bot, Brain, Check, Cog, GitBranch, Play, Radio, Sparkles, User, WandSparkles } from 'lucide-react'
 import type { ReactNode } from 'react'
 import { LaunchOptionsReadout } from '@/components/agent-session-launch'
 import { InlineError } from '@/components/shared'
@@ -26,6 +26,7 @@ import { createTerminalTab } from '../../tabs'
 import { type LaunchSource, useResolvedTaskLaunch } from '../hooks/useResolvedTaskLaunch'
 import { DisciplineSelect } from './DisciplineSelect'
 import { TaskLaunchOverridesDialog } from './TaskLaunchOverridesDialog'
+import { TaskWorktreeSection } from './TaskWorktreeSection'
 
 const PROVENANCE_CONFIG = {
   agent: { label: 'Agent', icon: Bot },
@@ -396,7 +397,7 @@ export function TaskSidebar({ task, inferredStatus }: { task: Task; inferredStat
     approveMutation.mutate({ id: task.id, status: 'pending' })
   }
 
-  const handleExecute = () => {
+  const handleExecute = (useWorktree = false) => {
     openTab(
       createTerminalTab({
         taskId,
@@ -404,7 +405,8 @@ export function TaskSidebar({ task, inferredStatus }: { task: Task; inferredStat
         agent: resolvedAgent ?? undefined,
         model: resolvedModel ?? undefined,
         effort: resolvedEffort ?? undefined,
-        thinking: resolvedThinking ?? undefined
+        thinking: resolvedThinking ?? undefined,
+        useWorktree: useWorktree || undefined
       })
     )
   }
@@ -441,7 +443,8 @@ export function TaskSidebar({ task, inferredStatus }: { task: Task; inferredStat
       modelSource,
       effortSource,
       thinkingSource
-    })
+    }),
+    <TaskWorktreeSection key="worktree" taskId={taskId} />
   ]
 
   if (signals.length > 0) {
@@ -464,7 +467,17 @@ export function TaskSidebar({ task, inferredStatus }: { task: Task; inferredStat
             triggerClassName="flex-1 min-w-0 h-8 rounded-none rounded-tl-md border-t-0 border-b-0 border-l-0 shadow-none"
           />
           <Button
-            onClick={handleExecute}
+            onClick={() => handleExecute(true)}
+            variant="outline"
+            size="icon"
+            className="rounded-none border-t-0 border-b-0 shadow-none"
+            disabled={status === 'done'}
+            title="Execute in a git worktree"
+            aria-label="Execute Task in Worktree">
+            <GitBranch className="h-3.5 w-3.5" />
+          </Button>
+          <Button
+            onClick={() => handleExecute()}
             variant="outline"
             size="icon"
             className="rounded-none rounded-tr-md border-t-0 border-b-0 border-r-0 shadow-none"
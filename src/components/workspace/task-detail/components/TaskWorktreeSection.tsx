@@ -0,0 +1,102 @@
+import { revealItemInDir } from '@tauri-apps/plugin-opener'
+import { FolderOpen, GitBranch, GitMerge, Trash2 } from 'lucide-react'
+import { InlineError } from '@/components/shared'
+import {
+  AlertDialog,
+  AlertDialogAction,
+  AlertDialogCancel,
+  AlertDialogContent,
+  AlertDialogDescription,
+  AlertDialogFooter,
+  AlertDialogHeader,
+  AlertDialogTitle,
+  AlertDialogTrigger
+} from '@/components/ui/alert-dialog'
+import { Button } from '@/components/ui/button'
+import { useInvokeMutation, useWorkspaceInvoke } from '@/hooks/api'
+import type { TasksWorktreeDiscardArgs, TasksWorktreeMergeArgs, TaskWorktreeInfo } from '@/types/generated'
+import { PropertyRow } from '../../PropertyRow'
+
+function shortCommit(commit: string | undefined) {
+  return commit ? commit.slice(0, 7) : '…'
+}
+
+export function TaskWorktreeSection({ taskId }: { taskId: number }) {
+  const { data: worktree } = useWorkspaceInvoke<TaskWorktreeInfo | null>('tasks_worktree_get', { taskId })
+  const invalidateKeys = [['tasks_worktree_get', { taskId }]]
+  const mergeMutation = useInvokeMutation<TasksWorktreeMergeArgs>('tasks_worktree_merge', {
+    queryDomain: 'workspace',
+    invalidateKeys
+  })
+  const discardMutation = useInvokeMutation<TasksWorktreeDiscardArgs>('tasks_worktree_discard', {
+    queryDomain: 'workspace',
+    invalidateKeys
+  })
+
+  if (!worktree?.exists) return null
+
+  const isPending = mergeMutation.isPending || discardMutation.isPending
+  const error = mergeMutation.error ?? discardMutation.error
+  const resetError = () => {
+    mergeMutation.reset()
+    discardMutation.reset()
+  }
+
+  return (
+    <PropertyRow key="worktree" label="Worktree">
+      <div className="flex flex-col gap-1.5">
+        <div className="flex items-center gap-1.5 min-w-0" title={worktree.path}>
+          <GitBranch className="h-3 w-3 shrink-0 text-muted-foreground" />
+          <span className="text-xs font-mono truncate">{worktree.branch}</span>
+          <span className="text-xs text-muted-foreground font-mono shrink-0">
+            {shortCommit(worktree.baseCommit)}..{shortCommit(worktree.headCommit)}
+          </span>
+        </div>
+        <div className="flex items-center gap-1">
+          <Button
+            variant="outline"
+            size="sm"
+            className="h-6 px-2 text-xs"
+            disabled={isPending}
+            onClick={() => mergeMutation.mutate({ taskId })}>
+            <GitMerge className="h-3 w-3 mr-1" />
+            Merge
+          </Button>
+          <AlertDialog>
+            <AlertDialogTrigger asChild>
+              <Button variant="outline" size="sm" className="h-6 px-2 text-xs" disabled={isPending}>
+                <Trash2 className="h-3 w-3 mr-1" />
+                Discard
+              </Button>
+            </AlertDialogTrigger>
+            <AlertDialogContent>
+              <AlertDialogHeader>
+                <AlertDialogTitle>Discard {worktree.branch}?</AlertDialogTitle>
+                <AlertDialogDescription>
+                  This removes the worktree and deletes the branch <span className="font-mono">{worktree.branch}</span>.
+                  Commits on it that were not merged are lost.
+                </AlertDialogDescription>
+              </AlertDialogHeader>
+              <AlertDialogFooter>
+                <AlertDialogCancel>Cancel</AlertDialogCancel>
+                <AlertDialogAction variant="destructive" onClick={() => discardMutation.mutate({ taskId })}>
+                  Discard branch
+                </AlertDialogAction>
+              </AlertDialogFooter>
+            </AlertDialogContent>
+          </AlertDialog>
+          <Button
+            variant="ghost"
+            size="sm"
+            className="h-6 px-2 text-xs"
+            onClick={() => void revealItemInDir(worktree.path)}
+            aria-label="Open worktree">
+            <FolderOpen className="h-3 w-3 mr-1" />
+            Open
+          </Button>
+        </div>
+        <InlineError error={error} onDismiss={resetError} />
+      </div>
+    </PropertyRow>
+  )
+}
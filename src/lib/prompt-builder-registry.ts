@@ -47,6 +47,13 @@ export const SECTION_REGISTRY: SectionMeta[] = [
     category: 'project',
     isInstruction: false
   },
+  {
+    name: 'repo_map',
+    displayName: 'Repository Map',
+    description: "Key symbols per file in the subsystem, ranked by the task's context files",
+    category: 'project',
+    isInstruction: false
+  },
   {
     name: 'feature_listing',
     displayName: 'Subsystem Listing',
@@ -64,7 +71,7 @@ export const SECTION_REGISTRY: SectionMeta[] = [
   {
     name: 'feature_files',
     displayName: 'Subsystem Files',
-    description: 'Inlined contents of subsystem context files',
+    description: 'Subsystem context files plus files past iterations touched most',
     category: 'subsystem',
     isInstruction: false
   },
@@ -176,7 +183,7 @@ export const SECTION_REGISTRY: SectionMeta[] = [
   {
     name: 'opus_review_instructions',
     displayName: 'Opus Review Instructions',
-    description: 'Instructions for reviewing recent work quality',
+    description: 'Instructions for reviewing a task reported as done',
     category: 'instructions',
     isInstruction: true
   }
@@ -234,6 +241,7 @@ export const PROMPT_BUILDER_REGISTRY: PromptBuilderDefinition[] = [
       'discipline_persona',
       'feature_context',
       'feature_files',
+      'repo_map',
       'feature_state',
       'state_files',
       'previous_attempts',
@@ -251,8 +259,11 @@ export const PROMPT_BUILDER_REGISTRY: PromptBuilderDefinition[] = [
       'feature_context',
       'feature_files',
       'feature_state',
-      'task_listing',
       'state_files',
+      'previous_attempts',
+      'task_details',
+      'task_files',
+      'user_input',
       'opus_review_instructions'
     ]
   }
@@ -377,29 +388,28 @@ You are executing a specific task. Complete it thoroughly, following the discipl
 
   opus_review_instructions: `## Instructions
 
-You are reviewing recent work for quality. Focus on correctness, code quality, and completeness.
+You are reviewing a task another agent just reported as done. Decide whether the work meets the task. You are the reviewer, not the implementer.
 
 ### What to do
 
-1. **Review recently completed tasks.** Check that the work actually implements what the task describes.
-2. **Verify the code works.** Run tests, check for compilation errors, and look for obvious bugs.
-3. **Check code quality.** Look for:
+1. **Read the task details** above, especially the acceptance criteria. The review brief lists the changes to review.
+2. **Read the diff.** Check every acceptance criterion against the actual changes, not against the implementer's summary.
+3. **Verify the code works.** Run the tests, check for compilation errors, and look for obvious bugs.
+4. **Check code quality.** Look for:
    - Code that does not follow project conventions
    - Missing error handling
    - Hardcoded values that should be configurable
    - Dead code or unused imports
    - Poor naming or unclear logic
-4. **Fix issues you find.** Make corrections directly rather than just noting them.
-5. **Update learnings.txt.** Add patterns, gotchas, or useful discoveries to \`learnings.txt\` so future iterations benefit.
-6. **Update task statuses** if you discover a "done" task that is not actually complete -- set it back to \`in_progress\` or \`blocked\` as appropriate.
+5. **Record your verdict** with the task signal tools, then stop:
+   - If every acceptance criterion is met, call \`done\` with a short summary of what you checked.
+   - Otherwise call \`flag\` once per problem, with severity \`warning\` or \`blocking\`. The task goes back to \`pending\` for another attempt, and your flags are shown to the next run.
 
 ### Guidelines
 
-- Quality over speed. It is better to fix one thing well than to skim many things.
-- Be specific in learnings -- "the X pattern causes Y problem, use Z instead" is useful; "be careful with X" is not.
-- If you find systemic issues (e.g., a pattern repeated across many files), note it in learnings and fix the instances you find.
-- Do not re-do completed work that is correct. Focus on finding and fixing actual problems.
-- Commit fixes with clear messages explaining what was wrong and how it was fixed.`
+- Do not fix the code yourself. Describe each problem precisely enough that the next attempt can fix it: file, behavior, and the criterion it breaks.
+- Flag only real problems. Style preferences that the project conventions don't cover are \`info\` at most, and \`info\` flags don't reopen the task.
+- Use \`learned\` for patterns or gotchas future tasks should know about.`
 }
 
 export const CATEGORY_COLORS: Record<string, string> = {
@@ -29,6 +29,7 @@ export interface TerminalSessionConfig {
     agent?: string
     postStartPreamble?: string
     initPrompt?: string
+    useWorktree?: boolean
   }
 }
 
@@ -107,6 +108,7 @@ export function useTerminalSession(config: TerminalSessionConfig, handlers: Term
   const humanAgent = humanSession?.agent
   const humanPostStartPreamble = humanSession?.postStartPreamble
   const humanInitPrompt = humanSession?.initPrompt
+  const humanUseWorktree = humanSession?.useWorktree
   const [listenersReady, setListenersReady] = useState(false)
   const [streamReady, setStreamReady] = useState(false)
 
@@ -220,7 +222,8 @@ export function useTerminalSession(config: TerminalSessionConfig, handlers: Term
       postStartPreamble: humanPostStartPreamble ?? undefined,
       initPrompt: humanInitPrompt ?? undefined,
       mcpMode: taskId !== undefined ? undefined : mcpMode || 'interactive',
-      thinking: thinking ?? undefined
+      thinking: thinking ?? undefined,
+      useWorktree: humanUseWorktree || undefined
     })
   }, [
     effort,
@@ -228,6 +231,7 @@ export function useTerminalSession(config: TerminalSessionConfig, handlers: Term
     humanInitPrompt,
     humanKind,
     humanPostStartPreamble,
+    humanUseWorktree,
     mcpMode,
     model,
     permissionLevel,
@@ -89,6 +89,7 @@ export type TerminalBridgeStartHumanSessionArgs = {
   initPrompt?: string
   mcpMode?: string
   thinking?: boolean
+  useWorktree?: boolean
 }
 
 export type TerminalBridgeStartHumanSessionResult = {
@@ -126,7 +127,8 @@ export async function terminalBridgeStartHumanSession(
     postStartPreamble: params.postStartPreamble ?? null,
     initPrompt: params.initPrompt ?? null,
     mcpMode: params.mcpMode ?? null,
-    thinking: params.thinking ?? null
+    thinking: params.thinking ?? null,
+    useWorktree: params.useWorktree ?? null
   })
   terminalBridgeDebugLog('tx.startHumanSession.result', result)
   return result
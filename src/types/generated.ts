@@ -18,6 +18,11 @@ export type AgentSession = {
   promptHash?: string
   outputBytes?: number
   errorText?: string
+  pid?: number
+  worktreeBranch?: string
+  worktreePath?: string
+  baseCommit?: string
+  headCommit?: string
 }
 export type AgentSessionCreateInput = {
   id: string
@@ -29,6 +34,25 @@ export type AgentSessionCreateInput = {
   postStartPreamble?: string
   initPrompt?: string
 }
+export type AgentSessionPrompt = {
+  sessionId: string
+  sessionNumber: number
+  recipe: string
+  configName?: string
+  sections: string[]
+  prompt: string
+  promptHash?: string
+  mcpConfig?: string
+  created: string
+}
+export type AgentSessionPromptDiff = {
+  from: AgentSessionPrompt
+  to: AgentSessionPrompt
+  sectionsAdded: string[]
+  sectionsRemoved: string[]
+  mcpConfigChanged: boolean
+  diff: string
+}
 export type AgentSessionUpdateInput = {
   id: string
   kind?: string
@@ -47,9 +71,13 @@ export type AgentSessionUpdateInput = {
   errorText?: string
 }
 export type AgentSessionsByIdArgs = { id: string }
+export type AgentSessionsPromptDiffArgs = { fromSessionNumber: number; toSessionNumber: number }
+export type AgentSessionsPromptGetArgs = { sessionNumber: number }
 export type BackendDiagnosticEvent = { level: BackendDiagnosticLevel; source: string; code: string; message: string }
 export type BackendDiagnosticLevel = 'warning' | 'error'
+export type BackupReason = 'manual' | 'execution' | 'migration' | 'restore'
 export type CropBoxData = { x: number; y: number; w: number; h: number }
+export type DbBackup = { name: string; reason: BackupReason; created: string; sizeBytes: bigint; taskCount?: number }
 export type DisciplineConfig = {
   id: number
   name: string
@@ -115,6 +143,10 @@ export type DisciplinesCreateArgs = {
 export type DisciplinesCroppedImageGetArgs = { disciplineName: string; crop: CropBoxData; label: string }
 export type DisciplinesDeleteArgs = { name: string }
 export type DisciplinesImageDataGetArgs = { disciplineName: string }
+export type DisciplinesReviewSettingsGetArgs = { name: string }
+export type DisciplinesReviewSettingsSetArgs = { name: string; settings: ReviewSettings }
+export type DisciplinesSessionLimitsGetArgs = { name: string }
+export type DisciplinesSessionLimitsSetArgs = { name: string; limits: SessionLimits }
 export type DisciplinesUpdateArgs = {
   name: string
   displayName: string
@@ -130,6 +162,44 @@ export type DisciplinesUpdateArgs = {
   conventions?: string
   mcpServers: McpServerConfigData[]
 }
+export type DoctorCheck = {
+  name: string
+  severity: DoctorSeverity
+  message: string
+  details: string[]
+  fix?: string
+  fixed: boolean
+}
+export type DoctorSeverity = 'ok' | 'info' | 'warning' | 'error'
+export type EnrichmentBatchRunArgs = { subsystem?: string; discipline?: string; autoApprove?: boolean }
+export type EnrichmentOutcome = 'proposed' | 'promoted' | 'rejected' | 'failed'
+export type EnrichmentProgress = {
+  taskId: number
+  index: number
+  total: number
+  outcome: EnrichmentOutcome
+  problems: string[]
+}
+export type EditHistory = { undo: string[]; redo: string[] }
+export type EnrichmentProposal = {
+  taskId: number
+  pseudocode: string
+  acceptanceCriteria: string[]
+  contextFiles: string[]
+  model?: string
+  created: string
+}
+export type EnrichmentProposalsDecideArgs = { taskIds: number[] }
+export type ExecutionConcurrencySetArgs = { concurrency: number }
+export type ExecutionStartArgs = { concurrency?: number; useWorktrees?: boolean }
+export type ExecutionState = {
+  status: ExecutionStatus
+  concurrency: number
+  useWorktrees: boolean
+  workers: ExecutionWorker[]
+}
+export type ExecutionStatus = 'idle' | 'running' | 'paused' | 'stopping'
+export type ExecutionWorker = { taskId: number; agentSessionId: string; terminalSessionId: string; review: boolean }
 export type FeatureLearning = {
   text: string
   reason?: string
@@ -155,11 +225,30 @@ export type GroupStats = {
 export type LearningSource = 'auto' | 'agent' | 'human' | 'opus_reviewed'
 export type McpServerConfig = { name: string; command: string; args: string[]; env: { [key in string]: string } }
 export type McpServerConfigData = { name: string; command: string; args: string[]; env: { [key in string]: string } }
+export type MirrorConflict = { path: string; line?: number; message: string }
+export type MirrorImportReport = { applied: string[]; removed: string[]; conflicts: MirrorConflict[] }
+export type MirrorStatus = { enabled: boolean; conflicts: MirrorConflict[] }
+export type PrdImportMode = 'merge' | 'replace'
+export type PrdImportReport = {
+  disciplinesCreated: number
+  disciplinesUpdated: number
+  subsystemsCreated: number
+  subsystemsUpdated: number
+  subsystemsRemoved: number
+  tasksCreated: number
+  tasksUpdated: number
+  tasksRemoved: number
+}
 export type Priority = 'low' | 'medium' | 'high' | 'critical'
+export type ProjectActivity = { pendingAsks: number; runningSessions: number; blockedTasks: number }
+export type ProjectBackupRestoreArgs = { name: string }
+export type ProjectDoctorArgs = { fix: boolean }
 export type ProjectInfo = { title: string; description?: string; created?: string }
 export type ProjectInitializeArgs = { path: string; projectTitle: string; stack: number }
 export type ProjectLockSetArgs = { path: string }
+export type ProjectPrdImportArgs = { yaml: string; mode: PrdImportMode; dryRun: boolean }
 export type ProjectProgress = { totalTasks: number; doneTasks: number; progressPercent: number }
+export type ProjectReportExportArgs = { format: ReportFormat }
 export type ProjectScanArgs = { rootDir?: string }
 export type ProjectValidatePathArgs = { path: string }
 export type PromptBuilderConfigData = {
@@ -189,6 +278,8 @@ export type RalphProject = { name: string; path: string }
 export type RemoteConnectArgs = { wsUrl: string }
 export type RemoteConnectResult = { wsUrl: string; protocol: ProtocolVersionInfo }
 export type RemoteStatus = { connected: boolean; wsUrl?: string; protocol?: ProtocolVersionInfo }
+export type ReportFormat = 'markdown' | 'csv' | 'dot' | 'mermaid'
+export type ReviewSettings = { required: boolean; agent?: string; model?: string }
 export type SectionConfig = { name: string; enabled: boolean; instructionOverride?: string }
 export type SectionInfo = {
   name: string
@@ -198,6 +289,7 @@ export type SectionInfo = {
   is_instruction: boolean
 }
 export type SectionSettingsData = { enabled: boolean; instructionOverride?: string }
+export type SessionLimits = { wallClockSecs?: number; idleSecs?: number }
 export type StackMetadataData = {
   stackId: number
   name: string
@@ -263,6 +355,8 @@ export type SubsystemsCommentUpdateArgs = {
 }
 export type SubsystemsCreateArgs = { name: string; displayName: string; acronym: string; description?: string }
 export type SubsystemsDeleteArgs = { name: string }
+export type SubsystemsListArgs = { showArchived: boolean }
+export type SubsystemsSetArchivedArgs = { name: string; archived: boolean }
 export type SubsystemsUpdateArgs = { name: string; displayName: string; acronym: string; description?: string }
 export type Task = {
   id: number
@@ -289,6 +383,7 @@ export type Task = {
   thinking?: boolean
   pseudocode?: string
   enrichedAt?: string
+  archivedAt?: string
   signals: TaskSignal[]
   subsystemDisplayName: string
   subsystemAcronym: string
@@ -310,6 +405,7 @@ export type TaskListItem = {
   acceptanceCriteriaCount: number
   signalCount: number
   provenance?: TaskProvenance
+  archivedAt?: string
   subsystemDisplayName: string
   subsystemAcronym: string
   disciplineDisplayName: string
@@ -380,6 +476,50 @@ export type TaskTemplate = {
   created?: string
   updated?: string
   pulledCount: number
+  isActive: boolean
+  placeholders: string[]
+}
+export type TaskTemplatesCreateArgs = {
+  discipline: string
+  title: string
+  description?: string
+  priority?: Priority
+  hints?: string
+  estimatedTurns?: number
+  agent?: string
+  model?: string
+  effort?: string
+  thinking?: boolean
+  pseudocode?: string
+}
+export type TaskTemplatesInstantiateArgs = {
+  templateId: number
+  subsystem: string
+  params: { [key in string]: string }
+}
+export type TaskTemplatesSaveFromTaskArgs = { taskId: number }
+export type TaskTemplatesSetActiveArgs = { id: number; active: boolean }
+export type TaskTemplatesUpdateArgs = {
+  id: number
+  discipline: string
+  title: string
+  description?: string
+  priority?: Priority
+  hints?: string
+  estimatedTurns?: number
+  agent?: string
+  model?: string
+  effort?: string
+  thinking?: boolean
+  pseudocode?: string
+}
+export type TaskWorktreeInfo = {
+  agentSessionId: string
+  branch: string
+  path: string
+  baseCommit: string
+  headCommit?: string
+  exists: boolean
 }
 export type TasksAskAnswerArgs = { signalId: number; answer: string }
 export type TasksCommentReplyAddArgs = { taskId: number; parentCommentId: number; priority?: string; body: string }
@@ -404,6 +544,11 @@ export type TasksCreateArgs = {
 }
 export type TasksDeleteArgs = { id: number }
 export type TasksGetArgs = { id: number }
+export type TasksListArgs = { showArchived: boolean }
+export type TasksSessionLimitsGetArgs = { taskId: number }
+export type TasksSessionLimitsGetResult = { overrides: SessionLimits; effective: SessionLimits }
+export type TasksSessionLimitsSetArgs = { taskId: number; limits: SessionLimits }
+export type TasksSetArchivedArgs = { id: number; archived: boolean }
 export type TasksSetStatusArgs = { id: number; status: string }
 export type TasksSignalAddArgs = {
   taskId: number
@@ -438,6 +583,9 @@ export type TasksUpdateArgs = {
   effort?: string
   thinking?: boolean
 }
+export type TasksWorktreeDiscardArgs = { taskId: number }
+export type TasksWorktreeGetArgs = { taskId: number }
+export type TasksWorktreeMergeArgs = { taskId: number }
 export type TerminalBridgeEmitSystemMessageArgs = { sessionId: string; text: string }
 export type TerminalBridgeListModelFormTreeResult = { providers: TerminalBridgeListModelsResult[] }
 export type TerminalBridgeListModelsResult = { agent: string; models: TerminalBridgeModelOption[] }
@@ -471,6 +619,7 @@ export type TerminalBridgeStartHumanSessionArgs = {
   initPrompt?: string
   mcpMode?: string
   thinking?: boolean
+  useWorktree?: boolean
 }
 export type TerminalBridgeStartHumanSessionResult = { agentSessionId: string; agentSessionNumber: number }
 export type TerminalBridgeStartSessionArgs = {
@@ -495,3 +644,13 @@ export type TerminalBridgeStartTaskSessionArgs = {
 }
 export type TerminalBridgeTerminateArgs = { sessionId: string }
 export type VisualIdentityData = { style: string; theme: string; tone: string; references: string }
+export type WorkspaceProject = { path: string; name: string; added: string }
+export type WorkspaceProjectArgs = { path: string }
+export type WorkspaceProjectSummary = {
+  path: string
+  name: string
+  active: boolean
+  open: boolean
+  activity?: ProjectActivity
+  error?: string
+}
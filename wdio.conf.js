@@ -162,6 +162,8 @@ async function startTauriDriver() {
 }
 
 const projectPath = resolveTauriProjectPath()
+// Scripted playback for `agent: "fake"` task sessions; inherited by tauri-driver and the app.
+process.env.RALPH_FAKE_AGENT_SCENARIO ??= path.resolve(projectRoot, 'e2e-tauri', 'scenarios', 'task-done.yaml')
 const specOverride = process.env.TAURI_E2E_SPEC?.trim()
 
 export const config = {